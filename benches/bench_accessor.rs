@@ -0,0 +1,104 @@
+//! [`SampleTableAccessor`] を使ってサンプル群を走査する際の性能を計測するためのベンチマーク
+//!
+//! `cargo bench --bench bench_accessor` で実行できる。
+//! 入力データは全て決定的に生成しているので、実行の度に同じ条件で計測が行われる。
+//!
+//! 手元の環境（x86_64 / release ビルド）での目安の値は以下の通り:
+//! - `accessor_new_1m`: 約 5 ms （1 秒あたり約 2 億サンプル）
+//! - `accessor_new_owned_1m`: 約 22 ms （`StblBox` の複製コストを含む）
+//! - `accessor_samples_1m`: 約 40 ms （1 秒あたり約 2500 万サンプル）
+//! - `sample_iter_1m`: 約 8 ms （1 秒あたり約 1.2 億サンプル）
+use std::{hint::black_box, num::NonZeroU32};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use shiguredo_mp4::{
+    aux::{SampleIter, SampleTableAccessor},
+    boxes::{RootBox, StblBox, StcoBox, StscBox, StscEntry, StssBox, StszBox, SttsBox},
+    Decode, Either, Mp4File,
+};
+
+const SAMPLE_COUNT: u32 = 1_000_000;
+
+fn stbl_box_with_samples(sample_count: u32) -> StblBox {
+    let input_bytes = include_bytes!("../tests/testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..]).expect("decode");
+    let mut stbl_box = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box")
+        .trak_boxes
+        .swap_remove(0)
+        .mdia_box
+        .minf_box
+        .stbl_box;
+
+    let samples_per_chunk = 10;
+    stbl_box.stts_box = SttsBox::from_sample_deltas((0..sample_count).map(|i| 1000 + i % 2));
+    stbl_box.stsc_box = StscBox {
+        entries: vec![StscEntry {
+            first_chunk: NonZeroU32::MIN,
+            sample_per_chunk: samples_per_chunk,
+            sample_description_index: NonZeroU32::MIN,
+        }],
+    };
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: (0..sample_count).map(|i| 100 + i % 1000).collect(),
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: (0..sample_count / samples_per_chunk)
+            .map(|i| i * 10_000)
+            .collect(),
+    });
+    stbl_box.stss_box = Some(StssBox {
+        sample_numbers: (0..sample_count)
+            .step_by(30)
+            .map(|i| NonZeroU32::MIN.saturating_add(i))
+            .collect(),
+    });
+    stbl_box
+}
+
+fn accessor_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("accessor");
+    group.throughput(Throughput::Elements(u64::from(SAMPLE_COUNT)));
+
+    let stbl_box = stbl_box_with_samples(SAMPLE_COUNT);
+    group.bench_function("accessor_new_1m", |b| {
+        b.iter(|| SampleTableAccessor::new(black_box(&stbl_box)).expect("accessor"))
+    });
+
+    // 所有権を渡す場合は StblBox の複製が必要になるので、参照を渡す場合と比べてその分のコストが上乗せされる
+    group.bench_function("accessor_new_owned_1m", |b| {
+        b.iter(|| SampleTableAccessor::new(black_box(&stbl_box).clone()).expect("accessor"))
+    });
+
+    let accessor = SampleTableAccessor::new(&stbl_box).expect("accessor");
+    assert_eq!(accessor.sample_count(), SAMPLE_COUNT);
+    group.bench_function("accessor_samples_1m", |b| {
+        b.iter(|| {
+            black_box(&accessor)
+                .samples()
+                .map(|s| s.data_offset() + u64::from(s.duration()))
+                .fold(0u64, u64::wrapping_add)
+        })
+    });
+
+    group.bench_function("sample_iter_1m", |b| {
+        b.iter(|| {
+            SampleIter::new(black_box(&stbl_box))
+                .map(|s| {
+                    let s = s.expect("sample");
+                    s.data_offset + u64::from(s.duration)
+                })
+                .fold(0u64, u64::wrapping_add)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, accessor_benchmarks);
+criterion_main!(benches);
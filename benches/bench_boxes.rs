@@ -0,0 +1,235 @@
+//! サンプルテーブルのエントリー数が多い場合のエンコード・デコード性能を計測するためのベンチマーク
+//!
+//! `cargo bench --bench bench_boxes` で実行できる。
+//! 入力データは全て決定的に生成しているので、実行の度に同じ条件で計測が行われる。
+//!
+//! 手元の環境（x86_64 / release ビルド）での目安の値は以下の通り:
+//! - `stts_from_sample_deltas_1m`: 約 2.5 ms （1 秒あたり約 4 億サンプル）
+//! - `stts_sample_deltas_1m`: 約 0.3 ms （1 秒あたり約 30 億サンプル）
+//! - `stts_encode_100k`: 約 0.2 ms （約 4 GiB/s）
+//! - `stts_decode_100k`: 約 1.3 ms （約 600 MiB/s）
+//! - `stco_encode_100k`: 約 0.1 ms （約 4 GiB/s）
+//! - `moov_encode_2x100k`: 約 20 ms （約 115 MiB/s）
+//! - `moov_decode_2x100k`: 約 12 ms （約 200 MiB/s）
+//! - `mvhd_encode_10k`: 約 0.6 ms
+//! - `tkhd_encode_10k`: 約 0.55 ms
+//! - `mvhd_box_size_10k`: 約 0.08 ms
+//! - `tkhd_box_size_10k`: 約 0.08 ms
+//! - `mp4_file_decode_100m`: 約 66 ms
+//! - `mp4_file_decode_fast_100m`: 約 12 ms （`mdat` のペイロードを読み飛ばすので、ほぼ `moov` のデコード時間のみとなる）
+//! - `mp4_file_decode_ref_100m`: 約 15 ms （`mdat` のペイロードは入力バイト列を参照するので、コピーも追加のヒープ確保も発生しない）
+use std::{hint::black_box, num::NonZeroU32};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use shiguredo_mp4::{
+    boxes::{
+        Brand, FastRootBox, FileTypeBoxContent, FtypBox, MdatBox, MoovBox, RootBox, RootBoxRef,
+        StcoBox, StscBox, StscEntry, StssBox, StszBox, SttsBox,
+    },
+    BaseBox, Decode, Either, Encode, Mp4File,
+};
+
+const TABLE_ENTRY_COUNT: u32 = 100_000;
+
+const LEAF_BOX_COUNT: usize = 10_000;
+
+const MDAT_PAYLOAD_SIZE: usize = 100 * 1024 * 1024;
+
+// 隣り合うサンプルの尺が異なるので、サンプル数と同じ数のエントリーを持つ stts ボックスになる
+fn alternating_sample_deltas(sample_count: u32) -> impl Iterator<Item = u32> {
+    (0..sample_count).map(|i| 1000 + i % 2)
+}
+
+fn moov_box_with_samples(sample_count: u32) -> MoovBox {
+    let input_bytes = include_bytes!("../tests/testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..]).expect("decode");
+    let mut moov_box = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box");
+
+    let samples_per_chunk = 10;
+    let stbl_box = &mut moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box;
+    stbl_box.stts_box = SttsBox::from_sample_deltas(alternating_sample_deltas(sample_count));
+    stbl_box.stsc_box = StscBox {
+        entries: vec![StscEntry {
+            first_chunk: NonZeroU32::MIN,
+            sample_per_chunk: samples_per_chunk,
+            sample_description_index: NonZeroU32::MIN,
+        }],
+    };
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: (0..sample_count).map(|i| 100 + i % 1000).collect(),
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: (0..sample_count / samples_per_chunk)
+            .map(|i| i * 10_000)
+            .collect(),
+    });
+    stbl_box.stss_box = Some(StssBox {
+        sample_numbers: (0..sample_count)
+            .step_by(30)
+            .map(|i| NonZeroU32::MIN.saturating_add(i))
+            .collect(),
+    });
+
+    let mut second_trak_box = moov_box.trak_boxes[0].clone();
+    second_trak_box.tkhd_box.track_id = 2;
+    moov_box.trak_boxes.push(second_trak_box);
+    moov_box.mvhd_box.next_track_id = 3;
+    moov_box
+}
+
+fn stts_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stts");
+
+    group.throughput(Throughput::Elements(1_000_000));
+    group.bench_function("stts_from_sample_deltas_1m", |b| {
+        b.iter(|| SttsBox::from_sample_deltas(std::iter::repeat_n(black_box(1000), 1_000_000)))
+    });
+
+    let stts_box = SttsBox::from_sample_deltas(std::iter::repeat_n(1000, 1_000_000));
+    group.bench_function("stts_sample_deltas_1m", |b| {
+        b.iter(|| {
+            black_box(&stts_box)
+                .sample_deltas()
+                .for_each(|x| _ = black_box(x))
+        })
+    });
+
+    let stts_box = SttsBox::from_sample_deltas(alternating_sample_deltas(TABLE_ENTRY_COUNT));
+    assert_eq!(stts_box.entries.len(), TABLE_ENTRY_COUNT as usize);
+    let bytes = stts_box.encode_to_vec().expect("encode");
+
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("stts_encode_100k", |b| {
+        b.iter(|| black_box(&stts_box).encode_to_vec().expect("encode"))
+    });
+    group.bench_function("stts_decode_100k", |b| {
+        b.iter(|| SttsBox::decode(black_box(&bytes[..])).expect("decode"))
+    });
+    group.finish();
+}
+
+fn stco_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stco");
+
+    let stco_box = StcoBox {
+        chunk_offsets: (0..TABLE_ENTRY_COUNT).map(|i| i * 10_000).collect(),
+    };
+    let bytes = stco_box.encode_to_vec().expect("encode");
+
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("stco_encode_100k", |b| {
+        b.iter(|| black_box(&stco_box).encode_to_vec().expect("encode"))
+    });
+    group.finish();
+}
+
+fn moov_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("moov");
+
+    let moov_box = moov_box_with_samples(TABLE_ENTRY_COUNT);
+    let bytes = moov_box.encode_to_vec().expect("encode");
+
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("moov_encode_2x100k", |b| {
+        b.iter(|| black_box(&moov_box).encode_to_vec().expect("encode"))
+    });
+    group.bench_function("moov_decode_2x100k", |b| {
+        b.iter(|| MoovBox::decode(black_box(&bytes[..])).expect("decode"))
+    });
+    group.finish();
+}
+
+fn leaf_box_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("leaf_box");
+
+    let moov_box = moov_box_with_samples(1);
+    let mvhd_box = moov_box.mvhd_box.clone();
+    let tkhd_box = moov_box.trak_boxes[0].tkhd_box.clone();
+    let mut buf = Vec::with_capacity(LEAF_BOX_COUNT * 128);
+
+    group.throughput(Throughput::Elements(LEAF_BOX_COUNT as u64));
+    group.bench_function("mvhd_encode_10k", |b| {
+        b.iter(|| {
+            buf.clear();
+            for _ in 0..LEAF_BOX_COUNT {
+                black_box(&mvhd_box).encode(&mut buf).expect("encode");
+            }
+        })
+    });
+    group.bench_function("tkhd_encode_10k", |b| {
+        b.iter(|| {
+            buf.clear();
+            for _ in 0..LEAF_BOX_COUNT {
+                black_box(&tkhd_box).encode(&mut buf).expect("encode");
+            }
+        })
+    });
+
+    // 親ボックスのサイズ計算の際には、子ボックスのサイズが繰り返し参照される
+    group.bench_function("mvhd_box_size_10k", |b| {
+        b.iter(|| {
+            for _ in 0..LEAF_BOX_COUNT {
+                black_box(black_box(&mvhd_box).box_size());
+            }
+        })
+    });
+    group.bench_function("tkhd_box_size_10k", |b| {
+        b.iter(|| {
+            for _ in 0..LEAF_BOX_COUNT {
+                black_box(black_box(&tkhd_box).box_size());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn mp4_file_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mp4_file");
+
+    // ftyp + moov + 100 MiB の mdat からなる MP4 ファイル
+    let ftyp_box = FtypBox(FileTypeBoxContent {
+        major_brand: Brand::ISOM,
+        minor_version: 0,
+        compatible_brands: vec![Brand::ISOM],
+    });
+    let moov_box = moov_box_with_samples(TABLE_ENTRY_COUNT);
+    let mdat_box = MdatBox {
+        is_variable_size: false,
+        payload: vec![0; MDAT_PAYLOAD_SIZE],
+    };
+    let file = Mp4File {
+        ftyp_box,
+        boxes: vec![RootBox::Moov(moov_box), RootBox::Mdat(mdat_box)],
+    };
+    let bytes = file.encode_to_vec().expect("encode");
+
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.sample_size(10);
+    group.bench_function("mp4_file_decode_100m", |b| {
+        b.iter(|| Mp4File::<RootBox>::decode(black_box(&bytes[..])).expect("decode"))
+    });
+    group.bench_function("mp4_file_decode_fast_100m", |b| {
+        b.iter(|| Mp4File::<FastRootBox>::decode_fast(black_box(&bytes[..])).expect("decode"))
+    });
+    group.bench_function("mp4_file_decode_ref_100m", |b| {
+        b.iter(|| Mp4File::<RootBoxRef>::decode_ref(black_box(&bytes[..])).expect("decode"))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    stts_benchmarks,
+    stco_benchmarks,
+    moov_benchmarks,
+    leaf_box_benchmarks,
+    mp4_file_benchmarks
+);
+criterion_main!(benches);
@@ -1,44 +1,218 @@
+//! MP4 ファイルの内容を JavaScript から参照するための WebAssembly 向けの関数群
+//!
+//! - `dump()`: ボックスツリー全体を JSON で返す
+//! - `metadata()`: 尺やトラック情報などのメタデータを JSON で返す
+//! - `duration_ms()`: MP4 ファイルの尺をミリ秒単位で返す
+//!
+//! # 尺の取得の高速パス
+//!
+//! `duration_ms()` はファイル全体をデコードせずに、ボックスヘッダーを辿って `moov` ボックス内の `mvhd` ボックスのみをデコードする。
+//! サンプルテーブルを含む `trak` ボックスはデコードされないので、尺だけが必要な場合には `metadata()` よりも高速に処理できる。
+//! `mvhd` ボックスの尺が 0 の場合には、各トラックの `mdhd` ボックスの尺の最大値が使われる（この場合も `mdhd` ボックスのみをデコードする）。
+//!
+//! 不正なボックスヘッダーを含む入力に対しては、走査を打ち切って失敗を返す
 use serde::Serialize;
-use shiguredo_mp4::{boxes::RootBox, BaseBox, Decode, Mp4File};
+use shiguredo_mp4::{
+    aux::{mp4_box_tree_json, scan_for_box_type, TrackKind},
+    boxes::{MdhdBox, MdiaBox, MoovBox, MvhdBox, RootBox, TrakBox},
+    BaseBox, BoxHeader, BoxType, Decode, Mp4File, Mp4FileTime,
+};
+
+#[no_mangle]
+#[expect(clippy::not_unsafe_ptr_arg_deref)]
+pub fn dump(bytes: *const u8, bytes_len: i32) -> *mut Vec<u8> {
+    let bytes = unsafe { std::slice::from_raw_parts(bytes, bytes_len as usize) };
+
+    let json = mp4_box_tree_json(bytes).unwrap_or_else(|e| e.to_string());
+
+    Box::into_raw(Box::new(json.into_bytes()))
+}
 
 #[derive(Debug, Serialize)]
-struct BoxInfo {
-    #[serde(rename = "type")]
-    pub ty: String,
-    pub size: u64,
+struct Metadata {
+    duration_ms: f64,
+    creation_time_iso8601: String,
+    tracks: Vec<TrackMetadata>,
+}
+
+impl Metadata {
+    fn new(moov_box: &MoovBox) -> Self {
+        let mvhd_box = &moov_box.mvhd_box;
+        Self {
+            duration_ms: to_ms(mvhd_box.duration, mvhd_box.timescale.get()),
+            creation_time_iso8601: to_iso8601(mvhd_box.creation_time),
+            tracks: moov_box
+                .trak_boxes
+                .iter()
+                .enumerate()
+                .map(|(i, trak_box)| TrackMetadata::new(i, trak_box))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TrackMetadata {
+    index: usize,
+    kind: &'static str,
+    codec_string: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub unknown: Option<bool>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub children: Vec<Self>,
+    height: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample_rate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel_count: Option<u16>,
+    duration_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
 }
 
-impl BoxInfo {
-    fn new(b: &dyn BaseBox) -> Self {
+impl TrackMetadata {
+    fn new(index: usize, trak_box: &TrakBox) -> Self {
+        let mdia_box = &trak_box.mdia_box;
+        let kind = match trak_box.track_kind() {
+            Some(TrackKind::Video) => "video",
+            Some(TrackKind::Audio) => "audio",
+            None => "other",
+        };
+        let sample_entry = trak_box.sample_entry();
         Self {
-            ty: b.box_type().to_string(),
-            size: b.box_size().get(),
-            unknown: b.is_unknown_box().then_some(true),
-            children: b.children().map(Self::new).collect(),
+            index,
+            kind,
+            codec_string: sample_entry.map(|entry| {
+                entry
+                    .codec_string()
+                    .unwrap_or_else(|| entry.box_type().to_string())
+            }),
+            width: sample_entry.and_then(|x| x.width()),
+            height: sample_entry.and_then(|x| x.height()),
+            sample_rate: sample_entry.and_then(|x| x.sample_rate()),
+            channel_count: sample_entry.and_then(|x| x.channel_count()),
+            duration_ms: to_ms(
+                mdia_box.mdhd_box.duration,
+                mdia_box.mdhd_box.timescale.get(),
+            ),
+            language: mdia_box.mdhd_box.language_str().map(|s| s.to_owned()),
         }
     }
 }
 
+fn to_ms(duration: u64, timescale: u32) -> f64 {
+    duration as f64 * 1000.0 / timescale as f64
+}
+
+fn to_iso8601(time: Mp4FileTime) -> String {
+    // 1904/1/1 から 1970/1/1 までの日数
+    const DAYS_FROM_1904_TO_1970: i64 = 24107;
+
+    let secs = time.as_secs() as i64;
+    let days = secs.div_euclid(86400) - DAYS_FROM_1904_TO_1970;
+    let secs_of_day = secs.rem_euclid(86400);
+
+    // 1970/1/1 からの日数を年月日に変換する（http://howardhinnant.github.io/date_algorithms.html）
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// MP4 ファイルのメタデータ（尺やトラック情報）を JSON 形式で返す
+///
+/// エラーが発生した場合には `{"error": "..."}` 形式の JSON が返される
 #[no_mangle]
 #[expect(clippy::not_unsafe_ptr_arg_deref)]
-pub fn dump(bytes: *const u8, bytes_len: i32) -> *mut Vec<u8> {
+pub fn metadata(bytes: *const u8, bytes_len: i32) -> *mut Vec<u8> {
     let bytes = unsafe { std::slice::from_raw_parts(bytes, bytes_len as usize) };
 
     let json = Mp4File::<RootBox>::decode(bytes)
         .map_err(|e| e.to_string())
         .and_then(|mp4| {
-            let infos = mp4.iter().map(BoxInfo::new).collect::<Vec<_>>();
-            serde_json::to_string_pretty(&infos).map_err(|e| e.to_string())
+            let moov_box = mp4
+                .boxes
+                .iter()
+                .find_map(|b| match b {
+                    RootBox::Moov(b) => Some(b),
+                    _ => None,
+                })
+                .ok_or_else(|| "No moov box".to_owned())?;
+            serde_json::to_string_pretty(&Metadata::new(moov_box)).map_err(|e| e.to_string())
         })
-        .unwrap_or_else(|e| e);
+        .unwrap_or_else(|e| serde_json::json!({ "error": e }).to_string());
 
     Box::into_raw(Box::new(json.into_bytes()))
 }
 
+/// MP4 ファイルの尺をミリ秒単位で `out_ms` に書き込む
+///
+/// ファイル全体のデコードは行わず、ボックスヘッダーを辿って `moov/mvhd` のみをデコードする高速パス。
+/// `mvhd` の尺が 0 の場合には、各トラックの `mdhd` の尺の最大値を使う（この場合も `trak` 全体はデコードしない）。
+///
+/// 成功時には 0 が、失敗時には 0 以外の値が返される
+#[no_mangle]
+#[expect(clippy::not_unsafe_ptr_arg_deref)]
+pub fn duration_ms(bytes: *const u8, bytes_len: i32, out_ms: *mut f64) -> u32 {
+    let bytes = unsafe { std::slice::from_raw_parts(bytes, bytes_len as usize) };
+
+    let Some(ms) = fast_duration_ms(bytes) else {
+        return 1;
+    };
+    unsafe { *out_ms = ms };
+    0
+}
+
+fn fast_duration_ms(bytes: &[u8]) -> Option<f64> {
+    let moov = scan_for_box_type(bytes, MoovBox::TYPE)?;
+    let mvhd_box = MvhdBox::decode(find_box(moov, MvhdBox::TYPE)?).ok()?;
+    if mvhd_box.duration != 0 {
+        return Some(to_ms(mvhd_box.duration, mvhd_box.timescale.get()));
+    }
+
+    raw_boxes(moov)
+        .filter(|(header, _)| header.box_type == TrakBox::TYPE)
+        .filter_map(|(header, trak)| {
+            let mdia = scan_for_box_type(&trak[header.external_size()..], MdiaBox::TYPE)?;
+            let mdhd_box = MdhdBox::decode(find_box(mdia, MdhdBox::TYPE)?).ok()?;
+            Some(to_ms(mdhd_box.duration, mdhd_box.timescale.get()))
+        })
+        .reduce(f64::max)
+}
+
+// 指定された種別の最初のボックスの（ヘッダーを含む）バイト列を返す
+fn find_box(bytes: &[u8], box_type: BoxType) -> Option<&[u8]> {
+    raw_boxes(bytes)
+        .find(|(header, _)| header.box_type == box_type)
+        .map(|(_, b)| b)
+}
+
+// バイト列内に並んでいるボックス群を、ペイロードをデコードせずにヘッダーとボックス全体のバイト列の組として走査する
+fn raw_boxes(mut bytes: &[u8]) -> impl Iterator<Item = (BoxHeader, &[u8])> {
+    std::iter::from_fn(move || {
+        let header = BoxHeader::decode(bytes).ok()?;
+        let size = BoxHeader::skip_box(bytes).ok()?;
+        if size < header.external_size() {
+            // 走査が先に進まなくなるのを防ぐ
+            return None;
+        }
+        let (b, rest) = bytes.split_at(size);
+        bytes = rest;
+        Some((header, b))
+    })
+}
+
 #[no_mangle]
 #[expect(clippy::not_unsafe_ptr_arg_deref)]
 pub fn vec_offset(v: *mut Vec<u8>) -> *mut u8 {
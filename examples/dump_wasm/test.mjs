@@ -0,0 +1,104 @@
+// dump_wasm の JavaScript からの呼び出しを確認するための結合テスト
+//
+// 事前に `cargo build --release --target wasm32-unknown-unknown -p dump_wasm` で
+// WebAssembly バイナリをビルドしておく必要がある。
+//
+// 実行方法: node examples/dump_wasm/test.mjs
+import assert from "node:assert/strict";
+import { readFile } from "node:fs/promises";
+
+const wasmPath = new URL("dump_wasm.wasm", import.meta.url);
+const testdataDir = new URL("../../tests/testdata/", import.meta.url);
+
+const { instance } = await WebAssembly.instantiate(await readFile(wasmPath));
+const wasm = instance.exports;
+
+// 入力バイト列を WebAssembly 側のメモリにコピーして `f(offset, length)` を呼び出す
+function callWithInput(inputBytes, f) {
+  const buffer = wasm.allocate_vec(inputBytes.length);
+  try {
+    const offset = wasm.vec_offset(buffer);
+    new Uint8Array(wasm.memory.buffer, offset, inputBytes.length).set(inputBytes);
+    return f(offset, inputBytes.length);
+  } finally {
+    wasm.free_vec(buffer);
+  }
+}
+
+function metadata(inputBytes) {
+  return callWithInput(inputBytes, (offset, length) => {
+    const output = wasm.metadata(offset, length);
+    const outputBytes = new Uint8Array(
+      wasm.memory.buffer,
+      wasm.vec_offset(output),
+      wasm.vec_len(output),
+    );
+    const json = new TextDecoder("utf-8").decode(outputBytes);
+    wasm.free_vec(output);
+    return JSON.parse(json);
+  });
+}
+
+function durationMs(inputBytes) {
+  return callWithInput(inputBytes, (offset, length) => {
+    const out = wasm.allocate_vec(8);
+    try {
+      const outOffset = wasm.vec_offset(out);
+      const result = wasm.duration_ms(offset, length, outOffset);
+      return result === 0 ? new Float64Array(wasm.memory.buffer, outOffset, 1)[0] : null;
+    } finally {
+      wasm.free_vec(out);
+    }
+  });
+}
+
+// 映像トラック
+{
+  const input = await readFile(new URL("black-h264-video.mp4", testdataDir));
+  const result = metadata(input);
+  assert.equal(result.error, undefined);
+  assert.ok(result.duration_ms > 0);
+  assert.match(result.creation_time_iso8601, /^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z$/);
+  assert.equal(result.tracks.length, 1);
+
+  const track = result.tracks[0];
+  assert.equal(track.index, 0);
+  assert.equal(track.kind, "video");
+  assert.equal(track.codec_string, "avc1");
+  assert.ok(track.width > 0);
+  assert.ok(track.height > 0);
+  assert.equal(track.sample_rate, undefined);
+
+  assert.equal(durationMs(input), result.duration_ms);
+}
+
+// 音声トラック
+{
+  const input = await readFile(new URL("beep-opus-audio.mp4", testdataDir));
+  const result = metadata(input);
+  assert.equal(result.error, undefined);
+
+  const track = result.tracks[0];
+  assert.equal(track.kind, "audio");
+  assert.equal(track.codec_string, "Opus");
+  assert.ok(track.sample_rate > 0);
+  assert.ok(track.channel_count > 0);
+  assert.equal(track.width, undefined);
+}
+
+// VP9 の場合はコーデック文字列に詳細なパラメーターが含まれる
+{
+  const input = await readFile(new URL("black-vp9-video.mp4", testdataDir));
+  const result = metadata(input);
+  assert.match(result.tracks[0].codec_string, /^vp09\.\d{2}\.\d{2}\.\d{2}/);
+}
+
+// 不正な入力の場合はエラーを表す JSON が返される
+{
+  const input = new Uint8Array([0, 0, 0, 8, 0x66, 0x72, 0x65, 0x65]);
+  const result = metadata(input);
+  assert.equal(typeof result.error, "string");
+  assert.equal(durationMs(input), null);
+}
+
+console.log("OK");
@@ -5,11 +5,11 @@ use serde::Serialize;
 use shiguredo_mp4::{
     aux::SampleTableAccessor,
     boxes::{
-        Brand, DinfBox, FtypBox, HdlrBox, MdatBox, MdhdBox, MdiaBox, MinfBox, MoovBox, MvhdBox,
-        RootBox, SampleEntry, SmhdBox, StblBox, StcoBox, StscBox, StscEntry, StsdBox, StssBox,
-        StszBox, SttsBox, TkhdBox, TrakBox, VmhdBox,
+        Brand, DinfBox, FileTypeBoxContent, FtypBox, HdlrBox, MdatBox, MdhdBox, MdiaBox,
+        MediaInfoHeader, MinfBox, MoovBox, MvhdBox, RootBox, SampleEntry, SmhdBox, StblBox,
+        StcoBox, StscBox, StscEntry, StsdBox, StssBox, StszBox, SttsBox, TkhdBox, TrakBox, VmhdBox,
     },
-    BaseBox, Decode, Either, FixedPointNumber, Mp4File, Mp4FileTime, Utf8String,
+    BaseBox, Decode, Either, FixedPointNumber, Mp4File, Mp4FileTime,
 };
 
 // 出力側はマイクロ秒に決め打ち
@@ -42,7 +42,7 @@ impl OutputMp4Builder {
     }
 
     fn build_ftyp_box(&mut self) -> FtypBox {
-        let ftyp_box = FtypBox {
+        let ftyp_box = FtypBox(FileTypeBoxContent {
             major_brand: Brand::ISOM,
             minor_version: 0,
             compatible_brands: vec![
@@ -52,7 +52,7 @@ impl OutputMp4Builder {
                 Brand::AVC1,
                 Brand::AV01,
             ],
-        };
+        });
         self.file_size += ftyp_box.box_size().get() as u32;
         ftyp_box
     }
@@ -125,6 +125,7 @@ impl OutputMp4Builder {
             tkhd_box,
             edts_box: None,
             mdia_box: self.build_mdia_box(track).or_fail()?,
+            udta_box: None,
             unknown_boxes: Vec::new(),
         })
     }
@@ -137,20 +138,27 @@ impl OutputMp4Builder {
             duration: track.duration().as_micros() as u64,
             language: MdhdBox::LANGUAGE_UNDEFINED,
         };
-        let hdlr_box = HdlrBox {
+        let mut hdlr_box = HdlrBox {
             handler_type: if track.is_audio {
                 HdlrBox::HANDLER_TYPE_SOUN
             } else {
                 HdlrBox::HANDLER_TYPE_VIDE
             },
-            name: Utf8String::EMPTY.into_null_terminated_bytes(),
+            name: Vec::new(),
         };
+
+        // プレイヤーのデバッグ表示などで使われるので、一般的なハンドラ名を設定しておく
+        hdlr_box.set_handler_name(if track.is_audio {
+            "SoundHandler"
+        } else {
+            "VideoHandler"
+        });
         let minf_box = MinfBox {
-            smhd_or_vmhd_box: if track.is_audio {
-                Either::A(SmhdBox::default())
+            media_header_box: Some(if track.is_audio {
+                MediaInfoHeader::Smhd(SmhdBox::default())
             } else {
-                Either::B(VmhdBox::default())
-            },
+                MediaInfoHeader::Vmhd(VmhdBox::default())
+            }),
             dinf_box: DinfBox::LOCAL_FILE,
             stbl_box: self.build_stbl_box(track).or_fail()?,
             unknown_boxes: Vec::new(),
@@ -158,6 +166,7 @@ impl OutputMp4Builder {
         Ok(MdiaBox {
             mdhd_box,
             hdlr_box,
+            elng_box: None,
             minf_box,
             unknown_boxes: Vec::new(),
         })
@@ -208,6 +217,10 @@ impl OutputMp4Builder {
             stsz_box,
             stco_or_co64_box: Either::A(stco_box),
             stss_box,
+            ctts_box: None,
+            subs_box: None,
+            sgpd_boxes: Vec::new(),
+            sbgp_boxes: Vec::new(),
             unknown_boxes: Vec::new(),
         })
     }
@@ -71,11 +71,7 @@ impl WebCodec {
     pub fn create_h264_decoder(config: &Avc1Box) -> impl Future<Output = orfail::Result<Coder>> {
         let (tx, rx) = oneshot::channel::<orfail::Result<_>>();
 
-        let mut description = Vec::new();
-        config
-            .avcc_box
-            .encode(&mut description)
-            .expect("unreachable");
+        let mut description = config.avcc_box.encode_to_vec().expect("unreachable");
         description.drain(..8); // ボックスヘッダ部分を取り除く
 
         let config = VideoDecoderConfig {
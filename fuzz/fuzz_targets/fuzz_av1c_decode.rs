@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shiguredo_mp4::{boxes::Av1cBox, Decode};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Av1cBox::decode(data);
+});
@@ -0,0 +1,911 @@
+//! プロパティベーステスト用の [`proptest::arbitrary::Arbitrary`] 実装群
+//!
+//! 生成される値は、エンコードしてからデコードすると元の値に戻る、構造的に正しいもののみとなる
+//! （ボックス間のエントリ数の整合性などまでは考慮していない）
+use std::num::{NonZeroU16, NonZeroU32};
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    option, prop_oneof,
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+
+use crate::{
+    boxes::{
+        AudioSampleEntryFields, Av01Box, Av1cBox, Avc1Box, AvccBox, Brand, Co64Box, CttsBox,
+        CttsEntry, DinfBox, DopsBox, DopsChannelMappingTable, EdtsBox, ElngBox, ElstBox, ElstEntry,
+        FileTypeBoxContent, FreeBox, FtypBox, HdlrBox, Hev1Box, Hvc1Box, HvccBox, HvccNalUintArray,
+        KindBox, MdatBox, MdhdBox, MdiaBox, MediaInfoHeader, MinfBox, MoovBox, MvhdBox, NmhdBox,
+        OpusBox, PrftBox, SampleEntry, SbgpBox, SbgpEntry, SgpdBox, SgpdEntry, SmhdBox, StblBox,
+        StcoBox, StscBox, StscEntry, StsdBox, StssBox, StszBox, SttsBox, SttsEntry, StypBox,
+        SubsBox, SubsEntry, SubsSubsample, TkhdBox, TmcdBox, TrakBox, UdtaBox,
+        VisualSampleEntryFields, VmhdBox, Vp09Box, VpccBox,
+    },
+    Either, FixedPointNumber, FullBoxFlags, Mp4FileTime, Uint, Utf8String,
+};
+
+macro_rules! impl_arbitrary {
+    ($ty:ty, $strategy:expr) => {
+        impl Arbitrary for $ty {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                $strategy.boxed()
+            }
+        }
+    };
+}
+
+fn bytes(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..=max_len)
+}
+
+fn utf8_string() -> impl Strategy<Value = Utf8String> {
+    "[^\\x00]{0,16}".prop_map(|s| Utf8String::new(&s).expect("no null character"))
+}
+
+fn uint_u8<const BITS: u32, const OFFSET: u32>() -> impl Strategy<Value = Uint<u8, BITS, OFFSET>> {
+    (0..=((1u16 << BITS) - 1) as u8).prop_map(Uint::new)
+}
+
+fn uint_u16<const BITS: u32>() -> impl Strategy<Value = Uint<u16, BITS>> {
+    (0..=((1u32 << BITS) - 1) as u16).prop_map(Uint::new)
+}
+
+fn uint_u64<const BITS: u32>() -> impl Strategy<Value = Uint<u64, BITS>> {
+    (0..=((1u128 << BITS) - 1) as u64).prop_map(Uint::new)
+}
+
+fn file_time() -> impl Strategy<Value = Mp4FileTime> {
+    any::<u64>().prop_map(Mp4FileTime::from_secs)
+}
+
+fn fixed<I: Arbitrary, F: Arbitrary>() -> impl Strategy<Value = FixedPointNumber<I, F>> {
+    (any::<I>(), any::<F>()).prop_map(|(integer, fraction)| FixedPointNumber { integer, fraction })
+}
+
+fn non_zero_u32() -> impl Strategy<Value = NonZeroU32> {
+    (1..=u32::MAX).prop_map(|v| NonZeroU32::new(v).expect("unreachable"))
+}
+
+impl_arbitrary!(
+    FtypBox,
+    (any::<[u8; 4]>(), any::<u32>(), vec(any::<[u8; 4]>(), 0..4)).prop_map(
+        |(major_brand, minor_version, compatible_brands)| FtypBox(FileTypeBoxContent {
+            major_brand: Brand::new(major_brand),
+            minor_version,
+            compatible_brands: compatible_brands.into_iter().map(Brand::new).collect(),
+        })
+    )
+);
+
+impl_arbitrary!(
+    StypBox,
+    (any::<[u8; 4]>(), any::<u32>(), vec(any::<[u8; 4]>(), 0..4)).prop_map(
+        |(major_brand, minor_version, compatible_brands)| StypBox(FileTypeBoxContent {
+            major_brand: Brand::new(major_brand),
+            minor_version,
+            compatible_brands: compatible_brands.into_iter().map(Brand::new).collect(),
+        })
+    )
+);
+
+impl_arbitrary!(FreeBox, bytes(64).prop_map(|payload| FreeBox { payload }));
+
+impl_arbitrary!(
+    PrftBox,
+    (
+        0..(1u32 << 24),
+        any::<u32>(),
+        any::<u64>(),
+        prop_oneof![any::<u32>().prop_map(u64::from), any::<u64>()]
+    )
+        .prop_map(
+            |(flags, reference_track_id, ntp_timestamp, media_time)| PrftBox {
+                flags: FullBoxFlags::new(flags),
+                reference_track_id,
+                ntp_timestamp,
+                media_time,
+            }
+        )
+);
+
+impl_arbitrary!(
+    MdatBox,
+    (any::<bool>(), bytes(64)).prop_map(|(is_variable_size, payload)| MdatBox {
+        is_variable_size,
+        payload
+    })
+);
+
+impl_arbitrary!(
+    MoovBox,
+    (any::<MvhdBox>(), vec(any::<TrakBox>(), 0..3)).prop_map(|(mvhd_box, trak_boxes)| MoovBox {
+        mvhd_box,
+        trak_boxes,
+        unknown_boxes: Vec::new(),
+    })
+);
+
+impl_arbitrary!(
+    MvhdBox,
+    (
+        file_time(),
+        file_time(),
+        non_zero_u32(),
+        any::<u64>(),
+        fixed::<i16, u16>(),
+        fixed::<i8, u8>(),
+        any::<[i32; 9]>(),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(
+                creation_time,
+                modification_time,
+                timescale,
+                duration,
+                rate,
+                volume,
+                matrix,
+                next_track_id,
+            )| MvhdBox {
+                creation_time,
+                modification_time,
+                timescale,
+                duration,
+                rate,
+                volume,
+                matrix,
+                next_track_id,
+            }
+        )
+);
+
+impl_arbitrary!(
+    TrakBox,
+    (
+        any::<TkhdBox>(),
+        option::of(any::<EdtsBox>()),
+        any::<MdiaBox>(),
+        option::of(any::<UdtaBox>())
+    )
+        .prop_map(|(tkhd_box, edts_box, mdia_box, udta_box)| TrakBox {
+            tkhd_box,
+            edts_box,
+            mdia_box,
+            udta_box,
+            unknown_boxes: Vec::new(),
+        })
+);
+
+impl_arbitrary!(
+    UdtaBox,
+    (
+        vec(any::<KindBox>(), 0..3),
+        prop_oneof![Just(Vec::new()), Just(vec![0; 4])]
+    )
+        .prop_map(|(kind_boxes, trailing_bytes)| UdtaBox {
+            kind_boxes,
+            unknown_boxes: Vec::new(),
+            trailing_bytes,
+        })
+);
+
+impl_arbitrary!(
+    KindBox,
+    (utf8_string(), utf8_string()).prop_map(|(scheme_uri, value)| KindBox { scheme_uri, value })
+);
+
+impl_arbitrary!(
+    TkhdBox,
+    (
+        any::<[bool; 4]>(),
+        file_time(),
+        file_time(),
+        any::<u32>(),
+        any::<u64>(),
+        any::<i16>(),
+        any::<i16>(),
+        fixed::<i8, u8>(),
+        any::<[i32; 9]>(),
+        fixed::<i16, u16>(),
+        fixed::<i16, u16>(),
+    )
+        .prop_map(
+            |(
+                flags,
+                creation_time,
+                modification_time,
+                track_id,
+                duration,
+                layer,
+                alternate_group,
+                volume,
+                matrix,
+                width,
+                height,
+            )| TkhdBox {
+                flag_track_enabled: flags[0],
+                flag_track_in_movie: flags[1],
+                flag_track_in_preview: flags[2],
+                flag_track_size_is_aspect_ratio: flags[3],
+                creation_time,
+                modification_time,
+                track_id,
+                duration,
+                layer,
+                alternate_group,
+                volume,
+                matrix,
+                width,
+                height,
+            }
+        )
+);
+
+impl_arbitrary!(
+    EdtsBox,
+    option::of(any::<ElstBox>()).prop_map(|elst_box| EdtsBox {
+        elst_box,
+        unknown_boxes: Vec::new(),
+    })
+);
+
+impl_arbitrary!(
+    ElstBox,
+    vec(
+        (any::<u64>(), any::<i64>(), fixed::<i16, i16>()).prop_map(
+            |(edit_duration, media_time, media_rate)| ElstEntry {
+                edit_duration,
+                media_time,
+                media_rate,
+            }
+        ),
+        0..4
+    )
+    .prop_map(|entries| ElstBox { entries })
+);
+
+impl_arbitrary!(
+    MdiaBox,
+    (
+        any::<MdhdBox>(),
+        any::<HdlrBox>(),
+        option::of(utf8_string().prop_map(|extended_language| ElngBox { extended_language })),
+        any::<MinfBox>()
+    )
+        .prop_map(|(mdhd_box, hdlr_box, elng_box, minf_box)| MdiaBox {
+            mdhd_box,
+            hdlr_box,
+            elng_box,
+            minf_box,
+            unknown_boxes: Vec::new(),
+        })
+);
+
+impl_arbitrary!(
+    MdhdBox,
+    (
+        file_time(),
+        file_time(),
+        non_zero_u32(),
+        any::<u64>(),
+        // 言語コードの各文字は 5 ビットで表現されるので、その範囲に収まるようにする
+        [0x60u8..=0x7f, 0x60u8..=0x7f, 0x60u8..=0x7f],
+    )
+        .prop_map(
+            |(creation_time, modification_time, timescale, duration, language)| MdhdBox {
+                creation_time,
+                modification_time,
+                timescale,
+                duration,
+                language,
+            }
+        )
+);
+
+impl_arbitrary!(
+    HdlrBox,
+    (any::<[u8; 4]>(), bytes(16)).prop_map(|(handler_type, name)| HdlrBox { handler_type, name })
+);
+
+impl_arbitrary!(
+    MinfBox,
+    (
+        prop_oneof![
+            fixed::<u8, u8>().prop_map(|balance| Some(MediaInfoHeader::Smhd(SmhdBox { balance }))),
+            (any::<u16>(), any::<[u16; 3]>()).prop_map(|(graphicsmode, opcolor)| Some(
+                MediaInfoHeader::Vmhd(VmhdBox {
+                    graphicsmode,
+                    opcolor
+                })
+            )),
+            Just(Some(MediaInfoHeader::Nmhd(NmhdBox))),
+            Just(None),
+        ],
+        any::<StblBox>()
+    )
+        .prop_map(|(media_header_box, stbl_box)| MinfBox {
+            media_header_box,
+            dinf_box: DinfBox::LOCAL_FILE,
+            stbl_box,
+            unknown_boxes: Vec::new(),
+        })
+);
+
+impl_arbitrary!(
+    StblBox,
+    (
+        vec(any::<SampleEntry>(), 0..3),
+        any::<SttsBox>(),
+        any::<StscBox>(),
+        any::<StszBox>(),
+        prop_oneof![
+            any::<StcoBox>().prop_map(Either::A),
+            any::<Co64Box>().prop_map(Either::B)
+        ],
+        option::of(any::<StssBox>()),
+        option::of(any::<CttsBox>()),
+        option::of(any::<SubsBox>()),
+        vec(any::<SgpdBox>(), 0..2),
+        vec(any::<SbgpBox>(), 0..2),
+    )
+        .prop_map(
+            |(
+                entries,
+                stts_box,
+                stsc_box,
+                stsz_box,
+                stco_or_co64_box,
+                stss_box,
+                ctts_box,
+                subs_box,
+                sgpd_boxes,
+                sbgp_boxes,
+            )| {
+                StblBox {
+                    stsd_box: StsdBox { entries },
+                    stts_box,
+                    stsc_box,
+                    stsz_box,
+                    stco_or_co64_box,
+                    stss_box,
+                    ctts_box,
+                    subs_box,
+                    sgpd_boxes,
+                    sbgp_boxes,
+                    unknown_boxes: Vec::new(),
+                }
+            }
+        )
+);
+
+impl_arbitrary!(
+    SampleEntry,
+    prop_oneof![
+        (any::<VisualSampleEntryFields>(), any::<AvccBox>()).prop_map(|(visual, avcc_box)| {
+            SampleEntry::Avc1(Avc1Box {
+                visual,
+                avcc_box,
+                unknown_boxes: Vec::new(),
+            })
+        }),
+        (any::<VisualSampleEntryFields>(), any::<HvccBox>()).prop_map(|(visual, hvcc_box)| {
+            SampleEntry::Hev1(Hev1Box {
+                visual,
+                hvcc_box,
+                unknown_boxes: Vec::new(),
+            })
+        }),
+        (any::<VisualSampleEntryFields>(), any::<HvccBox>()).prop_map(|(visual, hvcc_box)| {
+            SampleEntry::Hvc1(Hvc1Box {
+                visual,
+                hvcc_box,
+                unknown_boxes: Vec::new(),
+            })
+        }),
+        (any::<VisualSampleEntryFields>(), any::<VpccBox>()).prop_map(|(visual, vpcc_box)| {
+            SampleEntry::Vp09(Vp09Box {
+                visual,
+                vpcc_box,
+                unknown_boxes: Vec::new(),
+            })
+        }),
+        (any::<VisualSampleEntryFields>(), any::<Av1cBox>()).prop_map(|(visual, av1c_box)| {
+            SampleEntry::Av01(Av01Box {
+                visual,
+                av1c_box,
+                unknown_boxes: Vec::new(),
+            })
+        }),
+        (any::<AudioSampleEntryFields>(), any::<DopsBox>()).prop_map(|(audio, dops_box)| {
+            SampleEntry::Opus(OpusBox {
+                audio,
+                dops_box,
+                unknown_boxes: Vec::new(),
+            })
+        }),
+        any::<TmcdBox>().prop_map(SampleEntry::Tmcd),
+    ]
+);
+
+impl_arbitrary!(
+    TmcdBox,
+    (
+        (1..=u16::MAX).prop_map(|v| NonZeroU16::new(v).expect("unreachable")),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u8>(),
+    )
+        .prop_map(
+            |(data_reference_index, flags, time_scale, frame_duration, number_of_frames)| {
+                TmcdBox {
+                    data_reference_index,
+                    flags,
+                    time_scale,
+                    frame_duration,
+                    number_of_frames,
+                    unknown_boxes: Vec::new(),
+                }
+            }
+        )
+);
+
+impl_arbitrary!(
+    VisualSampleEntryFields,
+    (
+        (1..=u16::MAX).prop_map(|v| NonZeroU16::new(v).expect("unreachable")),
+        any::<u16>(),
+        any::<u16>(),
+        fixed::<u16, u16>(),
+        fixed::<u16, u16>(),
+        any::<u16>(),
+        any::<[u8; 32]>(),
+        any::<u16>(),
+    )
+        .prop_map(
+            |(
+                data_reference_index,
+                width,
+                height,
+                horizresolution,
+                vertresolution,
+                frame_count,
+                compressorname,
+                depth,
+            )| VisualSampleEntryFields {
+                data_reference_index,
+                width,
+                height,
+                horizresolution,
+                vertresolution,
+                frame_count,
+                compressorname,
+                depth,
+            }
+        )
+);
+
+impl_arbitrary!(
+    AudioSampleEntryFields,
+    (
+        any::<u16>(),
+        any::<u16>(),
+        any::<u16>(),
+        fixed::<u16, u16>()
+    )
+        .prop_map(
+            |(data_reference_index, channelcount, samplesize, samplerate)| {
+                AudioSampleEntryFields {
+                    data_reference_index,
+                    channelcount,
+                    samplesize,
+                    samplerate,
+                }
+            }
+        )
+);
+
+impl_arbitrary!(
+    SttsBox,
+    vec(
+        (any::<u32>(), any::<u32>()).prop_map(|(sample_count, sample_delta)| SttsEntry {
+            sample_count,
+            sample_delta
+        }),
+        0..8
+    )
+    .prop_map(|entries| SttsBox { entries })
+);
+
+impl_arbitrary!(
+    StscBox,
+    vec(
+        (non_zero_u32(), any::<u32>(), non_zero_u32()).prop_map(
+            |(first_chunk, sample_per_chunk, sample_description_index)| StscEntry {
+                first_chunk,
+                sample_per_chunk,
+                sample_description_index,
+            }
+        ),
+        0..8
+    )
+    .prop_map(|entries| StscBox { entries })
+);
+
+impl_arbitrary!(
+    StszBox,
+    prop_oneof![
+        (non_zero_u32(), any::<u32>()).prop_map(|(sample_size, sample_count)| StszBox::Fixed {
+            sample_size,
+            sample_count
+        }),
+        vec(any::<u32>(), 0..8).prop_map(|entry_sizes| StszBox::Variable { entry_sizes }),
+    ]
+);
+
+impl_arbitrary!(
+    StcoBox,
+    vec(any::<u32>(), 0..8).prop_map(|chunk_offsets| StcoBox { chunk_offsets })
+);
+
+impl_arbitrary!(
+    Co64Box,
+    vec(any::<u64>(), 0..8).prop_map(|chunk_offsets| Co64Box { chunk_offsets })
+);
+
+impl_arbitrary!(
+    StssBox,
+    vec(non_zero_u32(), 0..8).prop_map(|sample_numbers| StssBox { sample_numbers })
+);
+
+impl_arbitrary!(
+    CttsBox,
+    vec(
+        (any::<u32>(), any::<i32>()).prop_map(|(sample_count, sample_offset)| CttsEntry {
+            sample_count,
+            sample_offset
+        }),
+        0..8
+    )
+    .prop_map(|entries| CttsBox { entries })
+);
+
+impl_arbitrary!(
+    SubsBox,
+    (
+        0..(1u32 << 24),
+        vec(
+            (
+                any::<u32>(),
+                vec(
+                    (any::<u32>(), any::<u8>(), any::<u8>(), any::<u32>()).prop_map(
+                        |(
+                            subsample_size,
+                            subsample_priority,
+                            discardable,
+                            codec_specific_parameters,
+                        )| SubsSubsample {
+                            subsample_size,
+                            subsample_priority,
+                            discardable,
+                            codec_specific_parameters,
+                        }
+                    ),
+                    0..4
+                ),
+            )
+                .prop_map(|(sample_delta, subsamples)| SubsEntry {
+                    sample_delta,
+                    subsamples,
+                }),
+            0..4
+        ),
+    )
+        .prop_map(|(flags, entries)| SubsBox {
+            flags: FullBoxFlags::new(flags),
+            entries,
+        })
+);
+
+impl_arbitrary!(
+    SgpdBox,
+    (
+        any::<[u8; 4]>(),
+        option::of(any::<u32>()),
+        prop_oneof![
+            vec(bytes(16), 0..4).prop_map(|payloads| (0, payloads)),
+            (1..8usize).prop_flat_map(|len| {
+                vec(vec(any::<u8>(), len), 0..4).prop_map(move |payloads| (len as u32, payloads))
+            }),
+        ],
+    )
+        .prop_map(
+            |(grouping_type, default_group_description_index, (default_length, payloads))| {
+                SgpdBox {
+                    grouping_type,
+                    default_length,
+                    default_group_description_index,
+                    entries: payloads
+                        .into_iter()
+                        .map(|payload| SgpdEntry { payload })
+                        .collect(),
+                }
+            }
+        )
+);
+
+impl_arbitrary!(
+    SbgpBox,
+    (
+        any::<[u8; 4]>(),
+        option::of(any::<u32>()),
+        vec(
+            (any::<u32>(), any::<u32>()).prop_map(|(sample_count, group_description_index)| {
+                SbgpEntry {
+                    sample_count,
+                    group_description_index,
+                }
+            }),
+            0..8
+        ),
+    )
+        .prop_map(
+            |(grouping_type, grouping_type_parameter, entries)| SbgpBox {
+                grouping_type,
+                grouping_type_parameter,
+                entries,
+            }
+        )
+);
+
+impl_arbitrary!(
+    AvccBox,
+    (
+        any::<u8>(),
+        any::<u8>(),
+        any::<u8>(),
+        uint_u8::<2, 0>(),
+        // SPS の数は 5 ビットで表現される
+        vec(bytes(16), 0..=31),
+        vec(bytes(16), 0..4),
+        (
+            uint_u8::<2, 0>(),
+            uint_u8::<3, 0>(),
+            uint_u8::<3, 0>(),
+            vec(bytes(16), 0..4)
+        ),
+    )
+        .prop_map(
+            |(
+                avc_profile_indication,
+                profile_compatibility,
+                avc_level_indication,
+                length_size_minus_one,
+                sps_list,
+                pps_list,
+                (chroma_format, bit_depth_luma_minus8, bit_depth_chroma_minus8, sps_ext_list),
+            )| {
+                // 一部のプロファイルでは、拡張フィールド群は存在しない
+                let has_ext = !matches!(avc_profile_indication, 66 | 77 | 88);
+                AvccBox {
+                    avc_profile_indication,
+                    profile_compatibility,
+                    avc_level_indication,
+                    length_size_minus_one,
+                    sps_list,
+                    pps_list,
+                    chroma_format: has_ext.then_some(chroma_format),
+                    bit_depth_luma_minus8: has_ext.then_some(bit_depth_luma_minus8),
+                    bit_depth_chroma_minus8: has_ext.then_some(bit_depth_chroma_minus8),
+                    sps_ext_list: if has_ext { sps_ext_list } else { Vec::new() },
+                }
+            }
+        )
+);
+
+impl_arbitrary!(
+    HvccBox,
+    (
+        (
+            uint_u8::<2, 6>(),
+            uint_u8::<1, 5>(),
+            uint_u8::<5, 0>(),
+            any::<u32>(),
+            uint_u64::<48>(),
+            any::<u8>(),
+            uint_u16::<12>(),
+            uint_u8::<2, 0>(),
+        ),
+        (
+            uint_u8::<2, 0>(),
+            uint_u8::<3, 0>(),
+            uint_u8::<3, 0>(),
+            any::<u16>(),
+            uint_u8::<2, 6>(),
+            uint_u8::<3, 3>(),
+            uint_u8::<1, 2>(),
+            uint_u8::<2, 0>(),
+        ),
+        vec(
+            (uint_u8::<1, 7>(), uint_u8::<6, 0>(), vec(bytes(16), 0..4)).prop_map(
+                |(array_completeness, nal_unit_type, nalus)| HvccNalUintArray {
+                    array_completeness,
+                    nal_unit_type,
+                    nalus,
+                }
+            ),
+            0..4
+        ),
+    )
+        .prop_map(
+            |(
+                (
+                    general_profile_space,
+                    general_tier_flag,
+                    general_profile_idc,
+                    general_profile_compatibility_flags,
+                    general_constraint_indicator_flags,
+                    general_level_idc,
+                    min_spatial_segmentation_idc,
+                    parallelism_type,
+                ),
+                (
+                    chroma_format_idc,
+                    bit_depth_luma_minus8,
+                    bit_depth_chroma_minus8,
+                    avg_frame_rate,
+                    constant_frame_rate,
+                    num_temporal_layers,
+                    temporal_id_nested,
+                    length_size_minus_one,
+                ),
+                nalu_arrays,
+            )| HvccBox {
+                general_profile_space,
+                general_tier_flag,
+                general_profile_idc,
+                general_profile_compatibility_flags,
+                general_constraint_indicator_flags,
+                general_level_idc,
+                min_spatial_segmentation_idc,
+                parallelism_type,
+                chroma_format_idc,
+                bit_depth_luma_minus8,
+                bit_depth_chroma_minus8,
+                avg_frame_rate,
+                constant_frame_rate,
+                num_temporal_layers,
+                temporal_id_nested,
+                length_size_minus_one,
+                nalu_arrays,
+            }
+        )
+);
+
+impl_arbitrary!(
+    DopsBox,
+    (
+        any::<u16>(),
+        any::<u32>(),
+        any::<i16>(),
+        prop_oneof![
+            (1..=2u8).prop_map(|output_channel_count| (output_channel_count, 0, None)),
+            (1..=255u8, any::<u8>(), any::<u8>(), bytes(255)).prop_map(
+                |(channel_mapping_family, stream_count, coupled_count, channel_mapping)| {
+                    let table = DopsChannelMappingTable {
+                        stream_count,
+                        coupled_count,
+                        channel_mapping,
+                    };
+                    (
+                        table.channel_mapping.len() as u8,
+                        channel_mapping_family,
+                        Some(table),
+                    )
+                }
+            ),
+        ],
+    )
+        .prop_map(
+            |(
+                pre_skip,
+                input_sample_rate,
+                output_gain,
+                (output_channel_count, channel_mapping_family, channel_mapping_table),
+            )| DopsBox {
+                output_channel_count,
+                pre_skip,
+                input_sample_rate,
+                output_gain,
+                channel_mapping_family,
+                channel_mapping_table,
+            }
+        )
+);
+
+impl_arbitrary!(
+    Av1cBox,
+    (
+        uint_u8::<3, 5>(),
+        uint_u8::<5, 0>(),
+        uint_u8::<1, 7>(),
+        uint_u8::<1, 6>(),
+        uint_u8::<1, 5>(),
+        uint_u8::<1, 4>(),
+        uint_u8::<1, 3>(),
+        uint_u8::<1, 2>(),
+        uint_u8::<2, 0>(),
+        option::of(uint_u8::<4, 0>()),
+        bytes(32),
+    )
+        .prop_map(
+            |(
+                seq_profile,
+                seq_level_idx_0,
+                seq_tier_0,
+                high_bitdepth,
+                twelve_bit,
+                monochrome,
+                chroma_subsampling_x,
+                chroma_subsampling_y,
+                chroma_sample_position,
+                initial_presentation_delay_minus_one,
+                config_obus,
+            )| Av1cBox {
+                seq_profile,
+                seq_level_idx_0,
+                seq_tier_0,
+                high_bitdepth,
+                twelve_bit,
+                monochrome,
+                chroma_subsampling_x,
+                chroma_subsampling_y,
+                chroma_sample_position,
+                initial_presentation_delay_minus_one,
+                config_obus,
+            }
+        )
+);
+
+impl_arbitrary!(
+    VpccBox,
+    (
+        any::<u8>(),
+        any::<u8>(),
+        uint_u8::<4, 4>(),
+        uint_u8::<3, 1>(),
+        uint_u8::<1, 0>(),
+        any::<u8>(),
+        any::<u8>(),
+        any::<u8>(),
+        bytes(32),
+    )
+        .prop_map(
+            |(
+                profile,
+                level,
+                bit_depth,
+                chroma_subsampling,
+                video_full_range_flag,
+                colour_primaries,
+                transfer_characteristics,
+                matrix_coefficients,
+                codec_initialization_data,
+            )| VpccBox {
+                profile,
+                level,
+                bit_depth,
+                chroma_subsampling,
+                video_full_range_flag,
+                colour_primaries,
+                transfer_characteristics,
+                matrix_coefficients,
+                codec_initialization_data,
+            }
+        )
+);
@@ -1,18 +1,22 @@
 //! MP4 の仕様とは直接は関係がない、実装上便利な補助的なコンポーネントを集めたモジュール
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, ops::Range};
 
 use crate::{
-    boxes::{SampleEntry, StblBox, StscBox, StscEntry, StszBox},
-    BoxType, Either,
+    boxes::{Brand, CttsBox, HdlrBox, SampleEntry, StblBox, StscBox, StscEntry, StszBox, TrakBox},
+    BaseBox, BoxHeader, BoxType, Decode, Either, Error,
 };
 
 /// [`StblBox`] をラップして、その中の情報を簡単かつ効率的に取り出せるようにするための構造体
+///
+/// `T` には [`StblBox`] そのもの、または `&StblBox` が指定できる。
+/// 参照を渡した場合には [`StblBox`] の複製は行われないので、走査だけを行いたい場合にはこちらを使うのが効率的。
 #[derive(Debug, Clone)]
 pub struct SampleTableAccessor<T> {
     stbl_box: T,
     chunk_count: u32,
     sample_count: u32,
     sample_durations: Vec<(u32, u32, u64)>, // (累計サンプル数、尺、累計尺）
+    sample_composition_offsets: Vec<(u32, i32)>, // (累計サンプル数、コンポジションオフセット）
     sample_index_offsets: Vec<NonZeroU32>,  // チャンク先頭のサンプルインデックス
     sample_data_offsets: Vec<u64>,
 }
@@ -30,6 +34,27 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
             acc_duration += entry.sample_delta as u64 * entry.sample_count as u64;
         }
 
+        let mut sample_composition_offsets = Vec::new();
+        if let Some(ctts_box) = &stbl_box_ref.ctts_box {
+            let mut ctts_sample_count = 0;
+            for entry in &ctts_box.entries {
+                sample_composition_offsets.push((ctts_sample_count, entry.sample_offset));
+                ctts_sample_count += entry.sample_count;
+            }
+            if ctts_sample_count != sample_count {
+                // stts と ctts でサンプル数が異なる
+                return Err(SampleTableAccessorError::InconsistentSampleCount {
+                    stts_sample_count: sample_count,
+                    other_box_type: CttsBox::TYPE,
+                    other_sample_count: ctts_sample_count,
+                });
+            }
+        }
+
+        if sample_count > 0 && stbl_box_ref.stsz_box.sample_count() == 0 {
+            // サンプルが存在するのに stsz が空
+            return Err(SampleTableAccessorError::StszEmpty);
+        }
         if let StszBox::Variable { entry_sizes } = &stbl_box_ref.stsz_box {
             if entry_sizes.len() != sample_count as usize {
                 // stts と stsz でサンプル数が異なる
@@ -46,6 +71,12 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
             Either::B(b) => b.chunk_offsets.len() as u32,
         };
 
+        if chunk_count > 0 && stbl_box_ref.stsc_box.entries.is_empty() {
+            // チャンクが存在するのに stsc が空
+            return Err(SampleTableAccessorError::StscInconsistent {
+                message: format!("`stsc` box has no entries, but there are {chunk_count} chunks"),
+            });
+        }
         if let Some(x) = stbl_box_ref.stsc_box.entries.first() {
             if x.first_chunk.get() != 1 {
                 // チャンクインデックスが 1 以外から始まっている
@@ -117,6 +148,7 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
             chunk_count,
             sample_count,
             sample_durations,
+            sample_composition_offsets,
             sample_index_offsets,
             sample_data_offsets: Vec::new(),
         };
@@ -126,7 +158,13 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
             let mut offset = chunk.offset();
             for sample in chunk.samples() {
                 sample_data_offsets.push(offset);
-                offset += sample.data_size() as u64;
+                offset = offset.checked_add(sample.data_size() as u64).ok_or(
+                    // チャンク内のサンプルデータの終端位置が u64 の範囲に収まらない
+                    SampleTableAccessorError::Co64OffsetOverflow {
+                        chunk_index: chunk.index().get(),
+                        offset: chunk.offset(),
+                    },
+                )?;
             }
         }
         this.sample_data_offsets = sample_data_offsets;
@@ -144,20 +182,69 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
         self.chunk_count
     }
 
+    /// トラック内の全サンプルの尺の合計を取得する
+    ///
+    /// 単位はトラックのタイムスケール（[`MdhdBox::timescale`](crate::boxes::MdhdBox::timescale)）となる
+    pub fn total_duration(&self) -> u64 {
+        self.stbl_box()
+            .stts_box
+            .entries
+            .iter()
+            .map(|x| x.sample_count as u64 * x.sample_delta as u64)
+            .sum()
+    }
+
+    /// トラック内の全サンプルの尺の合計を秒単位で取得する
+    pub fn total_duration_secs(&self, timescale: NonZeroU32) -> f64 {
+        self.total_duration() as f64 / timescale.get() as f64
+    }
+
+    /// トラック内の全サンプルのデータサイズの合計を取得する
+    pub fn total_data_size(&self) -> u64 {
+        self.stbl_box().stsz_box.total_data_size()
+    }
+
+    /// トラックの平均ビットレート（bps）を取得する
+    ///
+    /// トラックの尺が 0 の場合には [`None`] が返される
+    pub fn average_bitrate(&self, timescale: NonZeroU32) -> Option<u64> {
+        let duration = self.total_duration();
+        if duration == 0 {
+            return None;
+        }
+        let bits = self.total_data_size() as u128 * 8 * timescale.get() as u128;
+        Some(u64::try_from(bits / duration as u128).unwrap_or(u64::MAX))
+    }
+
     /// 指定されたサンプルの情報を返す
     ///
     /// 存在しないサンプルが指定された場合には [`None`] が返される
-    pub fn get_sample(&self, sample_index: NonZeroU32) -> Option<SampleAccessor<T>> {
+    pub fn get_sample(&self, sample_index: NonZeroU32) -> Option<SampleAccessor<'_, T>> {
         (sample_index.get() <= self.sample_count).then_some(SampleAccessor {
             sample_table: self,
             index: sample_index,
         })
     }
 
+    /// 指定されたサンプルの情報を返す
+    ///
+    /// [`SampleTableAccessor::get_sample()`] とは異なり、存在しないサンプルが指定された場合には
+    /// [`SampleTableAccessorError::SampleIndexOutOfBounds`] が返される
+    pub fn try_get_sample(
+        &self,
+        sample_index: NonZeroU32,
+    ) -> Result<SampleAccessor<'_, T>, SampleTableAccessorError> {
+        self.get_sample(sample_index)
+            .ok_or(SampleTableAccessorError::SampleIndexOutOfBounds {
+                index: sample_index.get(),
+                count: self.sample_count,
+            })
+    }
+
     /// 指定されたタイムスタンプ（トラック先頭からの累計尺）を含むサンプルの情報を返す
     ///
     /// 該当のサンプルが存在しない場合には [`None`] が返される
-    pub fn get_sample_by_timestamp(&self, timestamp: u64) -> Option<SampleAccessor<T>> {
+    pub fn get_sample_by_timestamp(&self, timestamp: u64) -> Option<SampleAccessor<'_, T>> {
         let mut low = 0;
         let mut high = self.sample_count;
         while high > low {
@@ -184,18 +271,132 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
         None
     }
 
+    /// 指定されたタイムスタンプ以前で、最も近い位置にある同期サンプル（キーフレーム）の情報を返す
+    ///
+    /// シーク時に、デコードを開始するサンプルを求めるのに使える。
+    /// 該当のサンプルが存在しない場合には [`None`] が返される。
+    pub fn keyframe_before(&self, timestamp: u64) -> Option<SampleAccessor<'_, T>> {
+        self.get_sample_by_timestamp(timestamp)?.sync_sample()
+    }
+
+    /// 指定されたタイムスタンプ以降で、最も近い位置にある同期サンプル（キーフレーム）の情報を返す
+    ///
+    /// 指定タイムスタンプを含むサンプルが同期サンプルの場合には、そのサンプルが返される。
+    /// 該当のサンプルが存在しない場合には [`None`] が返される。
+    pub fn keyframe_after(&self, timestamp: u64) -> Option<SampleAccessor<'_, T>> {
+        let sample = self.get_sample_by_timestamp(timestamp)?;
+        let Some(stss_box) = &self.stbl_box().stss_box else {
+            // stss ボックスが存在しない場合は全てが同期サンプル扱い
+            return Some(sample);
+        };
+
+        let i = stss_box
+            .sample_numbers
+            .binary_search(&sample.index)
+            .unwrap_or_else(|i| i);
+        let index = *stss_box.sample_numbers.get(i)?;
+        self.get_sample(index)
+    }
+
+    /// 指定されたサンプルのデコードタイムスタンプを返す
+    ///
+    /// [`SampleAccessor::timestamp()`] の値と等しい。
+    /// 存在しないサンプルが指定された場合には [`None`] が返される。
+    pub fn decode_timestamp_of_sample(&self, sample_index: NonZeroU32) -> Option<u64> {
+        self.get_sample(sample_index).map(|s| s.timestamp())
+    }
+
+    /// 指定されたサンプルのデータのファイル内でのバイト範囲を返す
+    ///
+    /// [`SampleAccessor::data_range()`] の値と等しい。
+    /// 存在しないサンプルが指定された場合には [`None`] が返される。
+    pub fn sample_byte_range(&self, sample_index: NonZeroU32) -> Option<Range<u64>> {
+        self.get_sample(sample_index).map(|s| s.data_range())
+    }
+
+    /// `start` から `end` の直前までの連続したサンプル群のデータを含むファイル内でのバイト範囲を返す
+    ///
+    /// GOP 全体などの複数のサンプルのデータを一度に読み込みたい場合に有用。
+    ///
+    /// 以下の場合には [`None`] が返される:
+    /// - 存在しないサンプルが範囲に含まれている
+    /// - `start` が `end` 以上
+    /// - ファイル内でサンプル群のデータが隙間なく連続していない（サンプル群が複数のチャンクにまたがっていて、それらのチャンクが隣接していない場合など）
+    pub fn samples_byte_range(&self, start: NonZeroU32, end: NonZeroU32) -> Option<Range<u64>> {
+        if start >= end || end.get() - 1 > self.sample_count {
+            return None;
+        }
+
+        let mut range = self.sample_byte_range(start)?;
+        for i in start.get() + 1..end.get() {
+            let next = self.sample_byte_range(NonZeroU32::new(i)?)?;
+            if next.start != range.end {
+                return None;
+            }
+            range.end = next.end;
+        }
+        Some(range)
+    }
+
+    /// 指定されたサンプルのデータを、ファイル全体を保持するバイト列 `source` から取り出す
+    ///
+    /// [`SampleAccessor::read_data()`] の結果と等しい。
+    ///
+    /// 存在しないサンプルが指定された場合や、サンプルデータの範囲が `source` に収まらない場合にはエラーが返される
+    pub fn read_sample<'b>(
+        &self,
+        sample_index: NonZeroU32,
+        source: &'b [u8],
+    ) -> crate::Result<&'b [u8]> {
+        let sample = self
+            .try_get_sample(sample_index)
+            .map_err(|e| Error::invalid_input(&e.to_string()))?;
+        sample.read_data(source)
+    }
+
+    /// 指定されたチャンクのデータを、ファイル全体を保持するバイト列 `source` から取り出す
+    ///
+    /// [`ChunkAccessor::read_data()`] の結果と等しい。
+    ///
+    /// 存在しないチャンクが指定された場合や、チャンクデータの範囲が `source` に収まらない場合にはエラーが返される
+    pub fn read_chunk<'b>(
+        &self,
+        chunk_index: NonZeroU32,
+        source: &'b [u8],
+    ) -> crate::Result<&'b [u8]> {
+        let chunk = self
+            .try_get_chunk(chunk_index)
+            .map_err(|e| Error::invalid_input(&e.to_string()))?;
+        chunk.read_data(source)
+    }
+
     /// 指定されたチャンクの情報を返す
     ///
     /// 存在しないチャンクが指定された場合には [`None`] が返される
-    pub fn get_chunk(&self, chunk_index: NonZeroU32) -> Option<ChunkAccessor<T>> {
+    pub fn get_chunk(&self, chunk_index: NonZeroU32) -> Option<ChunkAccessor<'_, T>> {
         (chunk_index.get() <= self.chunk_count()).then_some(ChunkAccessor {
             sample_table: self,
             index: chunk_index,
         })
     }
 
+    /// 指定されたチャンクの情報を返す
+    ///
+    /// [`SampleTableAccessor::get_chunk()`] とは異なり、存在しないチャンクが指定された場合には
+    /// [`SampleTableAccessorError::ChunkIndexOutOfBounds`] が返される
+    pub fn try_get_chunk(
+        &self,
+        chunk_index: NonZeroU32,
+    ) -> Result<ChunkAccessor<'_, T>, SampleTableAccessorError> {
+        self.get_chunk(chunk_index)
+            .ok_or(SampleTableAccessorError::ChunkIndexOutOfBounds {
+                index: chunk_index.get(),
+                count: self.chunk_count,
+            })
+    }
+
     /// トラック内のサンプル群の情報を走査するイテレーターを返す
-    pub fn samples(&self) -> impl '_ + Iterator<Item = SampleAccessor<T>> {
+    pub fn samples(&self) -> impl '_ + Iterator<Item = SampleAccessor<'_, T>> {
         (0..self.sample_count()).map(|i| SampleAccessor {
             sample_table: self,
             index: NonZeroU32::MIN.saturating_add(i),
@@ -203,7 +404,7 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
     }
 
     /// トラック内のチャンク群の情報を走査するイテレーターを返す
-    pub fn chunks(&self) -> impl '_ + Iterator<Item = ChunkAccessor<T>> {
+    pub fn chunks(&self) -> impl '_ + Iterator<Item = ChunkAccessor<'_, T>> {
         (0..self.chunk_count()).map(|i| ChunkAccessor {
             sample_table: self,
             index: NonZeroU32::MIN.saturating_add(i),
@@ -216,7 +417,7 @@ impl<T: AsRef<StblBox>> SampleTableAccessor<T> {
     }
 }
 
-/// [`SampleTableAccessor::new()`] で発生する可能性があるエラー
+/// [`SampleTableAccessor::new()`] や [`SampleTableAccessor::try_get_sample()`] などで発生する可能性があるエラー
 #[derive(Debug)]
 pub enum SampleTableAccessorError {
     /// [`SttsBox`] と他のボックスで、表現しているサンプル数が異なる
@@ -260,6 +461,101 @@ pub enum SampleTableAccessorError {
 
     /// [`StscBox`] のチャンクインデックスが短調増加していない
     ChunkIndicesNotMonotonicallyIncreasing,
+
+    /// 存在しないサンプルのインデックスが指定された
+    SampleIndexOutOfBounds {
+        /// 指定されたサンプルインデックス
+        index: u32,
+
+        /// サンプルの総数
+        count: u32,
+    },
+
+    /// 存在しないチャンクのインデックスが指定された
+    ChunkIndexOutOfBounds {
+        /// 指定されたチャンクインデックス
+        index: u32,
+
+        /// チャンクの総数
+        count: u32,
+    },
+
+    /// [`StscBox`] の内容がチャンク群と整合していない
+    StscInconsistent {
+        /// 不整合の詳細
+        message: String,
+    },
+
+    /// サンプルが存在するのに [`StszBox`] が空になっている
+    StszEmpty,
+
+    /// チャンク内のサンプルデータの位置が [`u64`] の範囲に収まらない
+    Co64OffsetOverflow {
+        /// 該当チャンクのインデックス
+        chunk_index: u32,
+
+        /// 該当チャンクのオフセット
+        offset: u64,
+    },
+
+    /// [`SampleTableAccessorError::with_context()`] で付与された文脈情報を伴うエラー
+    WithContext {
+        /// 文脈情報のメッセージ群（先頭が最も外側で付与されたもの）
+        context: Vec<String>,
+
+        /// 元のエラー
+        error: Box<SampleTableAccessorError>,
+    },
+}
+
+impl SampleTableAccessorError {
+    /// エラーに文脈情報（対象のファイル名やトラック番号など）を付与する
+    ///
+    /// メッセージは既存の文脈情報の先頭に追加され、[`Display`](std::fmt::Display) の出力では
+    /// `"{外側の文脈}: {内側の文脈}: {元のエラー}"` の形式で表示される
+    pub fn with_context(self, message: impl Into<String>) -> Self {
+        match self {
+            Self::WithContext { mut context, error } => {
+                context.insert(0, message.into());
+                Self::WithContext { context, error }
+            }
+            error => Self::WithContext {
+                context: vec![message.into()],
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// [`SampleTableAccessorError::with_context()`] で付与された文脈情報を返す（先頭が最も外側）
+    pub fn context(&self) -> &[String] {
+        match self {
+            Self::WithContext { context, .. } => context,
+            _ => &[],
+        }
+    }
+
+    /// 文脈情報を取り除いた元のエラーを返す
+    pub fn inner_error(&self) -> &Self {
+        match self {
+            Self::WithContext { error, .. } => error,
+            _ => self,
+        }
+    }
+
+    /// 存在しないサンプルやチャンクを指定したことによるエラーかどうかを判定する
+    ///
+    /// このエラーはサンプルテーブル自体の問題ではなく、呼び出し側の指定の誤りを表している
+    pub fn is_bounds_error(&self) -> bool {
+        matches!(
+            self.inner_error(),
+            Self::SampleIndexOutOfBounds { .. } | Self::ChunkIndexOutOfBounds { .. }
+        )
+    }
+
+    /// サンプルテーブルの内容が壊れている（不整合がある）ことによるエラーかどうかを判定する
+    pub fn is_corruption_error(&self) -> bool {
+        !self.is_bounds_error()
+    }
 }
 
 impl std::fmt::Display for SampleTableAccessorError {
@@ -286,12 +582,364 @@ impl std::fmt::Display for SampleTableAccessorError {
             SampleTableAccessorError::ChunkIndicesNotMonotonicallyIncreasing => {
                 write!(f,"Chunk indices in `stsc` box is not monotonically increasing")
             }
+            SampleTableAccessorError::SampleIndexOutOfBounds { index, count } => {
+                write!(f, "Sample index {index} is out of bounds (sample count is {count})")
+            }
+            SampleTableAccessorError::ChunkIndexOutOfBounds { index, count } => {
+                write!(f, "Chunk index {index} is out of bounds (chunk count is {count})")
+            }
+            SampleTableAccessorError::StscInconsistent { message } => {
+                write!(f, "Inconsistent `stsc` box: {message}")
+            }
+            SampleTableAccessorError::StszEmpty => {
+                write!(f, "`stsz` box has no samples, but `stts` box has samples")
+            }
+            SampleTableAccessorError::Co64OffsetOverflow { chunk_index, offset } => {
+                write!(f, "Sample data offset overflows in chunk {chunk_index} (chunk offset is {offset})")
+            }
+            SampleTableAccessorError::WithContext { context, error } => {
+                for message in context {
+                    write!(f, "{message}: ")?;
+                }
+                write!(f, "{error}")
+            }
         }
     }
 }
 
 impl std::error::Error for SampleTableAccessorError {}
 
+/// [`StblBox::validate()`] などのボックスの整合性チェックで検出されるエラー
+///
+/// [`StblBox::validate()`]: crate::boxes::StblBox::validate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// [`SttsBox`] と他のボックスで、表現しているサンプル数が異なる
+    ///
+    /// [`SttsBox`]: crate::boxes::SttsBox
+    SampleCountMismatch {
+        /// [`SttsBox`] とは異なるサンプル数を表しているボックスの種別
+        ///
+        /// [`SttsBox`]: crate::boxes::SttsBox
+        from: BoxType,
+
+        /// [`SttsBox`] 準拠のサンプル数
+        ///
+        /// [`SttsBox`]: crate::boxes::SttsBox
+        expected: u64,
+
+        /// `from` 準拠のサンプル数
+        got: u64,
+    },
+
+    /// [`StscBox`] が [`StcoBox`] ないし [`Co64Box`] に存在しないチャンクを参照している
+    ///
+    /// [`StcoBox`]: crate::boxes::StcoBox
+    /// [`Co64Box`]: crate::boxes::Co64Box
+    ChunkCountMismatch {
+        /// [`StcoBox`] ないし [`Co64Box`] が表すチャンク数
+        ///
+        /// [`StcoBox`]: crate::boxes::StcoBox
+        /// [`Co64Box`]: crate::boxes::Co64Box
+        expected: u32,
+
+        /// [`StscBox`] が参照しているチャンクインデックスの最大値
+        got: u32,
+    },
+
+    /// [`StscBox`] のエントリの `first_chunk` が昇順に並んでいない
+    StscFirstChunkOutOfOrder {
+        /// 直前のエントリ以下の `first_chunk` を持つエントリのインデックス
+        entry_index: usize,
+    },
+
+    /// チャンクのオフセットにサンプルデータのサイズを加えた値が `u64` の範囲を超えている
+    ChunkOffsetOverflow,
+
+    /// チャンクのオフセットがファイルの範囲外を指している
+    ChunkOffsetOutOfRange {
+        /// チャンクオフセットを保持しているボックスの種別（`stco` ないし `co64`）
+        from: BoxType,
+
+        /// 該当チャンクのインデックス（1 始まり）
+        chunk_index: u32,
+
+        /// 該当チャンクのオフセット
+        offset: u64,
+
+        /// ファイルサイズ
+        file_size: u64,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::SampleCountMismatch {
+                from,
+                expected,
+                got,
+            } => {
+                write!(f, "Sample count in `stts` box is {expected}, but `{from}` box has sample count {got}")
+            }
+            ValidationError::ChunkCountMismatch { expected, got } => {
+                write!(
+                    f,
+                    "Chunk count is {expected}, but `stsc` box refers to chunk index {got}"
+                )
+            }
+            ValidationError::StscFirstChunkOutOfOrder { entry_index } => {
+                write!(
+                    f,
+                    "{entry_index}-th entry in `stsc` box has out of order first chunk index"
+                )
+            }
+            ValidationError::ChunkOffsetOverflow => {
+                write!(f, "Chunk offset overflow")
+            }
+            ValidationError::ChunkOffsetOutOfRange {
+                from,
+                chunk_index,
+                offset,
+                file_size,
+            } => {
+                write!(
+                    f,
+                    "{chunk_index}-th chunk offset in `{from}` box is out of range: offset={offset}, file_size={file_size}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// [`MoovBox::validate_deep()`] で検出された問題の重大度
+///
+/// [`MoovBox::validate_deep()`]: crate::boxes::MoovBox::validate_deep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationSeverity {
+    /// 仕様違反とまではいえないが、プレイヤーによっては問題となり得る
+    Warning,
+
+    /// 仕様違反、あるいは再生や編集に支障をきたす不整合
+    Error,
+}
+
+/// [`MoovBox::validate_deep()`] で検出された問題の種類
+///
+/// [`MoovBox::validate_deep()`]: crate::boxes::MoovBox::validate_deep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationCode {
+    /// `trak` ボックスが一つも存在しない
+    NoTracks,
+
+    /// `mvhd` ボックスの `next_track_id` が既存のトラック ID 以下になっている
+    NextTrackIdTooSmall,
+
+    /// `tkhd` ボックスのトラック ID が 0 になっている
+    TrackIdZero,
+
+    /// 同じトラック ID を持つトラックが複数存在する
+    TrackIdDuplicate,
+
+    /// `mdhd` ボックスの尺を `mvhd` ボックスのタイムスケールに変換した値が、`mvhd` ボックスの尺を超えている
+    ///
+    /// 編集リストを持つトラックはチェックの対象外
+    MdhdDurationMismatchMvhd,
+
+    /// `mdhd` ボックスの尺が `stts` ボックスのサンプルの尺の合計と一致しない
+    MdhdDurationMismatchStts,
+
+    /// `elst` ボックスの `media_time` がメディアの尺を超えている
+    ElstMediaTimeOutOfRange,
+
+    /// `hdlr` ボックスのハンドラー種別と、`vmhd` ないし `smhd` ボックスの種類が対応していない
+    HandlerMediaHeaderMismatch,
+
+    /// `stsd` ボックスにサンプルエントリーが一つも含まれていない
+    EmptySampleDescription,
+
+    /// サンプルが一つも存在しない
+    EmptySampleTable,
+
+    /// [`ValidationError::SampleCountMismatch`] に対応する
+    SampleCountMismatch,
+
+    /// [`ValidationError::ChunkCountMismatch`] に対応する
+    ChunkCountMismatch,
+
+    /// [`ValidationError::StscFirstChunkOutOfOrder`] に対応する
+    StscFirstChunkOutOfOrder,
+
+    /// `stsc` ボックスの先頭エントリーの `first_chunk` が 1 ではない
+    StscFirstChunkNotOne,
+
+    /// `stsc` ボックスのエントリーが、存在しないサンプルエントリーを参照している
+    StscSampleDescriptionIndexOutOfRange,
+
+    /// `stsc` ボックスに `sample_per_chunk` が 0 のエントリーが含まれている
+    StscZeroSamplesPerChunk,
+
+    /// [`ValidationError::ChunkOffsetOverflow`] に対応する
+    ChunkOffsetOverflow,
+
+    /// [`ValidationError::ChunkOffsetOutOfRange`] に対応する
+    ChunkOffsetOutOfRange,
+
+    /// 同じトラック内の複数のチャンクのデータ範囲が重なっている
+    ChunkDataOverlap,
+
+    /// `stss` ボックスが存在しないサンプルを参照している
+    StssSampleNumberOutOfRange,
+
+    /// `stss` ボックスのサンプル番号が昇順に並んでいない
+    StssNotSorted,
+}
+
+impl ValidationCode {
+    /// この種類の問題の重大度を返す
+    pub const fn severity(self) -> ValidationSeverity {
+        match self {
+            Self::NoTracks
+            | Self::MdhdDurationMismatchMvhd
+            | Self::MdhdDurationMismatchStts
+            | Self::ElstMediaTimeOutOfRange
+            | Self::HandlerMediaHeaderMismatch
+            | Self::EmptySampleTable
+            | Self::StscZeroSamplesPerChunk => ValidationSeverity::Warning,
+            Self::NextTrackIdTooSmall
+            | Self::TrackIdZero
+            | Self::TrackIdDuplicate
+            | Self::EmptySampleDescription
+            | Self::SampleCountMismatch
+            | Self::ChunkCountMismatch
+            | Self::StscFirstChunkOutOfOrder
+            | Self::StscFirstChunkNotOne
+            | Self::StscSampleDescriptionIndexOutOfRange
+            | Self::ChunkOffsetOverflow
+            | Self::ChunkOffsetOutOfRange
+            | Self::ChunkDataOverlap
+            | Self::StssSampleNumberOutOfRange
+            | Self::StssNotSorted => ValidationSeverity::Error,
+        }
+    }
+}
+
+impl From<&ValidationError> for ValidationCode {
+    fn from(value: &ValidationError) -> Self {
+        match value {
+            ValidationError::SampleCountMismatch { .. } => Self::SampleCountMismatch,
+            ValidationError::ChunkCountMismatch { .. } => Self::ChunkCountMismatch,
+            ValidationError::StscFirstChunkOutOfOrder { .. } => Self::StscFirstChunkOutOfOrder,
+            ValidationError::ChunkOffsetOverflow => Self::ChunkOffsetOverflow,
+            ValidationError::ChunkOffsetOutOfRange { .. } => Self::ChunkOffsetOutOfRange,
+        }
+    }
+}
+
+/// [`MoovBox::validate_deep()`] で検出された個々の問題
+///
+/// [`MoovBox::validate_deep()`]: crate::boxes::MoovBox::validate_deep
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationIssue {
+    /// 問題が検出されたボックスに至るまでのパス（`moov > trak[0] > tkhd` のような形式）
+    pub path: String,
+
+    /// 問題の種類
+    pub code: ValidationCode,
+
+    /// 問題の重大度
+    pub severity: ValidationSeverity,
+
+    /// 問題の詳細を説明するメッセージ
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn new(path: String, code: ValidationCode, message: String) -> Self {
+        Self {
+            path,
+            code,
+            severity: code.severity(),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {:?}: {} ({:?})",
+            self.path, self.severity, self.message, self.code
+        )
+    }
+}
+
+/// [`MoovBox::validate_deep()`] の結果
+///
+/// [`MoovBox::validate_deep()`]: crate::boxes::MoovBox::validate_deep
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationReport {
+    /// 検出された問題の一覧（検出順）
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// 重大度が [`ValidationSeverity::Error`] の最初の問題を返す
+    pub fn first_error(&self) -> Option<&ValidationIssue> {
+        self.issues
+            .iter()
+            .find(|x| x.severity == ValidationSeverity::Error)
+    }
+
+    /// 問題が一つも検出されなかったかどうかを判定する
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// [`FileTypeBoxContent::validate()`] で検出される、ブランド指定に関する警告
+///
+/// 仕様違反とまではいえないが、プレイヤーによっては問題となり得る指定を表す
+///
+/// [`FileTypeBoxContent::validate()`]: crate::boxes::FileTypeBoxContent::validate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BrandWarning {
+    /// メジャーブランドが互換ブランドのリストに含まれていない
+    MajorBrandNotInCompatibleBrands,
+
+    /// 互換ブランドのリストに同じブランドが重複して含まれている
+    DuplicateCompatibleBrand(Brand),
+
+    /// 互換ブランドのリストが空
+    EmptyCompatibleBrands,
+
+    /// 古いブランドだけが指定されていて、対応する `isom` 系のブランドが含まれていない
+    LegacyBrandWithoutModernEquivalent(Brand),
+}
+
+impl std::fmt::Display for BrandWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrandWarning::MajorBrandNotInCompatibleBrands => {
+                write!(f, "Major brand is not included in the compatible brands")
+            }
+            BrandWarning::DuplicateCompatibleBrand(brand) => {
+                write!(f, "Compatible brand {brand:?} is duplicated")
+            }
+            BrandWarning::EmptyCompatibleBrands => write!(f, "Compatible brands are empty"),
+            BrandWarning::LegacyBrandWithoutModernEquivalent(brand) => {
+                write!(
+                    f,
+                    "Legacy brand {brand:?} is used without any `isom` family brand"
+                )
+            }
+        }
+    }
+}
+
 /// [`StblBox`] 内の個々のサンプルの情報を取得するための構造体
 #[derive(Debug)]
 pub struct SampleAccessor<'a, T> {
@@ -326,6 +974,25 @@ impl<'a, T: AsRef<StblBox>> SampleAccessor<'a, T> {
         base_timestamp + duration as u64 * (self.index.get() - 1 - base_index_minus_1) as u64
     }
 
+    /// サンプルのコンポジションオフセット（表示時刻とデコード時刻の差）を取得する
+    ///
+    /// `ctts` ボックスが存在しない場合は 0 となる
+    pub fn composition_offset(&self) -> i32 {
+        let offsets = &self.sample_table.sample_composition_offsets;
+        if offsets.is_empty() {
+            return 0;
+        }
+        let i = offsets
+            .binary_search_by_key(&(self.index.get() - 1), |x| x.0)
+            .unwrap_or_else(|i| i.checked_sub(1).expect("unreachable"));
+        offsets[i].1
+    }
+
+    /// サンプルの表示タイムスタンプ（[`SampleAccessor::timestamp()`] にコンポジションオフセットを加えた値）を取得する
+    pub fn presentation_timestamp(&self) -> i64 {
+        self.timestamp() as i64 + self.composition_offset() as i64
+    }
+
     /// サンプルのデータサイズ（バイト数）を取得する
     pub fn data_size(&self) -> u32 {
         let i = self.index.get() as usize - 1;
@@ -340,6 +1007,21 @@ impl<'a, T: AsRef<StblBox>> SampleAccessor<'a, T> {
         self.sample_table.sample_data_offsets[self.index.get() as usize - 1]
     }
 
+    /// サンプルデータのファイル内でのバイト範囲を返す
+    ///
+    /// メモリマップしたファイルなどから、サンプルデータをコピーせずに取り出す場合に有用
+    pub fn data_range(&self) -> Range<u64> {
+        let start = self.data_offset();
+        start..start + self.data_size() as u64
+    }
+
+    /// サンプルデータを、ファイル全体を保持するバイト列 `source` から取り出す
+    ///
+    /// サンプルデータの範囲が `source` に収まらない場合にはエラーが返される
+    pub fn read_data<'b>(&self, source: &'b [u8]) -> crate::Result<&'b [u8]> {
+        slice_data(source, self.data_range())
+    }
+
     /// サンプルが同期サンプルかどうかを判定する
     pub fn is_sync_sample(&self) -> bool {
         let Some(stss_box) = &self.sample_table.stbl_box().stss_box else {
@@ -406,6 +1088,24 @@ impl<'a, T: AsRef<StblBox>> ChunkAccessor<'a, T> {
         }
     }
 
+    /// チャンクのデータサイズ（チャンクに属するサンプル群のデータサイズの合計）を返す
+    pub fn data_size(&self) -> u64 {
+        self.samples().map(|s| s.data_size() as u64).sum()
+    }
+
+    /// チャンクデータのファイル内でのバイト範囲を返す
+    pub fn data_range(&self) -> Range<u64> {
+        let start = self.offset();
+        start..start + self.data_size()
+    }
+
+    /// チャンクデータを、ファイル全体を保持するバイト列 `source` から取り出す
+    ///
+    /// チャンクデータの範囲が `source` に収まらない場合にはエラーが返される
+    pub fn read_data<'b>(&self, source: &'b [u8]) -> crate::Result<&'b [u8]> {
+        slice_data(source, self.data_range())
+    }
+
     /// チャンクが参照するサンプルエントリー返す
     pub fn sample_entry(&self) -> &'a SampleEntry {
         &self.sample_table.stbl_box().stsd_box.entries
@@ -418,7 +1118,7 @@ impl<'a, T: AsRef<StblBox>> ChunkAccessor<'a, T> {
     }
 
     /// チャンクに属するサンプル群を走査するイテレーターを返す
-    pub fn samples(&self) -> impl '_ + Iterator<Item = SampleAccessor<T>> {
+    pub fn samples(&self) -> impl '_ + Iterator<Item = SampleAccessor<'_, T>> {
         let count = self.sample_count();
         let sample_index_offset =
             self.sample_table.sample_index_offsets[self.index.get() as usize - 1];
@@ -442,11 +1142,446 @@ impl<'a, T: AsRef<StblBox>> ChunkAccessor<'a, T> {
     }
 }
 
+fn slice_data(source: &[u8], range: Range<u64>) -> crate::Result<&[u8]> {
+    usize::try_from(range.start)
+        .ok()
+        .zip(usize::try_from(range.end).ok())
+        .and_then(|(start, end)| source.get(start..end))
+        .ok_or_else(|| {
+            Error::invalid_data(&format!(
+                "Data range out of bounds: range={}..{}, source_len={}",
+                range.start,
+                range.end,
+                source.len()
+            ))
+        })
+}
+
+/// [`StblBox`] 内のサンプル群を、事前にテーブルを構築せずに順に走査するイテレーター
+///
+/// [`SampleTableAccessor`] とは異なり、生成時に全サンプル分の情報を保持した `Vec` を作らないため、
+/// 先頭から一度だけ走査できれば十分な場合にはこちらの方が省メモリとなる。
+///
+/// [`StblBox`] 内のボックス間で不整合が検出された場合には、エラーを返した後に走査を終了する。
+#[derive(Debug, Clone)]
+pub struct SampleIter<'a> {
+    stbl_box: &'a StblBox,
+    next_index: u32,
+    stts_entry_index: usize,
+    stts_remaining: u32,
+    sample_duration: u32,
+    timestamp: u64,
+    stsc_entry_index: usize,
+    chunk_index: u32,
+    chunk_remaining: u32,
+    data_offset: u64,
+    stss_position: usize,
+    ctts_entry_index: usize,
+    ctts_remaining: u32,
+    ctts_sample_offset: i32,
+    finished: bool,
+}
+
+impl<'a> SampleIter<'a> {
+    /// 引数で渡された [`StblBox`] 用の [`SampleIter`] インスタンスを生成する
+    pub fn new(stbl_box: &'a StblBox) -> Self {
+        Self {
+            stbl_box,
+            next_index: 1,
+            stts_entry_index: 0,
+            stts_remaining: 0,
+            sample_duration: 0,
+            timestamp: 0,
+            stsc_entry_index: 0,
+            chunk_index: 0,
+            chunk_remaining: 0,
+            data_offset: 0,
+            stss_position: 0,
+            ctts_entry_index: 0,
+            ctts_remaining: 0,
+            ctts_sample_offset: 0,
+            finished: false,
+        }
+    }
+
+    fn stts_sample_count(&self) -> u32 {
+        self.stbl_box
+            .stts_box
+            .entries
+            .iter()
+            .map(|x| x.sample_count)
+            .sum()
+    }
+
+    fn chunk_offset(&self, chunk_index: u32) -> Option<u64> {
+        let i = chunk_index as usize - 1;
+        match &self.stbl_box.stco_or_co64_box {
+            Either::A(b) => b.chunk_offsets.get(i).map(|&x| x as u64),
+            Either::B(b) => b.chunk_offsets.get(i).copied(),
+        }
+    }
+
+    fn next_chunk(&mut self) -> Result<(), SampleTableAccessorError> {
+        let entries = &self.stbl_box.stsc_box.entries;
+        while self.chunk_remaining == 0 {
+            self.chunk_index += 1;
+            let chunk_index = NonZeroU32::MIN.saturating_add(self.chunk_index - 1);
+
+            if let Some(x) = entries.first() {
+                if x.first_chunk.get() != 1 {
+                    // チャンクインデックスが 1 以外から始まっている
+                    return Err(SampleTableAccessorError::FirstChunkIndexIsNotOne {
+                        actual_chunk_index: x.first_chunk,
+                    });
+                }
+            }
+            while entries
+                .get(self.stsc_entry_index + 1)
+                .is_some_and(|x| x.first_chunk <= chunk_index)
+            {
+                self.stsc_entry_index += 1;
+            }
+
+            if entries.is_empty() && self.chunk_offset(self.chunk_index).is_some() {
+                // チャンクが存在するのに stsc が空
+                return Err(SampleTableAccessorError::StscInconsistent {
+                    message: "`stsc` box has no entries".to_owned(),
+                });
+            }
+            let (Some(entry), Some(offset)) = (
+                entries.get(self.stsc_entry_index),
+                self.chunk_offset(self.chunk_index),
+            ) else {
+                // stts が示すサンプル数に対して、チャンクが足りない
+                return Err(SampleTableAccessorError::InconsistentSampleCount {
+                    stts_sample_count: self.stts_sample_count(),
+                    other_box_type: StscBox::TYPE,
+                    other_sample_count: self.next_index - 1,
+                });
+            };
+            if self.stbl_box.stsd_box.entries.len() < entry.sample_description_index.get() as usize
+            {
+                // 存在しないサンプルエントリーを参照しているチャンクがある
+                return Err(SampleTableAccessorError::MissingSampleEntry {
+                    stsc_entry_index: self.stsc_entry_index,
+                    sample_description_index: entry.sample_description_index,
+                    sample_entry_count: self.stbl_box.stsd_box.entries.len(),
+                });
+            }
+
+            self.chunk_remaining = entry.sample_per_chunk;
+            self.data_offset = offset;
+        }
+        Ok(())
+    }
+
+    fn next_sample(&mut self) -> Option<Result<SampleInfo, SampleTableAccessorError>> {
+        while self.stts_remaining == 0 {
+            let entry = self.stbl_box.stts_box.entries.get(self.stts_entry_index)?;
+            self.stts_entry_index += 1;
+            self.stts_remaining = entry.sample_count;
+            self.sample_duration = entry.sample_delta;
+        }
+
+        let index = NonZeroU32::MIN.saturating_add(self.next_index - 1);
+        if let Err(e) = self.next_chunk() {
+            return Some(Err(e));
+        }
+
+        let data_size = match &self.stbl_box.stsz_box {
+            StszBox::Fixed { sample_size, .. } => sample_size.get(),
+            StszBox::Variable { entry_sizes } if entry_sizes.is_empty() => {
+                // サンプルが存在するのに stsz が空
+                return Some(Err(SampleTableAccessorError::StszEmpty));
+            }
+            StszBox::Variable { entry_sizes } => {
+                let Some(&size) = entry_sizes.get(self.next_index as usize - 1) else {
+                    // stts と stsz でサンプル数が異なる
+                    return Some(Err(SampleTableAccessorError::InconsistentSampleCount {
+                        stts_sample_count: self.stts_sample_count(),
+                        other_box_type: StszBox::TYPE,
+                        other_sample_count: entry_sizes.len() as u32,
+                    }));
+                };
+                size
+            }
+        };
+
+        let is_sync_sample = if let Some(stss_box) = &self.stbl_box.stss_box {
+            while stss_box
+                .sample_numbers
+                .get(self.stss_position)
+                .is_some_and(|&x| x < index)
+            {
+                self.stss_position += 1;
+            }
+            stss_box.sample_numbers.get(self.stss_position) == Some(&index)
+        } else {
+            // stss ボックスが存在しない場合は全てが同期サンプル扱い
+            true
+        };
+
+        let mut composition_offset = 0;
+        if let Some(ctts_box) = &self.stbl_box.ctts_box {
+            while self.ctts_remaining == 0 {
+                let Some(entry) = ctts_box.entries.get(self.ctts_entry_index) else {
+                    // stts と ctts でサンプル数が異なる
+                    return Some(Err(SampleTableAccessorError::InconsistentSampleCount {
+                        stts_sample_count: self.stts_sample_count(),
+                        other_box_type: CttsBox::TYPE,
+                        other_sample_count: self.next_index - 1,
+                    }));
+                };
+                self.ctts_entry_index += 1;
+                self.ctts_remaining = entry.sample_count;
+                self.ctts_sample_offset = entry.sample_offset;
+            }
+            self.ctts_remaining -= 1;
+            composition_offset = self.ctts_sample_offset;
+        }
+
+        let sample = SampleInfo {
+            index,
+            timestamp: self.timestamp,
+            composition_offset,
+            duration: self.sample_duration,
+            data_offset: self.data_offset,
+            data_size,
+            is_sync_sample,
+            chunk_index: NonZeroU32::MIN.saturating_add(self.chunk_index - 1),
+            sample_description_index: self.stbl_box.stsc_box.entries[self.stsc_entry_index]
+                .sample_description_index,
+        };
+
+        self.next_index += 1;
+        self.stts_remaining -= 1;
+        self.chunk_remaining -= 1;
+        self.timestamp += self.sample_duration as u64;
+        self.data_offset += data_size as u64;
+
+        Some(Ok(sample))
+    }
+}
+
+impl Iterator for SampleIter<'_> {
+    type Item = Result<SampleInfo, SampleTableAccessorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = self.next_sample();
+        if !matches!(item, Some(Ok(_))) {
+            self.finished = true;
+        }
+        item
+    }
+}
+
+/// [`SampleIter`] が返すサンプルの情報
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SampleInfo {
+    /// サンプルのインデックス
+    pub index: NonZeroU32,
+
+    /// サンプルのタイムスタンプ（累計尺）
+    pub timestamp: u64,
+
+    /// サンプルのコンポジションオフセット（`ctts` ボックスが存在しない場合は 0）
+    pub composition_offset: i32,
+
+    /// サンプルの尺
+    pub duration: u32,
+
+    /// サンプルデータのファイル内でのバイト位置
+    pub data_offset: u64,
+
+    /// サンプルのデータサイズ（バイト数）
+    pub data_size: u32,
+
+    /// サンプルが同期サンプルかどうか
+    pub is_sync_sample: bool,
+
+    /// サンプルが属するチャンクのインデックス
+    pub chunk_index: NonZeroU32,
+
+    /// サンプルが参照するサンプルエントリーのインデックス
+    pub sample_description_index: NonZeroU32,
+}
+
+impl SampleInfo {
+    /// サンプルの表示タイムスタンプ（`timestamp` に `composition_offset` を加えた値）を返す
+    pub fn presentation_timestamp(&self) -> i64 {
+        self.timestamp as i64 + self.composition_offset as i64
+    }
+
+    /// サンプルデータのファイル内でのバイト範囲を返す
+    pub fn data_range(&self) -> Range<u64> {
+        self.data_offset..self.data_offset + self.data_size as u64
+    }
+
+    /// サンプルデータを、ファイル全体を保持するバイト列 `source` からコピーせずに取り出す
+    ///
+    /// [`SampleAccessor::read_data()`] と同様に、サンプルデータの範囲が `source` に収まらない場合にはエラーが返される
+    pub fn read_data<'a>(&self, source: &'a [u8]) -> crate::Result<&'a [u8]> {
+        slice_data(source, self.data_range())
+    }
+
+    /// サンプルデータを、ファイル全体を保持するバイト列 `source` から取り出して [`Vec`] にコピーする
+    ///
+    /// サンプルデータの範囲が `source` に収まらない場合にはエラーが返される
+    pub fn read_to_vec(&self, source: &[u8]) -> crate::Result<Vec<u8>> {
+        self.read_data(source).map(|data| data.to_vec())
+    }
+
+    /// サンプルデータの範囲が、長さ `source_len` のバイト列に収まるかどうかを判定する
+    ///
+    /// [`SampleInfo::read_data()`] とは異なり、エラー用のメッセージの生成を伴わないので軽量
+    pub fn is_within_bounds(&self, source_len: usize) -> bool {
+        self.data_range().end <= source_len as u64
+    }
+}
+
+/// トラックの種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrackKind {
+    /// 映像トラック
+    Video,
+
+    /// 音声トラック
+    Audio,
+}
+
+/// ボックスが並んだバイト列を先頭から走査して、指定された種別の最初のボックスのペイロードを返す
+///
+/// 各ボックスはヘッダーのみが解釈され、ペイロードのデコードは行われない。
+/// そのため、このライブラリが未対応の独自ボックスの中身を取り出す用途に使える。
+///
+/// 該当するボックスが存在しない場合や、途中で不正なヘッダーに遭遇した場合には [`None`] が返される
+pub fn scan_for_box_type(data: &[u8], target: BoxType) -> Option<&[u8]> {
+    let mut remaining = data;
+    while !remaining.is_empty() {
+        let mut reader = remaining;
+        let header = BoxHeader::decode(&mut reader).ok()?;
+        let header_size = remaining.len() - reader.len();
+        let box_size = if header.box_size.get() == 0 {
+            // 可変長の場合は末尾までがペイロードとなる
+            remaining.len()
+        } else {
+            usize::try_from(header.box_size.get())
+                .ok()
+                .filter(|&size| header_size <= size && size <= remaining.len())?
+        };
+
+        if header.box_type == target {
+            return Some(&remaining[header_size..box_size]);
+        }
+        remaining = &remaining[box_size..];
+    }
+    None
+}
+
+/// GoPro のテレメトリー（GPMF）トラックのハンドラ名の接頭辞
+const GOPRO_MET_HANDLER_NAME_PREFIX: &[u8] = b"GoPro MET";
+
+/// トラックが GoPro のカメラが埋め込むテレメトリー（GPS やジャイロセンサーなどの GPMF 形式のデータ）のトラックかどうかを判定する
+///
+/// ハンドラー種別が `meta` で、かつハンドラ名が `"GoPro MET"` で始まるトラックが該当する。
+/// [`HdlrBox::name`] の先頭にサイズバイトが格納されている形式にも対応している。
+pub fn is_gopro_telemetry_track(trak_box: &TrakBox) -> bool {
+    let hdlr_box = &trak_box.mdia_box.hdlr_box;
+    if hdlr_box.handler_type != HdlrBox::HANDLER_TYPE_META {
+        return false;
+    }
+    let name = hdlr_box.name.as_slice();
+    name.starts_with(GOPRO_MET_HANDLER_NAME_PREFIX)
+        || name
+            .get(1..)
+            .is_some_and(|name| name.starts_with(GOPRO_MET_HANDLER_NAME_PREFIX))
+}
+
+/// GoPro のテレメトリートラックの各サンプルの GPMF ペイロードを、ファイル全体を保持するバイト列 `file_data` から取り出す
+///
+/// GPMF 自体の解析は行わず、サンプル毎の生のバイト列をデコード順に返す。
+///
+/// 以下の場合にはエラーが返される:
+/// - [`is_gopro_telemetry_track()`] の条件を満たさないトラックが指定された
+/// - サンプルテーブルが不正
+/// - サンプルデータの範囲が `file_data` に収まらない
+pub fn gopro_telemetry_samples<'a>(
+    trak_box: &TrakBox,
+    file_data: &'a [u8],
+) -> crate::Result<impl Iterator<Item = &'a [u8]>> {
+    if !is_gopro_telemetry_track(trak_box) {
+        return Err(Error::invalid_input("Not a GoPro telemetry track"));
+    }
+    let sample_table = SampleTableAccessor::new(&trak_box.mdia_box.minf_box.stbl_box)
+        .map_err(|e| Error::invalid_data(&e.to_string()))?;
+    let samples = sample_table
+        .samples()
+        .map(|sample| sample.read_data(file_data))
+        .collect::<crate::Result<Vec<_>>>()?;
+    Ok(samples.into_iter())
+}
+
+/// ボックスの木構造を表す構造体
+///
+/// `serde` feature が有効な場合には、JSON などの形式にシリアライズできる
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BoxTreeNode {
+    /// ボックス種別
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub box_type: String,
+
+    /// ヘッダーを含むボックスのサイズ
+    pub size: u64,
+
+    /// このライブラリが未対応のボックスかどうか
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "unknown", skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub is_unknown: bool,
+
+    /// 子ボックス群
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<Self>,
+}
+
+impl BoxTreeNode {
+    /// 指定されたボックスを根とする木構造を作成する
+    pub fn from_root(root: &dyn BaseBox) -> Self {
+        Self {
+            box_type: root.box_type().to_string(),
+            size: root.box_size().get(),
+            is_unknown: root.is_unknown_box(),
+            children: root.children().map(Self::from_root).collect(),
+        }
+    }
+
+    /// 木構造を整形済みの JSON 文字列に変換する
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// MP4 ファイルのバイト列をデコードして、トップレベルのボックス群の木構造を整形済みの JSON 配列として返す
+#[cfg(feature = "serde")]
+pub fn mp4_box_tree_json(data: &[u8]) -> crate::Result<String> {
+    let mp4 = crate::Mp4File::<crate::boxes::RootBox>::decode(data)?;
+    let nodes = mp4.iter().map(BoxTreeNode::from_root).collect::<Vec<_>>();
+    let json = serde_json::to_string_pretty(&nodes).map_err(std::io::Error::from)?;
+    Ok(json)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        boxes::{StcoBox, StscBox, StscEntry, StsdBox, StssBox, SttsBox, UnknownBox},
-        BaseBox, BoxSize, BoxType,
+        boxes::{Co64Box, StcoBox, StscBox, StscEntry, StsdBox, StssBox, SttsBox, UnknownBox},
+        BoxSize, BoxType, Encode,
     };
 
     use super::*;
@@ -485,6 +1620,10 @@ mod tests {
             stss_box: Some(StssBox {
                 sample_numbers: vec![index(1), index(3), index(5), index(7), index(9)],
             }),
+            ctts_box: None,
+            subs_box: None,
+            sgpd_boxes: Vec::new(),
+            sbgp_boxes: Vec::new(),
             unknown_boxes: Vec::new(),
         };
 
@@ -512,6 +1651,42 @@ mod tests {
         }
         assert!(sample_table.get_sample(index(11)).is_none());
 
+        for (i, &offset) in sample_offsets.iter().enumerate() {
+            let range = sample_table
+                .sample_byte_range(index(i as u32 + 1))
+                .expect("bug");
+            assert_eq!(range.start, offset as u64);
+            assert_eq!(range.end - range.start, i as u64 + 1);
+        }
+        assert!(sample_table.sample_byte_range(index(11)).is_none());
+
+        // 同じチャンク内のサンプル群
+        assert_eq!(
+            sample_table.samples_byte_range(index(5), index(8)),
+            Some(300..318)
+        );
+        assert_eq!(
+            sample_table.samples_byte_range(index(8), index(11)),
+            Some(400..427)
+        );
+        assert_eq!(
+            sample_table.samples_byte_range(index(1), index(2)),
+            sample_table.sample_byte_range(index(1))
+        );
+
+        // チャンクをまたいでいて、データが連続していない
+        assert!(sample_table
+            .samples_byte_range(index(2), index(4))
+            .is_none());
+
+        // 範囲が不正
+        assert!(sample_table
+            .samples_byte_range(index(3), index(3))
+            .is_none());
+        assert!(sample_table
+            .samples_byte_range(index(9), index(12))
+            .is_none());
+
         let sample_counts = [2, 2, 3, 3];
         for i in 0..4 {
             let chunk = sample_table.get_chunk(index(i as u32 + 1)).expect("bug");
@@ -535,7 +1710,348 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn duration_and_bitrate() {
+        let stbl_box = StblBox {
+            stsd_box: StsdBox {
+                entries: vec![SampleEntry::Unknown(UnknownBox {
+                    box_type: BoxType::Normal(*b"test"),
+                    box_size: BoxSize::U32(8),
+                    payload: Vec::new(),
+                })],
+            },
+            // 尺の合計は 3 * 1000 + 2 * 500 = 4000
+            stts_box: SttsBox::from_sample_deltas([1000, 1000, 1000, 500, 500]),
+            stsc_box: StscBox {
+                entries: vec![StscEntry {
+                    first_chunk: index(1),
+                    sample_per_chunk: 5,
+                    sample_description_index: index(1),
+                }],
+            },
+            stsz_box: StszBox::Fixed {
+                sample_size: index(100),
+                sample_count: 5,
+            },
+            stco_or_co64_box: Either::A(StcoBox {
+                chunk_offsets: vec![0],
+            }),
+            stss_box: None,
+            ctts_box: None,
+            subs_box: None,
+            sgpd_boxes: Vec::new(),
+            sbgp_boxes: Vec::new(),
+            unknown_boxes: Vec::new(),
+        };
+        let timescale = index(1000);
+
+        let sample_table = SampleTableAccessor::new(&stbl_box).expect("bug");
+        assert_eq!(sample_table.total_duration(), 4000);
+        assert_eq!(sample_table.total_duration_secs(timescale), 4.0);
+        assert_eq!(sample_table.total_data_size(), 500);
+        // 500 bytes * 8 / 4 secs
+        assert_eq!(sample_table.average_bitrate(timescale), Some(1000));
+
+        let mut stbl_box = stbl_box.clone();
+        stbl_box.stsz_box = StszBox::Variable {
+            entry_sizes: vec![100, 200, 300, 400, 1000],
+        };
+        let sample_table = SampleTableAccessor::new(&stbl_box).expect("bug");
+        assert_eq!(sample_table.total_data_size(), 2000);
+        assert_eq!(sample_table.average_bitrate(timescale), Some(4000));
+
+        // 尺が 0 の場合はビットレートは求められない
+        let mut stbl_box = stbl_box.clone();
+        stbl_box.stts_box = SttsBox::from_sample_deltas([0; 5]);
+        let sample_table = SampleTableAccessor::new(&stbl_box).expect("bug");
+        assert_eq!(sample_table.total_duration(), 0);
+        assert_eq!(sample_table.average_bitrate(timescale), None);
+    }
+
+    #[test]
+    fn sample_iter() {
+        let sample_durations = [10, 5, 5, 20, 20, 20, 1, 1, 1, 1];
+        let stbl_box = StblBox {
+            stsd_box: StsdBox {
+                entries: vec![SampleEntry::Unknown(UnknownBox {
+                    box_type: BoxType::Normal(*b"test"),
+                    box_size: BoxSize::U32(8),
+                    payload: Vec::new(),
+                })],
+            },
+            stts_box: SttsBox::from_sample_deltas(sample_durations),
+            stsc_box: StscBox {
+                entries: vec![
+                    StscEntry {
+                        first_chunk: index(1),
+                        sample_per_chunk: 2,
+                        sample_description_index: index(1),
+                    },
+                    StscEntry {
+                        first_chunk: index(3),
+                        sample_per_chunk: 3,
+                        sample_description_index: index(1),
+                    },
+                ],
+            },
+            stsz_box: StszBox::Variable {
+                entry_sizes: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
+            },
+            stco_or_co64_box: Either::B(Co64Box {
+                chunk_offsets: vec![100, 200, 300, 400],
+            }),
+            stss_box: Some(StssBox {
+                sample_numbers: vec![index(1), index(3), index(5), index(7), index(9)],
+            }),
+            ctts_box: None,
+            subs_box: None,
+            sgpd_boxes: Vec::new(),
+            sbgp_boxes: Vec::new(),
+            unknown_boxes: Vec::new(),
+        };
+
+        // SampleTableAccessor と同じ結果になることを確認する
+        let sample_table = SampleTableAccessor::new(&stbl_box).expect("bug");
+        let samples = SampleIter::new(&stbl_box)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("bug");
+        assert_eq!(samples.len(), sample_table.sample_count() as usize);
+        for (sample, expected) in samples.iter().zip(sample_table.samples()) {
+            assert_eq!(sample.index, expected.index());
+            assert_eq!(sample.timestamp, expected.timestamp());
+            assert_eq!(sample.duration, expected.duration());
+            assert_eq!(sample.data_offset, expected.data_offset());
+            assert_eq!(sample.data_size, expected.data_size());
+            assert_eq!(sample.is_sync_sample, expected.is_sync_sample());
+            assert_eq!(sample.chunk_index, expected.chunk().index());
+        }
+
+        // サンプル数が不整合な場合はエラーになる
+        let mut broken = stbl_box.clone();
+        broken.stsz_box = StszBox::Variable {
+            entry_sizes: vec![1, 2, 3],
+        };
+        let mut iter = SampleIter::new(&broken);
+        assert_eq!(iter.by_ref().take(3).filter(|x| x.is_ok()).count(), 3);
+        assert!(matches!(
+            iter.next(),
+            Some(Err(
+                SampleTableAccessorError::InconsistentSampleCount { .. }
+            ))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn keyframe_seek() {
+        // 各サンプルの尺は 10 で、同期サンプルは 1, 4, 7 番目
+        let stbl_box = StblBox {
+            stsd_box: StsdBox {
+                entries: vec![SampleEntry::Unknown(UnknownBox {
+                    box_type: BoxType::Normal(*b"test"),
+                    box_size: BoxSize::U32(8),
+                    payload: Vec::new(),
+                })],
+            },
+            stts_box: SttsBox::from_sample_deltas([10; 8]),
+            stsc_box: StscBox {
+                entries: vec![StscEntry {
+                    first_chunk: index(1),
+                    sample_per_chunk: 8,
+                    sample_description_index: index(1),
+                }],
+            },
+            stsz_box: StszBox::Fixed {
+                sample_size: index(1),
+                sample_count: 8,
+            },
+            stco_or_co64_box: Either::A(StcoBox {
+                chunk_offsets: vec![0],
+            }),
+            stss_box: Some(StssBox {
+                sample_numbers: vec![index(1), index(4), index(7)],
+            }),
+            ctts_box: None,
+            subs_box: None,
+            sgpd_boxes: Vec::new(),
+            sbgp_boxes: Vec::new(),
+            unknown_boxes: Vec::new(),
+        };
+        let sample_table = SampleTableAccessor::new(&stbl_box).expect("bug");
+
+        let before = |t| sample_table.keyframe_before(t).map(|s| s.index().get());
+        let after = |t| sample_table.keyframe_after(t).map(|s| s.index().get());
+        assert_eq!(before(0), Some(1));
+        assert_eq!(before(29), Some(1));
+        assert_eq!(before(30), Some(4));
+        assert_eq!(before(79), Some(7));
+        assert_eq!(before(80), None);
+        assert_eq!(after(0), Some(1));
+        assert_eq!(after(5), Some(1));
+        assert_eq!(after(10), Some(4));
+        assert_eq!(after(35), Some(4));
+        assert_eq!(after(40), Some(7));
+        assert_eq!(after(60), Some(7));
+        assert_eq!(after(65), Some(7));
+        assert_eq!(after(70), None);
+        assert_eq!(after(80), None);
+
+        // 最後の同期サンプルより後ろのサンプルを含むタイムスタンプ
+        let mut stbl_box = stbl_box.clone();
+        stbl_box.stss_box = Some(StssBox {
+            sample_numbers: vec![index(1), index(4)],
+        });
+        let sample_table = SampleTableAccessor::new(&stbl_box).expect("bug");
+        assert_eq!(
+            sample_table.keyframe_after(50).map(|s| s.index().get()),
+            None
+        );
+        assert_eq!(
+            sample_table.keyframe_before(50).map(|s| s.index().get()),
+            Some(4)
+        );
+
+        assert_eq!(sample_table.decode_timestamp_of_sample(index(1)), Some(0));
+        assert_eq!(sample_table.decode_timestamp_of_sample(index(8)), Some(70));
+        assert_eq!(sample_table.decode_timestamp_of_sample(index(9)), None);
+    }
+
+    #[test]
+    fn validate_stbl_box() {
+        let stbl_box = StblBox {
+            stsd_box: StsdBox {
+                entries: vec![SampleEntry::Unknown(UnknownBox {
+                    box_type: BoxType::Normal(*b"test"),
+                    box_size: BoxSize::U32(8),
+                    payload: Vec::new(),
+                })],
+            },
+            stts_box: SttsBox::from_sample_deltas([10; 5]),
+            stsc_box: StscBox {
+                entries: vec![
+                    StscEntry {
+                        first_chunk: index(1),
+                        sample_per_chunk: 2,
+                        sample_description_index: index(1),
+                    },
+                    StscEntry {
+                        first_chunk: index(2),
+                        sample_per_chunk: 3,
+                        sample_description_index: index(1),
+                    },
+                ],
+            },
+            stsz_box: StszBox::Variable {
+                entry_sizes: vec![1, 2, 3, 4, 5],
+            },
+            stco_or_co64_box: Either::A(StcoBox {
+                chunk_offsets: vec![100, 200],
+            }),
+            stss_box: None,
+            ctts_box: None,
+            subs_box: None,
+            sgpd_boxes: Vec::new(),
+            sbgp_boxes: Vec::new(),
+            unknown_boxes: Vec::new(),
+        };
+        assert_eq!(stbl_box.validate(), Ok(()));
+
+        let mut b = stbl_box.clone();
+        b.stsz_box = StszBox::Variable {
+            entry_sizes: vec![1, 2, 3, 4],
+        };
+        assert_eq!(
+            b.validate(),
+            Err(ValidationError::SampleCountMismatch {
+                from: StszBox::TYPE,
+                expected: 5,
+                got: 4
+            })
+        );
+
+        let mut b = stbl_box.clone();
+        b.stsc_box.entries[1].first_chunk = index(1);
+        assert_eq!(
+            b.validate(),
+            Err(ValidationError::StscFirstChunkOutOfOrder { entry_index: 1 })
+        );
+
+        let mut b = stbl_box.clone();
+        b.stco_or_co64_box = Either::A(StcoBox {
+            chunk_offsets: vec![100],
+        });
+        assert_eq!(
+            b.validate(),
+            Err(ValidationError::ChunkCountMismatch {
+                expected: 1,
+                got: 2
+            })
+        );
+
+        let mut b = stbl_box.clone();
+        b.stco_or_co64_box = Either::A(StcoBox {
+            chunk_offsets: vec![100, 200, 300],
+        });
+        assert_eq!(
+            b.validate(),
+            Err(ValidationError::SampleCountMismatch {
+                from: StscBox::TYPE,
+                expected: 5,
+                got: 8
+            })
+        );
+
+        let mut b = stbl_box.clone();
+        b.stco_or_co64_box = Either::B(Co64Box {
+            chunk_offsets: vec![100, u64::MAX - 1],
+        });
+        assert_eq!(b.validate(), Err(ValidationError::ChunkOffsetOverflow));
+    }
+
     fn index(i: u32) -> NonZeroU32 {
         NonZeroU32::new(i).expect("invalid index")
     }
+
+    #[test]
+    fn scan_unknown_boxes() {
+        let boxes = [
+            UnknownBox {
+                box_type: BoxType::Normal(*b"\xa9too"),
+                box_size: BoxSize::U32(8 + 5),
+                payload: b"hello".to_vec(),
+            },
+            UnknownBox {
+                box_type: BoxType::Normal(*b"GPMF"),
+                box_size: BoxSize::U32(8 + 4),
+                payload: 1234u32.to_be_bytes().to_vec(),
+            },
+        ];
+        let mut data = Vec::new();
+        for b in &boxes {
+            b.encode(&mut data).expect("bug");
+        }
+
+        assert_eq!(
+            scan_for_box_type(&data, BoxType::Normal(*b"\xa9too")),
+            Some(&b"hello"[..])
+        );
+        assert_eq!(
+            scan_for_box_type(&data, BoxType::Normal(*b"GPMF")),
+            Some(&1234u32.to_be_bytes()[..])
+        );
+        assert_eq!(scan_for_box_type(&data, BoxType::Normal(*b"none")), None);
+
+        // 途中で途切れているボックスがある
+        assert_eq!(
+            scan_for_box_type(&data[..data.len() - 1], BoxType::Normal(*b"GPMF")),
+            None
+        );
+
+        assert_eq!(boxes[0].try_as_string(), Some("hello"));
+        assert_eq!(boxes[0].try_as_u32(), None);
+        assert_eq!(boxes[0].type_tag(), "©too");
+        assert_eq!(boxes[1].try_as_u32(), Some(1234));
+        assert_eq!(boxes[1].type_tag(), "GPMF");
+        assert!(matches!(boxes[1].type_tag(), std::borrow::Cow::Borrowed(_)));
+    }
 }
@@ -1,13 +1,13 @@
 use std::{
-    io::{Read, Write},
+    io::{Read, Seek, SeekFrom, Write},
     ops::{BitAnd, Shl, Shr, Sub},
     time::Duration,
 };
 
 use crate::{
-    boxes::{FtypBox, RootBox},
-    io::PeekReader,
-    Decode, Encode, Error, Result,
+    boxes::{FastRootBox, FtypBox, IgnoredBox, MdatBox, MoovBox, RootBox, RootBoxRef},
+    io::{with_lenient_box_path, PeekReader},
+    Decode, DecodeRead, Encode, Error, Result,
 };
 
 /// 全てのボックスが実装するトレイト
@@ -55,6 +55,14 @@ pub trait FullBox: BaseBox {
 }
 
 /// MP4 ファイルを表す構造体
+///
+/// [`Decode::decode()`] は任意の [`Read`] を受け取るので、
+/// `std::fs::File` などから読み込む場合にも、事前にファイル全体をメモリ上に読み込んでおく必要はない
+/// （ただし `mdat` ボックスのペイロードは、デコード結果に含まれるためメモリ上に保持される）
+///
+/// 入力全体がすでにメモリ上にあり、`mdat` ボックスのペイロードが不要な場合には
+/// [`Mp4File::decode_skipping_mdat()`] を使うことで、ペイロードのコピーを避けられる。
+/// `moov` ボックスだけが必要な場合には [`Mp4File::decode_fast()`] も利用可能
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Mp4File<B = RootBox> {
     /// MP4 ファイルの先頭に位置する `ftyp` ボックス
@@ -71,6 +79,215 @@ impl<B: BaseBox> Mp4File<B> {
             .chain(std::iter::once(&self.ftyp_box).map(as_box_object))
             .chain(self.boxes.iter().map(as_box_object))
     }
+
+    /// ファイル全体をエンコードした際のバイト数を返す
+    ///
+    /// 各ボックスのサイズ情報から計算されるので、エンコード処理は行われない
+    /// （ペイロードサイズの計算のために内部でエンコード処理を行うボックスは除く）
+    pub fn total_encoded_size(&self) -> u64 {
+        self.iter()
+            .map(|b| {
+                let box_size = b.box_size();
+                if box_size.is_eof() {
+                    let header = BoxHeader {
+                        box_type: b.box_type(),
+                        box_size,
+                    };
+                    header.external_size() as u64 + b.box_payload_size()
+                } else {
+                    box_size.get()
+                }
+            })
+            .sum()
+    }
+}
+
+impl Mp4File<RootBox> {
+    /// `ftyp` ボックスを返す
+    ///
+    /// [`Mp4File::ftyp_box`] は必須フィールドなので、このメソッドは常に [`Some`] を返す
+    pub fn find_ftyp(&self) -> Option<&FtypBox> {
+        Some(&self.ftyp_box)
+    }
+
+    /// 最初の `moov` ボックスを返す
+    pub fn find_moov(&self) -> Option<&MoovBox> {
+        self.boxes.iter().find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+    }
+
+    /// 全ての `mdat` ボックスのペイロードを走査するイテレーターを返す
+    pub fn find_mdat_payloads(&self) -> impl Iterator<Item = &[u8]> {
+        self.boxes.iter().filter_map(|b| match b {
+            RootBox::Mdat(b) => Some(&b.payload[..]),
+            _ => None,
+        })
+    }
+
+    /// `moov` ボックス内の `mvhd` ボックスに記録されている尺をミリ秒単位で返す
+    ///
+    /// `moov` ボックスが存在しない場合には [`None`] が返される
+    pub fn duration_ms(&self) -> Option<f64> {
+        self.find_moov().map(|b| b.mvhd_box.duration_ms())
+    }
+}
+
+impl<B: BaseBox + Decode> Mp4File<Either<B, IgnoredBox>> {
+    /// メモリ上のバイト列から、`mdat` ボックスのペイロードを読み込まずに MP4 ファイルをデコードする
+    ///
+    /// `mdat` ボックスは [`IgnoredBox`] として扱われ、ペイロードのコピーは行われない。
+    /// また、そのサイズに関わらず O(1) で読み飛ばされるので、
+    /// 巨大な `mdat` ボックスを含むファイルから `moov` ボックスだけを取り出したい場合などに有用。
+    ///
+    /// `mdat` 以外のボックスは `B` としてデコードされる
+    pub fn decode_skipping_mdat(buf: &[u8]) -> Result<Self> {
+        let mut reader = buf;
+        let ftyp_box = FtypBox::decode(&mut reader)?;
+
+        let mut boxes = Vec::new();
+        while !reader.is_empty() {
+            let header = BoxHeader::decode(reader)?;
+            if header.box_type == MdatBox::TYPE {
+                let box_size = BoxHeader::skip_box(reader)?;
+                let box_payload_size =
+                    box_size
+                        .checked_sub(header.external_size())
+                        .ok_or_else(|| {
+                            Error::invalid_data(&format!("Too small box size: box_size={box_size}"))
+                                .with_box_type(header.box_type)
+                        })?;
+                boxes.push(Either::B(IgnoredBox {
+                    box_type: header.box_type,
+                    box_size: header.box_size,
+                    box_payload_size: box_payload_size as u64,
+                }));
+                reader = &reader[box_size..];
+            } else {
+                boxes.push(Either::A(B::decode(&mut reader)?));
+            }
+        }
+        Ok(Self { ftyp_box, boxes })
+    }
+}
+
+impl Mp4File<FastRootBox> {
+    /// バイト列から `ftyp` と `moov` ボックスだけをデコードする
+    ///
+    /// `moov` 以外のボックスは [`FastRootBox::Unknown`] として扱われ、ペイロードのコピーは行われない。
+    /// [`FastRootBox::Unknown`] の `byte_range` にはファイル先頭からのボックスの位置が格納されるので、
+    /// 必要に応じて `mdat` ボックスなどのペイロードを後から参照することができる。
+    ///
+    /// 返り値の `usize` は、最後にデコードしたボックスの終端位置（つまり次のボックスの開始位置）である。
+    /// 末尾のボックスが `moov` 以外で、かつ `buf` の範囲を超えている場合にはエラーとはならず、
+    /// そのボックスを記録した上で `buf.len()` よりも大きな値が返される。
+    /// これにより、ファイルの先頭部分だけを読み込んで、次に読むべき位置を判定することができる
+    pub fn decode_fast(buf: &[u8]) -> Result<(Self, usize)> {
+        let mut reader = buf;
+        let ftyp_box = FtypBox::decode(&mut reader)?;
+
+        let mut boxes = Vec::new();
+        let mut offset = buf.len() - reader.len();
+        while offset < buf.len() {
+            let mut reader = &buf[offset..];
+            let header = BoxHeader::decode(reader)?;
+            if header.box_type == MoovBox::TYPE {
+                boxes.push(FastRootBox::Moov(MoovBox::decode(&mut reader)?));
+                offset = buf.len() - reader.len();
+                continue;
+            }
+
+            let box_size = if header.box_size.is_eof() {
+                reader.len() as u64
+            } else {
+                header.box_size.get()
+            };
+            if box_size < header.external_size() as u64 {
+                return Err(Error::invalid_data(&format!(
+                    "Too small box size: actual={box_size}, expected={} or more",
+                    header.external_size()
+                ))
+                .with_box_type(header.box_type));
+            }
+            let start = offset as u64;
+            let end = start
+                .checked_add(box_size)
+                .and_then(|end| usize::try_from(end).ok())
+                .ok_or_else(|| {
+                    Error::invalid_data(&format!("Too large box size: box_size={box_size}"))
+                        .with_box_type(header.box_type)
+                })?;
+            boxes.push(FastRootBox::Unknown {
+                box_type: header.box_type,
+                box_size: header.box_size,
+                byte_range: start..end as u64,
+            });
+            offset = end;
+        }
+        Ok((Self { ftyp_box, boxes }, offset))
+    }
+}
+
+impl<'a> Mp4File<RootBoxRef<'a>> {
+    /// メモリ上のバイト列から、`mdat` ボックスのペイロードをコピーせずに MP4 ファイルをデコードする
+    ///
+    /// `mdat` ボックスは [`MdatBoxRef`](crate::boxes::MdatBoxRef) としてデコードされ、
+    /// そのペイロードは `buf` を直接参照する。
+    /// そのため、`mdat` ボックスのサイズに関わらず、追加で必要となるヒープ領域は `mdat` 以外のボックスの分だけとなる。
+    ///
+    /// 返り値の `usize` はデコードしたバイト数で、成功時には常に `buf.len()` と等しくなる
+    pub fn decode_ref(buf: &'a [u8]) -> Result<(Self, usize)> {
+        let mut reader = buf;
+        let ftyp_box = FtypBox::decode(&mut reader)?;
+
+        let mut boxes = Vec::new();
+        let mut offset = buf.len() - reader.len();
+        while offset < buf.len() {
+            let (b, size) = RootBoxRef::from_slice(&buf[offset..])?;
+            boxes.push(b);
+            offset += size;
+        }
+        Ok((Self { ftyp_box, boxes }, offset))
+    }
+
+    /// `mdat` ボックスのペイロードをコピーして、[`RootBox`] を要素とする [`Mp4File`] を作成する
+    pub fn to_owned_file(self) -> Mp4File<RootBox> {
+        Mp4File {
+            ftyp_box: self.ftyp_box,
+            boxes: self
+                .boxes
+                .into_iter()
+                .map(RootBoxRef::to_root_box)
+                .collect(),
+        }
+    }
+}
+
+impl<B: BaseBox + Decode> Mp4File<B> {
+    /// ファイルやソケットのようなストリームから、ボックスを一つずつ読み込んで MP4 ファイルをデコードする
+    ///
+    /// 各ボックスは [`DecodeRead::decode_from_read()`] を使って、ボックス一つ分ずつメモリに読み込まれてからデコードされる。
+    /// `reader` の終端に達するまでトップレベルのボックスの読み込みを繰り返し、
+    /// 終端の判定には [`Seek`] を使って取得した `reader` のサイズが用いられる。
+    ///
+    /// サイズが 0 （可変長）のボックスは `reader` の終端までを占めるものとして扱われる
+    pub fn decode_from_read<R: Read + Seek>(reader: &mut R) -> Result<Self> {
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let (ftyp_box, mut position) = FtypBox::decode_from_read(reader)?;
+        position += start;
+
+        let mut boxes = Vec::new();
+        while position < end {
+            let (b, size) = B::decode_from_read(reader)?;
+            boxes.push(b);
+            position += size;
+        }
+        Ok(Self { ftyp_box, boxes })
+    }
 }
 
 impl<B: BaseBox + Decode> Decode for Mp4File<B> {
@@ -96,6 +313,17 @@ impl<B: BaseBox + Encode> Encode for Mp4File<B> {
         }
         Ok(())
     }
+
+    fn encoded_size(&self) -> u64 {
+        self.total_encoded_size()
+    }
+
+    fn encode_to_vec(&self) -> Result<Vec<u8>> {
+        // サイズは各ボックスのサイズ情報から求められるので、事前に必要な分のメモリを確保しておく
+        let mut buf = Vec::with_capacity(self.encoded_size() as usize);
+        self.encode(&mut buf)?;
+        Ok(buf)
+    }
 }
 
 /// [`BaseBox`] に共通のヘッダー
@@ -118,6 +346,36 @@ impl BoxHeader {
         Self { box_type, box_size }
     }
 
+    /// ボックス種別とボックス全体（ヘッダーを含む）のサイズを指定してヘッダーを作成する
+    ///
+    /// ペイロードのサイズが事前に分かっている場合には、[`BoxHeader::from_box()`] とは異なり
+    /// サイズ計算のためにペイロードをエンコードする必要がない。
+    ///
+    /// `size` がヘッダー自体のサイズよりも小さい場合にはエラーが返される
+    pub fn new_with_size(box_type: BoxType, size: u32) -> Result<Self> {
+        Self::new_with_box_size(box_type, BoxSize::U32(size))
+    }
+
+    /// [`BoxHeader::new_with_size()`] の 64 ビット版
+    ///
+    /// `size` の値に関わらず、ヘッダーのサイズ部分は 64 ビットでエンコードされる
+    pub fn new_with_size_u64(box_type: BoxType, size: u64) -> Result<Self> {
+        Self::new_with_box_size(box_type, BoxSize::U64(size))
+    }
+
+    fn new_with_box_size(box_type: BoxType, box_size: BoxSize) -> Result<Self> {
+        let header = Self { box_type, box_size };
+        if box_size.get() < header.external_size() as u64 {
+            return Err(Error::invalid_input(&format!(
+                "Too small box size: actual={}, expected={} or more",
+                box_size.get(),
+                header.external_size()
+            ))
+            .with_box_type(box_type));
+        }
+        Ok(header)
+    }
+
     /// ヘッダーをエンコードした際のバイト数を返す
     pub fn external_size(self) -> usize {
         self.box_type.external_size() + self.box_size.external_size()
@@ -128,8 +386,9 @@ impl BoxHeader {
     where
         F: FnOnce(&mut std::io::Take<R>) -> Result<T>,
     {
-        let mut reader = if self.box_size.get() == 0 {
-            reader.take(u64::MAX)
+        // 可変長ボックスの場合は、ペイロードのサイズは不明（リーダーの末尾まで）となる
+        let payload_size = if self.box_size.is_eof() {
+            None
         } else {
             let payload_size = self
                 .box_size
@@ -143,11 +402,13 @@ impl BoxHeader {
                     ))
                     .with_box_type(self.box_type)
                 })?;
-            reader.take(payload_size)
+            Some(payload_size)
         };
+        let mut reader = reader.take(payload_size.unwrap_or(u64::MAX));
 
-        let value = f(&mut reader).map_err(|e| e.with_box_type(self.box_type))?;
-        if reader.limit() != 0 {
+        let value = with_lenient_box_path(self.box_type, || f(&mut reader))
+            .map_err(|e| e.with_box_type(self.box_type))?;
+        if payload_size.is_some() && reader.limit() != 0 {
             return Err(Error::invalid_data(&format!(
                 "Unconsumed {} bytes at the end of the box '{}'",
                 reader.limit(),
@@ -158,6 +419,57 @@ impl BoxHeader {
         Ok(value)
     }
 
+    /// `buf` の先頭にあるボックスのサイズ部分だけをデコードする
+    ///
+    /// ボックス種別やペイロードの中身は参照しないので、巨大なボックスを読み飛ばしたい場合に有用。
+    ///
+    /// サイズが 64 ビットで表現されている場合には、ボックス種別の後ろにあるサイズ値が返される。
+    /// 可変長ボックス（ファイル末尾までがボックスの範囲となる）の場合には `0` が返される。
+    pub fn decode_size_only(buf: &[u8]) -> Result<u64> {
+        let mut reader = buf;
+        let box_size = u32::decode(&mut reader)?;
+        if box_size != 1 {
+            return Ok(u64::from(box_size));
+        }
+
+        // 64 ビットのサイズ値はボックス種別（4 バイト）の後ろに位置する
+        let mut reader = reader.get(4..).unwrap_or_default();
+        u64::decode(&mut reader)
+    }
+
+    /// `buf` の先頭にあるボックス全体（ヘッダーを含む）のバイト数を返す
+    ///
+    /// ペイロードの中身は読み込まないので、ボックスのサイズに関わらず O(1) で処理が完了する。
+    /// 可変長ボックスの場合には `buf` の末尾までがボックスとみなされる。
+    ///
+    /// ボックスサイズがヘッダーのサイズよりも小さい場合や `buf` のサイズを超える場合にはエラーが返される
+    pub fn skip_box(buf: &[u8]) -> Result<usize> {
+        let header = Self::decode(buf)?;
+        if header.box_size.is_eof() {
+            return Ok(buf.len());
+        }
+        let box_size = header.box_size.get();
+        if box_size < header.external_size() as u64 {
+            // 64 ビットのサイズ表現で 0 が指定されている場合もここに該当する
+            return Err(Error::invalid_data(&format!(
+                "Too small box size: actual={box_size}, expected={} or more",
+                header.external_size()
+            ))
+            .with_box_type(header.box_type));
+        }
+
+        usize::try_from(box_size)
+            .ok()
+            .filter(|&size| size <= buf.len())
+            .ok_or_else(|| {
+                Error::invalid_data(&format!(
+                    "Too large box size: box_size={box_size}, buffer_size={}",
+                    buf.len()
+                ))
+                .with_box_type(header.box_type)
+            })
+    }
+
     /// ボックスのヘッダー部分を先読みする
     ///
     /// 返り値に含まれるリーダーには、ボックスのヘッダー部分のバイト列も含まれる
@@ -304,6 +616,42 @@ impl FullBoxFlags {
     pub const fn is_set(self, i: usize) -> bool {
         (self.0 & (1 << i)) != 0
     }
+
+    /// 指定されたビット位置のフラグをセットしたビットフラグを返す
+    pub const fn set_bit(self, i: usize) -> Self {
+        Self(self.0 | (1 << i))
+    }
+
+    /// 指定されたビット位置のフラグをクリアしたビットフラグを返す
+    pub const fn clear_bit(self, i: usize) -> Self {
+        Self(self.0 & !(1 << i))
+    }
+
+    /// 指定されたビット位置のフラグを反転したビットフラグを返す
+    pub const fn toggle_bit(self, i: usize) -> Self {
+        Self(self.0 ^ (1 << i))
+    }
+
+    /// セットされているフラグのビット位置を昇順に返すイテレーターを返す
+    pub fn bits(self) -> impl Iterator<Item = usize> {
+        (0..u32::BITS as usize).filter(move |&i| self.is_set(i))
+    }
+}
+
+impl std::ops::BitOr for FullBoxFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitAnd for FullBoxFlags {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
 }
 
 impl Encode for FullBoxFlags {
@@ -337,16 +685,36 @@ impl BoxSize {
     pub const VARIABLE_SIZE: Self = Self::U32(0);
 
     /// ボックス種別とペイロードサイズを受け取って、対応する [`BoxSize`] インスタンスを作成する
+    ///
+    /// ヘッダーを含めたボックス全体のサイズが 32 ビットに収まる場合には [`BoxSize::U32`] が、
+    /// そうではない場合には [`BoxSize::U64`] が使われる。
+    ///
+    /// # Panics
+    ///
+    /// ボックス全体のサイズが [`u64`] で表現できない場合にはパニックする
+    /// （オーバーフローを検出したい場合には [`BoxSize::checked_with_payload_size()`] を使うこと）
     pub fn with_payload_size(box_type: BoxType, payload_size: u64) -> Self {
-        let mut size = 4 + box_type.external_size() as u64 + payload_size;
+        Self::checked_with_payload_size(box_type, payload_size)
+            .unwrap_or_else(|| panic!("Too large payload size of '{box_type}' box: {payload_size}"))
+    }
+
+    /// [`BoxSize::with_payload_size()`] と同様だが、ボックス全体のサイズが [`u64`] で表現できない場合には [`None`] を返す
+    pub fn checked_with_payload_size(box_type: BoxType, payload_size: u64) -> Option<Self> {
+        let size = (4 + box_type.external_size() as u64).checked_add(payload_size)?;
         if let Ok(size) = u32::try_from(size) {
-            Self::U32(size)
+            Some(Self::U32(size))
         } else {
-            size += 8;
-            Self::U64(size)
+            size.checked_add(8).map(Self::U64)
         }
     }
 
+    /// ファイル（または親ボックス）の末尾までがペイロードとなる可変長のボックスを表しているかどうかを判定する
+    ///
+    /// サイズフィールドの値が 0 の場合がこれに該当し、[`BoxSize::VARIABLE_SIZE`] と等しいかどうかの判定と同じ
+    pub const fn is_eof(self) -> bool {
+        matches!(self, Self::U32(0))
+    }
+
     /// ボックスのサイズの値を取得する
     pub const fn get(self) -> u64 {
         match self {
@@ -375,6 +743,30 @@ pub enum BoxType {
 }
 
 impl BoxType {
+    /// PIFF (Protected Interoperable File Format) の SampleEncryptionBox を表す UUID 形式のボックス種別
+    ///
+    /// `a2394f52-5a9b-4f14-a244-6c427c648df4`
+    pub const PIFF_SAMPLE_ENCRYPTION: Self = Self::Uuid([
+        0xa2, 0x39, 0x4f, 0x52, 0x5a, 0x9b, 0x4f, 0x14, 0xa2, 0x44, 0x6c, 0x42, 0x7c, 0x64, 0x8d,
+        0xf4,
+    ]);
+
+    /// PIFF の TrackEncryptionBox を表す UUID 形式のボックス種別
+    ///
+    /// `8974dbce-7be7-4c51-84f9-7148f9882554`
+    pub const PIFF_TRACK_ENCRYPTION: Self = Self::Uuid([
+        0x89, 0x74, 0xdb, 0xce, 0x7b, 0xe7, 0x4c, 0x51, 0x84, 0xf9, 0x71, 0x48, 0xf9, 0x88, 0x25,
+        0x54,
+    ]);
+
+    /// PIFF の ProtectionSystemSpecificHeaderBox を表す UUID 形式のボックス種別
+    ///
+    /// `d08a4f18-10f3-4a82-b6c8-32d8aba183d3`
+    pub const PIFF_PROTECTION_SYSTEM_SPECIFIC_HEADER: Self = Self::Uuid([
+        0xd0, 0x8a, 0x4f, 0x18, 0x10, 0xf3, 0x4a, 0x82, 0xb6, 0xc8, 0x32, 0xd8, 0xab, 0xa1, 0x83,
+        0xd3,
+    ]);
+
     /// 種別を表すバイト列を返す
     pub fn as_bytes(&self) -> &[u8] {
         match self {
@@ -383,6 +775,11 @@ impl BoxType {
         }
     }
 
+    /// 先頭バイトが `0xA9` (`©`) の QuickTime 形式のメタデータタグ（`©nam` や `©ART` など）かどうかを判定する
+    pub fn is_copyright_tag(&self) -> bool {
+        matches!(self, BoxType::Normal([0xA9, ..]))
+    }
+
     /// [`BoxHeader`] 内のボックス種別フィールドをエンコードする際に必要となるバイト数を返す
     pub const fn external_size(self) -> usize {
         if matches!(self, Self::Normal(_)) {
@@ -420,14 +817,30 @@ impl std::fmt::Debug for BoxType {
     }
 }
 
+/// 以下の形式で表示される:
+/// - 四文字が全て表示可能な ASCII 文字の場合は、そのままの文字列（例: `moov`）
+/// - 先頭バイトが `0xA9` で残りが表示可能な ASCII 文字の場合は、`©` に続く文字列（例: `©nam`）
+/// - それ以外の四文字の種別の場合は、各バイトの 16 進数表記（例: `[00 01 ff 7f]`）
+/// - UUID 形式の場合は、バイト列
 impl std::fmt::Display for BoxType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let BoxType::Normal(ty) = self {
-            if let Ok(ty) = std::str::from_utf8(&ty[..]) {
-                return write!(f, "{ty}");
-            }
+        let ty = match self {
+            BoxType::Normal(ty) => ty,
+            BoxType::Uuid(ty) => return write!(f, "{:?}", &ty[..]),
+        };
+        let is_printable = |bytes: &[u8]| bytes.iter().all(|&b| (0x20..0x7F).contains(&b));
+        if is_printable(ty) {
+            // 表示可能な ASCII 文字のみなので UTF-8 としても妥当
+            write!(f, "{}", String::from_utf8_lossy(ty))
+        } else if self.is_copyright_tag() && is_printable(&ty[1..]) {
+            write!(f, "©{}", String::from_utf8_lossy(&ty[1..]))
+        } else {
+            write!(
+                f,
+                "[{:02x} {:02x} {:02x} {:02x}]",
+                ty[0], ty[1], ty[2], ty[3]
+            )
         }
-        write!(f, "{:?}", self.as_bytes())
     }
 }
 
@@ -436,11 +849,28 @@ impl std::fmt::Display for BoxType {
 pub struct Mp4FileTime(u64);
 
 impl Mp4FileTime {
+    /// UNIX エポック（1970/1/1）に対応する時刻（1904/1/1 からの経過秒数は 2082844800）
+    pub const UNIX_EPOCH: Self = Self(2082844800);
+
+    /// [`Mp4FileTime::try_from_secs()`] で受け付ける最大値（9999/12/31 23:59:59 に対応する）
+    pub const MAX_SECS: u64 = 255485145599;
+
     /// 1904/1/1 からの経過秒数を引数にとって [`Mp4FileTime`] インスタンスを作成する
     pub const fn from_secs(secs: u64) -> Self {
         Self(secs)
     }
 
+    /// 1904/1/1 からの経過秒数を引数にとって [`Mp4FileTime`] インスタンスを作成する
+    ///
+    /// [`Mp4FileTime::from_secs()`] とは異なり、9999 年よりも後の時刻が指定された場合には [`None`] が返される
+    pub const fn try_from_secs(secs: u64) -> Option<Self> {
+        if secs > Self::MAX_SECS {
+            None
+        } else {
+            Some(Self(secs))
+        }
+    }
+
     /// 1904/1/1 からの経過秒数を返す
     pub const fn as_secs(self) -> u64 {
         self.0
@@ -448,9 +878,27 @@ impl Mp4FileTime {
 
     /// [`std::time::UNIX_EPOCH`] を起点とした経過時間を受け取って、対応する [`Mp4FileTime`] インスタンスを作成する
     pub const fn from_unix_time(unix_time: Duration) -> Self {
-        let delta = 2082844800; // 1904/1/1 から 1970/1/1 までの経過秒数
         let unix_time_secs = unix_time.as_secs();
-        Self::from_secs(unix_time_secs + delta)
+        Self::from_secs(unix_time_secs + Self::UNIX_EPOCH.0)
+    }
+
+    /// UNIX エポック（1970/1/1）からの経過秒数を返す
+    ///
+    /// このインスタンスが UNIX エポックよりも前の時刻を表している場合には [`None`] が返される
+    pub const fn to_unix_secs(self) -> Option<i64> {
+        if self.0 < Self::UNIX_EPOCH.0 || self.0 - Self::UNIX_EPOCH.0 > i64::MAX as u64 {
+            return None;
+        }
+        Some((self.0 - Self::UNIX_EPOCH.0) as i64)
+    }
+
+    /// 対応する [`chrono::DateTime<chrono::Utc>`] を返す
+    ///
+    /// このインスタンスが UNIX エポックよりも前の時刻を表している場合や、
+    /// [`chrono`] で扱える範囲外の時刻を表している場合には [`None`] が返される
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono_utc(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.to_unix_secs()?, 0)
     }
 }
 
@@ -506,11 +954,45 @@ impl Utf8String {
         Some(Self(s.to_owned()))
     }
 
+    /// [`Utf8String::new_validated()`] で受け付ける文字列の最大バイト数（終端の null を含まない）
+    pub const MAX_LEN: usize = 65535;
+
+    /// 終端の null を含まない文字列を受け取って [`Utf8String`] インスタンスを作成する
+    ///
+    /// [`Utf8String::new()`] とは異なり、以下の場合には理由を含むエラーが返される:
+    /// - 引数の文字列内に null 文字が含まれている（null 終端文字列を前提とする C などの利用側で問題になる）
+    /// - 引数の文字列のバイト数が [`Utf8String::MAX_LEN`] を超えている
+    pub fn new_validated(s: &str) -> Result<Self> {
+        if let Some(i) = s.as_bytes().iter().position(|&b| b == 0) {
+            return Err(Error::invalid_input(&format!(
+                "String contains a null character at byte offset {i}"
+            )));
+        }
+        if s.len() > Self::MAX_LEN {
+            return Err(Error::invalid_input(&format!(
+                "String is too long: {} bytes (max {})",
+                s.len(),
+                Self::MAX_LEN
+            )));
+        }
+        Ok(Self(s.to_owned()))
+    }
+
     /// このインスタンスが保持する、null 終端部分を含まない文字列を返す
     pub fn get(&self) -> &str {
         &self.0
     }
 
+    /// 文字列のバイト数（終端の null を含まない）を返す
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// 空文字列かどうかを判定する
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// このインスタンスを、null 終端部分を含むバイト列へと変換する
     pub fn into_null_terminated_bytes(self) -> Vec<u8> {
         let mut v = self.0.into_bytes();
@@ -552,6 +1034,68 @@ pub enum Either<A, B> {
     B(B),
 }
 
+impl<A, B> Either<A, B> {
+    /// `A` の値を保持している場合には、それに `f` を適用した結果を保持するインスタンスを返す
+    pub fn map_a<C, F: FnOnce(A) -> C>(self, f: F) -> Either<C, B> {
+        match self {
+            Self::A(x) => Either::A(f(x)),
+            Self::B(x) => Either::B(x),
+        }
+    }
+
+    /// `B` の値を保持している場合には、それに `f` を適用した結果を保持するインスタンスを返す
+    pub fn map_b<C, F: FnOnce(B) -> C>(self, f: F) -> Either<A, C> {
+        match self {
+            Self::A(x) => Either::A(x),
+            Self::B(x) => Either::B(f(x)),
+        }
+    }
+
+    /// 保持している値への参照を保持するインスタンスを返す
+    pub const fn as_ref(&self) -> Either<&A, &B> {
+        match self {
+            Self::A(x) => Either::A(x),
+            Self::B(x) => Either::B(x),
+        }
+    }
+
+    /// 保持している値への可変参照を保持するインスタンスを返す
+    pub fn as_mut(&mut self) -> Either<&mut A, &mut B> {
+        match self {
+            Self::A(x) => Either::A(x),
+            Self::B(x) => Either::B(x),
+        }
+    }
+
+    /// `A` と `B` を入れ替えたインスタンスを返す
+    pub fn swap(self) -> Either<B, A> {
+        match self {
+            Self::A(x) => Either::B(x),
+            Self::B(x) => Either::A(x),
+        }
+    }
+
+    /// `A` の値を保持している場合にはそれを返し、そうでなければ [`None`] を返す
+    ///
+    /// 参照が欲しい場合には `either.as_ref().a()` のようにする
+    pub fn a(self) -> Option<A> {
+        match self {
+            Self::A(x) => Some(x),
+            Self::B(_) => None,
+        }
+    }
+
+    /// `B` の値を保持している場合にはそれを返し、そうでなければ [`None`] を返す
+    ///
+    /// 参照が欲しい場合には `either.as_ref().b()` のようにする
+    pub fn b(self) -> Option<B> {
+        match self {
+            Self::A(_) => None,
+            Self::B(x) => Some(x),
+        }
+    }
+}
+
 impl<A: BaseBox, B: BaseBox> Either<A, B> {
     fn inner_box(&self) -> &dyn BaseBox {
         match self {
@@ -600,6 +1144,9 @@ where
         + From<u8>,
 {
     /// 指定された数値を受け取ってインスタンスを作成する
+    ///
+    /// 定数の定義にも使えるように、このメソッドでは `v` が `BITS` の範囲に収まっているかはチェックされない。
+    /// 範囲外の値を扱う可能性がある場合には `checked_new()` を使うこと。
     pub const fn new(v: T) -> Self {
         Self(v)
     }
@@ -621,3 +1168,55 @@ where
         self.0 << OFFSET
     }
 }
+
+macro_rules! impl_uint_arithmetic {
+    ($ty:ty) => {
+        impl<const BITS: u32, const OFFSET: u32> Uint<$ty, BITS, OFFSET> {
+            /// `BITS` ビットで表現可能な最大値
+            pub const MAX: $ty = ((1u128 << BITS) - 1) as $ty;
+
+            /// 指定された数値が `BITS` ビットで表現可能な場合にのみ、インスタンスを作成する
+            pub const fn checked_new(v: $ty) -> Option<Self> {
+                if v <= Self::MAX {
+                    Some(Self(v))
+                } else {
+                    None
+                }
+            }
+
+            /// `BITS` ビットで表現可能な最大値を返す
+            pub const fn max_value() -> $ty {
+                Self::MAX
+            }
+
+            /// 加算結果を返す（`BITS` ビットの範囲を超える場合には [`None`] が返される）
+            pub const fn checked_add(self, rhs: $ty) -> Option<Self> {
+                match self.0.checked_add(rhs) {
+                    Some(v) => Self::checked_new(v),
+                    None => None,
+                }
+            }
+
+            /// 加算結果を返す（`BITS` ビットの範囲を超える場合には最大値に丸められる）
+            pub const fn saturating_add(self, rhs: $ty) -> Self {
+                match self.checked_add(rhs) {
+                    Some(v) => v,
+                    None => Self(Self::MAX),
+                }
+            }
+        }
+    };
+}
+
+impl_uint_arithmetic!(u8);
+impl_uint_arithmetic!(u16);
+impl_uint_arithmetic!(u32);
+impl_uint_arithmetic!(u64);
+
+impl<T: std::fmt::Display, const BITS: u32, const OFFSET: u32> std::fmt::Display
+    for Uint<T, BITS, OFFSET>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
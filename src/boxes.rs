@@ -1,30 +1,74 @@
 //! ボックス群
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     io::{Read, Write},
     num::{NonZeroU16, NonZeroU32},
 };
 
 use crate::{
-    basic_types::as_box_object, descriptors::EsDescriptor, io::ExternalBytes, BaseBox, BoxHeader,
-    BoxSize, BoxType, Decode, Either, Encode, Error, FixedPointNumber, FullBox, FullBoxFlags,
-    FullBoxHeader, Mp4FileTime, Result, Uint, Utf8String,
+    aux::{
+        BrandWarning, SampleTableAccessor, TrackKind, ValidationCode, ValidationError,
+        ValidationIssue, ValidationReport,
+    },
+    basic_types::as_box_object,
+    descriptors::{AacConfig, DecoderConfigDescriptor, EsDescriptor, SlConfigDescriptor},
+    io::{push_decode_warning, warn_if_lenient, BitReader, ExternalBytes},
+    BaseBox, BoxHeader, BoxSize, BoxType, Decode, Either, Encode, Error, FixedPointNumber, FullBox,
+    FullBoxFlags, FullBoxHeader, Mp4FileTime, Result, Uint, Utf8String,
 };
 
 /// ペイロードの解釈方法が不明なボックスを保持するための構造体
 ///
-/// ペイロードは単なるバイト列として扱われる
+/// ペイロードは単なるバイト列として扱われる。
+/// デコード時のヘッダーの形式（32 ビットないし 64 ビットのサイズ表現）も [`UnknownBox::box_size`] として保持されるので、
+/// 内容を変更せずにエンコードした場合には、元のバイト列がそのまま再現される
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UnknownBox {
     /// ボックス種別
     pub box_type: BoxType,
 
     /// ボックスサイズ
+    ///
+    /// エンコード時にはこの値がそのまま使われるので、`payload` を変更した場合には合わせて更新する必要がある
     pub box_size: BoxSize,
 
     /// ペイロード
     pub payload: Vec<u8>,
 }
 
+impl UnknownBox {
+    /// ペイロードを UTF-8 文字列として解釈できる場合には、その文字列を返す
+    pub fn try_as_string(&self) -> Option<&str> {
+        std::str::from_utf8(&self.payload).ok()
+    }
+
+    /// ペイロードがちょうど 4 バイトの場合には、それをビッグエンディアンの `u32` として解釈した値を返す
+    pub fn try_as_u32(&self) -> Option<u32> {
+        self.payload
+            .as_slice()
+            .try_into()
+            .ok()
+            .map(u32::from_be_bytes)
+    }
+
+    /// ボックス種別の文字列表現を返す
+    ///
+    /// 表現形式は [`BoxType`] の [`Display`](std::fmt::Display) 実装と同じで、
+    /// `©too` のように先頭が `0xA9` のものは `©` 付きで、それ以外の表示できないバイトを含むものは 16 進数で表される。
+    /// 表示可能な ASCII 文字のみからなる場合には、ボックス種別のバイト列をそのまま参照する
+    pub fn type_tag(&self) -> Cow<'_, str> {
+        if let BoxType::Normal(ty) = &self.box_type {
+            if let Ok(s) = std::str::from_utf8(ty) {
+                if s.bytes().all(|b| (0x20..0x7F).contains(&b)) {
+                    return Cow::Borrowed(s);
+                }
+            }
+        }
+        Cow::Owned(self.box_type.to_string())
+    }
+}
+
 impl Encode for UnknownBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
@@ -198,6 +242,30 @@ impl Brand {
     /// [<https://aomediacodec.github.io/av1-isobmff/>] `av01` ブランド
     pub const AV01: Self = Self::new(*b"av01");
 
+    /// [ISO/IEC 14496-15] `hvc1` ブランド
+    pub const HVC1: Self = Self::new(*b"hvc1");
+
+    /// [ISO/IEC 14496-15] `hev1` ブランド
+    pub const HEV1: Self = Self::new(*b"hev1");
+
+    /// [<https://opus-codec.org/docs/opus_in_isobmff.html>] `Opus` ブランド
+    pub const OPUS: Self = Self::new(*b"Opus");
+
+    /// [ISO/IEC 23000-19] `cmfc` ブランド (CMAF トラック)
+    pub const CMFC: Self = Self::new(*b"cmfc");
+
+    /// [ISO/IEC 23000-19] `cmf2` ブランド (CMAF トラック)
+    pub const CMF2: Self = Self::new(*b"cmf2");
+
+    /// [ISO/IEC 23000-19] `cmfs` ブランド (CMAF セグメント)
+    pub const CMFS: Self = Self::new(*b"cmfs");
+
+    /// [ISO/IEC 23000-19] `cfhd` ブランド (CMAF の AVC HD メディアプロファイル)
+    pub const CFHD: Self = Self::new(*b"cfhd");
+
+    /// [ISO/IEC 23000-19] `caac` ブランド (CMAF の AAC-LC メディアプロファイル)
+    pub const CAAC: Self = Self::new(*b"caac");
+
     /// バイト列を渡して、対応するブランドを作成する
     pub const fn new(brand: [u8; 4]) -> Self {
         Self(brand)
@@ -207,6 +275,68 @@ impl Brand {
     pub const fn get(self) -> [u8; 4] {
         self.0
     }
+
+    // このライブラリで定数が定義されているブランドと、その説明の一覧
+    const KNOWN_BRANDS: [(Self, &'static str); 24] = [
+        (Self::ISOM, "ISO Base Media file format"),
+        (Self::AVC1, "ISO Base Media file format with AVC support"),
+        (Self::ISO2, "ISO Base Media file format version 2"),
+        (
+            Self::MP71,
+            "ISO Base Media file format with MPEG-7 metadata",
+        ),
+        (Self::ISO3, "ISO Base Media file format version 3"),
+        (Self::ISO4, "ISO Base Media file format version 4"),
+        (Self::ISO5, "ISO Base Media file format version 5"),
+        (Self::ISO6, "ISO Base Media file format version 6"),
+        (Self::ISO7, "ISO Base Media file format version 7"),
+        (Self::ISO8, "ISO Base Media file format version 8"),
+        (Self::ISO9, "ISO Base Media file format version 9"),
+        (Self::ISOA, "ISO Base Media file format version 10"),
+        (Self::ISOB, "ISO Base Media file format version 11"),
+        (
+            Self::RELO,
+            "ISO Base Media file format with relocatable boxes",
+        ),
+        (Self::MP41, "MP4 file format version 1"),
+        (Self::AV01, "AV1 Codec ISO Media File Format"),
+        (Self::HVC1, "ISO Base Media file format with HEVC support"),
+        (
+            Self::HEV1,
+            "ISO Base Media file format with HEVC support (in-band parameter sets)",
+        ),
+        (Self::OPUS, "Opus in ISO Base Media file format"),
+        (Self::CMFC, "CMAF track"),
+        (Self::CMF2, "CMAF track (version 2)"),
+        (Self::CMFS, "CMAF segment"),
+        (Self::CFHD, "CMAF AVC HD media profile"),
+        (Self::CAAC, "CMAF AAC-LC media profile"),
+    ];
+
+    /// このブランドが、このライブラリで定数が定義されている既知のブランドかどうかを判定する
+    pub const fn is_known(self) -> bool {
+        self.description().is_some()
+    }
+
+    /// このライブラリで定数が定義されている既知のブランドを走査するイテレーターを返す
+    pub fn known_brands() -> impl Iterator<Item = Brand> {
+        Self::KNOWN_BRANDS.into_iter().map(|(brand, _)| brand)
+    }
+
+    /// 既知のブランドの場合には、その説明を返す
+    ///
+    /// 未知のブランドの場合には [`None`] が返される
+    pub const fn description(self) -> Option<&'static str> {
+        let mut i = 0;
+        while i < Self::KNOWN_BRANDS.len() {
+            let (brand, description) = Self::KNOWN_BRANDS[i];
+            if u32::from_be_bytes(brand.0) == u32::from_be_bytes(self.0) {
+                return Some(description);
+            }
+            i += 1;
+        }
+        None
+    }
 }
 
 impl std::fmt::Debug for Brand {
@@ -234,18 +364,82 @@ impl Decode for Brand {
     }
 }
 
-/// [ISO/IEC 14496-12] FileTypeBox class
+/// [`FtypBox`] と [`StypBox`] で共通のペイロードの内容
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct FtypBox {
+pub struct FileTypeBoxContent {
     pub major_brand: Brand,
     pub minor_version: u32,
     pub compatible_brands: Vec<Brand>,
 }
 
-impl FtypBox {
-    /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"ftyp");
+impl FileTypeBoxContent {
+    /// ブランドの指定内容をチェックして、問題となり得る箇所を警告として返す
+    ///
+    /// この検査は `self` を変更しない
+    pub fn validate(&self) -> Vec<BrandWarning> {
+        let mut warnings = Vec::new();
+        if self.compatible_brands.is_empty() {
+            warnings.push(BrandWarning::EmptyCompatibleBrands);
+        } else if !self.compatible_brands.contains(&self.major_brand) {
+            warnings.push(BrandWarning::MajorBrandNotInCompatibleBrands);
+        }
+
+        for (i, brand) in self.compatible_brands.iter().enumerate() {
+            if self.compatible_brands[..i].contains(brand)
+                && !warnings.contains(&BrandWarning::DuplicateCompatibleBrand(*brand))
+            {
+                warnings.push(BrandWarning::DuplicateCompatibleBrand(*brand));
+            }
+        }
+
+        const MP42: Brand = Brand::new(*b"mp42");
+        let has_iso_brand = self.brands().any(|b| {
+            matches!(
+                b,
+                Brand::ISOM
+                    | Brand::ISO2
+                    | Brand::ISO3
+                    | Brand::ISO4
+                    | Brand::ISO5
+                    | Brand::ISO6
+                    | Brand::ISO7
+                    | Brand::ISO8
+                    | Brand::ISO9
+                    | Brand::ISOA
+                    | Brand::ISOB
+            )
+        });
+        if !has_iso_brand {
+            for legacy in [Brand::MP41, MP42] {
+                if self.brands().any(|b| b == legacy) {
+                    warnings.push(BrandWarning::LegacyBrandWithoutModernEquivalent(legacy));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// フラグメント MP4 用のブランドが含まれているかどうかを判定する
+    ///
+    /// メジャーブランドないし互換ブランドに `mf4a`, `mf4v`, `cmf2`, `cmfc` のいずれかがあれば `true` となる
+    pub fn is_fragmented_mp4(&self) -> bool {
+        const MF4A: Brand = Brand::new(*b"mf4a");
+        const MF4V: Brand = Brand::new(*b"mf4v");
+        self.brands()
+            .any(|b| matches!(b, MF4A | MF4V | Brand::CMF2 | Brand::CMFC))
+    }
+
+    /// CMAF のブランド（`cmfc` ないし `cmf2`）が含まれているかどうかを判定する
+    pub fn is_cmaf(&self) -> bool {
+        self.brands()
+            .any(|b| matches!(b, Brand::CMF2 | Brand::CMFC))
+    }
+
+    fn brands(&self) -> impl '_ + Iterator<Item = Brand> {
+        std::iter::once(self.major_brand).chain(self.compatible_brands.iter().copied())
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         self.major_brand.encode(&mut writer)?;
@@ -255,12 +449,97 @@ impl FtypBox {
         }
         Ok(())
     }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let major_brand = Brand::decode(&mut reader)?;
+        let minor_version = u32::decode(&mut reader)?;
+        let mut compatible_brands = Vec::new();
+        while reader.limit() > 0 {
+            compatible_brands.push(Brand::decode(&mut reader)?);
+        }
+        Ok(Self {
+            major_brand,
+            minor_version,
+            compatible_brands,
+        })
+    }
+}
+
+/// [ISO/IEC 14496-12] FileTypeBox class
+///
+/// ペイロードの内容（ブランドの指定）は [`FileTypeBoxContent`] で表される。
+/// [`FileTypeBoxContent`] への [`Deref`](std::ops::Deref) を実装しているので、
+/// 各フィールドやメソッドには `FtypBox` から直接アクセスできる
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FtypBox(pub FileTypeBoxContent);
+
+impl FtypBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"ftyp");
+
+    /// サンプルエントリーの内容に応じたブランドを指定した `ftyp` ボックスを作成する
+    ///
+    /// 各サンプルエントリーのコーデックに対応するブランド（`avc1`, `hvc1`, `hev1`, `av01`, `Opus`）を
+    /// 出現順に互換ブランドに含め、その後に `isom`, `iso2`, `mp41` を続ける。
+    /// メジャーブランドには最初の映像コーデックのブランドが使われ、
+    /// 映像コーデックのブランドがない場合には最初の音声コーデックのブランド、それもない場合には `isom` となる。
+    ///
+    /// 例えば、AVC の映像トラックと AAC の音声トラックを含む場合には、
+    /// メジャーブランドは `avc1` で、互換ブランドは `[avc1, isom, iso2, mp41]` となる
+    pub fn from_sample_entries<'a, I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = &'a SampleEntry>,
+    {
+        let mut video_brands = Vec::new();
+        let mut audio_brands = Vec::new();
+        for entry in entries {
+            let (brands, brand) = match entry {
+                SampleEntry::Avc1(_) => (&mut video_brands, Brand::AVC1),
+                SampleEntry::Hev1(_) => (&mut video_brands, Brand::HEV1),
+                SampleEntry::Hvc1(_) => (&mut video_brands, Brand::HVC1),
+                SampleEntry::Av01(_) => (&mut video_brands, Brand::AV01),
+                SampleEntry::Opus(_) => (&mut audio_brands, Brand::OPUS),
+                SampleEntry::Vp08(_)
+                | SampleEntry::Vp09(_)
+                | SampleEntry::Mp4a(_)
+                | SampleEntry::Tmcd(_)
+                | SampleEntry::Unknown(_) => continue,
+            };
+            if !brands.contains(&brand) {
+                brands.push(brand);
+            }
+        }
+
+        let mut compatible_brands = video_brands;
+        compatible_brands.extend(audio_brands);
+        let major_brand = compatible_brands.first().copied().unwrap_or(Brand::ISOM);
+        compatible_brands.extend([Brand::ISOM, Brand::ISO2, Brand::MP41]);
+        Self(FileTypeBoxContent {
+            major_brand,
+            minor_version: 0,
+            compatible_brands,
+        })
+    }
+}
+
+impl std::ops::Deref for FtypBox {
+    type Target = FileTypeBoxContent;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for FtypBox {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
 }
 
 impl Encode for FtypBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
-        self.encode_payload(writer)?;
+        self.0.encode_payload(writer)?;
         Ok(())
     }
 }
@@ -269,24 +548,218 @@ impl Decode for FtypBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
+        header
+            .with_box_payload_reader(reader, FileTypeBoxContent::decode_payload)
+            .map(Self)
+    }
+}
 
-        header.with_box_payload_reader(reader, |mut reader| {
-            let major_brand = Brand::decode(&mut reader)?;
-            let minor_version = u32::decode(&mut reader)?;
-            let mut compatible_brands = Vec::new();
-            while reader.limit() > 0 {
-                compatible_brands.push(Brand::decode(&mut reader)?);
-            }
-            Ok(Self {
-                major_brand,
-                minor_version,
-                compatible_brands,
-            })
+impl BaseBox for FtypBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.0.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(std::iter::empty())
+    }
+}
+
+/// [ISO/IEC 14496-12] SegmentTypeBox class
+///
+/// ペイロードの形式は [`FtypBox`] と同じ（[`FileTypeBoxContent`]）で、
+/// DASH や CMAF のセグメントファイルの先頭に配置される
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StypBox(pub FileTypeBoxContent);
+
+impl StypBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"styp");
+
+    /// CMAF セグメント用の [`StypBox`] インスタンスを作成する
+    ///
+    /// メジャーブランドは `cmfs` で、互換ブランドには `cmfs` と `cmf2` が含まれる
+    pub fn cmaf_segment() -> Self {
+        Self::cmaf(&[Brand::CMFS, Brand::CMF2])
+    }
+
+    /// CMAF の音声セグメント用の [`StypBox`] インスタンスを作成する
+    ///
+    /// メジャーブランドは `cmfs` で、互換ブランドには `cmfs`, `cmfc` と
+    /// AAC-LC のメディアプロファイルを表す `caac` が含まれる
+    ///
+    /// 互換ブランドの保持に [`Vec`] を使っているため、定数ではなく関数として提供している
+    pub fn cmaf_audio() -> Self {
+        Self::cmaf(&[Brand::CMFS, Brand::CMFC, Brand::CAAC])
+    }
+
+    /// CMAF の映像セグメント用の [`StypBox`] インスタンスを作成する
+    ///
+    /// メジャーブランドは `cmfs` で、互換ブランドには `cmfs`, `cmfc` と
+    /// AVC の HD メディアプロファイルを表す `cfhd` が含まれる
+    ///
+    /// 互換ブランドの保持に [`Vec`] を使っているため、定数ではなく関数として提供している
+    pub fn cmaf_video() -> Self {
+        Self::cmaf(&[Brand::CMFS, Brand::CMFC, Brand::CFHD])
+    }
+
+    fn cmaf(compatible_brands: &[Brand]) -> Self {
+        Self(FileTypeBoxContent {
+            major_brand: Brand::CMFS,
+            minor_version: 0,
+            compatible_brands: compatible_brands.to_vec(),
         })
     }
 }
 
-impl BaseBox for FtypBox {
+impl std::ops::Deref for StypBox {
+    type Target = FileTypeBoxContent;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for StypBox {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Encode for StypBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.0.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for StypBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header
+            .with_box_payload_reader(reader, FileTypeBoxContent::decode_payload)
+            .map(Self)
+    }
+}
+
+impl BaseBox for StypBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.0.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(std::iter::empty())
+    }
+}
+
+/// [ISO/IEC 14496-12] ProducerReferenceTimeBox class
+///
+/// フラグメントのデコード時刻と、実時間（NTP タイムスタンプ）との対応を表す。
+/// 低遅延の DASH / CMAF ライブ配信で、配信側と再生側の時刻を同期するために使われる。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrftBox {
+    /// フルボックスのフラグ（タイムスタンプの取得タイミングを表す）
+    pub flags: FullBoxFlags,
+
+    /// 基準となるトラックの ID
+    pub reference_track_id: u32,
+
+    /// NTP 形式（上位 32 ビットが 1900/1/1 からの経過秒数、下位 32 ビットが秒未満の端数）のタイムスタンプ
+    pub ntp_timestamp: u64,
+
+    /// `reference_track_id` のトラックのタイムスケールでのメディア時刻
+    pub media_time: u64,
+}
+
+impl PrftBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"prft");
+
+    /// NTP のエポック（1900/1/1）から UNIX エポック（1970/1/1）までの経過秒数
+    const NTP_UNIX_EPOCH_OFFSET_SECS: u64 = 2208988800;
+
+    /// 実時間 `wall_clock` を NTP 形式に変換して [`PrftBox`] インスタンスを作成する
+    pub fn from_system_time(
+        track_id: u32,
+        media_time: u64,
+        wall_clock: std::time::SystemTime,
+    ) -> Self {
+        const NANOS_PER_SEC: i128 = 1_000_000_000;
+        let offset_nanos = Self::NTP_UNIX_EPOCH_OFFSET_SECS as i128 * NANOS_PER_SEC;
+        let nanos = match wall_clock.duration_since(std::time::UNIX_EPOCH) {
+            Ok(d) => offset_nanos + d.as_nanos() as i128,
+            Err(e) => offset_nanos - e.duration().as_nanos() as i128,
+        }
+        .max(0);
+
+        // NTP の秒部分は 32 ビットなので、範囲外の場合には折り返す（NTP のエラ（era）の扱いと同じ）
+        let secs = (nanos / NANOS_PER_SEC) as u32 as u64;
+        let fraction = (((nanos % NANOS_PER_SEC) << 32) / NANOS_PER_SEC) as u64;
+        Self {
+            flags: FullBoxFlags::new(0),
+            reference_track_id: track_id,
+            ntp_timestamp: (secs << 32) | fraction,
+            media_time,
+        }
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        self.reference_track_id.encode(&mut writer)?;
+        self.ntp_timestamp.encode(&mut writer)?;
+        if self.full_box_version() == 1 {
+            self.media_time.encode(writer)?;
+        } else {
+            (self.media_time as u32).encode(writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+        let reference_track_id = u32::decode(&mut reader)?;
+        let ntp_timestamp = u64::decode(&mut reader)?;
+        let media_time = if full_header.version == 1 {
+            u64::decode(reader)?
+        } else {
+            u32::decode(reader)? as u64
+        };
+        Ok(Self {
+            flags: full_header.flags,
+            reference_track_id,
+            ntp_timestamp,
+            media_time,
+        })
+    }
+}
+
+impl Encode for PrftBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for PrftBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for PrftBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -300,6 +773,48 @@ impl BaseBox for FtypBox {
     }
 }
 
+impl FullBox for PrftBox {
+    fn full_box_version(&self) -> u8 {
+        if self.media_time > u32::MAX as u64 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        self.flags
+    }
+}
+
+// 一つしか存在してはいけない種別のボックスが重複していた場合に、寛容モードであれば警告を追加する
+//
+// なお、重複した二つ目以降のボックスは、寛容モードかどうかに関わらず未知のボックスとして扱われる
+fn warn_if_duplicate(box_type: BoxType, single_box_types: &[BoxType]) {
+    if single_box_types.contains(&box_type) {
+        push_decode_warning(|| format!("Duplicate '{box_type}' box (only the first one is used)"));
+    }
+}
+
+// バージョン 0 と 1 のみが定義されているフルボックスで、それ以外のバージョンが指定されていた場合に、
+// 寛容モードであれば警告を追加する（この場合はバージョン 0 とみなしてデコードが行われる）
+fn warn_if_unknown_version(box_type: BoxType, version: u8) {
+    if version > 1 {
+        push_decode_warning(|| {
+            format!(
+                "Unknown full box version of '{box_type}' box: {version} (decoded as version 0)"
+            )
+        });
+    }
+}
+
+// ペイロード内のエントリー数 `count` から、エントリー群を格納する Vec の初期容量を求める
+//
+// `count` は信頼できない入力なので、ペイロードの残りのサイズ `remaining` に収まりうる数を上限とする
+fn entries_capacity(count: usize, remaining: u64, entry_size: usize) -> usize {
+    count.min(usize::try_from(remaining).unwrap_or(usize::MAX) / entry_size)
+}
+
 /// [`Mp4File`](crate::Mp4File) のトップレベルに位置するボックス群のデフォルト実装
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
@@ -307,6 +822,8 @@ pub enum RootBox {
     Free(FreeBox),
     Mdat(MdatBox),
     Moov(MoovBox),
+    Prft(PrftBox),
+    Styp(StypBox),
     Unknown(UnknownBox),
 }
 
@@ -316,6 +833,8 @@ impl RootBox {
             RootBox::Free(b) => b,
             RootBox::Mdat(b) => b,
             RootBox::Moov(b) => b,
+            RootBox::Prft(b) => b,
+            RootBox::Styp(b) => b,
             RootBox::Unknown(b) => b,
         }
     }
@@ -327,6 +846,8 @@ impl Encode for RootBox {
             RootBox::Free(b) => b.encode(writer),
             RootBox::Mdat(b) => b.encode(writer),
             RootBox::Moov(b) => b.encode(writer),
+            RootBox::Prft(b) => b.encode(writer),
+            RootBox::Styp(b) => b.encode(writer),
             RootBox::Unknown(b) => b.encode(writer),
         }
     }
@@ -339,6 +860,8 @@ impl Decode for RootBox {
             FreeBox::TYPE => Decode::decode(&mut reader).map(Self::Free),
             MdatBox::TYPE => Decode::decode(&mut reader).map(Self::Mdat),
             MoovBox::TYPE => Decode::decode(&mut reader).map(Self::Moov),
+            PrftBox::TYPE => Decode::decode(&mut reader).map(Self::Prft),
+            StypBox::TYPE => Decode::decode(&mut reader).map(Self::Styp),
             _ => Decode::decode(&mut reader).map(Self::Unknown),
         }
     }
@@ -366,47 +889,237 @@ impl BaseBox for RootBox {
     }
 }
 
-/// [ISO/IEC 14496-12] FreeSpaceBox class
+/// [`RootBox`] の `mdat` ボックスのペイロードをコピーせずに入力バイト列への参照として保持する版
+///
+/// [`Mp4File::decode_ref()`](crate::Mp4File::decode_ref) と組み合わせて使うことを想定している。
+/// `mdat` 以外のボックスの扱いは [`RootBox`] と同じ
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct FreeBox {
-    pub payload: Vec<u8>,
+pub enum RootBoxRef<'a> {
+    Free(FreeBox),
+    Mdat(MdatBoxRef<'a>),
+    Moov(MoovBox),
+    Prft(PrftBox),
+    Styp(StypBox),
+    Unknown(UnknownBox),
 }
 
-impl FreeBox {
-    /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"free");
-}
+impl<'a> RootBoxRef<'a> {
+    /// `buf` の先頭にあるボックスをデコードする
+    ///
+    /// `mdat` ボックスの場合には [`MdatBoxRef::from_slice()`] が、
+    /// それ以外の場合には [`RootBox`] と同様のデコード処理が使われる。
+    /// 返り値の二番目の要素は、ボックス全体（ヘッダーを含む）のバイト数となる。
+    pub fn from_slice(buf: &'a [u8]) -> Result<(Self, usize)> {
+        let header = BoxHeader::decode(buf)?;
+        if header.box_type == MdatBoxRef::TYPE {
+            return MdatBoxRef::from_slice(buf).map(|(b, size)| (Self::Mdat(b), size));
+        }
 
-impl Encode for FreeBox {
-    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
-        BoxHeader::from_box(self).encode(&mut writer)?;
-        writer.write_all(&self.payload)?;
-        Ok(())
+        let mut reader = buf;
+        let b = match RootBox::decode(&mut reader)? {
+            RootBox::Free(b) => Self::Free(b),
+            RootBox::Mdat(_) => unreachable!(),
+            RootBox::Moov(b) => Self::Moov(b),
+            RootBox::Prft(b) => Self::Prft(b),
+            RootBox::Styp(b) => Self::Styp(b),
+            RootBox::Unknown(b) => Self::Unknown(b),
+        };
+        Ok((b, buf.len() - reader.len()))
     }
-}
 
-impl Decode for FreeBox {
-    fn decode<R: Read>(mut reader: R) -> Result<Self> {
-        let header = BoxHeader::decode(&mut reader)?;
-        header.box_type.expect(Self::TYPE)?;
+    /// `mdat` ボックスのペイロードをコピーして、対応する [`RootBox`] を作成する
+    pub fn to_root_box(self) -> RootBox {
+        match self {
+            RootBoxRef::Free(b) => RootBox::Free(b),
+            RootBoxRef::Mdat(b) => RootBox::Mdat(b.to_mdat_box()),
+            RootBoxRef::Moov(b) => RootBox::Moov(b),
+            RootBoxRef::Prft(b) => RootBox::Prft(b),
+            RootBoxRef::Styp(b) => RootBox::Styp(b),
+            RootBoxRef::Unknown(b) => RootBox::Unknown(b),
+        }
+    }
 
-        let mut payload = Vec::new();
-        header.with_box_payload_reader(reader, |reader| Ok(reader.read_to_end(&mut payload)?))?;
-        Ok(Self { payload })
+    fn inner_box(&self) -> &dyn BaseBox {
+        match self {
+            RootBoxRef::Free(b) => b,
+            RootBoxRef::Mdat(b) => b,
+            RootBoxRef::Moov(b) => b,
+            RootBoxRef::Prft(b) => b,
+            RootBoxRef::Styp(b) => b,
+            RootBoxRef::Unknown(b) => b,
+        }
     }
 }
 
-impl BaseBox for FreeBox {
-    fn box_type(&self) -> BoxType {
-        Self::TYPE
+impl Encode for RootBoxRef<'_> {
+    fn encode<W: Write>(&self, writer: W) -> Result<()> {
+        match self {
+            RootBoxRef::Free(b) => b.encode(writer),
+            RootBoxRef::Mdat(b) => b.encode(writer),
+            RootBoxRef::Moov(b) => b.encode(writer),
+            RootBoxRef::Prft(b) => b.encode(writer),
+            RootBoxRef::Styp(b) => b.encode(writer),
+            RootBoxRef::Unknown(b) => b.encode(writer),
+        }
     }
+}
 
-    fn box_payload_size(&self) -> u64 {
-        self.payload.len() as u64
+impl BaseBox for RootBoxRef<'_> {
+    fn box_type(&self) -> BoxType {
+        self.inner_box().box_type()
     }
 
-    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+    fn box_size(&self) -> BoxSize {
+        self.inner_box().box_size()
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        self.inner_box().box_payload_size()
+    }
+
+    fn is_unknown_box(&self) -> bool {
+        self.inner_box().is_unknown_box()
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        self.inner_box().children()
+    }
+}
+
+/// [`Mp4File`](crate::Mp4File) のトップレベルに位置するボックス群のうち `moov` ボックスだけをデコードする実装
+///
+/// `moov` 以外のボックスはペイロードを保持せずに読み飛ばされ、
+/// ヘッダーとボックスの位置だけが [`FastRootBox::Unknown`] として記録される。
+///
+/// 巨大な `mdat` ボックスを含むファイルを扱う場合には
+/// [`Mp4File::decode_fast()`](crate::Mp4File::decode_fast) と組み合わせて使うことを想定している
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FastRootBox {
+    /// `moov` ボックス
+    Moov(MoovBox),
+
+    /// ペイロードが読み飛ばされたボックス
+    Unknown {
+        /// ボックス種別
+        box_type: BoxType,
+
+        /// ボックスサイズ
+        box_size: BoxSize,
+
+        /// ボックス全体（ヘッダーを含む）のバイト位置
+        ///
+        /// [`Mp4File::decode_fast()`](crate::Mp4File::decode_fast) でデコードした場合にはファイル先頭からの位置となる。
+        /// [`Decode::decode()`] で単体のボックスをデコードした場合には、
+        /// 位置情報が得られないのでボックス先頭からの相対位置（つまり常に `0` 始まり）となる
+        byte_range: std::ops::Range<u64>,
+    },
+}
+
+impl Decode for FastRootBox {
+    fn decode<R: Read>(reader: R) -> Result<Self> {
+        let (header, mut reader) = BoxHeader::peek(reader)?;
+        if header.box_type == MoovBox::TYPE {
+            return Decode::decode(&mut reader).map(Self::Moov);
+        }
+
+        let header = BoxHeader::decode(&mut reader)?;
+        let box_payload_size = header.with_box_payload_reader(reader, |reader| {
+            Ok(std::io::copy(reader, &mut std::io::sink())?)
+        })?;
+        Ok(Self::Unknown {
+            box_type: header.box_type,
+            box_size: header.box_size,
+            byte_range: 0..header.external_size() as u64 + box_payload_size,
+        })
+    }
+}
+
+impl BaseBox for FastRootBox {
+    fn box_type(&self) -> BoxType {
+        match self {
+            FastRootBox::Moov(b) => b.box_type(),
+            FastRootBox::Unknown { box_type, .. } => *box_type,
+        }
+    }
+
+    fn box_size(&self) -> BoxSize {
+        match self {
+            FastRootBox::Moov(b) => b.box_size(),
+            FastRootBox::Unknown { box_size, .. } => *box_size,
+        }
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        match self {
+            FastRootBox::Moov(b) => b.box_payload_size(),
+            FastRootBox::Unknown {
+                box_type,
+                box_size,
+                byte_range,
+            } => {
+                let header = BoxHeader {
+                    box_type: *box_type,
+                    box_size: *box_size,
+                };
+                (byte_range.end - byte_range.start).saturating_sub(header.external_size() as u64)
+            }
+        }
+    }
+
+    fn is_unknown_box(&self) -> bool {
+        matches!(self, FastRootBox::Unknown { .. })
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        match self {
+            FastRootBox::Moov(b) => b.children(),
+            FastRootBox::Unknown { .. } => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+/// [ISO/IEC 14496-12] FreeSpaceBox class
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct FreeBox {
+    pub payload: Vec<u8>,
+}
+
+impl FreeBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"free");
+}
+
+impl Encode for FreeBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        writer.write_all(&self.payload)?;
+        Ok(())
+    }
+}
+
+impl Decode for FreeBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+
+        let mut payload = Vec::new();
+        header.with_box_payload_reader(reader, |reader| Ok(reader.read_to_end(&mut payload)?))?;
+        Ok(Self { payload })
+    }
+}
+
+impl BaseBox for FreeBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        self.payload.len() as u64
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(std::iter::empty())
     }
 }
@@ -442,7 +1155,7 @@ impl Decode for MdatBox {
         let mut payload = Vec::new();
         header.with_box_payload_reader(reader, |reader| Ok(reader.read_to_end(&mut payload)?))?;
         Ok(Self {
-            is_variable_size: header.box_size == BoxSize::VARIABLE_SIZE,
+            is_variable_size: header.box_size.is_eof(),
             payload,
         })
     }
@@ -470,288 +1183,611 @@ impl BaseBox for MdatBox {
     }
 }
 
-/// [ISO/IEC 14496-12] MovieBox class
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct MoovBox {
-    pub mvhd_box: MvhdBox,
-    pub trak_boxes: Vec<TrakBox>,
-    pub unknown_boxes: Vec<UnknownBox>,
+/// ペイロードをコピーせずに入力バイト列への参照として保持する [`MdatBox`]
+///
+/// `mdat` ボックスのペイロードは巨大になりがちなので、
+/// 入力全体がすでにメモリ上にある場合にはこちらを使うことで余分なコピーを避けられる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MdatBoxRef<'a> {
+    /// ペイロードが可変長かどうか
+    pub is_variable_size: bool,
+
+    /// ペイロード
+    pub payload: &'a [u8],
 }
 
-impl MoovBox {
+impl<'a> MdatBoxRef<'a> {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"moov");
+    pub const TYPE: BoxType = MdatBox::TYPE;
 
-    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.mvhd_box.encode(&mut writer)?;
-        for b in &self.trak_boxes {
-            b.encode(&mut writer)?;
-        }
-        for b in &self.unknown_boxes {
-            b.encode(&mut writer)?;
-        }
-        Ok(())
-    }
+    /// `buf` の先頭にある `mdat` ボックスをデコードする
+    ///
+    /// 結果の `payload` は `buf` を直接参照する。
+    /// 返り値の二番目の要素は、ボックス全体（ヘッダーを含む）のバイト数となる。
+    ///
+    /// 結果が入力バイト列を参照するため、[`Decode`] トレイトではなくこのメソッドでデコードを行う
+    pub fn from_slice(buf: &'a [u8]) -> Result<(Self, usize)> {
+        let mut reader = buf;
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
 
-    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut mvhd_box = None;
-        let mut trak_boxes = Vec::new();
-        let mut unknown_boxes = Vec::new();
-        while reader.limit() > 0 {
-            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
-            match header.box_type {
-                MvhdBox::TYPE if mvhd_box.is_none() => {
-                    mvhd_box = Some(Decode::decode(&mut reader)?);
-                }
-                TrakBox::TYPE => {
-                    trak_boxes.push(Decode::decode(&mut reader)?);
-                }
-                _ => {
-                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
-                }
-            }
-        }
+        let header_size = buf.len() - reader.len();
+        let is_variable_size = header.box_size.is_eof();
+        let box_size = if is_variable_size {
+            buf.len()
+        } else {
+            usize::try_from(header.box_size.get())
+                .ok()
+                .filter(|&size| header_size <= size && size <= buf.len())
+                .ok_or_else(|| {
+                    Error::invalid_data(&format!(
+                        "Invalid box size: box_size={}, header_size={header_size}, buffer_size={}",
+                        header.box_size.get(),
+                        buf.len()
+                    ))
+                    .with_box_type(Self::TYPE)
+                })?
+        };
 
-        let mvhd_box = mvhd_box.ok_or_else(|| Error::missing_box("mvhd", Self::TYPE))?;
-        Ok(Self {
-            mvhd_box,
-            trak_boxes,
-            unknown_boxes,
-        })
+        let payload = &buf[header_size..box_size];
+        Ok((
+            Self {
+                is_variable_size,
+                payload,
+            },
+            box_size,
+        ))
+    }
+
+    /// ペイロードをコピーして、対応する [`MdatBox`] を作成する
+    pub fn to_mdat_box(self) -> MdatBox {
+        MdatBox {
+            is_variable_size: self.is_variable_size,
+            payload: self.payload.to_vec(),
+        }
     }
 }
 
-impl Encode for MoovBox {
+impl Encode for MdatBoxRef<'_> {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
-        self.encode_payload(writer)?;
+        writer.write_all(self.payload)?;
         Ok(())
     }
 }
 
-impl Decode for MoovBox {
-    fn decode<R: Read>(mut reader: R) -> Result<Self> {
-        let header = BoxHeader::decode(&mut reader)?;
-        header.box_type.expect(Self::TYPE)?;
-        header.with_box_payload_reader(reader, Self::decode_payload)
-    }
-}
-
-impl BaseBox for MoovBox {
+impl BaseBox for MdatBoxRef<'_> {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
 
+    fn box_size(&self) -> BoxSize {
+        if self.is_variable_size {
+            BoxSize::VARIABLE_SIZE
+        } else {
+            BoxSize::with_payload_size(Self::TYPE, self.box_payload_size())
+        }
+    }
+
     fn box_payload_size(&self) -> u64 {
-        ExternalBytes::calc(|writer| self.encode_payload(writer))
+        self.payload.len() as u64
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(
-            std::iter::empty()
-                .chain(std::iter::once(&self.mvhd_box).map(as_box_object))
-                .chain(self.trak_boxes.iter().map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
-        )
+        Box::new(std::iter::empty())
     }
 }
 
-/// [ISO/IEC 14496-12] MovieHeaderBox class (親: [`MoovBox`])
+// 未知のボックス群のうち、種別が `box_types` に含まれるものを、元の順番のまま走査する
+fn unknown_boxes_in<'a>(
+    boxes: &'a [UnknownBox],
+    box_types: &'a [BoxType],
+) -> impl 'a + Iterator<Item = &'a UnknownBox> {
+    boxes.iter().filter(|b| box_types.contains(&b.box_type))
+}
+
+// 未知のボックス群のうち、種別が `box_types` に含まれないものを、元の順番のまま走査する
+fn unknown_boxes_not_in<'a>(
+    boxes: &'a [UnknownBox],
+    box_types: &'a [BoxType],
+) -> impl 'a + Iterator<Item = &'a UnknownBox> {
+    boxes.iter().filter(|b| !box_types.contains(&b.box_type))
+}
+
+/// [ISO/IEC 14496-12] MovieBox class
+///
+/// エンコード時の子ボックスの順番は [ISO/IEC 14496-12] の推奨順序に従い、
+/// `mvhd`、（`unknown_boxes` 内の）`iods` と `meta`、`trak` 群、それ以外の `unknown_boxes` の順となる
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct MvhdBox {
-    pub creation_time: Mp4FileTime,
-    pub modification_time: Mp4FileTime,
-    pub timescale: NonZeroU32,
-    pub duration: u64,
-    pub rate: FixedPointNumber<i16, u16>,
-    pub volume: FixedPointNumber<i8, u8>,
-    pub matrix: [i32; 9],
-    pub next_track_id: u32,
+pub struct MoovBox {
+    pub mvhd_box: MvhdBox,
+    pub trak_boxes: Vec<TrakBox>,
+    pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl MvhdBox {
+impl MoovBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"mvhd");
+    pub const TYPE: BoxType = BoxType::Normal(*b"moov");
 
-    /// [`MvhdBox::rate`] のデフォルト値（通常の再生速度）
-    pub const DEFAULT_RATE: FixedPointNumber<i16, u16> = FixedPointNumber::new(1, 0);
+    /// 全トラックのサンプルエントリーの内容に応じたブランドを指定した `ftyp` ボックスを作成する
+    ///
+    /// 詳細は [`FtypBox::from_sample_entries()`] を参照のこと
+    pub fn compute_ftyp(&self) -> FtypBox {
+        FtypBox::from_sample_entries(
+            self.trak_boxes
+                .iter()
+                .flat_map(|t| &t.mdia_box.minf_box.stbl_box.stsd_box.entries),
+        )
+    }
 
-    /// [`MvhdBox::volume`] のデフォルト値（最大音量）
-    pub const DEFAULT_VOLUME: FixedPointNumber<i8, u8> = FixedPointNumber::new(1, 0);
+    /// 最初の映像トラック（ハンドラー種別が `vide` のトラック）を返す
+    pub fn video_track(&self) -> Option<&TrakBox> {
+        self.tracks_by_handler(HdlrBox::HANDLER_TYPE_VIDE).next()
+    }
 
-    /// [`MvhdBox::matrix`] のデフォルト値
-    pub const DEFAULT_MATRIX: [i32; 9] = [0x00010000, 0, 0, 0, 0x00010000, 0, 0, 0, 0x40000000];
+    /// 最初の映像トラックへの可変参照を返す
+    pub fn video_track_mut(&mut self) -> Option<&mut TrakBox> {
+        self.tracks_by_handler_mut(HdlrBox::HANDLER_TYPE_VIDE)
+    }
 
-    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        FullBoxHeader::from_box(self).encode(&mut writer)?;
-        if self.full_box_version() == 1 {
-            self.creation_time.as_secs().encode(&mut writer)?;
-            self.modification_time.as_secs().encode(&mut writer)?;
-            self.timescale.encode(&mut writer)?;
-            self.duration.encode(&mut writer)?;
-        } else {
-            (self.creation_time.as_secs() as u32).encode(&mut writer)?;
-            (self.modification_time.as_secs() as u32).encode(&mut writer)?;
-            self.timescale.encode(&mut writer)?;
-            (self.duration as u32).encode(&mut writer)?;
+    /// 最初の音声トラック（ハンドラー種別が `soun` のトラック）を返す
+    pub fn audio_track(&self) -> Option<&TrakBox> {
+        self.tracks_by_handler(HdlrBox::HANDLER_TYPE_SOUN).next()
+    }
+
+    /// 最初の音声トラックへの可変参照を返す
+    pub fn audio_track_mut(&mut self) -> Option<&mut TrakBox> {
+        self.tracks_by_handler_mut(HdlrBox::HANDLER_TYPE_SOUN)
+    }
+
+    /// 指定されたハンドラー種別を持つトラック群を走査するイテレーターを返す
+    pub fn tracks_by_handler(&self, handler_type: [u8; 4]) -> impl '_ + Iterator<Item = &TrakBox> {
+        self.trak_boxes
+            .iter()
+            .filter(move |b| b.mdia_box.hdlr_box.handler_type == handler_type)
+    }
+
+    /// 指定されたトラック ID を持つトラックを返す
+    pub fn track_by_id(&self, track_id: u32) -> Option<&TrakBox> {
+        self.trak_boxes
+            .iter()
+            .find(|b| b.tkhd_box.track_id == track_id)
+    }
+
+    fn tracks_by_handler_mut(&mut self, handler_type: [u8; 4]) -> Option<&mut TrakBox> {
+        self.trak_boxes
+            .iter_mut()
+            .find(|b| b.mdia_box.hdlr_box.handler_type == handler_type)
+    }
+
+    /// 全てのトラックに対して [`StblBox::validate()`] を呼び出して、整合性をチェックする
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        for trak_box in &self.trak_boxes {
+            trak_box.mdia_box.minf_box.stbl_box.validate()?;
         }
-        self.rate.encode(&mut writer)?;
-        self.volume.encode(&mut writer)?;
-        [0u8; 2 + 4 * 2].encode(&mut writer)?;
-        self.matrix.encode(&mut writer)?;
-        [0u8; 4 * 6].encode(&mut writer)?;
-        self.next_track_id.encode(writer)?;
         Ok(())
     }
 
-    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let full_header = FullBoxHeader::decode(&mut reader)?;
-        let mut this = Self {
-            creation_time: Mp4FileTime::default(),
-            modification_time: Mp4FileTime::default(),
-            timescale: NonZeroU32::MIN,
-            duration: 0,
-            rate: Self::DEFAULT_RATE,
-            volume: Self::DEFAULT_VOLUME,
-            matrix: Self::DEFAULT_MATRIX,
-            next_track_id: 0,
+    /// `moov` ボックス全体の構造的な整合性を詳細にチェックして、検出された問題の一覧を返す
+    ///
+    /// [`MoovBox::validate()`] とは異なり、最初の問題で打ち切らずに全ての問題を報告する。
+    /// 各問題には [`ValidationCode`] と重大度が付与されるので、QA ツールなどで機械的に扱うことができる。
+    ///
+    /// 主なチェック内容:
+    /// - トラック ID の重複や `next_track_id` との整合性
+    /// - `mvhd` / `mdhd` / `stts` の間での尺の整合性
+    /// - `elst` の `media_time` の範囲
+    /// - ハンドラー種別とメディアヘッダーボックスの対応
+    /// - サンプルテーブル内の各ボックスの整合性（[`StblBox::validate()`] の内容を含む）
+    /// - チャンクのデータ範囲の重なり
+    pub fn validate_deep(&self) -> ValidationReport {
+        let mut issues = Vec::new();
+        let mut push = |path: String, code: ValidationCode, message: String| {
+            issues.push(ValidationIssue::new(path, code, message));
         };
 
-        if full_header.version == 1 {
-            this.creation_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
-            this.modification_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
-            this.timescale = NonZeroU32::decode(&mut reader)?;
-            this.duration = u64::decode(&mut reader)?;
-        } else {
-            this.creation_time =
-                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
-            this.modification_time =
-                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
-            this.timescale = NonZeroU32::decode(&mut reader)?;
-            this.duration = u32::decode(&mut reader)? as u64;
+        if self.trak_boxes.is_empty() {
+            push(
+                "moov".to_owned(),
+                ValidationCode::NoTracks,
+                "No `trak` boxes".to_owned(),
+            );
         }
 
-        this.rate = FixedPointNumber::decode(&mut reader)?;
-        this.volume = FixedPointNumber::decode(&mut reader)?;
-        let _ = <[u8; 2 + 4 * 2]>::decode(&mut reader)?;
-        this.matrix = <[i32; 9]>::decode(&mut reader)?;
-        let _ = <[u8; 4 * 6]>::decode(&mut reader)?;
-        this.next_track_id = u32::decode(reader)?;
+        if let Some(max_track_id) = self.trak_boxes.iter().map(|b| b.tkhd_box.track_id).max() {
+            let next_track_id = self.mvhd_box.next_track_id;
+            // 全ビットが 1 の場合は「次のトラック ID が不明」を意味するので対象外
+            if next_track_id != u32::MAX && next_track_id <= max_track_id {
+                push(
+                    "moov > mvhd".to_owned(),
+                    ValidationCode::NextTrackIdTooSmall,
+                    format!("next_track_id {next_track_id} is not greater than the maximum track ID {max_track_id}"),
+                );
+            }
+        }
 
-        Ok(this)
-    }
-}
+        for (i, trak_box) in self.trak_boxes.iter().enumerate() {
+            let trak_path = format!("moov > trak[{i}]");
+            let track_id = trak_box.tkhd_box.track_id;
+            if track_id == 0 {
+                push(
+                    format!("{trak_path} > tkhd"),
+                    ValidationCode::TrackIdZero,
+                    "Track ID is 0".to_owned(),
+                );
+            } else if self.trak_boxes[..i]
+                .iter()
+                .any(|b| b.tkhd_box.track_id == track_id)
+            {
+                push(
+                    format!("{trak_path} > tkhd"),
+                    ValidationCode::TrackIdDuplicate,
+                    format!("Track ID {track_id} is duplicated"),
+                );
+            }
 
-impl Encode for MvhdBox {
-    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
-        BoxHeader::from_box(self).encode(&mut writer)?;
-        self.encode_payload(writer)?;
-        Ok(())
-    }
-}
+            self.validate_trak_deep(trak_box, &trak_path, &mut push);
+        }
 
-impl Decode for MvhdBox {
-    fn decode<R: Read>(mut reader: R) -> Result<Self> {
-        let header = BoxHeader::decode(&mut reader)?;
-        header.box_type.expect(Self::TYPE)?;
-        header.with_box_payload_reader(reader, Self::decode_payload)
+        ValidationReport { issues }
     }
-}
 
-impl BaseBox for MvhdBox {
-    fn box_type(&self) -> BoxType {
-        Self::TYPE
+    fn validate_trak_deep<F>(&self, trak_box: &TrakBox, trak_path: &str, push: &mut F)
+    where
+        F: FnMut(String, ValidationCode, String),
+    {
+        let mdia_box = &trak_box.mdia_box;
+        let mdhd_box = &mdia_box.mdhd_box;
+        let stbl_box = &mdia_box.minf_box.stbl_box;
+        let mdia_path = format!("{trak_path} > mdia");
+        let stbl_path = format!("{mdia_path} > minf > stbl");
+
+        let sample_count = stbl_box
+            .stts_box
+            .entries
+            .iter()
+            .map(|x| x.sample_count as u64)
+            .sum::<u64>();
+        // 不正なファイルでも panic しないように、合計は飽和演算で求める
+        let stts_duration = stbl_box
+            .stts_box
+            .entries
+            .iter()
+            .map(|x| x.sample_count as u64 * x.sample_delta as u64)
+            .fold(0u64, u64::saturating_add);
+
+        if trak_box.edts_box.is_none() {
+            let duration = u128::from(mdhd_box.duration)
+                * u128::from(self.mvhd_box.timescale.get())
+                / u128::from(mdhd_box.timescale.get());
+            if duration > u128::from(self.mvhd_box.duration) {
+                push(
+                    format!("{mdia_path} > mdhd"),
+                    ValidationCode::MdhdDurationMismatchMvhd,
+                    format!(
+                        "Media duration {duration} (in movie timescale) exceeds movie duration {}",
+                        self.mvhd_box.duration
+                    ),
+                );
+            }
+        }
+
+        if mdhd_box.duration != stts_duration {
+            push(
+                format!("{mdia_path} > mdhd"),
+                ValidationCode::MdhdDurationMismatchStts,
+                format!(
+                    "Media duration {} differs from the total sample duration {stts_duration}",
+                    mdhd_box.duration
+                ),
+            );
+        }
+
+        if let Some(elst_box) = trak_box.edts_box.as_ref().and_then(|b| b.elst_box.as_ref()) {
+            for entry in &elst_box.entries {
+                // -1 は空の編集を表す
+                if entry.media_time >= 0 && entry.media_time as u64 > mdhd_box.duration {
+                    push(
+                        format!("{trak_path} > edts > elst"),
+                        ValidationCode::ElstMediaTimeOutOfRange,
+                        format!(
+                            "media_time {} exceeds media duration {}",
+                            entry.media_time, mdhd_box.duration
+                        ),
+                    );
+                }
+            }
+        }
+
+        let handler_type = mdia_box.hdlr_box.handler_type;
+        let mismatch = match &mdia_box.minf_box.media_header_box {
+            Some(MediaInfoHeader::Smhd(_)) => handler_type == HdlrBox::HANDLER_TYPE_VIDE,
+            Some(MediaInfoHeader::Vmhd(_)) => handler_type == HdlrBox::HANDLER_TYPE_SOUN,
+            Some(MediaInfoHeader::Nmhd(_)) | None => false,
+        };
+        if mismatch {
+            push(
+                format!("{mdia_path} > minf"),
+                ValidationCode::HandlerMediaHeaderMismatch,
+                format!(
+                    "Media header box does not match handler type {:?}",
+                    String::from_utf8_lossy(&handler_type)
+                ),
+            );
+        }
+
+        let sample_entry_count = stbl_box.stsd_box.entries.len();
+        if sample_entry_count == 0 {
+            push(
+                format!("{stbl_path} > stsd"),
+                ValidationCode::EmptySampleDescription,
+                "No sample entries".to_owned(),
+            );
+        }
+
+        if sample_count == 0 {
+            push(
+                stbl_path.clone(),
+                ValidationCode::EmptySampleTable,
+                "No samples".to_owned(),
+            );
+        }
+
+        let stsc_path = format!("{stbl_path} > stsc");
+        if let Some(first) = stbl_box.stsc_box.entries.first() {
+            if first.first_chunk.get() != 1 {
+                push(
+                    stsc_path.clone(),
+                    ValidationCode::StscFirstChunkNotOne,
+                    format!("First chunk of the first entry is {}", first.first_chunk),
+                );
+            }
+        }
+        for (i, entry) in stbl_box.stsc_box.entries.iter().enumerate() {
+            if sample_entry_count > 0
+                && entry.sample_description_index.get() as usize > sample_entry_count
+            {
+                push(
+                    stsc_path.clone(),
+                    ValidationCode::StscSampleDescriptionIndexOutOfRange,
+                    format!(
+                        "{i}-th entry refers to sample description index {}, but there are only {sample_entry_count} entries",
+                        entry.sample_description_index
+                    ),
+                );
+            }
+            if entry.sample_per_chunk == 0 {
+                push(
+                    stsc_path.clone(),
+                    ValidationCode::StscZeroSamplesPerChunk,
+                    format!("{i}-th entry has zero samples per chunk"),
+                );
+            }
+        }
+
+        if let Some(stss_box) = &stbl_box.stss_box {
+            let stss_path = format!("{stbl_path} > stss");
+            if let Some(n) = stss_box
+                .sample_numbers
+                .iter()
+                .find(|n| u64::from(n.get()) > sample_count)
+            {
+                push(
+                    stss_path.clone(),
+                    ValidationCode::StssSampleNumberOutOfRange,
+                    format!("Sample number {n} exceeds sample count {sample_count}"),
+                );
+            }
+            if stss_box.sample_numbers.windows(2).any(|x| x[0] >= x[1]) {
+                push(
+                    stss_path,
+                    ValidationCode::StssNotSorted,
+                    "Sample numbers are not in strictly increasing order".to_owned(),
+                );
+            }
+        }
+
+        if let Err(e) = stbl_box.validate() {
+            push(stbl_path, ValidationCode::from(&e), e.to_string());
+            return;
+        }
+
+        // 以降はサンプルテーブルの整合性が取れていることが前提のチェック
+        let Ok(sample_table) = SampleTableAccessor::new(stbl_box) else {
+            return;
+        };
+        let mut ranges = sample_table
+            .chunks()
+            .map(|c| c.data_range())
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|r| r.start);
+        if let Some(x) = ranges.windows(2).find(|x| x[0].end > x[1].start) {
+            push(
+                format!("{stbl_path} > {}", stbl_box.stco_or_co64_box.box_type()),
+                ValidationCode::ChunkDataOverlap,
+                format!(
+                    "Chunk data ranges {}..{} and {}..{} overlap",
+                    x[0].start, x[0].end, x[1].start, x[1].end
+                ),
+            );
+        }
     }
 
-    fn box_payload_size(&self) -> u64 {
-        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    /// 全てのトラックのメディアのタイムスケール（[`MdhdBox::timescale`]）を `target_timescale` に揃える
+    ///
+    /// `mdhd` の尺、`stts` の各サンプルの尺、`elst` の `media_time` が新しいタイムスケールに変換される。
+    /// 変換後の値が整数にならない場合（例えば 48000 Hz から 44100 Hz への変換）にはエラーが返され、
+    /// `self` は変更されない。
+    ///
+    /// なお `ctts` ボックスには未対応なので、それを含むトラックがある場合にもエラーとなる。
+    pub fn normalize_timescale(&mut self, target_timescale: NonZeroU32) -> Result<()> {
+        let mut trak_boxes = self.trak_boxes.clone();
+        for trak_box in &mut trak_boxes {
+            trak_box
+                .rescale_media_timescale(target_timescale)
+                .map_err(|e| e.with_box_type(TrakBox::TYPE))?;
+        }
+        self.trak_boxes = trak_boxes;
+        Ok(())
     }
 
-    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(std::iter::empty())
+    /// 全てのトラックに対して [`TrakBox::strip_edit_list()`] を呼び出して、その結果を返す
+    pub fn strip_all_edit_lists(&mut self) -> Vec<i64> {
+        let movie_timescale = self.mvhd_box.timescale;
+        self.trak_boxes
+            .iter_mut()
+            .map(|trak_box| trak_box.strip_edit_list(movie_timescale))
+            .collect()
     }
-}
 
-impl FullBox for MvhdBox {
-    fn full_box_version(&self) -> u8 {
-        if self.creation_time.as_secs() > u32::MAX as u64
-            || self.modification_time.as_secs() > u32::MAX as u64
-            || self.duration > u32::MAX as u64
+    /// `mapping` に従って各トラックのトラック ID を書き換える
+    ///
+    /// `mapping` のキーが変更前、値が変更後のトラック ID となる。
+    /// 書き換え後には [`MvhdBox::next_track_id`] も、全トラック ID の最大値 + 1 に更新される。
+    ///
+    /// 以下の場合にはエラーが返され、`self` は変更されない:
+    /// - `mapping` のキーに存在しないトラック ID が含まれている
+    /// - 書き換えによってトラック ID が重複する
+    pub fn remap_track_ids(&mut self, mapping: &HashMap<u32, u32>) -> Result<()> {
+        if let Some(id) = mapping.keys().find(|&&id| {
+            !self
+                .trak_boxes
+                .iter()
+                .any(|trak_box| trak_box.tkhd_box.track_id == id)
+        }) {
+            return Err(Error::invalid_input(&format!(
+                "No such track: track_id={id}"
+            )));
+        }
+
+        let new_ids = self
+            .trak_boxes
+            .iter()
+            .map(|trak_box| {
+                let id = trak_box.tkhd_box.track_id;
+                mapping.get(&id).copied().unwrap_or(id)
+            })
+            .collect::<Vec<_>>();
+        if let Some(id) = new_ids
+            .iter()
+            .enumerate()
+            .find(|(i, id)| new_ids[..*i].contains(id))
+            .map(|(_, id)| id)
         {
-            1
-        } else {
-            0
+            return Err(Error::invalid_input(&format!(
+                "Duplicate track ID after remapping: track_id={id}"
+            )));
+        }
+
+        for (trak_box, id) in self.trak_boxes.iter_mut().zip(&new_ids) {
+            trak_box.tkhd_box.track_id = *id;
+        }
+        if let Some(max_id) = new_ids.iter().copied().max() {
+            self.mvhd_box.next_track_id = max_id.saturating_add(1);
         }
+        Ok(())
     }
 
-    fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(0)
+    /// `track_index` 番目のトラックのみを含む [`MoovBox`] を返す
+    ///
+    /// 複数トラックを含む MP4 ファイルを、トラックごとのファイルに分割する場合などに使える。
+    /// 変換後の [`MvhdBox::next_track_id`] は残ったトラックの ID + 1 に、
+    /// [`MvhdBox::duration`] は残ったトラックの [`MdhdBox::duration`] をムービーのタイムスケールに変換した値に更新される。
+    ///
+    /// `track_index` が範囲外の場合にはエラーが返される
+    pub fn into_single_track(mut self, track_index: usize) -> Result<MoovBox> {
+        if track_index >= self.trak_boxes.len() {
+            return Err(Error::invalid_input(&format!(
+                "Track index out of range: track_index={track_index}, track_count={}",
+                self.trak_boxes.len()
+            )));
+        }
+
+        let trak_box = self.trak_boxes.swap_remove(track_index);
+        let mdhd_box = &trak_box.mdia_box.mdhd_box;
+        let duration = mdhd_box.duration as u128 * self.mvhd_box.timescale.get() as u128
+            / mdhd_box.timescale.get() as u128;
+        self.mvhd_box.duration = u64::try_from(duration).unwrap_or(u64::MAX);
+        self.mvhd_box.next_track_id = trak_box.tkhd_box.track_id.saturating_add(1);
+        self.trak_boxes = vec![trak_box];
+        Ok(self)
     }
-}
 
-/// [ISO/IEC 14496-12] TrackBox class (親: [`MoovBox`])
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct TrakBox {
-    pub tkhd_box: TkhdBox,
-    pub edts_box: Option<EdtsBox>,
-    pub mdia_box: MdiaBox,
-    pub unknown_boxes: Vec<UnknownBox>,
-}
+    /// 全てのトラックのチャンクオフセット（`stco` / `co64` ボックス）に `delta` を加算する
+    ///
+    /// `moov` ボックスを `mdat` ボックスの前に配置する場合などに、
+    /// `moov` ボックスのサイズ分だけオフセットをずらすために使える。
+    ///
+    /// いずれかのトラックでオフセットが範囲外になる場合にはエラーが返され、`self` は変更されない
+    pub fn patch_chunk_offsets(&mut self, delta: i64) -> Result<()> {
+        let mut patched = self
+            .trak_boxes
+            .iter()
+            .map(|trak_box| trak_box.mdia_box.minf_box.stbl_box.stco_or_co64_box.clone())
+            .collect::<Vec<_>>();
+        for b in &mut patched {
+            match b {
+                Either::A(b) => b.patch_offsets(delta)?,
+                Either::B(b) => b.patch_offsets(delta)?,
+            }
+        }
+        for (trak_box, b) in self.trak_boxes.iter_mut().zip(patched) {
+            trak_box.mdia_box.minf_box.stbl_box.stco_or_co64_box = b;
+        }
+        Ok(())
+    }
 
-impl TrakBox {
-    /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"trak");
+    // [ISO/IEC 14496-12] の推奨順序で `mvhd` と `trak` の間に置かれる（未知の）ボックスの種別
+    const BOX_TYPES_BEFORE_TRAK: [BoxType; 2] =
+        [BoxType::Normal(*b"iods"), BoxType::Normal(*b"meta")];
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.tkhd_box.encode(&mut writer)?;
-        if let Some(b) = &self.edts_box {
+        self.mvhd_box.encode(&mut writer)?;
+        for b in unknown_boxes_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_TRAK) {
             b.encode(&mut writer)?;
         }
-        self.mdia_box.encode(&mut writer)?;
-        for b in &self.unknown_boxes {
+        for b in &self.trak_boxes {
+            b.encode(&mut writer)?;
+        }
+        for b in unknown_boxes_not_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_TRAK) {
             b.encode(&mut writer)?;
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut tkhd_box = None;
-        let mut edts_box = None;
-        let mut mdia_box = None;
+        let mut mvhd_box = None;
+        let mut trak_boxes = Vec::new();
         let mut unknown_boxes = Vec::new();
         while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                TkhdBox::TYPE if tkhd_box.is_none() => {
-                    tkhd_box = Some(TkhdBox::decode(&mut reader)?)
-                }
-                EdtsBox::TYPE if edts_box.is_none() => {
-                    edts_box = Some(EdtsBox::decode(&mut reader)?)
+                MvhdBox::TYPE if mvhd_box.is_none() => {
+                    mvhd_box = Some(Decode::decode(&mut reader)?);
                 }
-                MdiaBox::TYPE if mdia_box.is_none() => {
-                    mdia_box = Some(MdiaBox::decode(&mut reader)?);
+                TrakBox::TYPE => {
+                    let i = trak_boxes.len();
+                    trak_boxes.push(Decode::decode(&mut reader).map_err(|e| e.with_box_index(i))?);
                 }
                 _ => {
+                    warn_if_duplicate(header.box_type, &[MvhdBox::TYPE]);
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
 
-        let tkhd_box = tkhd_box.ok_or_else(|| Error::missing_box("tkhd", Self::TYPE))?;
-        let mdia_box = mdia_box.ok_or_else(|| Error::missing_box("mdia", Self::TYPE))?;
+        let mvhd_box = mvhd_box.ok_or_else(|| Error::missing_box("mvhd", Self::TYPE))?;
         Ok(Self {
-            tkhd_box,
-            edts_box,
-            mdia_box,
+            mvhd_box,
+            trak_boxes,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for TrakBox {
+impl Encode for MoovBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -759,7 +1795,7 @@ impl Encode for TrakBox {
     }
 }
 
-impl Decode for TrakBox {
+impl Decode for MoovBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -767,7 +1803,7 @@ impl Decode for TrakBox {
     }
 }
 
-impl BaseBox for TrakBox {
+impl BaseBox for MoovBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -779,135 +1815,114 @@ impl BaseBox for TrakBox {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(std::iter::once(&self.tkhd_box).map(as_box_object))
-                .chain(self.edts_box.iter().map(as_box_object))
-                .chain(std::iter::once(&self.mdia_box).map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
+                .chain(std::iter::once(&self.mvhd_box).map(as_box_object))
+                .chain(
+                    unknown_boxes_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_TRAK)
+                        .map(as_box_object),
+                )
+                .chain(self.trak_boxes.iter().map(as_box_object))
+                .chain(
+                    unknown_boxes_not_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_TRAK)
+                        .map(as_box_object),
+                ),
         )
     }
 }
 
-/// [ISO/IEC 14496-12] TrackHeaderBox class (親: [`TrakBox`])
+/// [ISO/IEC 14496-12] MovieHeaderBox class (親: [`MoovBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct TkhdBox {
-    pub flag_track_enabled: bool,
-    pub flag_track_in_movie: bool,
-    pub flag_track_in_preview: bool,
-    pub flag_track_size_is_aspect_ratio: bool,
-
+pub struct MvhdBox {
     pub creation_time: Mp4FileTime,
     pub modification_time: Mp4FileTime,
-    pub track_id: u32,
+    pub timescale: NonZeroU32,
     pub duration: u64,
-    pub layer: i16,
-    pub alternate_group: i16,
+    pub rate: FixedPointNumber<i16, u16>,
     pub volume: FixedPointNumber<i8, u8>,
     pub matrix: [i32; 9],
-    pub width: FixedPointNumber<i16, u16>,
-    pub height: FixedPointNumber<i16, u16>,
+    pub next_track_id: u32,
 }
 
-impl TkhdBox {
+impl MvhdBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"tkhd");
-
-    /// [`TkhdBox::layer`] のデフォルト値
-    pub const DEFAULT_LAYER: i16 = 0;
-
-    /// [`TkhdBox::alternate_group`] のデフォルト値
-    pub const DEFAULT_ALTERNATE_GROUP: i16 = 0;
+    pub const TYPE: BoxType = BoxType::Normal(*b"mvhd");
 
-    /// 音声用の [`TkhdBox::volume`] のデフォルト値（最大音量）
-    pub const DEFAULT_AUDIO_VOLUME: FixedPointNumber<i8, u8> = FixedPointNumber::new(1, 0);
+    /// [`MvhdBox::rate`] のデフォルト値（通常の再生速度）
+    pub const DEFAULT_RATE: FixedPointNumber<i16, u16> = FixedPointNumber::new(1, 0);
 
-    /// 映像用の [`TkhdBox::volume`] のデフォルト値（無音）
-    pub const DEFAULT_VIDEO_VOLUME: FixedPointNumber<i8, u8> = FixedPointNumber::new(0, 0);
+    /// [`MvhdBox::volume`] のデフォルト値（最大音量）
+    pub const DEFAULT_VOLUME: FixedPointNumber<i8, u8> = FixedPointNumber::new(1, 0);
 
-    /// [`TkhdBox::matrix`] のデフォルト値
+    /// [`MvhdBox::matrix`] のデフォルト値
     pub const DEFAULT_MATRIX: [i32; 9] = [0x00010000, 0, 0, 0, 0x00010000, 0, 0, 0, 0x40000000];
 
+    /// [`MvhdBox::duration`] をミリ秒単位に変換した値を返す
+    pub fn duration_ms(&self) -> f64 {
+        self.duration as f64 * 1000.0 / self.timescale.get() as f64
+    }
+
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
         if self.full_box_version() == 1 {
             self.creation_time.as_secs().encode(&mut writer)?;
             self.modification_time.as_secs().encode(&mut writer)?;
-            self.track_id.encode(&mut writer)?;
-            [0u8; 4].encode(&mut writer)?;
+            self.timescale.encode(&mut writer)?;
             self.duration.encode(&mut writer)?;
         } else {
             (self.creation_time.as_secs() as u32).encode(&mut writer)?;
             (self.modification_time.as_secs() as u32).encode(&mut writer)?;
-            self.track_id.encode(&mut writer)?;
-            [0u8; 4].encode(&mut writer)?;
+            self.timescale.encode(&mut writer)?;
             (self.duration as u32).encode(&mut writer)?;
         }
-        [0u8; 4 * 2].encode(&mut writer)?;
-        self.layer.encode(&mut writer)?;
-        self.alternate_group.encode(&mut writer)?;
+        self.rate.encode(&mut writer)?;
         self.volume.encode(&mut writer)?;
-        [0u8; 2].encode(&mut writer)?;
+        [0u8; 2 + 4 * 2].encode(&mut writer)?;
         self.matrix.encode(&mut writer)?;
-        self.width.encode(&mut writer)?;
-        self.height.encode(writer)?;
+        [0u8; 4 * 6].encode(&mut writer)?;
+        self.next_track_id.encode(writer)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
         let mut this = Self {
-            flag_track_enabled: false,
-            flag_track_in_movie: false,
-            flag_track_in_preview: false,
-            flag_track_size_is_aspect_ratio: false,
-
             creation_time: Mp4FileTime::default(),
             modification_time: Mp4FileTime::default(),
-            track_id: 0,
+            timescale: NonZeroU32::MIN,
             duration: 0,
-            layer: Self::DEFAULT_LAYER,
-            alternate_group: Self::DEFAULT_ALTERNATE_GROUP,
-            volume: Self::DEFAULT_AUDIO_VOLUME,
+            rate: Self::DEFAULT_RATE,
+            volume: Self::DEFAULT_VOLUME,
             matrix: Self::DEFAULT_MATRIX,
-            width: FixedPointNumber::new(0, 0),
-            height: FixedPointNumber::new(0, 0),
+            next_track_id: 0,
         };
 
-        this.flag_track_enabled = full_header.flags.is_set(0);
-        this.flag_track_in_movie = full_header.flags.is_set(1);
-        this.flag_track_in_preview = full_header.flags.is_set(2);
-        this.flag_track_size_is_aspect_ratio = full_header.flags.is_set(3);
-
         if full_header.version == 1 {
             this.creation_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
             this.modification_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
-            this.track_id = u32::decode(&mut reader)?;
-            let _ = <[u8; 4]>::decode(&mut reader)?;
+            this.timescale = NonZeroU32::decode(&mut reader)?;
             this.duration = u64::decode(&mut reader)?;
         } else {
             this.creation_time =
                 u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
             this.modification_time =
                 u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
-            this.track_id = u32::decode(&mut reader)?;
-            let _ = <[u8; 4]>::decode(&mut reader)?;
+            this.timescale = NonZeroU32::decode(&mut reader)?;
             this.duration = u32::decode(&mut reader)? as u64;
         }
 
-        let _ = <[u8; 4 * 2]>::decode(&mut reader)?;
-        this.layer = i16::decode(&mut reader)?;
-        this.alternate_group = i16::decode(&mut reader)?;
+        this.rate = FixedPointNumber::decode(&mut reader)?;
         this.volume = FixedPointNumber::decode(&mut reader)?;
-        let _ = <[u8; 2]>::decode(&mut reader)?;
+        let _ = <[u8; 2 + 4 * 2]>::decode(&mut reader)?;
         this.matrix = <[i32; 9]>::decode(&mut reader)?;
-        this.width = FixedPointNumber::decode(&mut reader)?;
-        this.height = FixedPointNumber::decode(reader)?;
+        let _ = <[u8; 4 * 6]>::decode(&mut reader)?;
+        this.next_track_id = u32::decode(reader)?;
 
         Ok(this)
     }
 }
 
-impl Encode for TkhdBox {
+impl Encode for MvhdBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -915,7 +1930,7 @@ impl Encode for TkhdBox {
     }
 }
 
-impl Decode for TkhdBox {
+impl Decode for MvhdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -923,7 +1938,7 @@ impl Decode for TkhdBox {
     }
 }
 
-impl BaseBox for TkhdBox {
+impl BaseBox for MvhdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -937,7 +1952,7 @@ impl BaseBox for TkhdBox {
     }
 }
 
-impl FullBox for TkhdBox {
+impl FullBox for MvhdBox {
     fn full_box_version(&self) -> u8 {
         if self.creation_time.as_secs() > u32::MAX as u64
             || self.modification_time.as_secs() > u32::MAX as u64
@@ -950,59 +1965,243 @@ impl FullBox for TkhdBox {
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::from_flags([
-            (0, self.flag_track_enabled),
-            (1, self.flag_track_in_movie),
-            (2, self.flag_track_in_preview),
-            (3, self.flag_track_size_is_aspect_ratio),
-        ])
+        FullBoxFlags::new(0)
     }
 }
 
-/// [ISO/IEC 14496-12] EditBox class (親: [`TrakBox`])
+/// [ISO/IEC 14496-12] TrackBox class (親: [`MoovBox`])
+///
+/// エンコード時の子ボックスの順番は [ISO/IEC 14496-12] の推奨順序に従い、
+/// `tkhd`、（`unknown_boxes` 内の）`tref` と `trgr`、`edts`、（`unknown_boxes` 内の）`meta`、
+/// `mdia`、`udta`、それ以外の `unknown_boxes` の順となる
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct EdtsBox {
-    pub elst_box: Option<ElstBox>,
+pub struct TrakBox {
+    pub tkhd_box: TkhdBox,
+    pub edts_box: Option<EdtsBox>,
+    pub mdia_box: MdiaBox,
+    pub udta_box: Option<UdtaBox>,
     pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl EdtsBox {
+impl TrakBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"edts");
+    pub const TYPE: BoxType = BoxType::Normal(*b"trak");
+
+    fn rescale_media_timescale(&mut self, target_timescale: NonZeroU32) -> Result<()> {
+        let stbl_box = &mut self.mdia_box.minf_box.stbl_box;
+        if stbl_box.ctts_box.is_some() {
+            return Err(Error::unsupported(
+                "Cannot rescale the timescale of a track with a ctts box",
+            ));
+        }
+
+        let from = self.mdia_box.mdhd_box.timescale.get() as u128;
+        let to = target_timescale.get() as u128;
+        let rescale = |v: u128| {
+            let scaled = v * to;
+            if !scaled.is_multiple_of(from) {
+                return Err(Error::invalid_input(&format!(
+                    "Cannot rescale the value {v} from timescale {from} to {to} without loss"
+                )));
+            }
+            Ok(scaled / from)
+        };
+
+        let duration = rescale(self.mdia_box.mdhd_box.duration as u128)?;
+        self.mdia_box.mdhd_box.duration = u64::try_from(duration)
+            .map_err(|_| Error::invalid_input("Too large duration after rescaling"))?;
+        for entry in &mut stbl_box.stts_box.entries {
+            let delta = rescale(entry.sample_delta as u128)?;
+            entry.sample_delta = u32::try_from(delta)
+                .map_err(|_| Error::invalid_input("Too large sample delta after rescaling"))?;
+        }
+        if let Some(elst_box) = self.edts_box.as_mut().and_then(|b| b.elst_box.as_mut()) {
+            for entry in elst_box.entries.iter_mut().filter(|x| x.media_time >= 0) {
+                let media_time = rescale(entry.media_time as u128)?;
+                entry.media_time = i64::try_from(media_time)
+                    .map_err(|_| Error::invalid_input("Too large media time after rescaling"))?;
+            }
+        }
+        self.mdia_box.mdhd_box.timescale = target_timescale;
+        Ok(())
+    }
+
+    /// トラックの最初のサンプルエントリーを返す
+    ///
+    /// `stsd` ボックスが空の場合には [`None`] が返される
+    pub fn sample_entry(&self) -> Option<&SampleEntry> {
+        self.mdia_box.minf_box.stbl_box.stsd_box.entries.first()
+    }
+
+    /// トラックの最初のサンプルエントリーへの可変参照を返す
+    pub fn sample_entry_mut(&mut self) -> Option<&mut SampleEntry> {
+        self.mdia_box.minf_box.stbl_box.stsd_box.entries.first_mut()
+    }
+
+    /// トラック内のサンプルの数を返す
+    pub fn sample_count(&self) -> u32 {
+        self.mdia_box.minf_box.stbl_box.stsz_box.sample_count()
+    }
+
+    /// 映像トラックの平均フレームレート（サンプル数をメディアの尺で割った値）を返す
+    ///
+    /// 映像トラック以外の場合や、メディアの尺が 0 の場合には [`None`] が返される
+    pub fn frame_rate(&self) -> Option<f64> {
+        let mdhd_box = &self.mdia_box.mdhd_box;
+        if self.track_kind() != Some(TrackKind::Video) || mdhd_box.duration == 0 {
+            return None;
+        }
+        let duration_secs = mdhd_box.duration as f64 / mdhd_box.timescale.get() as f64;
+        Some(self.sample_count() as f64 / duration_secs)
+    }
+
+    /// トラックの平均ビットレート（bps）を返す
+    ///
+    /// メディアの尺が 0 の場合には [`None`] が返される
+    pub fn average_bitrate(&self) -> Option<u64> {
+        let mdhd_box = &self.mdia_box.mdhd_box;
+        if mdhd_box.duration == 0 {
+            return None;
+        }
+        let data_size = self.mdia_box.minf_box.stbl_box.stsz_box.total_data_size();
+        let bits = data_size as u128 * 8 * mdhd_box.timescale.get() as u128;
+        Some(u64::try_from(bits / mdhd_box.duration as u128).unwrap_or(u64::MAX))
+    }
+
+    /// `udta` ボックスに含まれる `kind` ボックス群を返す
+    ///
+    /// `udta` ボックスが存在しない場合には空のスライスが返される
+    pub fn kinds(&self) -> &[KindBox] {
+        self.udta_box
+            .as_ref()
+            .map(|b| b.kind_boxes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// `hdlr` ボックスのハンドラー種別から判定したトラックの種別を返す
+    ///
+    /// 映像と音声以外のハンドラー種別の場合には [`None`] が返される
+    pub fn track_kind(&self) -> Option<TrackKind> {
+        match self.mdia_box.hdlr_box.handler_type {
+            HdlrBox::HANDLER_TYPE_VIDE => Some(TrackKind::Video),
+            HdlrBox::HANDLER_TYPE_SOUN => Some(TrackKind::Audio),
+            _ => None,
+        }
+    }
+
+    /// 編集リスト（`edts` ボックス）を取り除いて、その分のメディアの表示開始位置のずれを返す
+    ///
+    /// 返り値の単位はメディアのタイムスケール（[`MdhdBox::timescale`]）で、以下のように計算される:
+    /// - 先頭の空編集（`media_time == -1`）の尺の合計を加算する（これは `movie_timescale` 単位からの変換を伴う）
+    /// - 空編集に続く最初の編集の `media_time` （メディア先頭でスキップされる尺）を減算する
+    ///
+    /// つまり、正の値はトラックの表示開始が遅延していたことを、
+    /// 負の値はメディアの先頭部分が表示対象外だった（プリロール）ことを意味する。
+    /// 編集リストが存在しない場合には 0 が返される。
+    pub fn strip_edit_list(&mut self, movie_timescale: NonZeroU32) -> i64 {
+        let Some(edts_box) = self.edts_box.take() else {
+            return 0;
+        };
+        let Some(elst_box) = edts_box.elst_box else {
+            return 0;
+        };
+
+        let media_timescale = self.mdia_box.mdhd_box.timescale;
+        let mut empty_duration = 0u64;
+        let mut offset = 0i64;
+        for entry in &elst_box.entries {
+            if entry.media_time == -1 {
+                empty_duration = empty_duration.saturating_add(entry.edit_duration);
+            } else {
+                offset = offset.saturating_sub(entry.media_time);
+                break;
+            }
+        }
+
+        let delay =
+            empty_duration as u128 * media_timescale.get() as u128 / movie_timescale.get() as u128;
+        offset.saturating_add(i64::try_from(delay).unwrap_or(i64::MAX))
+    }
+
+    // [ISO/IEC 14496-12] の推奨順序で `tkhd` と `edts` の間に置かれる（未知の）ボックスの種別
+    const BOX_TYPES_BEFORE_EDTS: [BoxType; 2] =
+        [BoxType::Normal(*b"tref"), BoxType::Normal(*b"trgr")];
+
+    // [ISO/IEC 14496-12] の推奨順序で `edts` と `mdia` の間に置かれる（未知の）ボックスの種別
+    const BOX_TYPES_BEFORE_MDIA: [BoxType; 1] = [BoxType::Normal(*b"meta")];
+
+    const BOX_TYPES_BEFORE_MDIA_ALL: [BoxType; 3] = [
+        Self::BOX_TYPES_BEFORE_EDTS[0],
+        Self::BOX_TYPES_BEFORE_EDTS[1],
+        Self::BOX_TYPES_BEFORE_MDIA[0],
+    ];
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        if let Some(b) = &self.elst_box {
+        self.tkhd_box.encode(&mut writer)?;
+        for b in unknown_boxes_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_EDTS) {
             b.encode(&mut writer)?;
         }
-        for b in &self.unknown_boxes {
+        if let Some(b) = &self.edts_box {
+            b.encode(&mut writer)?;
+        }
+        for b in unknown_boxes_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_MDIA) {
+            b.encode(&mut writer)?;
+        }
+        self.mdia_box.encode(&mut writer)?;
+        if let Some(b) = &self.udta_box {
+            b.encode(&mut writer)?;
+        }
+        for b in unknown_boxes_not_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_MDIA_ALL) {
             b.encode(&mut writer)?;
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut elst_box = None;
+        let mut tkhd_box = None;
+        let mut edts_box = None;
+        let mut mdia_box = None;
+        let mut udta_box = None;
         let mut unknown_boxes = Vec::new();
         while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                ElstBox::TYPE if elst_box.is_none() => {
-                    elst_box = Some(ElstBox::decode(&mut reader)?);
+                TkhdBox::TYPE if tkhd_box.is_none() => {
+                    tkhd_box = Some(TkhdBox::decode(&mut reader)?)
+                }
+                EdtsBox::TYPE if edts_box.is_none() => {
+                    edts_box = Some(EdtsBox::decode(&mut reader)?)
+                }
+                MdiaBox::TYPE if mdia_box.is_none() => {
+                    mdia_box = Some(MdiaBox::decode(&mut reader)?);
+                }
+                UdtaBox::TYPE if udta_box.is_none() => {
+                    udta_box = Some(UdtaBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(
+                        header.box_type,
+                        &[TkhdBox::TYPE, EdtsBox::TYPE, MdiaBox::TYPE, UdtaBox::TYPE],
+                    );
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
+
+        let tkhd_box = tkhd_box.ok_or_else(|| Error::missing_box("tkhd", Self::TYPE))?;
+        let mdia_box = mdia_box.ok_or_else(|| Error::missing_box("mdia", Self::TYPE))?;
         Ok(Self {
-            elst_box,
+            tkhd_box,
+            edts_box,
+            mdia_box,
+            udta_box,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for EdtsBox {
+impl Encode for TrakBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1010,7 +2209,7 @@ impl Encode for EdtsBox {
     }
 }
 
-impl Decode for EdtsBox {
+impl Decode for TrakBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1018,7 +2217,7 @@ impl Decode for EdtsBox {
     }
 }
 
-impl BaseBox for EdtsBox {
+impl BaseBox for TrakBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1030,86 +2229,156 @@ impl BaseBox for EdtsBox {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(self.elst_box.iter().map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
+                .chain(std::iter::once(&self.tkhd_box).map(as_box_object))
+                .chain(
+                    unknown_boxes_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_EDTS)
+                        .map(as_box_object),
+                )
+                .chain(self.edts_box.iter().map(as_box_object))
+                .chain(
+                    unknown_boxes_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_MDIA)
+                        .map(as_box_object),
+                )
+                .chain(std::iter::once(&self.mdia_box).map(as_box_object))
+                .chain(self.udta_box.iter().map(as_box_object))
+                .chain(
+                    unknown_boxes_not_in(&self.unknown_boxes, &Self::BOX_TYPES_BEFORE_MDIA_ALL)
+                        .map(as_box_object),
+                ),
         )
     }
 }
 
-/// [`ElstBox`] に含まれるエントリー
+/// [ISO/IEC 14496-12] TrackHeaderBox class (親: [`TrakBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct ElstEntry {
-    pub edit_duration: u64,
-    pub media_time: i64,
-    pub media_rate: FixedPointNumber<i16, i16>,
-}
+pub struct TkhdBox {
+    pub flag_track_enabled: bool,
+    pub flag_track_in_movie: bool,
+    pub flag_track_in_preview: bool,
+    pub flag_track_size_is_aspect_ratio: bool,
 
-/// [ISO/IEC 14496-12] EditListBox class (親: [`EdtsBox`])
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct ElstBox {
-    pub entries: Vec<ElstEntry>,
+    pub creation_time: Mp4FileTime,
+    pub modification_time: Mp4FileTime,
+    pub track_id: u32,
+    pub duration: u64,
+    pub layer: i16,
+    pub alternate_group: i16,
+    pub volume: FixedPointNumber<i8, u8>,
+    pub matrix: [i32; 9],
+    pub width: FixedPointNumber<i16, u16>,
+    pub height: FixedPointNumber<i16, u16>,
 }
 
-impl ElstBox {
+impl TkhdBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"elst");
+    pub const TYPE: BoxType = BoxType::Normal(*b"tkhd");
+
+    /// [`TkhdBox::layer`] のデフォルト値
+    pub const DEFAULT_LAYER: i16 = 0;
+
+    /// [`TkhdBox::alternate_group`] のデフォルト値
+    pub const DEFAULT_ALTERNATE_GROUP: i16 = 0;
+
+    /// 音声用の [`TkhdBox::volume`] のデフォルト値（最大音量）
+    pub const DEFAULT_AUDIO_VOLUME: FixedPointNumber<i8, u8> = FixedPointNumber::new(1, 0);
+
+    /// 映像用の [`TkhdBox::volume`] のデフォルト値（無音）
+    pub const DEFAULT_VIDEO_VOLUME: FixedPointNumber<i8, u8> = FixedPointNumber::new(0, 0);
+
+    /// [`TkhdBox::matrix`] のデフォルト値
+    pub const DEFAULT_MATRIX: [i32; 9] = [0x00010000, 0, 0, 0, 0x00010000, 0, 0, 0, 0x40000000];
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-
-        let version = self.full_box_version();
-        (self.entries.len() as u32).encode(&mut writer)?;
-        for entry in &self.entries {
-            if version == 1 {
-                entry.edit_duration.encode(&mut writer)?;
-                entry.media_time.encode(&mut writer)?;
-            } else {
-                (entry.edit_duration as u32).encode(&mut writer)?;
-                (entry.media_time as i32).encode(&mut writer)?;
-            }
-            entry.media_rate.encode(&mut writer)?;
+        if self.full_box_version() == 1 {
+            self.creation_time.as_secs().encode(&mut writer)?;
+            self.modification_time.as_secs().encode(&mut writer)?;
+            self.track_id.encode(&mut writer)?;
+            [0u8; 4].encode(&mut writer)?;
+            self.duration.encode(&mut writer)?;
+        } else {
+            (self.creation_time.as_secs() as u32).encode(&mut writer)?;
+            (self.modification_time.as_secs() as u32).encode(&mut writer)?;
+            self.track_id.encode(&mut writer)?;
+            [0u8; 4].encode(&mut writer)?;
+            (self.duration as u32).encode(&mut writer)?;
         }
+        [0u8; 4 * 2].encode(&mut writer)?;
+        self.layer.encode(&mut writer)?;
+        self.alternate_group.encode(&mut writer)?;
+        self.volume.encode(&mut writer)?;
+        [0u8; 2].encode(&mut writer)?;
+        self.matrix.encode(&mut writer)?;
+        self.width.encode(&mut writer)?;
+        self.height.encode(writer)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+        let mut this = Self {
+            flag_track_enabled: false,
+            flag_track_in_movie: false,
+            flag_track_in_preview: false,
+            flag_track_size_is_aspect_ratio: false,
 
-        let mut entries = Vec::new();
-        let count = u32::decode(&mut reader)? as usize;
-        for _ in 0..count {
-            let edit_duration;
-            let media_time;
-            if full_header.version == 1 {
-                edit_duration = u64::decode(&mut reader)?;
-                media_time = i64::decode(&mut reader)?;
-            } else {
-                edit_duration = u32::decode(&mut reader)? as u64;
-                media_time = i32::decode(&mut reader)? as i64;
-            }
-            let media_rate = FixedPointNumber::decode(&mut reader)?;
-            entries.push(ElstEntry {
-                edit_duration,
-                media_time,
-                media_rate,
-            });
-        }
-
-        Ok(Self { entries })
-    }
-}
+            creation_time: Mp4FileTime::default(),
+            modification_time: Mp4FileTime::default(),
+            track_id: 0,
+            duration: 0,
+            layer: Self::DEFAULT_LAYER,
+            alternate_group: Self::DEFAULT_ALTERNATE_GROUP,
+            volume: Self::DEFAULT_AUDIO_VOLUME,
+            matrix: Self::DEFAULT_MATRIX,
+            width: FixedPointNumber::new(0, 0),
+            height: FixedPointNumber::new(0, 0),
+        };
 
-impl Encode for ElstBox {
-    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        this.flag_track_enabled = full_header.flags.is_set(0);
+        this.flag_track_in_movie = full_header.flags.is_set(1);
+        this.flag_track_in_preview = full_header.flags.is_set(2);
+        this.flag_track_size_is_aspect_ratio = full_header.flags.is_set(3);
+
+        if full_header.version == 1 {
+            this.creation_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
+            this.modification_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
+            this.track_id = u32::decode(&mut reader)?;
+            let _ = <[u8; 4]>::decode(&mut reader)?;
+            this.duration = u64::decode(&mut reader)?;
+        } else {
+            this.creation_time =
+                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
+            this.modification_time =
+                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
+            this.track_id = u32::decode(&mut reader)?;
+            let _ = <[u8; 4]>::decode(&mut reader)?;
+            this.duration = u32::decode(&mut reader)? as u64;
+        }
+
+        let _ = <[u8; 4 * 2]>::decode(&mut reader)?;
+        this.layer = i16::decode(&mut reader)?;
+        this.alternate_group = i16::decode(&mut reader)?;
+        this.volume = FixedPointNumber::decode(&mut reader)?;
+        let _ = <[u8; 2]>::decode(&mut reader)?;
+        this.matrix = <[i32; 9]>::decode(&mut reader)?;
+        this.width = FixedPointNumber::decode(&mut reader)?;
+        this.height = FixedPointNumber::decode(reader)?;
+
+        Ok(this)
+    }
+}
+
+impl Encode for TkhdBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
         Ok(())
     }
 }
 
-impl Decode for ElstBox {
+impl Decode for TkhdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1117,7 +2386,7 @@ impl Decode for ElstBox {
     }
 }
 
-impl BaseBox for ElstBox {
+impl BaseBox for TkhdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1131,12 +2400,12 @@ impl BaseBox for ElstBox {
     }
 }
 
-impl FullBox for ElstBox {
+impl FullBox for TkhdBox {
     fn full_box_version(&self) -> u8 {
-        let large = self.entries.iter().any(|x| {
-            u32::try_from(x.edit_duration).is_err() || i32::try_from(x.media_time).is_err()
-        });
-        if large {
+        if self.creation_time.as_secs() > u32::MAX as u64
+            || self.modification_time.as_secs() > u32::MAX as u64
+            || self.duration > u32::MAX as u64
+        {
             1
         } else {
             0
@@ -1144,28 +2413,31 @@ impl FullBox for ElstBox {
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(0)
+        FullBoxFlags::from_flags([
+            (0, self.flag_track_enabled),
+            (1, self.flag_track_in_movie),
+            (2, self.flag_track_in_preview),
+            (3, self.flag_track_size_is_aspect_ratio),
+        ])
     }
 }
 
-/// [ISO/IEC 14496-12] MediaBox class (親: [`TrakBox`])
+/// [ISO/IEC 14496-12] EditBox class (親: [`TrakBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct MdiaBox {
-    pub mdhd_box: MdhdBox,
-    pub hdlr_box: HdlrBox,
-    pub minf_box: MinfBox,
+pub struct EdtsBox {
+    pub elst_box: Option<ElstBox>,
     pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl MdiaBox {
+impl EdtsBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"mdia");
+    pub const TYPE: BoxType = BoxType::Normal(*b"edts");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.mdhd_box.encode(&mut writer)?;
-        self.hdlr_box.encode(&mut writer)?;
-        self.minf_box.encode(&mut writer)?;
+        if let Some(b) = &self.elst_box {
+            b.encode(&mut writer)?;
+        }
         for b in &self.unknown_boxes {
             b.encode(&mut writer)?;
         }
@@ -1173,40 +2445,28 @@ impl MdiaBox {
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut mdhd_box = None;
-        let mut hdlr_box = None;
-        let mut minf_box = None;
+        let mut elst_box = None;
         let mut unknown_boxes = Vec::new();
         while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                MdhdBox::TYPE if mdhd_box.is_none() => {
-                    mdhd_box = Some(MdhdBox::decode(&mut reader)?);
-                }
-                HdlrBox::TYPE if hdlr_box.is_none() => {
-                    hdlr_box = Some(HdlrBox::decode(&mut reader)?);
-                }
-                MinfBox::TYPE if minf_box.is_none() => {
-                    minf_box = Some(MinfBox::decode(&mut reader)?);
+                ElstBox::TYPE if elst_box.is_none() => {
+                    elst_box = Some(ElstBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(header.box_type, &[ElstBox::TYPE]);
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
-        let mdhd_box = mdhd_box.ok_or_else(|| Error::missing_box("mdhd", Self::TYPE))?;
-        let hdlr_box = hdlr_box.ok_or_else(|| Error::missing_box("hdlr", Self::TYPE))?;
-        let minf_box = minf_box.ok_or_else(|| Error::missing_box("minf", Self::TYPE))?;
         Ok(Self {
-            mdhd_box,
-            hdlr_box,
-            minf_box,
+            elst_box,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for MdiaBox {
+impl Encode for EdtsBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1214,7 +2474,7 @@ impl Encode for MdiaBox {
     }
 }
 
-impl Decode for MdiaBox {
+impl Decode for EdtsBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1222,7 +2482,7 @@ impl Decode for MdiaBox {
     }
 }
 
-impl BaseBox for MdiaBox {
+impl BaseBox for EdtsBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1234,99 +2494,68 @@ impl BaseBox for MdiaBox {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(std::iter::once(&self.mdhd_box).map(as_box_object))
-                .chain(std::iter::once(&self.hdlr_box).map(as_box_object))
-                .chain(std::iter::once(&self.minf_box).map(as_box_object))
+                .chain(self.elst_box.iter().map(as_box_object))
                 .chain(self.unknown_boxes.iter().map(as_box_object)),
         )
     }
 }
 
-/// [ISO/IEC 14496-12] MediaHeaderBox class (親: [`MdiaBox`])
+/// [ISO/IEC 14496-12] UserDataBox class (親: [`TrakBox`])
+///
+/// 現時点では [`KindBox`] のみに対応しており、それ以外のボックスは [`UdtaBox::unknown_boxes`] に格納される
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct MdhdBox {
-    pub creation_time: Mp4FileTime,
-    pub modification_time: Mp4FileTime,
-    pub timescale: NonZeroU32,
-    pub duration: u64,
+pub struct UdtaBox {
+    pub kind_boxes: Vec<KindBox>,
+    pub unknown_boxes: Vec<UnknownBox>,
 
-    /// ISO-639-2/T language code
-    pub language: [u8; 3],
+    /// ペイロード末尾の、ボックスヘッダーのサイズ（8 バイト）に満たないバイト列
+    ///
+    /// QuickTime 形式のファイルでは `udta` ボックスの末尾に 32 ビットのゼロ終端が置かれることがあるため、
+    /// それをそのまま保持して、エンコード時に再現できるようにしている
+    pub trailing_bytes: Vec<u8>,
 }
 
-impl MdhdBox {
+impl UdtaBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"mdhd");
-
-    /// 未定義を表す言語コード
-    pub const LANGUAGE_UNDEFINED: [u8; 3] = *b"und";
+    pub const TYPE: BoxType = BoxType::Normal(*b"udta");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        FullBoxHeader::from_box(self).encode(&mut writer)?;
-        if self.full_box_version() == 1 {
-            self.creation_time.as_secs().encode(&mut writer)?;
-            self.modification_time.as_secs().encode(&mut writer)?;
-            self.timescale.encode(&mut writer)?;
-            self.duration.encode(&mut writer)?;
-        } else {
-            (self.creation_time.as_secs() as u32).encode(&mut writer)?;
-            (self.modification_time.as_secs() as u32).encode(&mut writer)?;
-            self.timescale.encode(&mut writer)?;
-            (self.duration as u32).encode(&mut writer)?;
+        for b in &self.kind_boxes {
+            b.encode(&mut writer)?;
         }
-
-        let mut language: u16 = 0;
-        for l in &self.language {
-            language = (language << 5)
-                | l.checked_sub(0x60).ok_or_else(|| {
-                    Error::invalid_input(&format!("Invalid language code: {:?}", self.language))
-                })? as u16;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
         }
-        language.encode(&mut writer)?;
-        [0u8; 2].encode(writer)?;
-
+        writer.write_all(&self.trailing_bytes)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let full_header = FullBoxHeader::decode(&mut reader)?;
-        let mut this = Self {
-            creation_time: Default::default(),
-            modification_time: Default::default(),
-            timescale: NonZeroU32::MIN,
-            duration: Default::default(),
-            language: Default::default(),
-        };
-
-        if full_header.version == 1 {
-            this.creation_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
-            this.modification_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
-            this.timescale = NonZeroU32::decode(&mut reader)?;
-            this.duration = u64::decode(&mut reader)?;
-        } else {
-            this.creation_time =
-                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
-            this.modification_time =
-                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
-            this.timescale = NonZeroU32::decode(&mut reader)?;
-            this.duration = u32::decode(&mut reader)? as u64;
+        let mut kind_boxes = Vec::new();
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() >= 8 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                KindBox::TYPE => {
+                    kind_boxes.push(KindBox::decode(&mut reader)?);
+                }
+                _ => {
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
         }
-
-        let language = u16::decode(&mut reader)?;
-        this.language = [
-            ((language >> 10) & 0b11111) as u8 + 0x60,
-            ((language >> 5) & 0b11111) as u8 + 0x60,
-            (language & 0b11111) as u8 + 0x60,
-        ];
-
-        let _ = <[u8; 2]>::decode(reader)?;
-
-        Ok(this)
+        let mut trailing_bytes = Vec::new();
+        reader.read_to_end(&mut trailing_bytes)?;
+        Ok(Self {
+            kind_boxes,
+            unknown_boxes,
+            trailing_bytes,
+        })
     }
 }
 
-impl Encode for MdhdBox {
+impl Encode for UdtaBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1334,7 +2563,7 @@ impl Encode for MdhdBox {
     }
 }
 
-impl Decode for MdhdBox {
+impl Decode for UdtaBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1342,7 +2571,7 @@ impl Decode for MdhdBox {
     }
 }
 
-impl BaseBox for MdhdBox {
+impl BaseBox for UdtaBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1352,74 +2581,56 @@ impl BaseBox for MdhdBox {
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(std::iter::empty())
-    }
-}
-
-impl FullBox for MdhdBox {
-    fn full_box_version(&self) -> u8 {
-        if self.creation_time.as_secs() > u32::MAX as u64
-            || self.modification_time.as_secs() > u32::MAX as u64
-            || self.duration > u32::MAX as u64
-        {
-            1
-        } else {
-            0
-        }
-    }
-
-    fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(0)
+        Box::new(
+            std::iter::empty()
+                .chain(self.kind_boxes.iter().map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
     }
 }
 
-/// [ISO/IEC 14496-12] HandlerBox class (親: [`MdiaBox`])
+/// [ISO/IEC 14496-12] KindBox class (親: [`UdtaBox`])
+///
+/// 字幕や音声解説といった、トラックの役割を表すラベル
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct HdlrBox {
-    pub handler_type: [u8; 4],
+pub struct KindBox {
+    /// ラベルの意味を定義するスキームの URI
+    pub scheme_uri: Utf8String,
 
-    /// ハンドラ名
-    ///
-    /// ISO の仕様書上はここは [`Utf8String`] であるべきだが、
-    /// 中身が UTF-8 ではなかったり、
-    /// null 終端文字列ではなく先頭にサイズバイトを格納する形式で
-    /// MP4 ファイルを作成する実装が普通に存在するため、
-    /// ここでは単なるバイト列として扱っている
-    pub name: Vec<u8>,
+    /// スキーム内でのラベルの値（スキームによっては空文字列）
+    pub value: Utf8String,
 }
 
-impl HdlrBox {
+impl KindBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"hdlr");
+    pub const TYPE: BoxType = BoxType::Normal(*b"kind");
 
-    /// 音声用のハンドラー種別
-    pub const HANDLER_TYPE_SOUN: [u8; 4] = *b"soun";
+    /// MPEG-DASH の Role スキーム
+    ///
+    /// 値には `"main"`, `"alternate"`, `"subtitle"`, `"caption"`, `"description"` などが使われる
+    pub const DASH_ROLE_SCHEME: &'static str = "urn:mpeg:dash:role:2011";
 
-    /// 映像用のハンドラー種別
-    pub const HANDLER_TYPE_VIDE: [u8; 4] = *b"vide";
+    /// HTML のテキストトラックの種別（`kind` 属性）を表すスキーム
+    ///
+    /// 値には `"subtitles"`, `"captions"`, `"descriptions"`, `"chapters"`, `"metadata"` などが使われる
+    pub const HTML_KIND_SCHEME: &'static str = "about:html-kind";
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        [0u8; 4].encode(&mut writer)?;
-        self.handler_type.encode(&mut writer)?;
-        [0u8; 4 * 3].encode(&mut writer)?;
-        writer.write_all(&self.name)?;
+        self.scheme_uri.encode(&mut writer)?;
+        self.value.encode(writer)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let _full_header = FullBoxHeader::decode(&mut reader)?;
-        let _ = <[u8; 4]>::decode(&mut reader)?;
-        let handler_type = <[u8; 4]>::decode(&mut reader)?;
-        let _ = <[u8; 4 * 3]>::decode(&mut reader)?;
-        let mut name = Vec::new();
-        reader.read_to_end(&mut name)?;
-        Ok(Self { handler_type, name })
+        let scheme_uri = Utf8String::decode(&mut reader)?;
+        let value = Utf8String::decode(reader)?;
+        Ok(Self { scheme_uri, value })
     }
 }
 
-impl Encode for HdlrBox {
+impl Encode for KindBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1427,7 +2638,7 @@ impl Encode for HdlrBox {
     }
 }
 
-impl Decode for HdlrBox {
+impl Decode for KindBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1435,7 +2646,7 @@ impl Decode for HdlrBox {
     }
 }
 
-impl BaseBox for HdlrBox {
+impl BaseBox for KindBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1449,7 +2660,7 @@ impl BaseBox for HdlrBox {
     }
 }
 
-impl FullBox for HdlrBox {
+impl FullBox for KindBox {
     fn full_box_version(&self) -> u8 {
         0
     }
@@ -1459,27 +2670,225 @@ impl FullBox for HdlrBox {
     }
 }
 
-/// [ISO/IEC 14496-12] MediaInformationBox class (親: [`MdiaBox`])
+/// [`ElstBox`] に含まれるエントリー
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct MinfBox {
-    pub smhd_or_vmhd_box: Either<SmhdBox, VmhdBox>,
-    pub dinf_box: DinfBox,
-    pub stbl_box: StblBox,
-    pub unknown_boxes: Vec<UnknownBox>,
-}
+pub struct ElstEntry {
+    pub edit_duration: u64,
+    pub media_time: i64,
+    pub media_rate: FixedPointNumber<i16, i16>,
+}
 
-impl MinfBox {
+/// [ISO/IEC 14496-12] EditListBox class (親: [`EdtsBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct ElstBox {
+    pub entries: Vec<ElstEntry>,
+}
+
+impl ElstBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"minf");
+    pub const TYPE: BoxType = BoxType::Normal(*b"elst");
+
+    /// 空の編集（再生開始前の無音・無表示区間）を表す `media_time` の値
+    pub const EMPTY_EDIT_MEDIA_TIME: i64 = -1;
+
+    /// 先頭のエントリーが空の編集かどうかを返す
+    ///
+    /// 空の編集は、トラックの表示開始を `edit_duration` 分（ムービーのタイムスケール単位）だけ遅らせることを意味する
+    pub fn is_initial_empty_edit(&self) -> bool {
+        self.entries
+            .first()
+            .is_some_and(|x| x.media_time == Self::EMPTY_EDIT_MEDIA_TIME)
+    }
+
+    /// 空ではない最初のエントリーの `media_time` （メディアのタイムスケール単位）を返す
+    pub fn first_media_time(&self) -> Option<i64> {
+        self.entries
+            .iter()
+            .find(|x| x.media_time != Self::EMPTY_EDIT_MEDIA_TIME)
+            .map(|x| x.media_time)
+    }
+
+    /// 全エントリーの `edit_duration` の合計（ムービーのタイムスケール単位）を返す
+    pub fn total_edit_duration(&self) -> u64 {
+        self.entries
+            .iter()
+            .fold(0u64, |acc, x| acc.saturating_add(x.edit_duration))
+    }
+
+    /// 音声トラックのプライミングフレーム数（デコード後に先頭から破棄すべきサンプル数）を返す
+    ///
+    /// AAC などでは、エンコーダーが先頭に付与した無音区間をスキップするために、
+    /// 空ではない最初のエントリーの `media_time` にプライミング分の時間が設定される。
+    /// この値を `media_timescale` （メディアのタイムスケール）から `sample_rate` 単位に変換したものを返す。
+    ///
+    /// 空ではないエントリーが存在しない場合や、値が範囲外の場合には [`None`] が返される
+    pub fn audio_priming_frames(
+        &self,
+        media_timescale: NonZeroU32,
+        sample_rate: NonZeroU32,
+    ) -> Option<u32> {
+        let media_time = u64::try_from(self.first_media_time()?).ok()?;
+        let frames = u128::from(media_time) * u128::from(sample_rate.get())
+            / u128::from(media_timescale.get());
+        u32::try_from(frames).ok()
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        match &self.smhd_or_vmhd_box {
-            Either::A(b) => b.encode(&mut writer)?,
-            Either::B(b) => b.encode(&mut writer)?,
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+
+        let version = self.full_box_version();
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            if version == 1 {
+                entry.edit_duration.encode(&mut writer)?;
+                entry.media_time.encode(&mut writer)?;
+            } else {
+                (entry.edit_duration as u32).encode(&mut writer)?;
+                (entry.media_time as i32).encode(&mut writer)?;
+            }
+            entry.media_rate.encode(&mut writer)?;
         }
-        self.dinf_box.encode(&mut writer)?;
-        self.stbl_box.encode(&mut writer)?;
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+
+        let mut entries = Vec::new();
+        let count = u32::decode(&mut reader)? as usize;
+        for _ in 0..count {
+            let edit_duration;
+            let media_time;
+            if full_header.version == 1 {
+                edit_duration = u64::decode(&mut reader)?;
+                media_time = i64::decode(&mut reader)?;
+            } else {
+                edit_duration = u32::decode(&mut reader)? as u64;
+                media_time = i32::decode(&mut reader)? as i64;
+            }
+            let media_rate = FixedPointNumber::decode(&mut reader)?;
+            entries.push(ElstEntry {
+                edit_duration,
+                media_time,
+                media_rate,
+            });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl Encode for ElstBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for ElstBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for ElstBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(std::iter::empty())
+    }
+}
+
+impl FullBox for ElstBox {
+    fn full_box_version(&self) -> u8 {
+        let large = self.entries.iter().any(|x| {
+            u32::try_from(x.edit_duration).is_err() || i32::try_from(x.media_time).is_err()
+        });
+        if large {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [ISO/IEC 14496-12] MediaBox class (親: [`TrakBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct MdiaBox {
+    pub mdhd_box: MdhdBox,
+    pub hdlr_box: HdlrBox,
+    pub elng_box: Option<ElngBox>,
+    pub minf_box: MinfBox,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl MdiaBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"mdia");
+
+    /// ISO 639-2/T の言語コードと、対応する BCP 47 の言語タグの一覧
+    const BCP47_LANGUAGE_TABLE: [([u8; 3], &'static str); 20] = [
+        (*b"ara", "ar"),
+        (*b"deu", "de"),
+        (*b"eng", "en"),
+        (*b"fra", "fr"),
+        (*b"heb", "he"),
+        (*b"hin", "hi"),
+        (*b"ind", "id"),
+        (*b"ita", "it"),
+        (*b"jpn", "ja"),
+        (*b"kor", "ko"),
+        (*b"nld", "nl"),
+        (*b"pol", "pl"),
+        (*b"por", "pt"),
+        (*b"rus", "ru"),
+        (*b"spa", "es"),
+        (*b"swe", "sv"),
+        (*b"tha", "th"),
+        (*b"tur", "tr"),
+        (*b"vie", "vi"),
+        (*b"zho", "zh"),
+    ];
+
+    /// トラックの言語を BCP 47 形式の言語タグとして返す
+    ///
+    /// [`ElngBox`] が存在する場合にはその値が、
+    /// 存在しない場合には [`MdhdBox::language`] を組み込みの対応表で変換した値が返される。
+    /// 対応表は主要な 20 言語のみを含んでいるため、それ以外の言語や未定義（`und`）の場合には [`None`] が返される。
+    pub fn language_bcp47(&self) -> Option<&str> {
+        if let Some(elng_box) = &self.elng_box {
+            return Some(elng_box.extended_language.get());
+        }
+        Self::BCP47_LANGUAGE_TABLE
+            .iter()
+            .find(|(code, _)| *code == self.mdhd_box.language)
+            .map(|(_, tag)| *tag)
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.mdhd_box.encode(&mut writer)?;
+        self.hdlr_box.encode(&mut writer)?;
+        if let Some(b) = &self.elng_box {
+            b.encode(&mut writer)?;
+        }
+        self.minf_box.encode(&mut writer)?;
         for b in &self.unknown_boxes {
             b.encode(&mut writer)?;
         }
@@ -1487,47 +2896,49 @@ impl MinfBox {
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut smhd_box = None;
-        let mut vmhd_box = None;
-        let mut dinf_box = None;
-        let mut stbl_box = None;
+        let mut mdhd_box = None;
+        let mut hdlr_box = None;
+        let mut elng_box = None;
+        let mut minf_box = None;
         let mut unknown_boxes = Vec::new();
         while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                SmhdBox::TYPE if smhd_box.is_none() => {
-                    smhd_box = Some(SmhdBox::decode(&mut reader)?);
+                MdhdBox::TYPE if mdhd_box.is_none() => {
+                    mdhd_box = Some(MdhdBox::decode(&mut reader)?);
                 }
-                VmhdBox::TYPE if vmhd_box.is_none() => {
-                    vmhd_box = Some(VmhdBox::decode(&mut reader)?);
+                HdlrBox::TYPE if hdlr_box.is_none() => {
+                    hdlr_box = Some(HdlrBox::decode(&mut reader)?);
                 }
-                DinfBox::TYPE if dinf_box.is_none() => {
-                    dinf_box = Some(DinfBox::decode(&mut reader)?);
+                ElngBox::TYPE if elng_box.is_none() => {
+                    elng_box = Some(ElngBox::decode(&mut reader)?);
                 }
-                StblBox::TYPE if stbl_box.is_none() => {
-                    stbl_box = Some(StblBox::decode(&mut reader)?);
+                MinfBox::TYPE if minf_box.is_none() => {
+                    minf_box = Some(MinfBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(
+                        header.box_type,
+                        &[MdhdBox::TYPE, HdlrBox::TYPE, ElngBox::TYPE, MinfBox::TYPE],
+                    );
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
-        let smhd_or_vmhd_box = smhd_box
-            .map(Either::A)
-            .or(vmhd_box.map(Either::B))
-            .ok_or_else(|| Error::missing_box("smhd | vmhd", Self::TYPE))?;
-        let dinf_box = dinf_box.ok_or_else(|| Error::missing_box("dinf", Self::TYPE))?;
-        let stbl_box = stbl_box.ok_or_else(|| Error::missing_box("stbl", Self::TYPE))?;
+        let mdhd_box = mdhd_box.ok_or_else(|| Error::missing_box("mdhd", Self::TYPE))?;
+        let hdlr_box = hdlr_box.ok_or_else(|| Error::missing_box("hdlr", Self::TYPE))?;
+        let minf_box = minf_box.ok_or_else(|| Error::missing_box("minf", Self::TYPE))?;
         Ok(Self {
-            smhd_or_vmhd_box,
-            dinf_box,
-            stbl_box,
+            mdhd_box,
+            hdlr_box,
+            elng_box,
+            minf_box,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for MinfBox {
+impl Encode for MdiaBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1535,7 +2946,7 @@ impl Encode for MinfBox {
     }
 }
 
-impl Decode for MinfBox {
+impl Decode for MdiaBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1543,7 +2954,7 @@ impl Decode for MinfBox {
     }
 }
 
-impl BaseBox for MinfBox {
+impl BaseBox for MdiaBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1555,44 +2966,42 @@ impl BaseBox for MinfBox {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(std::iter::once(&self.smhd_or_vmhd_box).map(as_box_object))
-                .chain(std::iter::once(&self.dinf_box).map(as_box_object))
-                .chain(std::iter::once(&self.stbl_box).map(as_box_object))
+                .chain(std::iter::once(&self.mdhd_box).map(as_box_object))
+                .chain(std::iter::once(&self.hdlr_box).map(as_box_object))
+                .chain(self.elng_box.iter().map(as_box_object))
+                .chain(std::iter::once(&self.minf_box).map(as_box_object))
                 .chain(self.unknown_boxes.iter().map(as_box_object)),
         )
     }
 }
 
-/// [ISO/IEC 14496-12] SoundMediaHeaderBox class (親: [`MinfBox`]）
-#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct SmhdBox {
-    pub balance: FixedPointNumber<u8, u8>,
+/// [ISO/IEC 14496-12] ExtendedLanguageBox class (親: [`MdiaBox`])
+///
+/// [`MdhdBox::language`] よりも詳細な、BCP 47 形式の言語タグ（`"en-US"` や `"zh-Hans"` など）を保持する
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ElngBox {
+    /// BCP 47 形式の言語タグ
+    pub extended_language: Utf8String,
 }
 
-impl SmhdBox {
+impl ElngBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"smhd");
-
-    /// [`SmhdBox::balance`] のデフォルト値（中央）
-    pub const DEFAULT_BALANCE: FixedPointNumber<u8, u8> = FixedPointNumber::new(0, 0);
+    pub const TYPE: BoxType = BoxType::Normal(*b"elng");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        self.balance.encode(&mut writer)?;
-        [0u8; 2].encode(writer)?;
+        self.extended_language.encode(writer)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let _full_header = FullBoxHeader::decode(&mut reader)?;
-        let balance = FixedPointNumber::decode(&mut reader)?;
-        let _ = <[u8; 2]>::decode(reader)?;
-        Ok(Self { balance })
+        let extended_language = Utf8String::decode(reader)?;
+        Ok(Self { extended_language })
     }
 }
 
-impl Encode for SmhdBox {
+impl Encode for ElngBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1600,7 +3009,7 @@ impl Encode for SmhdBox {
     }
 }
 
-impl Decode for SmhdBox {
+impl Decode for ElngBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1608,7 +3017,7 @@ impl Decode for SmhdBox {
     }
 }
 
-impl BaseBox for SmhdBox {
+impl BaseBox for ElngBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1622,7 +3031,7 @@ impl BaseBox for SmhdBox {
     }
 }
 
-impl FullBox for SmhdBox {
+impl FullBox for ElngBox {
     fn full_box_version(&self) -> u8 {
         0
     }
@@ -1632,50 +3041,110 @@ impl FullBox for SmhdBox {
     }
 }
 
-/// [ISO/IEC 14496-12] VideoMediaHeaderBox class (親: [`MinfBox`]）
-#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+/// [ISO/IEC 14496-12] MediaHeaderBox class (親: [`MdiaBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct VmhdBox {
-    pub graphicsmode: u16,
-    pub opcolor: [u16; 3],
+pub struct MdhdBox {
+    pub creation_time: Mp4FileTime,
+    pub modification_time: Mp4FileTime,
+    pub timescale: NonZeroU32,
+    pub duration: u64,
+
+    /// ISO-639-2/T language code
+    pub language: [u8; 3],
 }
 
-impl VmhdBox {
+impl MdhdBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"vmhd");
-
-    /// [`Vmhd::graphicsmode`] のデフォルト値（コピー）
-    pub const DEFAULT_GRAPHICSMODE: u16 = 0;
+    pub const TYPE: BoxType = BoxType::Normal(*b"mdhd");
 
-    /// [`Vmhd::graphicsmode`] のデフォルト値
-    pub const DEFAULT_OPCOLOR: [u16; 3] = [0, 0, 0];
+    /// 未定義を表す言語コード
+    pub const LANGUAGE_UNDEFINED: [u8; 3] = *b"und";
 
-    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        FullBoxHeader::from_box(self).encode(&mut writer)?;
-        self.graphicsmode.encode(&mut writer)?;
-        self.opcolor.encode(writer)?;
-        Ok(())
+    /// [`MdhdBox::duration`] をミリ秒単位に変換した値を返す
+    pub fn duration_ms(&self) -> f64 {
+        self.duration as f64 * 1000.0 / self.timescale.get() as f64
     }
 
-    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let full_header = FullBoxHeader::decode(&mut reader)?;
-        if full_header.flags.get() != 1 {
-            return Err(Error::invalid_data(&format!(
-                "Unexpected FullBox header flags of 'vmhd' box: {}",
-                full_header.flags.get()
-            )));
+    /// 言語コードを文字列として返す
+    ///
+    /// 言語コードが [`MdhdBox::LANGUAGE_UNDEFINED`] の場合や、
+    /// 小文字の ASCII アルファベット三文字になっていない場合には [`None`] が返される
+    pub fn language_str(&self) -> Option<&str> {
+        if self.language == Self::LANGUAGE_UNDEFINED
+            || !self.language.iter().all(|c| c.is_ascii_lowercase())
+        {
+            return None;
         }
+        std::str::from_utf8(&self.language).ok()
+    }
 
-        let graphicsmode = u16::decode(&mut reader)?;
-        let opcolor = <[u16; 3]>::decode(reader)?;
-        Ok(Self {
-            graphicsmode,
-            opcolor,
-        })
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        if self.full_box_version() == 1 {
+            self.creation_time.as_secs().encode(&mut writer)?;
+            self.modification_time.as_secs().encode(&mut writer)?;
+            self.timescale.encode(&mut writer)?;
+            self.duration.encode(&mut writer)?;
+        } else {
+            (self.creation_time.as_secs() as u32).encode(&mut writer)?;
+            (self.modification_time.as_secs() as u32).encode(&mut writer)?;
+            self.timescale.encode(&mut writer)?;
+            (self.duration as u32).encode(&mut writer)?;
+        }
+
+        let mut language: u16 = 0;
+        for l in &self.language {
+            language = (language << 5)
+                | l.checked_sub(0x60).ok_or_else(|| {
+                    Error::invalid_input(&format!("Invalid language code: {:?}", self.language))
+                })? as u16;
+        }
+        language.encode(&mut writer)?;
+        [0u8; 2].encode(writer)?;
+
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+        let mut this = Self {
+            creation_time: Default::default(),
+            modification_time: Default::default(),
+            timescale: NonZeroU32::MIN,
+            duration: Default::default(),
+            language: Default::default(),
+        };
+
+        if full_header.version == 1 {
+            this.creation_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
+            this.modification_time = u64::decode(&mut reader).map(Mp4FileTime::from_secs)?;
+            this.timescale = NonZeroU32::decode(&mut reader)?;
+            this.duration = u64::decode(&mut reader)?;
+        } else {
+            this.creation_time =
+                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
+            this.modification_time =
+                u32::decode(&mut reader).map(|v| Mp4FileTime::from_secs(v as u64))?;
+            this.timescale = NonZeroU32::decode(&mut reader)?;
+            this.duration = u32::decode(&mut reader)? as u64;
+        }
+
+        let language = u16::decode(&mut reader)?;
+        this.language = [
+            ((language >> 10) & 0b11111) as u8 + 0x60,
+            ((language >> 5) & 0b11111) as u8 + 0x60,
+            (language & 0b11111) as u8 + 0x60,
+        ];
+
+        let _ = <[u8; 2]>::decode(reader)?;
+
+        Ok(this)
     }
 }
 
-impl Encode for VmhdBox {
+impl Encode for MdhdBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1683,7 +3152,7 @@ impl Encode for VmhdBox {
     }
 }
 
-impl Decode for VmhdBox {
+impl Decode for MdhdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1691,7 +3160,7 @@ impl Decode for VmhdBox {
     }
 }
 
-impl BaseBox for VmhdBox {
+impl BaseBox for MdhdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1705,65 +3174,109 @@ impl BaseBox for VmhdBox {
     }
 }
 
-impl FullBox for VmhdBox {
+impl FullBox for MdhdBox {
     fn full_box_version(&self) -> u8 {
-        0
+        if self.creation_time.as_secs() > u32::MAX as u64
+            || self.modification_time.as_secs() > u32::MAX as u64
+            || self.duration > u32::MAX as u64
+        {
+            1
+        } else {
+            0
+        }
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(1)
+        FullBoxFlags::new(0)
     }
 }
 
-/// [ISO/IEC 14496-12] DataInformationBox class (親: [`MinfBox`]）
+/// [ISO/IEC 14496-12] HandlerBox class (親: [`MdiaBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct DinfBox {
-    pub dref_box: DrefBox,
-    pub unknown_boxes: Vec<UnknownBox>,
+pub struct HdlrBox {
+    pub handler_type: [u8; 4],
+
+    /// ハンドラ名
+    ///
+    /// ISO の仕様書上はここは [`Utf8String`] であるべきだが、
+    /// 中身が UTF-8 ではなかったり、
+    /// null 終端文字列ではなく先頭にサイズバイトを格納する形式で
+    /// MP4 ファイルを作成する実装が普通に存在するため、
+    /// ここでは単なるバイト列として扱っている
+    pub name: Vec<u8>,
 }
 
-impl DinfBox {
+impl HdlrBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"dinf");
+    pub const TYPE: BoxType = BoxType::Normal(*b"hdlr");
 
-    /// メディアデータが同じファイル内に格納されていることを示す [`DinfBox`] の値
-    pub const LOCAL_FILE: Self = Self {
-        dref_box: DrefBox::LOCAL_FILE,
-        unknown_boxes: Vec::new(),
-    };
+    /// 音声用のハンドラー種別
+    pub const HANDLER_TYPE_SOUN: [u8; 4] = *b"soun";
+
+    /// 映像用のハンドラー種別
+    pub const HANDLER_TYPE_VIDE: [u8; 4] = *b"vide";
+
+    /// 字幕用のハンドラー種別
+    pub const HANDLER_TYPE_SUBT: [u8; 4] = *b"subt";
+
+    /// テキスト用のハンドラー種別
+    pub const HANDLER_TYPE_TEXT: [u8; 4] = *b"text";
+
+    /// メタデータ用のハンドラー種別
+    pub const HANDLER_TYPE_META: [u8; 4] = *b"meta";
+
+    /// ヒントトラック用のハンドラー種別
+    pub const HANDLER_TYPE_HINT: [u8; 4] = *b"hint";
+
+    /// ハンドラ名を UTF-8 文字列として返す
+    ///
+    /// 末尾の null 文字は取り除かれる。
+    /// [`HdlrBox::name`] が UTF-8 として不正な場合には [`None`] が返される。
+    pub fn handler_name_utf8(&self) -> Option<&str> {
+        let name = self.name.strip_suffix(&[0]).unwrap_or(&self.name);
+        std::str::from_utf8(name).ok()
+    }
+
+    /// ハンドラ名を文字列として返す
+    ///
+    /// 末尾の null 文字は取り除かれ、UTF-8 として不正な部分は置換文字に変換される
+    pub fn handler_name_lossy(&self) -> std::borrow::Cow<'_, str> {
+        let name = self.name.strip_suffix(&[0]).unwrap_or(&self.name);
+        String::from_utf8_lossy(name)
+    }
+
+    /// ハンドラ名を null 終端の UTF-8 文字列として設定する
+    ///
+    /// `name` に null 文字が含まれている場合には、その手前までが設定される
+    pub fn set_handler_name(&mut self, name: &str) {
+        let name = name.split('\0').next().unwrap_or_default();
+        self.name = Utf8String::new(name)
+            .unwrap_or(Utf8String::EMPTY)
+            .into_null_terminated_bytes();
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.dref_box.encode(&mut writer)?;
-        for b in &self.unknown_boxes {
-            b.encode(&mut writer)?;
-        }
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        [0u8; 4].encode(&mut writer)?;
+        self.handler_type.encode(&mut writer)?;
+        [0u8; 4 * 3].encode(&mut writer)?;
+        writer.write_all(&self.name)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut dref_box = None;
-        let mut unknown_boxes = Vec::new();
-        while reader.limit() > 0 {
-            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
-            match header.box_type {
-                DrefBox::TYPE if dref_box.is_none() => {
-                    dref_box = Some(DrefBox::decode(&mut reader)?);
-                }
-                _ => {
-                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
-                }
-            }
-        }
-        let dref_box = dref_box.ok_or_else(|| Error::missing_box("dref", Self::TYPE))?;
-        Ok(Self {
-            dref_box,
-            unknown_boxes,
-        })
+        let _full_header = FullBoxHeader::decode(&mut reader)?;
+        let _ = <[u8; 4]>::decode(&mut reader)?;
+        let handler_type = <[u8; 4]>::decode(&mut reader)?;
+        let _ = <[u8; 4 * 3]>::decode(&mut reader)?;
+        let mut name = Vec::new();
+        reader.read_to_end(&mut name)?;
+        Ok(Self { handler_type, name })
     }
 }
 
-impl Encode for DinfBox {
+impl Encode for HdlrBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1771,7 +3284,7 @@ impl Encode for DinfBox {
     }
 }
 
-impl Decode for DinfBox {
+impl Decode for HdlrBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1779,7 +3292,7 @@ impl Decode for DinfBox {
     }
 }
 
-impl BaseBox for DinfBox {
+impl BaseBox for HdlrBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1789,39 +3302,43 @@ impl BaseBox for DinfBox {
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(
-            std::iter::empty()
-                .chain(std::iter::once(&self.dref_box).map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
-        )
+        Box::new(std::iter::empty())
     }
 }
 
-/// [ISO/IEC 14496-12] DataReferenceBox class (親: [`DinfBox`])
+impl FullBox for HdlrBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [ISO/IEC 14496-12] MediaInformationBox class (親: [`MdiaBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct DrefBox {
-    pub url_box: Option<UrlBox>,
+pub struct MinfBox {
+    /// メディア種別に応じたヘッダーボックス
+    ///
+    /// [`SmhdBox`] / [`VmhdBox`] / [`NmhdBox`] のいずれも存在しない場合（QuickTime の `gmhd` ボックスを使うトラックなど）は [`None`] になる
+    pub media_header_box: Option<MediaInfoHeader>,
+    pub dinf_box: DinfBox,
+    pub stbl_box: StblBox,
     pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl DrefBox {
+impl MinfBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"dref");
-
-    /// メディアデータが同じファイル内に格納されていることを示す [`DrefBox`] の値
-    pub const LOCAL_FILE: Self = Self {
-        url_box: Some(UrlBox::LOCAL_FILE),
-        unknown_boxes: Vec::new(),
-    };
+    pub const TYPE: BoxType = BoxType::Normal(*b"minf");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        FullBoxHeader::from_box(self).encode(&mut writer)?;
-        let entry_count = (self.url_box.is_some() as usize + self.unknown_boxes.len()) as u32;
-        entry_count.encode(&mut writer)?;
-        if let Some(b) = &self.url_box {
+        if let Some(b) = &self.media_header_box {
             b.encode(&mut writer)?;
         }
+        self.dinf_box.encode(&mut writer)?;
+        self.stbl_box.encode(&mut writer)?;
         for b in &self.unknown_boxes {
             b.encode(&mut writer)?;
         }
@@ -1829,29 +3346,61 @@ impl DrefBox {
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let _ = FullBoxHeader::decode(&mut reader)?;
-        let entry_count = u32::decode(&mut reader)?;
-        let mut url_box = None;
+        let mut smhd_box = None;
+        let mut vmhd_box = None;
+        let mut nmhd_box = None;
+        let mut dinf_box = None;
+        let mut stbl_box = None;
         let mut unknown_boxes = Vec::new();
-        for _ in 0..entry_count {
+        while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                UrlBox::TYPE if url_box.is_none() => {
-                    url_box = Some(UrlBox::decode(&mut reader)?);
+                SmhdBox::TYPE if smhd_box.is_none() => {
+                    smhd_box = Some(SmhdBox::decode(&mut reader)?);
+                }
+                VmhdBox::TYPE if vmhd_box.is_none() => {
+                    vmhd_box = Some(VmhdBox::decode(&mut reader)?);
+                }
+                NmhdBox::TYPE if nmhd_box.is_none() => {
+                    nmhd_box = Some(NmhdBox::decode(&mut reader)?);
+                }
+                DinfBox::TYPE if dinf_box.is_none() => {
+                    dinf_box = Some(DinfBox::decode(&mut reader)?);
+                }
+                StblBox::TYPE if stbl_box.is_none() => {
+                    stbl_box = Some(StblBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(
+                        header.box_type,
+                        &[
+                            SmhdBox::TYPE,
+                            VmhdBox::TYPE,
+                            NmhdBox::TYPE,
+                            DinfBox::TYPE,
+                            StblBox::TYPE,
+                        ],
+                    );
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
+        let media_header_box = smhd_box
+            .map(MediaInfoHeader::Smhd)
+            .or(vmhd_box.map(MediaInfoHeader::Vmhd))
+            .or(nmhd_box.map(MediaInfoHeader::Nmhd));
+        let dinf_box = dinf_box.ok_or_else(|| Error::missing_box("dinf", Self::TYPE))?;
+        let stbl_box = stbl_box.ok_or_else(|| Error::missing_box("stbl", Self::TYPE))?;
         Ok(Self {
-            url_box,
+            media_header_box,
+            dinf_box,
+            stbl_box,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for DrefBox {
+impl Encode for MinfBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1859,7 +3408,7 @@ impl Encode for DrefBox {
     }
 }
 
-impl Decode for DrefBox {
+impl Decode for MinfBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1867,7 +3416,7 @@ impl Decode for DrefBox {
     }
 }
 
-impl BaseBox for DrefBox {
+impl BaseBox for MinfBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1879,56 +3428,106 @@ impl BaseBox for DrefBox {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(self.url_box.iter().map(as_box_object))
+                .chain(self.media_header_box.iter().map(as_box_object))
+                .chain(std::iter::once(&self.dinf_box).map(as_box_object))
+                .chain(std::iter::once(&self.stbl_box).map(as_box_object))
                 .chain(self.unknown_boxes.iter().map(as_box_object)),
         )
     }
 }
 
-impl FullBox for DrefBox {
-    fn full_box_version(&self) -> u8 {
-        0
-    }
-
-    fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(0)
-    }
-}
-
-/// [ISO/IEC 14496-12] DataEntryUrlBox class (親: [`DrefBox`])
+/// [`MinfBox`] に含まれるメディア種別ごとのヘッダーボックス
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct UrlBox {
-    pub location: Option<Utf8String>,
+pub enum MediaInfoHeader {
+    Smhd(SmhdBox),
+    Vmhd(VmhdBox),
+    Nmhd(NmhdBox),
 }
 
-impl UrlBox {
-    /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"url ");
+impl MediaInfoHeader {
+    fn inner_box(&self) -> &dyn BaseBox {
+        match self {
+            MediaInfoHeader::Smhd(b) => b,
+            MediaInfoHeader::Vmhd(b) => b,
+            MediaInfoHeader::Nmhd(b) => b,
+        }
+    }
+}
 
-    /// メディアデータが同じファイル内に格納されていることを示す [`UrlBox`] の値
-    pub const LOCAL_FILE: Self = Self { location: None };
+impl Encode for MediaInfoHeader {
+    fn encode<W: Write>(&self, writer: W) -> Result<()> {
+        match self {
+            MediaInfoHeader::Smhd(b) => b.encode(writer),
+            MediaInfoHeader::Vmhd(b) => b.encode(writer),
+            MediaInfoHeader::Nmhd(b) => b.encode(writer),
+        }
+    }
+}
+
+impl Decode for MediaInfoHeader {
+    fn decode<R: Read>(reader: R) -> Result<Self> {
+        let (header, mut reader) = BoxHeader::peek(reader)?;
+        match header.box_type {
+            SmhdBox::TYPE => Decode::decode(&mut reader).map(Self::Smhd),
+            VmhdBox::TYPE => Decode::decode(&mut reader).map(Self::Vmhd),
+            NmhdBox::TYPE => Decode::decode(&mut reader).map(Self::Nmhd),
+            _ => Err(Error::invalid_data(&format!(
+                "Unexpected media header box type: {}",
+                header.box_type
+            ))),
+        }
+    }
+}
+
+impl BaseBox for MediaInfoHeader {
+    fn box_type(&self) -> BoxType {
+        self.inner_box().box_type()
+    }
+
+    fn box_size(&self) -> BoxSize {
+        self.inner_box().box_size()
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        self.inner_box().box_payload_size()
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        self.inner_box().children()
+    }
+}
+
+/// [ISO/IEC 14496-12] SoundMediaHeaderBox class (親: [`MinfBox`]）
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct SmhdBox {
+    pub balance: FixedPointNumber<u8, u8>,
+}
+
+impl SmhdBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"smhd");
+
+    /// [`SmhdBox::balance`] のデフォルト値（中央）
+    pub const DEFAULT_BALANCE: FixedPointNumber<u8, u8> = FixedPointNumber::new(0, 0);
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        if let Some(l) = &self.location {
-            l.encode(writer)?;
-        }
+        self.balance.encode(&mut writer)?;
+        [0u8; 2].encode(writer)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let full_header = FullBoxHeader::decode(&mut reader)?;
-        let location = if full_header.flags.is_set(0) {
-            None
-        } else {
-            Some(Utf8String::decode(reader)?)
-        };
-        Ok(Self { location })
+        let _full_header = FullBoxHeader::decode(&mut reader)?;
+        let balance = FixedPointNumber::decode(&mut reader)?;
+        let _ = <[u8; 2]>::decode(reader)?;
+        Ok(Self { balance })
     }
 }
 
-impl Encode for UrlBox {
+impl Encode for SmhdBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -1936,7 +3535,7 @@ impl Encode for UrlBox {
     }
 }
 
-impl Decode for UrlBox {
+impl Decode for SmhdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -1944,7 +3543,7 @@ impl Decode for UrlBox {
     }
 }
 
-impl BaseBox for UrlBox {
+impl BaseBox for SmhdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -1958,110 +3557,60 @@ impl BaseBox for UrlBox {
     }
 }
 
-impl FullBox for UrlBox {
+impl FullBox for SmhdBox {
     fn full_box_version(&self) -> u8 {
         0
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(self.location.is_none() as u32)
+        FullBoxFlags::new(0)
     }
 }
 
-/// [ISO/IEC 14496-12] SampleTableBox class (親: [`MinfBox`])
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// [ISO/IEC 14496-12] VideoMediaHeaderBox class (親: [`MinfBox`]）
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct StblBox {
-    pub stsd_box: StsdBox,
-    pub stts_box: SttsBox,
-    pub stsc_box: StscBox,
-    pub stsz_box: StszBox,
-    pub stco_or_co64_box: Either<StcoBox, Co64Box>,
-    pub stss_box: Option<StssBox>,
-    pub unknown_boxes: Vec<UnknownBox>,
+pub struct VmhdBox {
+    pub graphicsmode: u16,
+    pub opcolor: [u16; 3],
 }
 
-impl StblBox {
+impl VmhdBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stbl");
+    pub const TYPE: BoxType = BoxType::Normal(*b"vmhd");
+
+    /// [`Vmhd::graphicsmode`] のデフォルト値（コピー）
+    pub const DEFAULT_GRAPHICSMODE: u16 = 0;
+
+    /// [`Vmhd::graphicsmode`] のデフォルト値
+    pub const DEFAULT_OPCOLOR: [u16; 3] = [0, 0, 0];
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.stsd_box.encode(&mut writer)?;
-        self.stts_box.encode(&mut writer)?;
-        self.stsc_box.encode(&mut writer)?;
-        self.stsz_box.encode(&mut writer)?;
-        match &self.stco_or_co64_box {
-            Either::A(b) => b.encode(&mut writer)?,
-            Either::B(b) => b.encode(&mut writer)?,
-        }
-        if let Some(b) = &self.stss_box {
-            b.encode(&mut writer)?;
-        }
-        for b in &self.unknown_boxes {
-            b.encode(&mut writer)?;
-        }
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        self.graphicsmode.encode(&mut writer)?;
+        self.opcolor.encode(writer)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let mut stsd_box = None;
-        let mut stts_box = None;
-        let mut stsc_box = None;
-        let mut stsz_box = None;
-        let mut stco_box = None;
-        let mut co64_box = None;
-        let mut stss_box = None;
-        let mut unknown_boxes = Vec::new();
-        while reader.limit() > 0 {
-            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
-            match header.box_type {
-                StsdBox::TYPE if stsd_box.is_none() => {
-                    stsd_box = Some(StsdBox::decode(&mut reader)?);
-                }
-                SttsBox::TYPE if stts_box.is_none() => {
-                    stts_box = Some(SttsBox::decode(&mut reader)?);
-                }
-                StscBox::TYPE if stsc_box.is_none() => {
-                    stsc_box = Some(StscBox::decode(&mut reader)?);
-                }
-                StszBox::TYPE if stsz_box.is_none() => {
-                    stsz_box = Some(StszBox::decode(&mut reader)?);
-                }
-                StcoBox::TYPE if stco_box.is_none() => {
-                    stco_box = Some(StcoBox::decode(&mut reader)?);
-                }
-                Co64Box::TYPE if co64_box.is_none() => {
-                    co64_box = Some(Co64Box::decode(&mut reader)?);
-                }
-                StssBox::TYPE if stss_box.is_none() => {
-                    stss_box = Some(StssBox::decode(&mut reader)?);
-                }
-                _ => {
-                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
-                }
-            }
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        if full_header.flags.get() != 1 {
+            warn_if_lenient(Error::invalid_data(&format!(
+                "Unexpected FullBox header flags of 'vmhd' box: {}",
+                full_header.flags.get()
+            )))?;
         }
-        let stsd_box = stsd_box.ok_or_else(|| Error::missing_box("stsd", Self::TYPE))?;
-        let stts_box = stts_box.ok_or_else(|| Error::missing_box("stts", Self::TYPE))?;
-        let stsc_box = stsc_box.ok_or_else(|| Error::missing_box("stsc", Self::TYPE))?;
-        let stsz_box = stsz_box.ok_or_else(|| Error::missing_box("stsz", Self::TYPE))?;
-        let stco_or_co64_box = stco_box
-            .map(Either::A)
-            .or(co64_box.map(Either::B))
-            .ok_or_else(|| Error::missing_box("stco | co64", Self::TYPE))?;
+
+        let graphicsmode = u16::decode(&mut reader)?;
+        let opcolor = <[u16; 3]>::decode(reader)?;
         Ok(Self {
-            stsd_box,
-            stts_box,
-            stsc_box,
-            stsz_box,
-            stco_or_co64_box,
-            stss_box,
-            unknown_boxes,
+            graphicsmode,
+            opcolor,
         })
     }
 }
 
-impl Encode for StblBox {
+impl Encode for VmhdBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2069,7 +3618,7 @@ impl Encode for StblBox {
     }
 }
 
-impl Decode for StblBox {
+impl Decode for VmhdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2077,7 +3626,7 @@ impl Decode for StblBox {
     }
 }
 
-impl BaseBox for StblBox {
+impl BaseBox for VmhdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2087,58 +3636,42 @@ impl BaseBox for StblBox {
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(
-            std::iter::empty()
-                .chain(std::iter::once(&self.stsd_box).map(as_box_object))
-                .chain(std::iter::once(&self.stts_box).map(as_box_object))
-                .chain(std::iter::once(&self.stsc_box).map(as_box_object))
-                .chain(std::iter::once(&self.stsz_box).map(as_box_object))
-                .chain(std::iter::once(&self.stco_or_co64_box).map(as_box_object))
-                .chain(self.stss_box.iter().map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
-        )
+        Box::new(std::iter::empty())
     }
 }
 
-impl AsRef<StblBox> for StblBox {
-    fn as_ref(&self) -> &StblBox {
-        self
+impl FullBox for VmhdBox {
+    fn full_box_version(&self) -> u8 {
+        0
     }
-}
 
-/// [ISO/IEC 14496-12] SampleDescriptionBox class (親: [`StblBox`])
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct StsdBox {
-    pub entries: Vec<SampleEntry>,
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(1)
+    }
 }
 
-impl StsdBox {
+/// [ISO/IEC 14496-12] NullMediaHeaderBox class (親: [`MinfBox`]）
+///
+/// ヒントトラックやメタデータトラックなど、音声・映像以外のトラックで使われる
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NmhdBox;
+
+impl NmhdBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stsd");
+    pub const TYPE: BoxType = BoxType::Normal(*b"nmhd");
 
-    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        FullBoxHeader::from_box(self).encode(&mut writer)?;
-        let entry_count = (self.entries.len()) as u32;
-        entry_count.encode(&mut writer)?;
-        for b in &self.entries {
-            b.encode(&mut writer)?;
-        }
+    fn encode_payload<W: Write>(&self, writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(writer)?;
         Ok(())
     }
 
-    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let _ = FullBoxHeader::decode(&mut reader)?;
-        let entry_count = u32::decode(&mut reader)?;
-        let mut entries = Vec::new();
-        for _ in 0..entry_count {
-            entries.push(SampleEntry::decode(&mut reader)?);
-        }
-        Ok(Self { entries })
+    fn decode_payload<R: Read>(reader: &mut std::io::Take<R>) -> Result<Self> {
+        let _full_header = FullBoxHeader::decode(reader)?;
+        Ok(Self)
     }
 }
 
-impl Encode for StsdBox {
+impl Encode for NmhdBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2146,7 +3679,7 @@ impl Encode for StsdBox {
     }
 }
 
-impl Decode for StsdBox {
+impl Decode for NmhdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2154,7 +3687,7 @@ impl Decode for StsdBox {
     }
 }
 
-impl BaseBox for StsdBox {
+impl BaseBox for NmhdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2164,11 +3697,11 @@ impl BaseBox for StsdBox {
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(self.entries.iter().map(as_box_object))
+        Box::new(std::iter::empty())
     }
 }
 
-impl FullBox for StsdBox {
+impl FullBox for NmhdBox {
     fn full_box_version(&self) -> u8 {
         0
     }
@@ -2178,183 +3711,2032 @@ impl FullBox for StsdBox {
     }
 }
 
-/// [`StsdBox`] に含まれるエントリー
+/// [ISO/IEC 14496-12] DataInformationBox class (親: [`MinfBox`]）
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub enum SampleEntry {
-    Avc1(Avc1Box),
-    Hev1(Hev1Box),
-    Vp08(Vp08Box),
-    Vp09(Vp09Box),
-    Av01(Av01Box),
-    Opus(OpusBox),
-    Mp4a(Mp4aBox),
-    Unknown(UnknownBox),
+pub struct DinfBox {
+    pub dref_box: DrefBox,
+    pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl SampleEntry {
-    fn inner_box(&self) -> &dyn BaseBox {
-        match self {
-            Self::Avc1(b) => b,
-            Self::Hev1(b) => b,
-            Self::Vp08(b) => b,
-            Self::Vp09(b) => b,
-            Self::Av01(b) => b,
-            Self::Opus(b) => b,
-            Self::Mp4a(b) => b,
-            Self::Unknown(b) => b,
+impl DinfBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"dinf");
+
+    /// メディアデータが同じファイル内に格納されていることを示す [`DinfBox`] の値
+    pub const LOCAL_FILE: Self = Self {
+        dref_box: DrefBox::LOCAL_FILE,
+        unknown_boxes: Vec::new(),
+    };
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.dref_box.encode(&mut writer)?;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
         }
+        Ok(())
     }
-}
 
-impl Encode for SampleEntry {
-    fn encode<W: Write>(&self, writer: W) -> Result<()> {
-        match self {
-            Self::Avc1(b) => b.encode(writer),
-            Self::Hev1(b) => b.encode(writer),
-            Self::Vp08(b) => b.encode(writer),
-            Self::Vp09(b) => b.encode(writer),
-            Self::Av01(b) => b.encode(writer),
-            Self::Opus(b) => b.encode(writer),
-            Self::Mp4a(b) => b.encode(writer),
-            Self::Unknown(b) => b.encode(writer),
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let mut dref_box = None;
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                DrefBox::TYPE if dref_box.is_none() => {
+                    dref_box = Some(DrefBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(header.box_type, &[DrefBox::TYPE]);
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
         }
+        let dref_box = dref_box.ok_or_else(|| Error::missing_box("dref", Self::TYPE))?;
+        Ok(Self {
+            dref_box,
+            unknown_boxes,
+        })
     }
 }
 
-impl Decode for SampleEntry {
-    fn decode<R: Read>(reader: R) -> Result<Self> {
-        let (header, mut reader) = BoxHeader::peek(reader)?;
-        match header.box_type {
-            Avc1Box::TYPE => Decode::decode(&mut reader).map(Self::Avc1),
-            Hev1Box::TYPE => Decode::decode(&mut reader).map(Self::Hev1),
-            Vp08Box::TYPE => Decode::decode(&mut reader).map(Self::Vp08),
-            Vp09Box::TYPE => Decode::decode(&mut reader).map(Self::Vp09),
-            Av01Box::TYPE => Decode::decode(&mut reader).map(Self::Av01),
-            OpusBox::TYPE => Decode::decode(&mut reader).map(Self::Opus),
-            Mp4aBox::TYPE => Decode::decode(&mut reader).map(Self::Mp4a),
-            _ => Decode::decode(&mut reader).map(Self::Unknown),
-        }
+impl Encode for DinfBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
     }
 }
 
-impl BaseBox for SampleEntry {
-    fn box_type(&self) -> BoxType {
-        self.inner_box().box_type()
+impl Decode for DinfBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
     }
+}
 
-    fn box_size(&self) -> BoxSize {
-        self.inner_box().box_size()
+impl BaseBox for DinfBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
     }
 
     fn box_payload_size(&self) -> u64 {
-        self.inner_box().box_payload_size()
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
     }
 
-    fn is_unknown_box(&self) -> bool {
-        self.inner_box().is_unknown_box()
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(
+            std::iter::empty()
+                .chain(std::iter::once(&self.dref_box).map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
+    }
+}
+
+/// [ISO/IEC 14496-12] DataReferenceBox class (親: [`DinfBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct DrefBox {
+    pub url_box: Option<UrlBox>,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl DrefBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"dref");
+
+    /// メディアデータが同じファイル内に格納されていることを示す [`DrefBox`] の値
+    pub const LOCAL_FILE: Self = Self {
+        url_box: Some(UrlBox::LOCAL_FILE),
+        unknown_boxes: Vec::new(),
+    };
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        let entry_count = (self.url_box.is_some() as usize + self.unknown_boxes.len()) as u32;
+        entry_count.encode(&mut writer)?;
+        if let Some(b) = &self.url_box {
+            b.encode(&mut writer)?;
+        }
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let _ = FullBoxHeader::decode(&mut reader)?;
+        let entry_count = u32::decode(&mut reader)?;
+        let mut url_box = None;
+        let mut unknown_boxes = Vec::new();
+        for _ in 0..entry_count {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                UrlBox::TYPE if url_box.is_none() => {
+                    url_box = Some(UrlBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(header.box_type, &[UrlBox::TYPE]);
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
+        }
+        Ok(Self {
+            url_box,
+            unknown_boxes,
+        })
+    }
+}
+
+impl Encode for DrefBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for DrefBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for DrefBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        self.inner_box().children()
+        Box::new(
+            std::iter::empty()
+                .chain(self.url_box.iter().map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
     }
 }
 
-/// 映像系の [`SampleEntry`] に共通のフィールドをまとめた構造体
+impl FullBox for DrefBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [ISO/IEC 14496-12] DataEntryUrlBox class (親: [`DrefBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct VisualSampleEntryFields {
-    pub data_reference_index: NonZeroU16,
-    pub width: u16,
-    pub height: u16,
-    pub horizresolution: FixedPointNumber<u16, u16>,
-    pub vertresolution: FixedPointNumber<u16, u16>,
-    pub frame_count: u16,
-    pub compressorname: [u8; 32],
-    pub depth: u16,
+pub struct UrlBox {
+    pub location: Option<Utf8String>,
 }
 
-impl VisualSampleEntryFields {
-    /// [`VisualSampleEntryFields::data_reference_index`] のデフォルト値
-    pub const DEFAULT_DATA_REFERENCE_INDEX: NonZeroU16 = NonZeroU16::MIN;
+impl UrlBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"url ");
 
-    /// [`VisualSampleEntryFields::horizresolution`] のデフォルト値 (72 dpi)
-    pub const DEFAULT_HORIZRESOLUTION: FixedPointNumber<u16, u16> = FixedPointNumber::new(0x48, 0);
+    /// メディアデータが同じファイル内に格納されていることを示す [`UrlBox`] の値
+    pub const LOCAL_FILE: Self = Self { location: None };
 
-    /// [`VisualSampleEntryFields::vertresolution`] のデフォルト値 (72 dpi)
-    pub const DEFAULT_VERTRESOLUTION: FixedPointNumber<u16, u16> = FixedPointNumber::new(0x48, 0);
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        if let Some(l) = &self.location {
+            l.encode(writer)?;
+        }
+        Ok(())
+    }
 
-    /// [`VisualSampleEntryFields::frame_count`] のデフォルト値 (1)
-    pub const DEFAULT_FRAME_COUNT: u16 = 1;
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        let location = if full_header.flags.is_set(0) {
+            None
+        } else {
+            Some(Utf8String::decode(reader)?)
+        };
+        Ok(Self { location })
+    }
+}
 
-    /// [`VisualSampleEntryFields::depth`] のデフォルト値 (images are in colour with no alpha)
-    pub const DEFAULT_DEPTH: u16 = 0x0018;
+impl Encode for UrlBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
 
-    /// 名前なしを表す [`VisualSampleEntryFields::compressorname`] の値
-    pub const NULL_COMPRESSORNAME: [u8; 32] = [0; 32];
+impl Decode for UrlBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for UrlBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(std::iter::empty())
+    }
+}
+
+impl FullBox for UrlBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(self.location.is_none() as u32)
+    }
+}
+
+/// [ISO/IEC 14496-12] SampleTableBox class (親: [`MinfBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct StblBox {
+    pub stsd_box: StsdBox,
+    pub stts_box: SttsBox,
+    pub stsc_box: StscBox,
+    pub stsz_box: StszBox,
+    pub stco_or_co64_box: Either<StcoBox, Co64Box>,
+    pub stss_box: Option<StssBox>,
+    pub ctts_box: Option<CttsBox>,
+    pub subs_box: Option<SubsBox>,
+    pub sgpd_boxes: Vec<SgpdBox>,
+    pub sbgp_boxes: Vec<SbgpBox>,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl StblBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"stbl");
+
+    /// サンプルテーブル内の各ボックスの間で、エントリ数などの整合性が取れているかどうかをチェックする
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        let sample_count = self
+            .stts_box
+            .entries
+            .iter()
+            .map(|x| x.sample_count as u64)
+            .sum::<u64>();
+
+        let stsz_sample_count = match &self.stsz_box {
+            StszBox::Fixed { sample_count, .. } => *sample_count as u64,
+            StszBox::Variable { entry_sizes } => entry_sizes.len() as u64,
+        };
+        if stsz_sample_count != sample_count {
+            return Err(ValidationError::SampleCountMismatch {
+                from: StszBox::TYPE,
+                expected: sample_count,
+                got: stsz_sample_count,
+            });
+        }
+
+        if let Some(ctts_box) = &self.ctts_box {
+            let ctts_sample_count = ctts_box
+                .entries
+                .iter()
+                .map(|x| x.sample_count as u64)
+                .sum::<u64>();
+            if ctts_sample_count != sample_count {
+                return Err(ValidationError::SampleCountMismatch {
+                    from: CttsBox::TYPE,
+                    expected: sample_count,
+                    got: ctts_sample_count,
+                });
+            }
+        }
+
+        let entries = &self.stsc_box.entries;
+        if let Some(i) = entries
+            .iter()
+            .zip(entries.iter().skip(1))
+            .position(|(prev, next)| prev.first_chunk >= next.first_chunk)
+        {
+            return Err(ValidationError::StscFirstChunkOutOfOrder { entry_index: i + 1 });
+        }
+
+        let chunk_offsets = match &self.stco_or_co64_box {
+            Either::A(b) => Either::A(&b.chunk_offsets),
+            Either::B(b) => Either::B(&b.chunk_offsets),
+        };
+        let chunk_count = match chunk_offsets {
+            Either::A(x) => x.len(),
+            Either::B(x) => x.len(),
+        } as u32;
+        if let Some(last) = entries.last().filter(|x| x.first_chunk.get() > chunk_count) {
+            return Err(ValidationError::ChunkCountMismatch {
+                expected: chunk_count,
+                got: last.first_chunk.get(),
+            });
+        }
+
+        let mut stsc_sample_count = 0u64;
+        for (i, entry) in entries.iter().enumerate() {
+            let end_chunk = entries
+                .get(i + 1)
+                .map(|x| x.first_chunk.get())
+                .unwrap_or(chunk_count + 1);
+            for chunk_index in entry.first_chunk.get()..end_chunk {
+                let j = chunk_index as usize - 1;
+                let offset = match chunk_offsets {
+                    Either::A(x) => x[j] as u64,
+                    Either::B(x) => x[j],
+                };
+
+                // stts のサンプル数を超える分は（後でエラーになるので）チャンクサイズの計算対象外とする
+                //
+                // なお、各サイズは u32 でサンプル数も u32 の範囲なので、チャンクサイズの計算自体は u64 に収まる
+                let samples_in_chunk = (entry.sample_per_chunk as u64)
+                    .min(sample_count.saturating_sub(stsc_sample_count));
+                let chunk_size = match &self.stsz_box {
+                    StszBox::Fixed { sample_size, .. } => {
+                        sample_size.get() as u64 * samples_in_chunk
+                    }
+                    StszBox::Variable { entry_sizes } => entry_sizes
+                        .get(stsc_sample_count as usize..)
+                        .unwrap_or_default()
+                        .iter()
+                        .take(samples_in_chunk as usize)
+                        .map(|&size| size as u64)
+                        .sum(),
+                };
+                offset
+                    .checked_add(chunk_size)
+                    .ok_or(ValidationError::ChunkOffsetOverflow)?;
+                stsc_sample_count = stsc_sample_count.saturating_add(entry.sample_per_chunk as u64);
+            }
+        }
+        if stsc_sample_count != sample_count {
+            return Err(ValidationError::SampleCountMismatch {
+                from: StscBox::TYPE,
+                expected: sample_count,
+                got: stsc_sample_count,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// `stco` ないし `co64` ボックスのチャンクオフセットが、サイズ `file_size` のファイルの範囲内に収まっているかどうかをチェックする
+    ///
+    /// 範囲外のオフセットを持つチャンクごとに [`ValidationError::ChunkOffsetOutOfRange`] が返される
+    /// （問題がない場合は空の [`Vec`] となる）
+    pub fn validate_chunk_offsets(&self, file_size: u64) -> Vec<ValidationError> {
+        let (from, indices) = match &self.stco_or_co64_box {
+            Either::A(b) => (StcoBox::TYPE, b.out_of_range_chunks(file_size)),
+            Either::B(b) => (Co64Box::TYPE, b.out_of_range_chunks(file_size)),
+        };
+        indices
+            .into_iter()
+            .map(|i| ValidationError::ChunkOffsetOutOfRange {
+                from,
+                chunk_index: i as u32 + 1,
+                offset: match &self.stco_or_co64_box {
+                    Either::A(b) => b.chunk_offsets[i] as u64,
+                    Either::B(b) => b.chunk_offsets[i],
+                },
+                file_size,
+            })
+            .collect()
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.stsd_box.encode(&mut writer)?;
+        self.stts_box.encode(&mut writer)?;
+        self.stsc_box.encode(&mut writer)?;
+        self.stsz_box.encode(&mut writer)?;
+        match &self.stco_or_co64_box {
+            Either::A(b) => b.encode(&mut writer)?,
+            Either::B(b) => b.encode(&mut writer)?,
+        }
+        if let Some(b) = &self.stss_box {
+            b.encode(&mut writer)?;
+        }
+        if let Some(b) = &self.ctts_box {
+            b.encode(&mut writer)?;
+        }
+        if let Some(b) = &self.subs_box {
+            b.encode(&mut writer)?;
+        }
+        for b in &self.sgpd_boxes {
+            b.encode(&mut writer)?;
+        }
+        for b in &self.sbgp_boxes {
+            b.encode(&mut writer)?;
+        }
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let mut stsd_box = None;
+        let mut stts_box = None;
+        let mut stsc_box = None;
+        let mut stsz_box = None;
+        let mut stco_box = None;
+        let mut co64_box = None;
+        let mut stss_box = None;
+        let mut ctts_box = None;
+        let mut subs_box = None;
+        let mut sgpd_boxes = Vec::new();
+        let mut sbgp_boxes = Vec::new();
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                StsdBox::TYPE if stsd_box.is_none() => {
+                    stsd_box = Some(StsdBox::decode(&mut reader)?);
+                }
+                SttsBox::TYPE if stts_box.is_none() => {
+                    stts_box = Some(SttsBox::decode(&mut reader)?);
+                }
+                StscBox::TYPE if stsc_box.is_none() => {
+                    stsc_box = Some(StscBox::decode(&mut reader)?);
+                }
+                StszBox::TYPE if stsz_box.is_none() => {
+                    stsz_box = Some(StszBox::decode(&mut reader)?);
+                }
+                StcoBox::TYPE if stco_box.is_none() => {
+                    stco_box = Some(StcoBox::decode(&mut reader)?);
+                }
+                Co64Box::TYPE if co64_box.is_none() => {
+                    co64_box = Some(Co64Box::decode(&mut reader)?);
+                }
+                StssBox::TYPE if stss_box.is_none() => {
+                    stss_box = Some(StssBox::decode(&mut reader)?);
+                }
+                CttsBox::TYPE if ctts_box.is_none() => {
+                    ctts_box = Some(CttsBox::decode(&mut reader)?);
+                }
+                SubsBox::TYPE if subs_box.is_none() => {
+                    subs_box = Some(SubsBox::decode(&mut reader)?);
+                }
+                SgpdBox::TYPE => {
+                    // バージョン 0 は未対応なので、未知のボックスとしてそのまま保持する
+                    let b = UnknownBox::decode(&mut reader)?;
+                    if b.payload.first() == Some(&0) {
+                        unknown_boxes.push(b);
+                    } else {
+                        sgpd_boxes.push(SgpdBox::decode(&b.encode_to_vec()?[..])?);
+                    }
+                }
+                SbgpBox::TYPE => {
+                    sbgp_boxes.push(SbgpBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(
+                        header.box_type,
+                        &[
+                            StsdBox::TYPE,
+                            SttsBox::TYPE,
+                            StscBox::TYPE,
+                            StszBox::TYPE,
+                            StcoBox::TYPE,
+                            Co64Box::TYPE,
+                            StssBox::TYPE,
+                            CttsBox::TYPE,
+                            SubsBox::TYPE,
+                        ],
+                    );
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
+        }
+        let stsd_box = stsd_box.ok_or_else(|| Error::missing_box("stsd", Self::TYPE))?;
+        let stts_box = stts_box.ok_or_else(|| Error::missing_box("stts", Self::TYPE))?;
+        let stsc_box = stsc_box.ok_or_else(|| Error::missing_box("stsc", Self::TYPE))?;
+        let stsz_box = stsz_box.ok_or_else(|| Error::missing_box("stsz", Self::TYPE))?;
+        let stco_or_co64_box = stco_box
+            .map(Either::A)
+            .or(co64_box.map(Either::B))
+            .ok_or_else(|| Error::missing_box("stco | co64", Self::TYPE))?;
+        Ok(Self {
+            stsd_box,
+            stts_box,
+            stsc_box,
+            stsz_box,
+            stco_or_co64_box,
+            stss_box,
+            ctts_box,
+            subs_box,
+            sgpd_boxes,
+            sbgp_boxes,
+            unknown_boxes,
+        })
+    }
+}
+
+impl Encode for StblBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for StblBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for StblBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(
+            std::iter::empty()
+                .chain(std::iter::once(&self.stsd_box).map(as_box_object))
+                .chain(std::iter::once(&self.stts_box).map(as_box_object))
+                .chain(std::iter::once(&self.stsc_box).map(as_box_object))
+                .chain(std::iter::once(&self.stsz_box).map(as_box_object))
+                .chain(std::iter::once(&self.stco_or_co64_box).map(as_box_object))
+                .chain(self.stss_box.iter().map(as_box_object))
+                .chain(self.ctts_box.iter().map(as_box_object))
+                .chain(self.subs_box.iter().map(as_box_object))
+                .chain(self.sgpd_boxes.iter().map(as_box_object))
+                .chain(self.sbgp_boxes.iter().map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
+    }
+}
+
+impl AsRef<StblBox> for StblBox {
+    fn as_ref(&self) -> &StblBox {
+        self
+    }
+}
+
+/// [ISO/IEC 14496-12] SampleDescriptionBox class (親: [`StblBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct StsdBox {
+    pub entries: Vec<SampleEntry>,
+}
+
+impl StsdBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"stsd");
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        let entry_count = (self.entries.len()) as u32;
+        entry_count.encode(&mut writer)?;
+        for b in &self.entries {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let _ = FullBoxHeader::decode(&mut reader)?;
+        let entry_count = u32::decode(&mut reader)?;
+        if entry_count == 0 {
+            push_decode_warning(|| "No sample entries in 'stsd' box".to_owned());
+        }
+        let mut entries = Vec::new();
+        for i in 0..entry_count as usize {
+            entries.push(SampleEntry::decode(&mut reader).map_err(|e| e.with_box_index(i))?);
+        }
+        Ok(Self { entries })
+    }
+}
+
+impl Encode for StsdBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for StsdBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for StsdBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(self.entries.iter().map(as_box_object))
+    }
+}
+
+impl FullBox for StsdBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [`StsdBox`] に含まれるエントリー
+///
+/// [`Hash`] と [`Eq`] を実装しているので、
+/// 複数のトラック間で同一のサンプルエントリーを [`HashSet`](std::collections::HashSet) などで重複排除することができる
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum SampleEntry {
+    Avc1(Avc1Box),
+    Hev1(Hev1Box),
+    Hvc1(Hvc1Box),
+    Vp08(Vp08Box),
+    Vp09(Vp09Box),
+    Av01(Av01Box),
+    Opus(OpusBox),
+    Mp4a(Mp4aBox),
+    Tmcd(TmcdBox),
+    Unknown(UnknownBox),
+}
+
+impl SampleEntry {
+    /// 映像用のサンプルエントリーの場合には、共通フィールドへの参照を返す
+    pub fn visual_fields(&self) -> Option<&VisualSampleEntryFields> {
+        match self {
+            Self::Avc1(b) => Some(&b.visual),
+            Self::Hev1(b) => Some(&b.visual),
+            Self::Hvc1(b) => Some(&b.visual),
+            Self::Vp08(b) => Some(&b.visual),
+            Self::Vp09(b) => Some(&b.visual),
+            Self::Av01(b) => Some(&b.visual),
+            Self::Opus(_) | Self::Mp4a(_) | Self::Tmcd(_) | Self::Unknown(_) => None,
+        }
+    }
+
+    /// 音声用のサンプルエントリーの場合には、共通フィールドへの参照を返す
+    pub fn audio_fields(&self) -> Option<&AudioSampleEntryFields> {
+        match self {
+            Self::Opus(b) => Some(&b.audio),
+            Self::Mp4a(b) => Some(&b.audio),
+            Self::Avc1(_)
+            | Self::Hev1(_)
+            | Self::Hvc1(_)
+            | Self::Vp08(_)
+            | Self::Vp09(_)
+            | Self::Av01(_)
+            | Self::Tmcd(_)
+            | Self::Unknown(_) => None,
+        }
+    }
+
+    /// 映像用のサンプルエントリーかどうかを返す
+    pub fn is_video(&self) -> bool {
+        self.track_kind() == Some(TrackKind::Video)
+    }
+
+    /// 音声用のサンプルエントリーかどうかを返す
+    pub fn is_audio(&self) -> bool {
+        self.track_kind() == Some(TrackKind::Audio)
+    }
+
+    /// このライブラリがデコードに対応しているサンプルエントリーかどうかを返す
+    ///
+    /// [`SampleEntry::Unknown`] の場合にのみ `false` となる
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Self::Unknown(_))
+    }
+
+    /// サンプルエントリーに対応するトラックの種別を返す
+    ///
+    /// 映像・音声のいずれでもないサンプルエントリー（`tmcd` や未知のエントリー）の場合には [`None`] が返される
+    pub fn track_kind(&self) -> Option<TrackKind> {
+        match self {
+            Self::Avc1(_)
+            | Self::Hev1(_)
+            | Self::Hvc1(_)
+            | Self::Vp08(_)
+            | Self::Vp09(_)
+            | Self::Av01(_) => Some(TrackKind::Video),
+            Self::Opus(_) | Self::Mp4a(_) => Some(TrackKind::Audio),
+            Self::Tmcd(_) | Self::Unknown(_) => None,
+        }
+    }
+
+    /// 映像の幅を返す（映像用のサンプルエントリー以外の場合は [`None`]）
+    pub fn width(&self) -> Option<u16> {
+        self.visual_fields().map(|x| x.width)
+    }
+
+    /// 映像の高さを返す（映像用のサンプルエントリー以外の場合は [`None`]）
+    pub fn height(&self) -> Option<u16> {
+        self.visual_fields().map(|x| x.height)
+    }
+
+    /// 音声のサンプリングレートを返す（音声用のサンプルエントリー以外の場合は [`None`]）
+    ///
+    /// [`AudioSampleEntryFields::samplerate`] の整数部の値となる
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.audio_fields().map(|x| x.samplerate.integer as u32)
+    }
+
+    /// 音声のチャンネル数を返す（音声用のサンプルエントリー以外の場合は [`None`]）
+    pub fn channel_count(&self) -> Option<u16> {
+        self.audio_fields().map(|x| x.channelcount)
+    }
+
+    /// MSE や WebCodecs で使われる形式のコーデック文字列を返す
+    ///
+    /// コーデック文字列の生成に対応していないサンプルエントリーの場合には [`None`] が返される
+    pub fn codec_string(&self) -> Option<String> {
+        match self {
+            Self::Hev1(b) => Some(b.hvcc_box.codec_string_with_fourcc("hev1")),
+            Self::Hvc1(b) => Some(b.hvcc_box.codec_string_with_fourcc("hvc1")),
+            Self::Vp08(b) => Some(b.vpcc_box.codec_string_short(true)),
+            Self::Vp09(b) => Some(b.vpcc_box.codec_string_short(false)),
+            Self::Av01(b) => Some(b.av1c_box.codec_string(b.colr_box().as_ref())),
+            Self::Avc1(_) | Self::Opus(_) | Self::Mp4a(_) | Self::Tmcd(_) | Self::Unknown(_) => {
+                None
+            }
+        }
+    }
+
+    fn inner_box(&self) -> &dyn BaseBox {
+        match self {
+            Self::Avc1(b) => b,
+            Self::Hev1(b) => b,
+            Self::Hvc1(b) => b,
+            Self::Vp08(b) => b,
+            Self::Vp09(b) => b,
+            Self::Av01(b) => b,
+            Self::Opus(b) => b,
+            Self::Mp4a(b) => b,
+            Self::Tmcd(b) => b,
+            Self::Unknown(b) => b,
+        }
+    }
+}
+
+impl Encode for SampleEntry {
+    fn encode<W: Write>(&self, writer: W) -> Result<()> {
+        match self {
+            Self::Avc1(b) => b.encode(writer),
+            Self::Hev1(b) => b.encode(writer),
+            Self::Hvc1(b) => b.encode(writer),
+            Self::Vp08(b) => b.encode(writer),
+            Self::Vp09(b) => b.encode(writer),
+            Self::Av01(b) => b.encode(writer),
+            Self::Opus(b) => b.encode(writer),
+            Self::Mp4a(b) => b.encode(writer),
+            Self::Tmcd(b) => b.encode(writer),
+            Self::Unknown(b) => b.encode(writer),
+        }
+    }
+}
+
+impl Decode for SampleEntry {
+    fn decode<R: Read>(reader: R) -> Result<Self> {
+        let (header, mut reader) = BoxHeader::peek(reader)?;
+        match header.box_type {
+            Avc1Box::TYPE => Decode::decode(&mut reader).map(Self::Avc1),
+            Hev1Box::TYPE => Decode::decode(&mut reader).map(Self::Hev1),
+            Hvc1Box::TYPE => Decode::decode(&mut reader).map(Self::Hvc1),
+            Vp08Box::TYPE => Decode::decode(&mut reader).map(Self::Vp08),
+            Vp09Box::TYPE => Decode::decode(&mut reader).map(Self::Vp09),
+            Av01Box::TYPE => Decode::decode(&mut reader).map(Self::Av01),
+            OpusBox::TYPE => Decode::decode(&mut reader).map(Self::Opus),
+            Mp4aBox::TYPE => Decode::decode(&mut reader).map(Self::Mp4a),
+            TmcdBox::TYPE => Decode::decode(&mut reader).map(Self::Tmcd),
+            _ => Decode::decode(&mut reader).map(Self::Unknown),
+        }
+    }
+}
+
+impl BaseBox for SampleEntry {
+    fn box_type(&self) -> BoxType {
+        self.inner_box().box_type()
+    }
+
+    fn box_size(&self) -> BoxSize {
+        self.inner_box().box_size()
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        self.inner_box().box_payload_size()
+    }
+
+    fn is_unknown_box(&self) -> bool {
+        self.inner_box().is_unknown_box()
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        self.inner_box().children()
+    }
+}
+
+/// 映像系の [`SampleEntry`] に共通のフィールドをまとめた構造体
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct VisualSampleEntryFields {
+    pub data_reference_index: NonZeroU16,
+    pub width: u16,
+    pub height: u16,
+    pub horizresolution: FixedPointNumber<u16, u16>,
+    pub vertresolution: FixedPointNumber<u16, u16>,
+    pub frame_count: u16,
+    pub compressorname: [u8; 32],
+    pub depth: u16,
+}
+
+impl VisualSampleEntryFields {
+    /// [`VisualSampleEntryFields::data_reference_index`] のデフォルト値
+    pub const DEFAULT_DATA_REFERENCE_INDEX: NonZeroU16 = NonZeroU16::MIN;
+
+    /// [`VisualSampleEntryFields::horizresolution`] のデフォルト値 (72 dpi)
+    pub const DEFAULT_HORIZRESOLUTION: FixedPointNumber<u16, u16> = FixedPointNumber::new(0x48, 0);
+
+    /// [`VisualSampleEntryFields::vertresolution`] のデフォルト値 (72 dpi)
+    pub const DEFAULT_VERTRESOLUTION: FixedPointNumber<u16, u16> = FixedPointNumber::new(0x48, 0);
+
+    /// [`VisualSampleEntryFields::frame_count`] のデフォルト値 (1)
+    pub const DEFAULT_FRAME_COUNT: u16 = 1;
+
+    /// [`VisualSampleEntryFields::depth`] のデフォルト値 (images are in colour with no alpha)
+    pub const DEFAULT_DEPTH: u16 = 0x0018;
+
+    /// 名前なしを表す [`VisualSampleEntryFields::compressorname`] の値
+    pub const NULL_COMPRESSORNAME: [u8; 32] = [0; 32];
+}
+
+impl Encode for VisualSampleEntryFields {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        [0u8; 6].encode(&mut writer)?;
+        self.data_reference_index.encode(&mut writer)?;
+        [0u8; 2 + 2 + 4 * 3].encode(&mut writer)?;
+        self.width.encode(&mut writer)?;
+        self.height.encode(&mut writer)?;
+        self.horizresolution.encode(&mut writer)?;
+        self.vertresolution.encode(&mut writer)?;
+        [0u8; 4].encode(&mut writer)?;
+        self.frame_count.encode(&mut writer)?;
+        self.compressorname.encode(&mut writer)?;
+        self.depth.encode(&mut writer)?;
+        (-1i16).encode(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for VisualSampleEntryFields {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let _ = <[u8; 6]>::decode(&mut reader)?;
+        let data_reference_index = NonZeroU16::decode(&mut reader)?;
+        let _ = <[u8; 2 + 2 + 4 * 3]>::decode(&mut reader)?;
+        let width = u16::decode(&mut reader)?;
+        let height = u16::decode(&mut reader)?;
+        let horizresolution = FixedPointNumber::decode(&mut reader)?;
+        let vertresolution = FixedPointNumber::decode(&mut reader)?;
+        let _ = <[u8; 4]>::decode(&mut reader)?;
+        let frame_count = u16::decode(&mut reader)?;
+        let compressorname = <[u8; 32]>::decode(&mut reader)?;
+        let depth = u16::decode(&mut reader)?;
+        let _ = <[u8; 2]>::decode(reader)?;
+        Ok(Self {
+            data_reference_index,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            frame_count,
+            compressorname,
+            depth,
+        })
+    }
+}
+
+/// [ISO/IEC 14496-15] AVCSampleEntry class (親: [`StsdBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct Avc1Box {
+    pub visual: VisualSampleEntryFields,
+    pub avcc_box: AvccBox,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl Avc1Box {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"avc1");
+
+    /// SPS から解析した、実際の映像の解像度 `(幅, 高さ)` を返す
+    ///
+    /// エンコーダーによっては [`VisualSampleEntryFields`] の `width` と `height` が不正な値になっていることがあるため、
+    /// 正確な解像度が必要な場合にはこちらを使うこと。
+    /// 詳細は [`AvccBox::parse_sps_resolution()`] を参照のこと
+    pub fn true_resolution(&self) -> Option<(u16, u16)> {
+        self.avcc_box.parse_sps_resolution()
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.visual.encode(&mut writer)?;
+        self.avcc_box.encode(&mut writer)?;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let visual = VisualSampleEntryFields::decode(&mut reader)?;
+        let mut avcc_box = None;
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                AvccBox::TYPE if avcc_box.is_none() => {
+                    avcc_box = Some(AvccBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(header.box_type, &[AvccBox::TYPE]);
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
+        }
+        let avcc_box = avcc_box.ok_or_else(|| Error::missing_box("avcc", Self::TYPE))?;
+        Ok(Self {
+            visual,
+            avcc_box,
+            unknown_boxes,
+        })
+    }
+}
+
+impl Encode for Avc1Box {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for Avc1Box {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for Avc1Box {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(
+            std::iter::empty()
+                .chain(std::iter::once(&self.avcc_box).map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
+    }
+}
+
+/// [ISO/IEC 14496-15] AVCConfigurationBox class (親: [`Avc1Box`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct AvccBox {
+    pub avc_profile_indication: u8,
+    pub profile_compatibility: u8,
+    pub avc_level_indication: u8,
+    pub length_size_minus_one: Uint<u8, 2>,
+    pub sps_list: Vec<Vec<u8>>,
+    pub pps_list: Vec<Vec<u8>>,
+    pub chroma_format: Option<Uint<u8, 2>>,
+    pub bit_depth_luma_minus8: Option<Uint<u8, 3>>,
+    pub bit_depth_chroma_minus8: Option<Uint<u8, 3>>,
+    pub sps_ext_list: Vec<Vec<u8>>,
+}
+
+impl AvccBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"avcC");
+
+    const CONFIGURATION_VERSION: u8 = 1;
+
+    /// 先頭の SPS を解析して、クロッピングを考慮した映像の解像度 `(幅, 高さ)` をピクセル単位で返す
+    ///
+    /// SPS が存在しない場合や、解析に失敗した場合には [`None`] が返される
+    pub fn parse_sps_resolution(&self) -> Option<(u16, u16)> {
+        let sps = self.sps_list.first()?;
+
+        // NAL ユニットヘッダーを飛ばして、エミュレーション防止バイトを取り除く
+        if sps.first()? & 0x1F != 7 {
+            return None;
+        }
+        let mut rbsp = Vec::with_capacity(sps.len());
+        let mut zeros = 0;
+        for &b in &sps[1..] {
+            if zeros >= 2 && b == 3 {
+                zeros = 0;
+                continue;
+            }
+            zeros = if b == 0 { zeros + 1 } else { 0 };
+            rbsp.push(b);
+        }
+        let mut reader = BitReader::new(&rbsp);
+
+        let profile_idc = reader.read(8)?;
+        let _constraint_flags_and_level_idc = reader.read(16)?;
+        let _seq_parameter_set_id = reader.read_ue()?;
+
+        let mut chroma_format_idc = 1;
+        let mut separate_colour_plane_flag = 0;
+        if matches!(
+            profile_idc,
+            100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+        ) {
+            chroma_format_idc = reader.read_ue()?;
+            if chroma_format_idc == 3 {
+                separate_colour_plane_flag = reader.read(1)?;
+            }
+            let _bit_depth_luma_minus8 = reader.read_ue()?;
+            let _bit_depth_chroma_minus8 = reader.read_ue()?;
+            let _qpprime_y_zero_transform_bypass_flag = reader.read(1)?;
+            let seq_scaling_matrix_present_flag = reader.read(1)?;
+            if seq_scaling_matrix_present_flag == 1 {
+                let count = if chroma_format_idc == 3 { 12 } else { 8 };
+                for i in 0..count {
+                    let seq_scaling_list_present_flag = reader.read(1)?;
+                    if seq_scaling_list_present_flag == 1 {
+                        let size = if i < 6 { 16 } else { 64 };
+                        let mut last_scale = 8;
+                        let mut next_scale = 8;
+                        for _ in 0..size {
+                            if next_scale != 0 {
+                                // 仕様上 delta_scale は -128..=127 の範囲に収まる
+                                let delta_scale = reader.read_se()?;
+                                if !(-128..=127).contains(&delta_scale) {
+                                    return None;
+                                }
+                                next_scale = (last_scale + delta_scale + 256) % 256;
+                            }
+                            if next_scale != 0 {
+                                last_scale = next_scale;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let _log2_max_frame_num_minus4 = reader.read_ue()?;
+        let pic_order_cnt_type = reader.read_ue()?;
+        if pic_order_cnt_type == 0 {
+            let _log2_max_pic_order_cnt_lsb_minus4 = reader.read_ue()?;
+        } else if pic_order_cnt_type == 1 {
+            let _delta_pic_order_always_zero_flag = reader.read(1)?;
+            let _offset_for_non_ref_pic = reader.read_se()?;
+            let _offset_for_top_to_bottom_field = reader.read_se()?;
+            let num_ref_frames_in_pic_order_cnt_cycle = reader.read_ue()?;
+            for _ in 0..num_ref_frames_in_pic_order_cnt_cycle {
+                let _offset_for_ref_frame = reader.read_se()?;
+            }
+        }
+        let _max_num_ref_frames = reader.read_ue()?;
+        let _gaps_in_frame_num_value_allowed_flag = reader.read(1)?;
+        let pic_width_in_mbs_minus1 = u64::from(reader.read_ue()?);
+        let pic_height_in_map_units_minus1 = u64::from(reader.read_ue()?);
+        let frame_mbs_only_flag = u64::from(reader.read(1)?);
+        if frame_mbs_only_flag == 0 {
+            let _mb_adaptive_frame_field_flag = reader.read(1)?;
+        }
+        let _direct_8x8_inference_flag = reader.read(1)?;
+        let frame_cropping_flag = reader.read(1)?;
+        let mut crop = [0u64; 4]; // left, right, top, bottom
+        if frame_cropping_flag == 1 {
+            for x in &mut crop {
+                *x = u64::from(reader.read_ue()?);
+            }
+        }
+
+        let (crop_unit_x, crop_unit_y) =
+            if separate_colour_plane_flag == 1 || chroma_format_idc == 0 {
+                (1, 2 - frame_mbs_only_flag)
+            } else {
+                let sub_width_c = if chroma_format_idc == 3 { 1 } else { 2 };
+                let sub_height_c = if chroma_format_idc == 1 { 2 } else { 1 };
+                (sub_width_c, sub_height_c * (2 - frame_mbs_only_flag))
+            };
+        let width =
+            ((pic_width_in_mbs_minus1 + 1) * 16).checked_sub((crop[0] + crop[1]) * crop_unit_x)?;
+        let height = ((2 - frame_mbs_only_flag) * (pic_height_in_map_units_minus1 + 1) * 16)
+            .checked_sub((crop[2] + crop[3]) * crop_unit_y)?;
+        Some((width.try_into().ok()?, height.try_into().ok()?))
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        Self::CONFIGURATION_VERSION.encode(&mut writer)?;
+        self.avc_profile_indication.encode(&mut writer)?;
+        self.profile_compatibility.encode(&mut writer)?;
+        self.avc_level_indication.encode(&mut writer)?;
+        (0b1111_1100 | self.length_size_minus_one.get()).encode(&mut writer)?;
+
+        let sps_count =
+            u8::try_from(self.sps_list.len()).map_err(|_| Error::invalid_input("Too many SPSs"))?;
+        (0b1110_0000 | sps_count).encode(&mut writer)?;
+        for sps in &self.sps_list {
+            let size = u16::try_from(sps.len())
+                .map_err(|e| Error::invalid_input(&format!("Too long SPS: {e}")))?;
+            size.encode(&mut writer)?;
+            writer.write_all(sps)?;
+        }
+
+        let pps_count =
+            u8::try_from(self.pps_list.len()).map_err(|_| Error::invalid_input("Too many PPSs"))?;
+        pps_count.encode(&mut writer)?;
+        for pps in &self.pps_list {
+            let size = u16::try_from(pps.len())
+                .map_err(|e| Error::invalid_input(&format!("Too long PPS: {e}")))?;
+            size.encode(&mut writer)?;
+            writer.write_all(pps)?;
+        }
+
+        if !matches!(self.avc_profile_indication, 66 | 77 | 88) {
+            let chroma_format = self.chroma_format.ok_or_else(|| {
+                Error::invalid_input("Missing 'chroma_format' field in 'avcC' boc")
+            })?;
+            let bit_depth_luma_minus8 = self.bit_depth_luma_minus8.ok_or_else(|| {
+                Error::invalid_input("Missing 'bit_depth_luma_minus8' field in 'avcC' boc")
+            })?;
+            let bit_depth_chroma_minus8 = self.bit_depth_chroma_minus8.ok_or_else(|| {
+                Error::invalid_input("Missing 'bit_depth_chroma_minus8' field in 'avcC' boc")
+            })?;
+            (0b1111_1100 | chroma_format.get()).encode(&mut writer)?;
+            (0b1111_1000 | bit_depth_luma_minus8.get()).encode(&mut writer)?;
+            (0b1111_1000 | bit_depth_chroma_minus8.get()).encode(&mut writer)?;
+
+            let sps_ext_count = u8::try_from(self.sps_ext_list.len())
+                .map_err(|_| Error::invalid_input("Too many SPS EXTs"))?;
+            sps_ext_count.encode(&mut writer)?;
+            for sps_ext in &self.sps_ext_list {
+                let size = u16::try_from(sps_ext.len())
+                    .map_err(|e| Error::invalid_input(&format!("Too long SPS EXT: {e}")))?;
+                size.encode(&mut writer)?;
+                writer.write_all(sps_ext)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let configuration_version = u8::decode(&mut reader)?;
+        if configuration_version != Self::CONFIGURATION_VERSION {
+            return Err(Error::invalid_data(&format!(
+                "Unsupported avcC configuration version: {configuration_version}"
+            )));
+        }
+
+        let avc_profile_indication = u8::decode(&mut reader)?;
+        let profile_compatibility = u8::decode(&mut reader)?;
+        let avc_level_indication = u8::decode(&mut reader)?;
+        let length_size_minus_one = Uint::from_bits(u8::decode(&mut reader)?);
+
+        let sps_count = Uint::<u8, 5>::from_bits(u8::decode(&mut reader)?).get() as usize;
+        let mut sps_list = Vec::with_capacity(sps_count);
+        for _ in 0..sps_count {
+            let size = u16::decode(&mut reader)? as usize;
+            let mut sps = vec![0; size];
+            reader.read_exact(&mut sps)?;
+            sps_list.push(sps);
+        }
+
+        let pps_count = u8::decode(&mut reader)? as usize;
+        let mut pps_list = Vec::with_capacity(pps_count);
+        for _ in 0..pps_count {
+            let size = u16::decode(&mut reader)? as usize;
+            let mut pps = vec![0; size];
+            reader.read_exact(&mut pps)?;
+            pps_list.push(pps);
+        }
+
+        let mut chroma_format = None;
+        let mut bit_depth_luma_minus8 = None;
+        let mut bit_depth_chroma_minus8 = None;
+        let mut sps_ext_list = Vec::new();
+        if !matches!(avc_profile_indication, 66 | 77 | 88) {
+            chroma_format = Some(Uint::from_bits(u8::decode(&mut reader)?));
+            bit_depth_luma_minus8 = Some(Uint::from_bits(u8::decode(&mut reader)?));
+            bit_depth_chroma_minus8 = Some(Uint::from_bits(u8::decode(&mut reader)?));
+
+            let sps_ext_count = u8::decode(&mut reader)? as usize;
+            for _ in 0..sps_ext_count {
+                let size = u16::decode(&mut reader)? as usize;
+                let mut pps = vec![0; size];
+                reader.read_exact(&mut pps)?;
+                sps_ext_list.push(pps);
+            }
+        }
+
+        Ok(Self {
+            avc_profile_indication,
+            profile_compatibility,
+            avc_level_indication,
+            length_size_minus_one,
+            sps_list,
+            pps_list,
+            chroma_format,
+            bit_depth_luma_minus8,
+            bit_depth_chroma_minus8,
+            sps_ext_list,
+        })
+    }
+}
+
+impl Encode for AvccBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for AvccBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for AvccBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(std::iter::empty())
+    }
+}
+
+/// [`AvccBox`] を段階的に構築するためのビルダー
+///
+/// NAL ユニットの長さフィールドのサイズはデフォルトで 4 バイトとなる。
+/// Baseline / Main / Extended 以外のプロファイルの場合には、[`AvccBoxBuilder::chroma_format()`] と
+/// [`AvccBoxBuilder::bit_depth()`] の指定が必須となる（[`AvccBoxBuilder::build()`] 時にチェックされる）。
+#[derive(Debug, Clone)]
+pub struct AvccBoxBuilder {
+    avc_profile_indication: u8,
+    profile_compatibility: u8,
+    avc_level_indication: u8,
+    length_size_minus_one: Uint<u8, 2>,
+    sps_list: Vec<Vec<u8>>,
+    pps_list: Vec<Vec<u8>>,
+    chroma_format: Option<u8>,
+    bit_depth: Option<(u8, u8)>,
+    sps_ext_list: Vec<Vec<u8>>,
+}
+
+impl AvccBoxBuilder {
+    /// SPS の最大数
+    pub const MAX_SPS_COUNT: usize = 31;
+
+    /// PPS の最大数
+    pub const MAX_PPS_COUNT: usize = 255;
+
+    /// プロファイル、プロファイル互換性フラグ、レベルを指定してビルダーを作成する
+    pub fn new(profile: u8, compatibility: u8, level: u8) -> Self {
+        Self {
+            avc_profile_indication: profile,
+            profile_compatibility: compatibility,
+            avc_level_indication: level,
+            length_size_minus_one: Uint::new(3),
+            sps_list: Vec::new(),
+            pps_list: Vec::new(),
+            chroma_format: None,
+            bit_depth: None,
+            sps_ext_list: Vec::new(),
+        }
+    }
+
+    /// SPS を追加する
+    pub fn add_sps(mut self, sps: Vec<u8>) -> Self {
+        self.sps_list.push(sps);
+        self
+    }
+
+    /// PPS を追加する
+    pub fn add_pps(mut self, pps: Vec<u8>) -> Self {
+        self.pps_list.push(pps);
+        self
+    }
+
+    /// SPS EXT を追加する
+    ///
+    /// Baseline / Main / Extended 以外のプロファイルの場合にのみ指定可能
+    pub fn add_sps_ext(mut self, sps_ext: Vec<u8>) -> Self {
+        self.sps_ext_list.push(sps_ext);
+        self
+    }
+
+    /// NAL ユニットの長さフィールドのサイズ（バイト数）を指定する
+    ///
+    /// `size` には 1、2、4 のいずれかが指定可能で、それ以外の場合にはエラーが返される
+    pub fn length_size(mut self, size: u8) -> Result<Self> {
+        if !matches!(size, 1 | 2 | 4) {
+            return Err(Error::invalid_input(&format!(
+                "Invalid NAL unit length size: {size} (expected 1, 2 or 4)"
+            )));
+        }
+        self.length_size_minus_one = Uint::new(size - 1);
+        Ok(self)
+    }
+
+    /// クロマフォーマット（`chroma_format_idc`）を指定する
+    ///
+    /// 値の範囲（0..=3）は [`AvccBoxBuilder::build()`] 時にチェックされる
+    pub fn chroma_format(mut self, chroma_format: u8) -> Self {
+        self.chroma_format = Some(chroma_format);
+        self
+    }
+
+    /// 輝度と色差のビット深度を指定する
+    ///
+    /// 値の範囲（8..=15）は [`AvccBoxBuilder::build()`] 時にチェックされる
+    pub fn bit_depth(mut self, luma: u8, chroma: u8) -> Self {
+        self.bit_depth = Some((luma, chroma));
+        self
+    }
+
+    /// [`AvccBox`] を構築する
+    ///
+    /// 以下の場合にはエラーが返される:
+    /// - SPS の数が [`AvccBoxBuilder::MAX_SPS_COUNT`] を、PPS の数が [`AvccBoxBuilder::MAX_PPS_COUNT`] を超えている
+    /// - SPS / PPS / SPS EXT のサイズが 65535 バイトを超えている
+    /// - Baseline / Main / Extended 以外のプロファイルで、クロマフォーマットまたはビット深度が未指定
+    /// - Baseline / Main / Extended のプロファイルで、クロマフォーマット、ビット深度、SPS EXT のいずれかが指定されている
+    /// - クロマフォーマットまたはビット深度の値が範囲外
+    pub fn build(self) -> Result<AvccBox> {
+        if self.sps_list.len() > Self::MAX_SPS_COUNT {
+            return Err(Error::invalid_input(&format!(
+                "Too many SPSs: {} (max {})",
+                self.sps_list.len(),
+                Self::MAX_SPS_COUNT
+            )));
+        }
+        if self.pps_list.len() > Self::MAX_PPS_COUNT {
+            return Err(Error::invalid_input(&format!(
+                "Too many PPSs: {} (max {})",
+                self.pps_list.len(),
+                Self::MAX_PPS_COUNT
+            )));
+        }
+        for (name, list) in [
+            ("SPS", &self.sps_list),
+            ("PPS", &self.pps_list),
+            ("SPS EXT", &self.sps_ext_list),
+        ] {
+            if let Some(nalu) = list.iter().find(|x| x.len() > u16::MAX as usize) {
+                return Err(Error::invalid_input(&format!(
+                    "Too long {name}: {} bytes",
+                    nalu.len()
+                )));
+            }
+        }
+
+        // エンコード時と同じ条件で、High プロファイル以降用のフィールドの要否を判定する
+        let is_high_profile_fields_required = !matches!(self.avc_profile_indication, 66 | 77 | 88);
+        let (chroma_format, bit_depth_luma_minus8, bit_depth_chroma_minus8) =
+            if is_high_profile_fields_required {
+                let chroma_format = self.chroma_format.ok_or_else(|| {
+                    Error::invalid_input(&format!(
+                        "Missing chroma format for profile {}",
+                        self.avc_profile_indication
+                    ))
+                })?;
+                let (luma, chroma) = self.bit_depth.ok_or_else(|| {
+                    Error::invalid_input(&format!(
+                        "Missing bit depth for profile {}",
+                        self.avc_profile_indication
+                    ))
+                })?;
+                let chroma_format = Uint::<u8, 2>::checked_new(chroma_format).ok_or_else(|| {
+                    Error::invalid_input(&format!("Invalid chroma format: {chroma_format}"))
+                })?;
+                let bit_depth_minus8 = |bit_depth: u8| {
+                    bit_depth
+                        .checked_sub(8)
+                        .and_then(Uint::<u8, 3>::checked_new)
+                        .ok_or_else(|| {
+                            Error::invalid_input(&format!("Invalid bit depth: {bit_depth}"))
+                        })
+                };
+                (
+                    Some(chroma_format),
+                    Some(bit_depth_minus8(luma)?),
+                    Some(bit_depth_minus8(chroma)?),
+                )
+            } else {
+                if self.chroma_format.is_some()
+                    || self.bit_depth.is_some()
+                    || !self.sps_ext_list.is_empty()
+                {
+                    return Err(Error::invalid_input(&format!(
+                        "Chroma format, bit depth and SPS EXT are not allowed for profile {}",
+                        self.avc_profile_indication
+                    )));
+                }
+                (None, None, None)
+            };
+
+        Ok(AvccBox {
+            avc_profile_indication: self.avc_profile_indication,
+            profile_compatibility: self.profile_compatibility,
+            avc_level_indication: self.avc_level_indication,
+            length_size_minus_one: self.length_size_minus_one,
+            sps_list: self.sps_list,
+            pps_list: self.pps_list,
+            chroma_format,
+            bit_depth_luma_minus8,
+            bit_depth_chroma_minus8,
+            sps_ext_list: self.sps_ext_list,
+        })
+    }
+}
+
+/// [ISO/IEC 14496-15] HEVCSampleEntry class (親: [`StsdBox`])
+///
+/// サンプルエントリー種別が `hev1` の場合には、VPS / SPS / PPS などのパラメーターセットが
+/// [`HvccBox`] だけではなく、ビットストリーム中（各サンプル内）にも含まれる可能性がある。
+/// 一方で `hvc1` ([`Hvc1Box`]) の場合には、全てのパラメーターセットが [`HvccBox`] に格納されている必要がある。
+///
+/// どちらの場合も、ボックスの構造自体は同一となる。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct Hev1Box {
+    pub visual: VisualSampleEntryFields,
+    pub hvcc_box: HvccBox,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl Hev1Box {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"hev1");
+
+    /// パラメーターセットがビットストリーム中にも含まれる可能性があるかどうかを返す
+    ///
+    /// [`HvccBox`] のいずれかの NAL ユニット配列の `array_completeness` が 0 の場合に `true` となる
+    pub fn is_parameter_set_in_bitstream(&self) -> bool {
+        !self.hvcc_box.is_all_parameter_sets_complete()
+    }
+
+    /// `hvc1` 形式のサンプルエントリーに変換する
+    ///
+    /// パラメーターセットがビットストリーム中に含まれる可能性がある場合
+    /// （[`Hev1Box::is_parameter_set_in_bitstream()`] が `true` の場合）にはエラーとなる
+    pub fn into_hvc1(self) -> Result<Hvc1Box> {
+        if self.is_parameter_set_in_bitstream() {
+            return Err(Error::invalid_input(
+                "Cannot convert 'hev1' to 'hvc1': some parameter sets may be in the bitstream",
+            ));
+        }
+        Ok(Hvc1Box {
+            visual: self.visual,
+            hvcc_box: self.hvcc_box,
+            unknown_boxes: self.unknown_boxes,
+        })
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.visual.encode(&mut writer)?;
+        self.hvcc_box.encode(&mut writer)?;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let visual = VisualSampleEntryFields::decode(&mut reader)?;
+        let mut hvcc_box = None;
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                HvccBox::TYPE if hvcc_box.is_none() => {
+                    hvcc_box = Some(HvccBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(header.box_type, &[HvccBox::TYPE]);
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
+        }
+        let hvcc_box = hvcc_box.ok_or_else(|| Error::missing_box("hvcc", Self::TYPE))?;
+        Ok(Self {
+            visual,
+            hvcc_box,
+            unknown_boxes,
+        })
+    }
+}
+
+impl Encode for Hev1Box {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for Hev1Box {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for Hev1Box {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(
+            std::iter::empty()
+                .chain(std::iter::once(&self.hvcc_box).map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
+    }
+}
+
+/// [ISO/IEC 14496-15] HEVCSampleEntry class (親: [`StsdBox`])
+///
+/// サンプルエントリー種別が `hvc1` の場合で、全てのパラメーターセットが [`HvccBox`] に格納されている必要がある
+/// （[`Hev1Box`] との違いについては、そちらのドキュメントを参照のこと）。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct Hvc1Box {
+    pub visual: VisualSampleEntryFields,
+    pub hvcc_box: HvccBox,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl Hvc1Box {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"hvc1");
+
+    /// [`HvccBox`] の全ての NAL ユニット配列の `array_completeness` が 1 になっているかどうかを返す
+    ///
+    /// `hvc1` では全てのパラメーターセットが [`HvccBox`] に格納されている必要があるので、
+    /// 正しいボックスであれば常に `true` となる
+    pub fn validate_all_parameter_sets_complete(&self) -> bool {
+        self.hvcc_box.is_all_parameter_sets_complete()
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.visual.encode(&mut writer)?;
+        self.hvcc_box.encode(&mut writer)?;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let visual = VisualSampleEntryFields::decode(&mut reader)?;
+        let mut hvcc_box = None;
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                HvccBox::TYPE if hvcc_box.is_none() => {
+                    hvcc_box = Some(HvccBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(header.box_type, &[HvccBox::TYPE]);
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
+        }
+        let hvcc_box = hvcc_box.ok_or_else(|| Error::missing_box("hvcc", Self::TYPE))?;
+        Ok(Self {
+            visual,
+            hvcc_box,
+            unknown_boxes,
+        })
+    }
+}
+
+impl Encode for Hvc1Box {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for Hvc1Box {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for Hvc1Box {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(
+            std::iter::empty()
+                .chain(std::iter::once(&self.hvcc_box).map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
+    }
+}
+
+/// [`HvccBox`] 内の NAL ユニット配列を保持する構造体
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct HvccNalUintArray {
+    pub array_completeness: Uint<u8, 1, 7>,
+    pub nal_unit_type: Uint<u8, 6, 0>,
+    pub nalus: Vec<Vec<u8>>,
+}
+
+/// [ISO/IEC 14496-15] HVCConfigurationBox class (親: [`Hev1Box`], [`Hvc1Box`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct HvccBox {
+    pub general_profile_space: Uint<u8, 2, 6>,
+    pub general_tier_flag: Uint<u8, 1, 5>,
+    pub general_profile_idc: Uint<u8, 5, 0>,
+    pub general_profile_compatibility_flags: u32,
+    pub general_constraint_indicator_flags: Uint<u64, 48>,
+    pub general_level_idc: u8,
+    pub min_spatial_segmentation_idc: Uint<u16, 12>,
+    pub parallelism_type: Uint<u8, 2>,
+    pub chroma_format_idc: Uint<u8, 2>,
+    pub bit_depth_luma_minus8: Uint<u8, 3>,
+    pub bit_depth_chroma_minus8: Uint<u8, 3>,
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: Uint<u8, 2, 6>,
+    pub num_temporal_layers: Uint<u8, 3, 3>,
+    pub temporal_id_nested: Uint<u8, 1, 2>,
+    pub length_size_minus_one: Uint<u8, 2, 0>,
+    pub nalu_arrays: Vec<HvccNalUintArray>,
+}
+
+impl HvccBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"hvcC");
+
+    fn is_all_parameter_sets_complete(&self) -> bool {
+        self.nalu_arrays
+            .iter()
+            .all(|x| x.array_completeness.get() == 1)
+    }
+
+    const CONFIGURATION_VERSION: u8 = 1;
+
+    /// [ISO/IEC 14496-15] Annex E で定義されている形式のコーデック文字列（`hvc1.1.6.L93.B0` など）を返す
+    ///
+    /// 先頭のサンプルエントリー種別には `hvc1` が使われる。
+    /// `hev1` サンプルエントリーの場合には [`SampleEntry::codec_string()`] を使うこと。
+    pub fn codec_string(&self) -> String {
+        self.codec_string_with_fourcc("hvc1")
+    }
+
+    fn codec_string_with_fourcc(&self, fourcc: &str) -> String {
+        let profile_space = match self.general_profile_space.get() {
+            1 => "A",
+            2 => "B",
+            3 => "C",
+            _ => "",
+        };
+        let tier = if self.general_tier_flag.get() == 0 {
+            "L"
+        } else {
+            "H"
+        };
+
+        let mut s = format!(
+            "{fourcc}.{profile_space}{}.{:X}.{tier}{}",
+            self.general_profile_idc.get(),
+            // 互換性フラグはビット順を反転した上で 16 進数表記にする
+            self.general_profile_compatibility_flags.reverse_bits(),
+            self.general_level_idc
+        );
+
+        // 制約フラグは 1 バイトずつ 16 進数表記にする（末尾の 0 のバイトは省略する）
+        let constraint_flags = &self.general_constraint_indicator_flags.get().to_be_bytes()[2..];
+        let len = constraint_flags
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |i| i + 1);
+        for b in &constraint_flags[..len] {
+            s.push_str(&format!(".{b:X}"));
+        }
+        s
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        Self::CONFIGURATION_VERSION.encode(&mut writer)?;
+        (self.general_profile_space.to_bits()
+            | self.general_tier_flag.to_bits()
+            | self.general_profile_idc.to_bits())
+        .encode(&mut writer)?;
+        self.general_profile_compatibility_flags
+            .encode(&mut writer)?;
+        writer.write_all(&self.general_constraint_indicator_flags.get().to_be_bytes()[2..])?;
+        self.general_level_idc.encode(&mut writer)?;
+        (0b1111_0000_0000_0000 | self.min_spatial_segmentation_idc.to_bits())
+            .encode(&mut writer)?;
+        (0b1111_1100 | self.parallelism_type.to_bits()).encode(&mut writer)?;
+        (0b1111_1100 | self.chroma_format_idc.to_bits()).encode(&mut writer)?;
+        (0b1111_1000 | self.bit_depth_luma_minus8.to_bits()).encode(&mut writer)?;
+        (0b1111_1000 | self.bit_depth_chroma_minus8.to_bits()).encode(&mut writer)?;
+        self.avg_frame_rate.encode(&mut writer)?;
+        (self.constant_frame_rate.to_bits()
+            | self.num_temporal_layers.to_bits()
+            | self.temporal_id_nested.to_bits()
+            | self.length_size_minus_one.to_bits())
+        .encode(&mut writer)?;
+        u8::try_from(self.nalu_arrays.len())
+            .map_err(|_| {
+                Error::invalid_input(&format!("Too many NALU arrays: {}", self.nalu_arrays.len()))
+            })?
+            .encode(&mut writer)?;
+        for nalu_array in &self.nalu_arrays {
+            (nalu_array.array_completeness.to_bits() | nalu_array.nal_unit_type.to_bits())
+                .encode(&mut writer)?;
+            u16::try_from(nalu_array.nalus.len())
+                .map_err(|_| {
+                    Error::invalid_input(&format!("Too many NALUs: {}", self.nalu_arrays.len()))
+                })?
+                .encode(&mut writer)?;
+            for nalu in &nalu_array.nalus {
+                u16::try_from(nalu.len())
+                    .map_err(|_| {
+                        Error::invalid_input(&format!("Too large NALU: {}", self.nalu_arrays.len()))
+                    })?
+                    .encode(&mut writer)?;
+                writer.write_all(nalu)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let configuration_version = u8::decode(&mut reader)?;
+        if configuration_version != Self::CONFIGURATION_VERSION {
+            return Err(Error::invalid_data(&format!(
+                "Unsupported avcC version: {configuration_version}"
+            )));
+        }
+
+        let b = u8::decode(&mut reader)?;
+        let general_profile_space = Uint::from_bits(b);
+        let general_tier_flag = Uint::from_bits(b);
+        let general_profile_idc = Uint::from_bits(b);
+
+        let general_profile_compatibility_flags = u32::decode(&mut reader)?;
+
+        let mut buf = [0; 8];
+        reader.read_exact(&mut buf[2..])?;
+        let general_constraint_indicator_flags = Uint::from_bits(u64::from_be_bytes(buf));
+
+        let general_level_idc = u8::decode(&mut reader)?;
+        let min_spatial_segmentation_idc = Uint::from_bits(u16::decode(&mut reader)?);
+        let parallelism_type = Uint::from_bits(u8::decode(&mut reader)?);
+        let chroma_format_idc = Uint::from_bits(u8::decode(&mut reader)?);
+        let bit_depth_luma_minus8 = Uint::from_bits(u8::decode(&mut reader)?);
+        let bit_depth_chroma_minus8 = Uint::from_bits(u8::decode(&mut reader)?);
+        let avg_frame_rate = u16::decode(&mut reader)?;
+
+        let b = u8::decode(&mut reader)?;
+        let constant_frame_rate = Uint::from_bits(b);
+        let num_temporal_layers = Uint::from_bits(b);
+        let temporal_id_nested = Uint::from_bits(b);
+        let length_size_minus_one = Uint::from_bits(b);
+
+        let num_of_arrays = u8::decode(&mut reader)?;
+        let mut nalu_arrays = Vec::new();
+        for _ in 0..num_of_arrays {
+            let b = u8::decode(&mut reader)?;
+            let array_completeness = Uint::from_bits(b);
+            let nal_unit_type = Uint::from_bits(b);
+
+            let num_nalus = u16::decode(&mut reader)?;
+            let mut nalus = Vec::new();
+            for _ in 0..num_nalus {
+                let nal_unit_length = u16::decode(&mut reader)? as usize;
+                let mut nal_unit = vec![0; nal_unit_length];
+                reader.read_exact(&mut nal_unit)?;
+                nalus.push(nal_unit);
+            }
+            nalu_arrays.push(HvccNalUintArray {
+                array_completeness,
+                nal_unit_type,
+                nalus,
+            });
+        }
+
+        Ok(Self {
+            general_profile_space,
+            general_tier_flag,
+            general_profile_idc,
+            general_profile_compatibility_flags,
+            general_constraint_indicator_flags,
+            general_level_idc,
+            min_spatial_segmentation_idc,
+            parallelism_type,
+            chroma_format_idc,
+            bit_depth_luma_minus8,
+            bit_depth_chroma_minus8,
+            avg_frame_rate,
+            constant_frame_rate,
+            num_temporal_layers,
+            temporal_id_nested,
+            length_size_minus_one,
+            nalu_arrays,
+        })
+    }
+}
+
+impl Encode for HvccBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for HvccBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for HvccBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(std::iter::empty())
+    }
+}
+
+/// [<https://www.webmproject.org/vp9/mp4/>] VP8SampleEntry class (親: [`StsdBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct Vp08Box {
+    pub visual: VisualSampleEntryFields,
+    pub vpcc_box: VpccBox,
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl Vp08Box {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"vp08");
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.visual.encode(&mut writer)?;
+        self.vpcc_box.encode(&mut writer)?;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let visual = VisualSampleEntryFields::decode(&mut reader)?;
+        let mut vpcc_box = None;
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
+            match header.box_type {
+                VpccBox::TYPE if vpcc_box.is_none() => {
+                    vpcc_box = Some(VpccBox::decode(&mut reader)?);
+                }
+                _ => {
+                    warn_if_duplicate(header.box_type, &[VpccBox::TYPE]);
+                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+                }
+            }
+        }
+        let vpcc_box = vpcc_box.ok_or_else(|| Error::missing_box("vpcC", Self::TYPE))?;
+        Ok(Self {
+            visual,
+            vpcc_box,
+            unknown_boxes,
+        })
+    }
 }
 
-impl Encode for VisualSampleEntryFields {
+impl Encode for Vp08Box {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
-        [0u8; 6].encode(&mut writer)?;
-        self.data_reference_index.encode(&mut writer)?;
-        [0u8; 2 + 2 + 4 * 3].encode(&mut writer)?;
-        self.width.encode(&mut writer)?;
-        self.height.encode(&mut writer)?;
-        self.horizresolution.encode(&mut writer)?;
-        self.vertresolution.encode(&mut writer)?;
-        [0u8; 4].encode(&mut writer)?;
-        self.frame_count.encode(&mut writer)?;
-        self.compressorname.encode(&mut writer)?;
-        self.depth.encode(&mut writer)?;
-        (-1i16).encode(writer)?;
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
         Ok(())
     }
 }
 
-impl Decode for VisualSampleEntryFields {
+impl Decode for Vp08Box {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
-        let _ = <[u8; 6]>::decode(&mut reader)?;
-        let data_reference_index = NonZeroU16::decode(&mut reader)?;
-        let _ = <[u8; 2 + 2 + 4 * 3]>::decode(&mut reader)?;
-        let width = u16::decode(&mut reader)?;
-        let height = u16::decode(&mut reader)?;
-        let horizresolution = FixedPointNumber::decode(&mut reader)?;
-        let vertresolution = FixedPointNumber::decode(&mut reader)?;
-        let _ = <[u8; 4]>::decode(&mut reader)?;
-        let frame_count = u16::decode(&mut reader)?;
-        let compressorname = <[u8; 32]>::decode(&mut reader)?;
-        let depth = u16::decode(&mut reader)?;
-        let _ = <[u8; 2]>::decode(reader)?;
-        Ok(Self {
-            data_reference_index,
-            width,
-            height,
-            horizresolution,
-            vertresolution,
-            frame_count,
-            compressorname,
-            depth,
-        })
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
     }
 }
 
-/// [ISO/IEC 14496-15] AVCSampleEntry class (親: [`StsdBox`])
+impl BaseBox for Vp08Box {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(
+            std::iter::empty()
+                .chain(std::iter::once(&self.vpcc_box).map(as_box_object))
+                .chain(self.unknown_boxes.iter().map(as_box_object)),
+        )
+    }
+}
+
+/// [<https://www.webmproject.org/vp9/mp4/>] VP9SampleEntry class (親: [`StsdBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Avc1Box {
+pub struct Vp09Box {
     pub visual: VisualSampleEntryFields,
-    pub avcc_box: AvccBox,
+    pub vpcc_box: VpccBox,
     pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl Avc1Box {
+impl Vp09Box {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"avc1");
+    pub const TYPE: BoxType = BoxType::Normal(*b"vp09");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         self.visual.encode(&mut writer)?;
-        self.avcc_box.encode(&mut writer)?;
+        self.vpcc_box.encode(&mut writer)?;
         for b in &self.unknown_boxes {
             b.encode(&mut writer)?;
         }
@@ -2363,29 +5745,30 @@ impl Avc1Box {
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let visual = VisualSampleEntryFields::decode(&mut reader)?;
-        let mut avcc_box = None;
+        let mut vpcc_box = None;
         let mut unknown_boxes = Vec::new();
         while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                AvccBox::TYPE if avcc_box.is_none() => {
-                    avcc_box = Some(AvccBox::decode(&mut reader)?);
+                VpccBox::TYPE if vpcc_box.is_none() => {
+                    vpcc_box = Some(VpccBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(header.box_type, &[VpccBox::TYPE]);
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
-        let avcc_box = avcc_box.ok_or_else(|| Error::missing_box("avcc", Self::TYPE))?;
+        let vpcc_box = vpcc_box.ok_or_else(|| Error::missing_box("vpcC", Self::TYPE))?;
         Ok(Self {
             visual,
-            avcc_box,
+            vpcc_box,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for Avc1Box {
+impl Encode for Vp09Box {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2393,7 +5776,7 @@ impl Encode for Avc1Box {
     }
 }
 
-impl Decode for Avc1Box {
+impl Decode for Vp09Box {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2401,7 +5784,7 @@ impl Decode for Avc1Box {
     }
 }
 
-impl BaseBox for Avc1Box {
+impl BaseBox for Vp09Box {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2413,154 +5796,143 @@ impl BaseBox for Avc1Box {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(std::iter::once(&self.avcc_box).map(as_box_object))
+                .chain(std::iter::once(&self.vpcc_box).map(as_box_object))
                 .chain(self.unknown_boxes.iter().map(as_box_object)),
         )
     }
 }
 
-/// [ISO/IEC 14496-15] AVCConfigurationBox class (親: [`Avc1Box`])
+/// [<https://www.webmproject.org/vp9/mp4/>] VPCodecConfigurationBox class (親: [`Vp08Box`], [`Vp09Box`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct AvccBox {
-    pub avc_profile_indication: u8,
-    pub profile_compatibility: u8,
-    pub avc_level_indication: u8,
-    pub length_size_minus_one: Uint<u8, 2>,
-    pub sps_list: Vec<Vec<u8>>,
-    pub pps_list: Vec<Vec<u8>>,
-    pub chroma_format: Option<Uint<u8, 2>>,
-    pub bit_depth_luma_minus8: Option<Uint<u8, 3>>,
-    pub bit_depth_chroma_minus8: Option<Uint<u8, 3>>,
-    pub sps_ext_list: Vec<Vec<u8>>,
+pub struct VpccBox {
+    pub profile: u8,
+    pub level: u8,
+    pub bit_depth: Uint<u8, 4, 4>,
+    pub chroma_subsampling: Uint<u8, 3, 1>,
+    pub video_full_range_flag: Uint<u8, 1>,
+    pub colour_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+    pub codec_initialization_data: Vec<u8>,
 }
 
-impl AvccBox {
+impl VpccBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"avcC");
-
-    const CONFIGURATION_VERSION: u8 = 1;
+    pub const TYPE: BoxType = BoxType::Normal(*b"vpcC");
 
-    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        Self::CONFIGURATION_VERSION.encode(&mut writer)?;
-        self.avc_profile_indication.encode(&mut writer)?;
-        self.profile_compatibility.encode(&mut writer)?;
-        self.avc_level_indication.encode(&mut writer)?;
-        (0b1111_1100 | self.length_size_minus_one.get()).encode(&mut writer)?;
+    /// [`VpccBox::chroma_subsampling`] のデフォルト値（4:2:0、輝度サンプルと同位置）
+    pub const DEFAULT_CHROMA_SUBSAMPLING: Uint<u8, 3, 1> = Uint::new(1);
 
-        let sps_count =
-            u8::try_from(self.sps_list.len()).map_err(|_| Error::invalid_input("Too many SPSs"))?;
-        (0b1110_0000 | sps_count).encode(&mut writer)?;
-        for sps in &self.sps_list {
-            let size = u16::try_from(sps.len())
-                .map_err(|e| Error::invalid_input(&format!("Too long SPS: {e}")))?;
-            size.encode(&mut writer)?;
-            writer.write_all(sps)?;
-        }
+    /// [`VpccBox::colour_primaries`] のデフォルト値（BT.709）
+    pub const DEFAULT_COLOUR_PRIMARIES: u8 = 1;
 
-        let pps_count =
-            u8::try_from(self.pps_list.len()).map_err(|_| Error::invalid_input("Too many PPSs"))?;
-        pps_count.encode(&mut writer)?;
-        for pps in &self.pps_list {
-            let size = u16::try_from(pps.len())
-                .map_err(|e| Error::invalid_input(&format!("Too long PPS: {e}")))?;
-            size.encode(&mut writer)?;
-            writer.write_all(pps)?;
-        }
+    /// [`VpccBox::transfer_characteristics`] のデフォルト値（BT.709）
+    pub const DEFAULT_TRANSFER_CHARACTERISTICS: u8 = 1;
 
-        if !matches!(self.avc_profile_indication, 66 | 77 | 88) {
-            let chroma_format = self.chroma_format.ok_or_else(|| {
-                Error::invalid_input("Missing 'chroma_format' field in 'avcC' boc")
-            })?;
-            let bit_depth_luma_minus8 = self.bit_depth_luma_minus8.ok_or_else(|| {
-                Error::invalid_input("Missing 'bit_depth_luma_minus8' field in 'avcC' boc")
-            })?;
-            let bit_depth_chroma_minus8 = self.bit_depth_chroma_minus8.ok_or_else(|| {
-                Error::invalid_input("Missing 'bit_depth_chroma_minus8' field in 'avcC' boc")
-            })?;
-            (0b1111_1100 | chroma_format.get()).encode(&mut writer)?;
-            (0b1111_1000 | bit_depth_luma_minus8.get()).encode(&mut writer)?;
-            (0b1111_1000 | bit_depth_chroma_minus8.get()).encode(&mut writer)?;
+    /// [`VpccBox::matrix_coefficients`] のデフォルト値（BT.709）
+    pub const DEFAULT_MATRIX_COEFFICIENTS: u8 = 1;
 
-            let sps_ext_count = u8::try_from(self.sps_ext_list.len())
-                .map_err(|_| Error::invalid_input("Too many SPS EXTs"))?;
-            sps_ext_count.encode(&mut writer)?;
-            for sps_ext in &self.sps_ext_list {
-                let size = u16::try_from(sps_ext.len())
-                    .map_err(|e| Error::invalid_input(&format!("Too long SPS EXT: {e}")))?;
-                size.encode(&mut writer)?;
-                writer.write_all(sps_ext)?;
-            }
-        }
+    /// [`VpccBox::video_full_range_flag`] のデフォルト値（限定レンジ）
+    pub const DEFAULT_VIDEO_FULL_RANGE_FLAG: Uint<u8, 1> = Uint::new(0);
 
-        Ok(())
+    /// [<https://www.webmproject.org/vp9/mp4/#codecs-parameter-string>] で定義されている形式の、
+    /// 全てのフィールドを含むコーデック文字列（`vp09.00.10.08.01.01.01.01.00` など）を返す
+    ///
+    /// `is_vp8` が `true` の場合には `vp08`、そうではない場合には `vp09` が先頭のサンプルエントリー種別として使われる
+    pub fn codec_string(&self, is_vp8: bool) -> String {
+        format!(
+            "{}.{:02}.{:02}.{:02}.{:02}.{:02}",
+            self.mandatory_codec_string_fields(is_vp8),
+            self.chroma_subsampling.get(),
+            self.colour_primaries,
+            self.transfer_characteristics,
+            self.matrix_coefficients,
+            self.video_full_range_flag.get()
+        )
     }
 
-    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let configuration_version = u8::decode(&mut reader)?;
-        if configuration_version != Self::CONFIGURATION_VERSION {
-            return Err(Error::invalid_data(&format!(
-                "Unsupported avcC configuration version: {configuration_version}"
-            )));
-        }
-
-        let avc_profile_indication = u8::decode(&mut reader)?;
-        let profile_compatibility = u8::decode(&mut reader)?;
-        let avc_level_indication = u8::decode(&mut reader)?;
-        let length_size_minus_one = Uint::from_bits(u8::decode(&mut reader)?);
-
-        let sps_count = Uint::<u8, 5>::from_bits(u8::decode(&mut reader)?).get() as usize;
-        let mut sps_list = Vec::with_capacity(sps_count);
-        for _ in 0..sps_count {
-            let size = u16::decode(&mut reader)? as usize;
-            let mut sps = vec![0; size];
-            reader.read_exact(&mut sps)?;
-            sps_list.push(sps);
+    /// [`VpccBox::codec_string()`] と同様だが、省略可能なフィールドが全てデフォルト値の場合にはそれらを省略した文字列
+    /// （`vp09.00.10.08` など）を返す
+    ///
+    /// 省略可能なフィールドは全て指定するか全て省略するかのどちらかである必要があるため、
+    /// 一つでもデフォルト値以外のフィールドがある場合には [`VpccBox::codec_string()`] と同じ結果になる
+    pub fn codec_string_short(&self, is_vp8: bool) -> String {
+        if self.chroma_subsampling == Self::DEFAULT_CHROMA_SUBSAMPLING
+            && self.colour_primaries == Self::DEFAULT_COLOUR_PRIMARIES
+            && self.transfer_characteristics == Self::DEFAULT_TRANSFER_CHARACTERISTICS
+            && self.matrix_coefficients == Self::DEFAULT_MATRIX_COEFFICIENTS
+            && self.video_full_range_flag == Self::DEFAULT_VIDEO_FULL_RANGE_FLAG
+        {
+            self.mandatory_codec_string_fields(is_vp8)
+        } else {
+            self.codec_string(is_vp8)
         }
+    }
 
-        let pps_count = u8::decode(&mut reader)? as usize;
-        let mut pps_list = Vec::with_capacity(pps_count);
-        for _ in 0..pps_count {
-            let size = u16::decode(&mut reader)? as usize;
-            let mut pps = vec![0; size];
-            reader.read_exact(&mut pps)?;
-            pps_list.push(pps);
-        }
+    fn mandatory_codec_string_fields(&self, is_vp8: bool) -> String {
+        format!(
+            "{}.{:02}.{:02}.{:02}",
+            if is_vp8 { "vp08" } else { "vp09" },
+            self.profile,
+            self.level,
+            self.bit_depth.get()
+        )
+    }
 
-        let mut chroma_format = None;
-        let mut bit_depth_luma_minus8 = None;
-        let mut bit_depth_chroma_minus8 = None;
-        let mut sps_ext_list = Vec::new();
-        if !matches!(avc_profile_indication, 66 | 77 | 88) {
-            chroma_format = Some(Uint::from_bits(u8::decode(&mut reader)?));
-            bit_depth_luma_minus8 = Some(Uint::from_bits(u8::decode(&mut reader)?));
-            bit_depth_chroma_minus8 = Some(Uint::from_bits(u8::decode(&mut reader)?));
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        self.profile.encode(&mut writer)?;
+        self.level.encode(&mut writer)?;
+        (self.bit_depth.to_bits()
+            | self.chroma_subsampling.to_bits()
+            | self.video_full_range_flag.to_bits())
+        .encode(&mut writer)?;
+        self.colour_primaries.encode(&mut writer)?;
+        self.transfer_characteristics.encode(&mut writer)?;
+        self.matrix_coefficients.encode(&mut writer)?;
+        (self.codec_initialization_data.len() as u16).encode(&mut writer)?;
+        writer.write_all(&self.codec_initialization_data)?;
+        Ok(())
+    }
 
-            let sps_ext_count = u8::decode(&mut reader)? as usize;
-            for _ in 0..sps_ext_count {
-                let size = u16::decode(&mut reader)? as usize;
-                let mut pps = vec![0; size];
-                reader.read_exact(&mut pps)?;
-                sps_ext_list.push(pps);
-            }
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let header = FullBoxHeader::decode(&mut reader)?;
+        if header.version != 1 {
+            return Err(Error::invalid_data(&format!(
+                "Unexpected full box header version: box=vpcC, version={}",
+                header.version
+            )));
         }
 
+        let profile = u8::decode(&mut reader)?;
+        let level = u8::decode(&mut reader)?;
+
+        let b = u8::decode(&mut reader)?;
+        let bit_depth = Uint::from_bits(b);
+        let chroma_subsampling = Uint::from_bits(b);
+        let video_full_range_flag = Uint::from_bits(b);
+        let colour_primaries = u8::decode(&mut reader)?;
+        let transfer_characteristics = u8::decode(&mut reader)?;
+        let matrix_coefficients = u8::decode(&mut reader)?;
+        let mut codec_initialization_data = vec![0; u16::decode(&mut reader)? as usize];
+        reader.read_exact(&mut codec_initialization_data)?;
+
         Ok(Self {
-            avc_profile_indication,
-            profile_compatibility,
-            avc_level_indication,
-            length_size_minus_one,
-            sps_list,
-            pps_list,
-            chroma_format,
-            bit_depth_luma_minus8,
-            bit_depth_chroma_minus8,
-            sps_ext_list,
+            profile,
+            level,
+            bit_depth,
+            chroma_subsampling,
+            video_full_range_flag,
+            colour_primaries,
+            transfer_characteristics,
+            matrix_coefficients,
+            codec_initialization_data,
         })
     }
 }
 
-impl Encode for AvccBox {
+impl Encode for VpccBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2568,7 +5940,7 @@ impl Encode for AvccBox {
     }
 }
 
-impl Decode for AvccBox {
+impl Decode for VpccBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2576,7 +5948,7 @@ impl Decode for AvccBox {
     }
 }
 
-impl BaseBox for AvccBox {
+impl BaseBox for VpccBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2590,22 +5962,41 @@ impl BaseBox for AvccBox {
     }
 }
 
-/// [ISO/IEC 14496-15] HEVCSampleEntry class (親: [`StsdBox`])
+impl FullBox for VpccBox {
+    fn full_box_version(&self) -> u8 {
+        1
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [<https://aomediacodec.github.io/av1-isobmff/>] AV1SampleEntry class (親: [`StsdBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Hev1Box {
+pub struct Av01Box {
     pub visual: VisualSampleEntryFields,
-    pub hvcc_box: HvccBox,
+    pub av1c_box: Av1cBox,
     pub unknown_boxes: Vec<UnknownBox>,
 }
 
-impl Hev1Box {
+impl Av01Box {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"hev1");
+    pub const TYPE: BoxType = BoxType::Normal(*b"av01");
+
+    /// `unknown_boxes` 内に `colr` ボックスが含まれている場合には、それをデコードした結果を返す
+    pub fn colr_box(&self) -> Option<ColrBox> {
+        let b = self
+            .unknown_boxes
+            .iter()
+            .find(|b| b.box_type == ColrBox::TYPE)?;
+        ColrBox::decode(&b.encode_to_vec().ok()?[..]).ok()
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         self.visual.encode(&mut writer)?;
-        self.hvcc_box.encode(&mut writer)?;
+        self.av1c_box.encode(&mut writer)?;
         for b in &self.unknown_boxes {
             b.encode(&mut writer)?;
         }
@@ -2614,29 +6005,30 @@ impl Hev1Box {
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let visual = VisualSampleEntryFields::decode(&mut reader)?;
-        let mut hvcc_box = None;
+        let mut av1c_box = None;
         let mut unknown_boxes = Vec::new();
         while reader.limit() > 0 {
             let (header, mut reader) = BoxHeader::peek(&mut reader)?;
             match header.box_type {
-                HvccBox::TYPE if hvcc_box.is_none() => {
-                    hvcc_box = Some(HvccBox::decode(&mut reader)?);
+                Av1cBox::TYPE if av1c_box.is_none() => {
+                    av1c_box = Some(Av1cBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(header.box_type, &[Av1cBox::TYPE]);
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
         }
-        let hvcc_box = hvcc_box.ok_or_else(|| Error::missing_box("hvcc", Self::TYPE))?;
+        let av1c_box = av1c_box.ok_or_else(|| Error::missing_box("av1c", Self::TYPE))?;
         Ok(Self {
             visual,
-            hvcc_box,
+            av1c_box,
             unknown_boxes,
         })
     }
 }
 
-impl Encode for Hev1Box {
+impl Encode for Av01Box {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2644,7 +6036,7 @@ impl Encode for Hev1Box {
     }
 }
 
-impl Decode for Hev1Box {
+impl Decode for Av01Box {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2652,7 +6044,7 @@ impl Decode for Hev1Box {
     }
 }
 
-impl BaseBox for Hev1Box {
+impl BaseBox for Av01Box {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2664,175 +6056,167 @@ impl BaseBox for Hev1Box {
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
         Box::new(
             std::iter::empty()
-                .chain(std::iter::once(&self.hvcc_box).map(as_box_object))
+                .chain(std::iter::once(&self.av1c_box).map(as_box_object))
                 .chain(self.unknown_boxes.iter().map(as_box_object)),
         )
     }
 }
 
-/// [`HvccBox`] 内の NAL ユニット配列を保持する構造体
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct HvccNalUintArray {
-    pub array_completeness: Uint<u8, 1, 7>,
-    pub nal_unit_type: Uint<u8, 6, 0>,
-    pub nalus: Vec<Vec<u8>>,
-}
-
-/// [ISO/IEC 14496-15] HVCConfigurationBox class (親: [`Hev1Box`])
+/// [<https://aomediacodec.github.io/av1-isobmff/>] AV1CodecConfigurationBox class (親: [`StsdBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct HvccBox {
-    pub general_profile_space: Uint<u8, 2, 6>,
-    pub general_tier_flag: Uint<u8, 1, 5>,
-    pub general_profile_idc: Uint<u8, 5, 0>,
-    pub general_profile_compatibility_flags: u32,
-    pub general_constraint_indicator_flags: Uint<u64, 48>,
-    pub general_level_idc: u8,
-    pub min_spatial_segmentation_idc: Uint<u16, 12>,
-    pub parallelism_type: Uint<u8, 2>,
-    pub chroma_format_idc: Uint<u8, 2>,
-    pub bit_depth_luma_minus8: Uint<u8, 3>,
-    pub bit_depth_chroma_minus8: Uint<u8, 3>,
-    pub avg_frame_rate: u16,
-    pub constant_frame_rate: Uint<u8, 2, 6>,
-    pub num_temporal_layers: Uint<u8, 3, 3>,
-    pub temporal_id_nested: Uint<u8, 1, 2>,
-    pub length_size_minus_one: Uint<u8, 2, 0>,
-    pub nalu_arrays: Vec<HvccNalUintArray>,
+pub struct Av1cBox {
+    pub seq_profile: Uint<u8, 3, 5>,
+    pub seq_level_idx_0: Uint<u8, 5, 0>,
+    pub seq_tier_0: Uint<u8, 1, 7>,
+    pub high_bitdepth: Uint<u8, 1, 6>,
+    pub twelve_bit: Uint<u8, 1, 5>,
+    pub monochrome: Uint<u8, 1, 4>,
+    pub chroma_subsampling_x: Uint<u8, 1, 3>,
+    pub chroma_subsampling_y: Uint<u8, 1, 2>,
+    pub chroma_sample_position: Uint<u8, 2, 0>,
+    pub initial_presentation_delay_minus_one: Option<Uint<u8, 4, 0>>,
+    pub config_obus: Vec<u8>,
 }
 
-impl HvccBox {
+impl Av1cBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"hvcC");
+    pub const TYPE: BoxType = BoxType::Normal(*b"av1C");
 
-    const CONFIGURATION_VERSION: u8 = 1;
+    const MARKER: Uint<u8, 1, 7> = Uint::new(1);
+    const VERSION: Uint<u8, 7, 0> = Uint::new(1);
+
+    /// [<https://aomediacodec.github.io/av1-isobmff/#codecsparam>] で定義されている形式のコーデック文字列
+    /// （`av01.0.04M.08` など）を返す
+    ///
+    /// 色情報は `color_info` が `nclx` 形式の [`ColrBox`] の場合にはその値が、
+    /// それ以外の場合にはデフォルト値（BT.709 / 限定レンジ）が使われる。
+    ///
+    /// 省略可能なフィールドが全てデフォルト値の場合には、それらを省略した短い形式の文字列が返される
+    pub fn codec_string(&self, color_info: Option<&ColrBox>) -> String {
+        let bit_depth = match (self.high_bitdepth.get(), self.twelve_bit.get()) {
+            (0, _) => 8,
+            (_, 0) => 10,
+            _ => 12,
+        };
+        let tier = if self.seq_tier_0.get() == 0 { 'M' } else { 'H' };
+        let mut s = format!(
+            "av01.{}.{:02}{tier}.{bit_depth:02}",
+            self.seq_profile.get(),
+            self.seq_level_idx_0.get()
+        );
+
+        let (colour_primaries, transfer_characteristics, matrix_coefficients, full_range_flag) =
+            match color_info {
+                Some(ColrBox::Nclx {
+                    colour_primaries,
+                    transfer_characteristics,
+                    matrix_coefficients,
+                    full_range_flag,
+                }) => (
+                    *colour_primaries,
+                    *transfer_characteristics,
+                    *matrix_coefficients,
+                    *full_range_flag,
+                ),
+                _ => (1, 1, 1, false),
+            };
+        let chroma_sample_position =
+            if self.chroma_subsampling_x.get() == 1 && self.chroma_subsampling_y.get() == 1 {
+                self.chroma_sample_position.get()
+            } else {
+                0
+            };
+        let optional_fields = format!(
+            ".{}.{}{}{}.{colour_primaries:02}.{transfer_characteristics:02}.{matrix_coefficients:02}.{}",
+            self.monochrome.get(),
+            self.chroma_subsampling_x.get(),
+            self.chroma_subsampling_y.get(),
+            chroma_sample_position,
+            u8::from(full_range_flag)
+        );
+
+        // 省略可能なフィールドは全て指定するか全て省略するかのどちらかである必要がある
+        if optional_fields != ".0.110.01.01.01.0" {
+            s.push_str(&optional_fields);
+        }
+        s
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        Self::CONFIGURATION_VERSION.encode(&mut writer)?;
-        (self.general_profile_space.to_bits()
-            | self.general_tier_flag.to_bits()
-            | self.general_profile_idc.to_bits())
-        .encode(&mut writer)?;
-        self.general_profile_compatibility_flags
-            .encode(&mut writer)?;
-        writer.write_all(&self.general_constraint_indicator_flags.get().to_be_bytes()[2..])?;
-        self.general_level_idc.encode(&mut writer)?;
-        (0b1111_0000_0000_0000 | self.min_spatial_segmentation_idc.to_bits())
-            .encode(&mut writer)?;
-        (0b1111_1100 | self.parallelism_type.to_bits()).encode(&mut writer)?;
-        (0b1111_1100 | self.chroma_format_idc.to_bits()).encode(&mut writer)?;
-        (0b1111_1000 | self.bit_depth_luma_minus8.to_bits()).encode(&mut writer)?;
-        (0b1111_1000 | self.bit_depth_chroma_minus8.to_bits()).encode(&mut writer)?;
-        self.avg_frame_rate.encode(&mut writer)?;
-        (self.constant_frame_rate.to_bits()
-            | self.num_temporal_layers.to_bits()
-            | self.temporal_id_nested.to_bits()
-            | self.length_size_minus_one.to_bits())
+        (Self::MARKER.to_bits() | Self::VERSION.to_bits()).encode(&mut writer)?;
+        (self.seq_profile.to_bits() | self.seq_level_idx_0.to_bits()).encode(&mut writer)?;
+        (self.seq_tier_0.to_bits()
+            | self.high_bitdepth.to_bits()
+            | self.twelve_bit.to_bits()
+            | self.monochrome.to_bits()
+            | self.chroma_subsampling_x.to_bits()
+            | self.chroma_subsampling_y.to_bits()
+            | self.chroma_sample_position.to_bits())
         .encode(&mut writer)?;
-        u8::try_from(self.nalu_arrays.len())
-            .map_err(|_| {
-                Error::invalid_input(&format!("Too many NALU arrays: {}", self.nalu_arrays.len()))
-            })?
-            .encode(&mut writer)?;
-        for nalu_array in &self.nalu_arrays {
-            (nalu_array.array_completeness.to_bits() | nalu_array.nal_unit_type.to_bits())
-                .encode(&mut writer)?;
-            u16::try_from(nalu_array.nalus.len())
-                .map_err(|_| {
-                    Error::invalid_input(&format!("Too many NALUs: {}", self.nalu_arrays.len()))
-                })?
-                .encode(&mut writer)?;
-            for nalu in &nalu_array.nalus {
-                u16::try_from(nalu.len())
-                    .map_err(|_| {
-                        Error::invalid_input(&format!("Too large NALU: {}", self.nalu_arrays.len()))
-                    })?
-                    .encode(&mut writer)?;
-                writer.write_all(nalu)?;
-            }
+        if let Some(v) = self.initial_presentation_delay_minus_one {
+            (0b1_0000 | v.to_bits()).encode(&mut writer)?;
+        } else {
+            0u8.encode(&mut writer)?;
         }
+        writer.write_all(&self.config_obus)?;
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let configuration_version = u8::decode(&mut reader)?;
-        if configuration_version != Self::CONFIGURATION_VERSION {
-            return Err(Error::invalid_data(&format!(
-                "Unsupported avcC version: {configuration_version}"
-            )));
-        }
-
-        let b = u8::decode(&mut reader)?;
-        let general_profile_space = Uint::from_bits(b);
-        let general_tier_flag = Uint::from_bits(b);
-        let general_profile_idc = Uint::from_bits(b);
-
-        let general_profile_compatibility_flags = u32::decode(&mut reader)?;
-
-        let mut buf = [0; 8];
-        reader.read_exact(&mut buf[2..])?;
-        let general_constraint_indicator_flags = Uint::from_bits(u64::from_be_bytes(buf));
-
-        let general_level_idc = u8::decode(&mut reader)?;
-        let min_spatial_segmentation_idc = Uint::from_bits(u16::decode(&mut reader)?);
-        let parallelism_type = Uint::from_bits(u8::decode(&mut reader)?);
-        let chroma_format_idc = Uint::from_bits(u8::decode(&mut reader)?);
-        let bit_depth_luma_minus8 = Uint::from_bits(u8::decode(&mut reader)?);
-        let bit_depth_chroma_minus8 = Uint::from_bits(u8::decode(&mut reader)?);
-        let avg_frame_rate = u16::decode(&mut reader)?;
-
         let b = u8::decode(&mut reader)?;
-        let constant_frame_rate = Uint::from_bits(b);
-        let num_temporal_layers = Uint::from_bits(b);
-        let temporal_id_nested = Uint::from_bits(b);
-        let length_size_minus_one = Uint::from_bits(b);
-
-        let num_of_arrays = u8::decode(&mut reader)?;
-        let mut nalu_arrays = Vec::new();
-        for _ in 0..num_of_arrays {
-            let b = u8::decode(&mut reader)?;
-            let array_completeness = Uint::from_bits(b);
-            let nal_unit_type = Uint::from_bits(b);
-
-            let num_nalus = u16::decode(&mut reader)?;
-            let mut nalus = Vec::new();
-            for _ in 0..num_nalus {
-                let nal_unit_length = u16::decode(&mut reader)? as usize;
-                let mut nal_unit = vec![0; nal_unit_length];
-                reader.read_exact(&mut nal_unit)?;
-                nalus.push(nal_unit);
-            }
-            nalu_arrays.push(HvccNalUintArray {
-                array_completeness,
-                nal_unit_type,
-                nalus,
-            });
+        let marker = Uint::from_bits(b);
+        let version = Uint::from_bits(b);
+        if marker != Self::MARKER {
+            return Err(Error::invalid_data("Unexpected av1C marker"));
+        }
+        if version != Self::VERSION {
+            return Err(Error::invalid_data(&format!(
+                "Unsupported av1C version: {}",
+                version.get()
+            )));
         }
 
+        let b = u8::decode(&mut reader)?;
+        let seq_profile = Uint::from_bits(b);
+        let seq_level_idx_0 = Uint::from_bits(b);
+
+        let b = u8::decode(&mut reader)?;
+        let seq_tier_0 = Uint::from_bits(b);
+        let high_bitdepth = Uint::from_bits(b);
+        let twelve_bit = Uint::from_bits(b);
+        let monochrome = Uint::from_bits(b);
+        let chroma_subsampling_x = Uint::from_bits(b);
+        let chroma_subsampling_y = Uint::from_bits(b);
+        let chroma_sample_position = Uint::from_bits(b);
+
+        let b = u8::decode(&mut reader)?;
+        let initial_presentation_delay_minus_one = if Uint::<u8, 1, 4>::from_bits(b).get() == 1 {
+            Some(Uint::from_bits(b))
+        } else {
+            None
+        };
+
+        let mut config_obus = Vec::new();
+        reader.read_to_end(&mut config_obus)?;
+
         Ok(Self {
-            general_profile_space,
-            general_tier_flag,
-            general_profile_idc,
-            general_profile_compatibility_flags,
-            general_constraint_indicator_flags,
-            general_level_idc,
-            min_spatial_segmentation_idc,
-            parallelism_type,
-            chroma_format_idc,
-            bit_depth_luma_minus8,
-            bit_depth_chroma_minus8,
-            avg_frame_rate,
-            constant_frame_rate,
-            num_temporal_layers,
-            temporal_id_nested,
-            length_size_minus_one,
-            nalu_arrays,
+            seq_profile,
+            seq_level_idx_0,
+            seq_tier_0,
+            high_bitdepth,
+            twelve_bit,
+            monochrome,
+            chroma_subsampling_x,
+            chroma_subsampling_y,
+            chroma_sample_position,
+            initial_presentation_delay_minus_one,
+            config_obus,
         })
     }
 }
 
-impl Encode for HvccBox {
+impl Encode for Av1cBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2840,7 +6224,7 @@ impl Encode for HvccBox {
     }
 }
 
-impl Decode for HvccBox {
+impl Decode for Av1cBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2848,7 +6232,7 @@ impl Decode for HvccBox {
     }
 }
 
-impl BaseBox for HvccBox {
+impl BaseBox for Av1cBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2862,53 +6246,80 @@ impl BaseBox for HvccBox {
     }
 }
 
-/// [<https://www.webmproject.org/vp9/mp4/>] VP8SampleEntry class (親: [`StsdBox`])
+/// [ISO/IEC 14496-12] ColourInformationBox class (親: 映像用のサンプルエントリー)
+///
+/// 現時点では、サンプルエントリーの構造体はこのボックスを専用のフィールドとしては保持せずに、
+/// `unknown_boxes` の要素として扱っている
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Vp08Box {
-    pub visual: VisualSampleEntryFields,
-    pub vpcc_box: VpccBox,
-    pub unknown_boxes: Vec<UnknownBox>,
+pub enum ColrBox {
+    /// `nclx` 形式の色情報
+    Nclx {
+        colour_primaries: u16,
+        transfer_characteristics: u16,
+        matrix_coefficients: u16,
+        full_range_flag: bool,
+    },
+
+    /// `nclx` 以外の形式（ICC プロファイルなど）の色情報
+    Other {
+        colour_type: [u8; 4],
+        payload: Vec<u8>,
+    },
 }
 
-impl Vp08Box {
+impl ColrBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"vp08");
+    pub const TYPE: BoxType = BoxType::Normal(*b"colr");
+
+    const COLOUR_TYPE_NCLX: [u8; 4] = *b"nclx";
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.visual.encode(&mut writer)?;
-        self.vpcc_box.encode(&mut writer)?;
-        for b in &self.unknown_boxes {
-            b.encode(&mut writer)?;
+        match self {
+            Self::Nclx {
+                colour_primaries,
+                transfer_characteristics,
+                matrix_coefficients,
+                full_range_flag,
+            } => {
+                Self::COLOUR_TYPE_NCLX.encode(&mut writer)?;
+                colour_primaries.encode(&mut writer)?;
+                transfer_characteristics.encode(&mut writer)?;
+                matrix_coefficients.encode(&mut writer)?;
+                (u8::from(*full_range_flag) << 7).encode(writer)?;
+            }
+            Self::Other {
+                colour_type,
+                payload,
+            } => {
+                colour_type.encode(&mut writer)?;
+                writer.write_all(payload)?;
+            }
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let visual = VisualSampleEntryFields::decode(&mut reader)?;
-        let mut vpcc_box = None;
-        let mut unknown_boxes = Vec::new();
-        while reader.limit() > 0 {
-            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
-            match header.box_type {
-                VpccBox::TYPE if vpcc_box.is_none() => {
-                    vpcc_box = Some(VpccBox::decode(&mut reader)?);
-                }
-                _ => {
-                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
-                }
-            }
+        let colour_type = <[u8; 4]>::decode(&mut reader)?;
+        if colour_type == Self::COLOUR_TYPE_NCLX {
+            Ok(Self::Nclx {
+                colour_primaries: u16::decode(&mut reader)?,
+                transfer_characteristics: u16::decode(&mut reader)?,
+                matrix_coefficients: u16::decode(&mut reader)?,
+                full_range_flag: Uint::<u8, 1, 7>::from_bits(u8::decode(reader)?).get() == 1,
+            })
+        } else {
+            let mut payload = Vec::new();
+            reader.read_to_end(&mut payload)?;
+            Ok(Self::Other {
+                colour_type,
+                payload,
+            })
         }
-        let vpcc_box = vpcc_box.ok_or_else(|| Error::missing_box("vpcC", Self::TYPE))?;
-        Ok(Self {
-            visual,
-            vpcc_box,
-            unknown_boxes,
-        })
     }
 }
 
-impl Encode for Vp08Box {
+impl Encode for ColrBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -2916,7 +6327,7 @@ impl Encode for Vp08Box {
     }
 }
 
-impl Decode for Vp08Box {
+impl Decode for ColrBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -2924,7 +6335,7 @@ impl Decode for Vp08Box {
     }
 }
 
-impl BaseBox for Vp08Box {
+impl BaseBox for ColrBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -2934,69 +6345,114 @@ impl BaseBox for Vp08Box {
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(
-            std::iter::empty()
-                .chain(std::iter::once(&self.vpcc_box).map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
-        )
+        Box::new(std::iter::empty())
     }
 }
 
-/// [<https://www.webmproject.org/vp9/mp4/>] VP9SampleEntry class (親: [`StsdBox`])
+/// [`SttsBox`] が保持するエントリー
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Vp09Box {
-    pub visual: VisualSampleEntryFields,
-    pub vpcc_box: VpccBox,
-    pub unknown_boxes: Vec<UnknownBox>,
+pub struct SttsEntry {
+    pub sample_count: u32,
+    pub sample_delta: u32,
 }
 
-impl Vp09Box {
+/// [ISO/IEC 14496-12] TimeToSampleBox class (親: [`StblBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct SttsBox {
+    pub entries: Vec<SttsEntry>,
+}
+
+impl SttsBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"vp09");
+    pub const TYPE: BoxType = BoxType::Normal(*b"stts");
+
+    /// サンプル群の尺を走査するイテレーターを受け取って、対応する [`SttsBox`] インスタンスを作成する
+    pub fn from_sample_deltas<I>(sample_deltas: I) -> Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        let mut entries = Vec::<SttsEntry>::new();
+        for sample_delta in sample_deltas {
+            if let Some(last) = entries.last_mut() {
+                if last.sample_delta == sample_delta {
+                    last.sample_count += 1;
+                    continue;
+                }
+            }
+            entries.push(SttsEntry {
+                sample_count: 1,
+                sample_delta,
+            });
+        }
+        Self { entries }
+    }
+
+    /// エントリー群を展開して、サンプル群の尺を先頭から順に走査するイテレーターを返す
+    ///
+    /// [`SttsBox::from_sample_deltas()`] の逆変換に相当する
+    pub fn sample_deltas(&self) -> impl '_ + Iterator<Item = u32> {
+        self.entries
+            .iter()
+            .flat_map(|x| std::iter::repeat_n(x.sample_delta, x.sample_count as usize))
+    }
+
+    /// 指定されたサンプルの尺を返す
+    ///
+    /// `sample_index` は 0 始まりのインデックスで、範囲外の場合には [`None`] が返される。
+    ///
+    /// サンプル単位ではなくエントリー単位で走査を行うので、計算量はエントリー数に比例する。
+    /// 多数のサンプルに対して繰り返し呼び出す場合には、累計サンプル数を事前に計算して
+    /// 二分探索を行う [`SampleTableAccessor`](crate::aux::SampleTableAccessor) を使うこと。
+    pub fn sample_delta_at(&self, sample_index: u32) -> Option<u32> {
+        let mut remaining = sample_index;
+        for entry in &self.entries {
+            if remaining < entry.sample_count {
+                return Some(entry.sample_delta);
+            }
+            remaining -= entry.sample_count;
+        }
+        None
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.visual.encode(&mut writer)?;
-        self.vpcc_box.encode(&mut writer)?;
-        for b in &self.unknown_boxes {
-            b.encode(&mut writer)?;
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            entry.sample_count.encode(&mut writer)?;
+            entry.sample_delta.encode(&mut writer)?;
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let visual = VisualSampleEntryFields::decode(&mut reader)?;
-        let mut vpcc_box = None;
-        let mut unknown_boxes = Vec::new();
-        while reader.limit() > 0 {
-            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
-            match header.box_type {
-                VpccBox::TYPE if vpcc_box.is_none() => {
-                    vpcc_box = Some(VpccBox::decode(&mut reader)?);
-                }
-                _ => {
-                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
-                }
-            }
+        let _ = FullBoxHeader::decode(&mut reader)?;
+        let count = u32::decode(&mut reader)? as usize;
+        let mut entries = Vec::with_capacity(entries_capacity(count, reader.limit(), 8));
+        for _ in 0..count {
+            entries.push(SttsEntry {
+                sample_count: u32::decode(&mut reader)?,
+                sample_delta: u32::decode(&mut reader)?,
+            });
         }
-        let vpcc_box = vpcc_box.ok_or_else(|| Error::missing_box("vpcC", Self::TYPE))?;
-        Ok(Self {
-            visual,
-            vpcc_box,
-            unknown_boxes,
-        })
+        Ok(Self { entries })
     }
 }
 
-impl Encode for Vp09Box {
+impl Encode for SttsBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
         Ok(())
     }
+
+    fn encoded_size(&self) -> u64 {
+        self.box_size().get()
+    }
 }
 
-impl Decode for Vp09Box {
+impl Decode for SttsBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3004,96 +6460,130 @@ impl Decode for Vp09Box {
     }
 }
 
-impl BaseBox for Vp09Box {
+impl BaseBox for SttsBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
 
     fn box_payload_size(&self) -> u64 {
-        ExternalBytes::calc(|writer| self.encode_payload(writer))
+        // エントリ数が多くなりがちなボックスなので、エンコードせずに計算でサイズを求める
+        // (フルボックスヘッダー + エントリ数 + エントリ群)
+        4 + 4 + self.entries.len() as u64 * 8
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(
-            std::iter::empty()
-                .chain(std::iter::once(&self.vpcc_box).map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
-        )
+        Box::new(std::iter::empty())
     }
 }
 
-/// [<https://www.webmproject.org/vp9/mp4/>] VPCodecConfigurationBox class (親: [`Vp08Box`], [`Vp09Box`])
+impl FullBox for SttsBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [`StscBox`] が保持するエントリー
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct VpccBox {
-    pub profile: u8,
-    pub level: u8,
-    pub bit_depth: Uint<u8, 4, 4>,
-    pub chroma_subsampling: Uint<u8, 3, 1>,
-    pub video_full_range_flag: Uint<u8, 1>,
-    pub colour_primaries: u8,
-    pub transfer_characteristics: u8,
-    pub matrix_coefficients: u8,
-    pub codec_initialization_data: Vec<u8>,
+pub struct StscEntry {
+    pub first_chunk: NonZeroU32,
+    pub sample_per_chunk: u32,
+    pub sample_description_index: NonZeroU32,
 }
 
-impl VpccBox {
+/// [ISO/IEC 14496-12] SampleToChunkBox class (親: [`StblBox`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct StscBox {
+    pub entries: Vec<StscEntry>,
+}
+
+impl StscBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"vpcC");
+    pub const TYPE: BoxType = BoxType::Normal(*b"stsc");
+
+    /// 各チャンクに含まれるサンプルの範囲を走査するイテレーターを返す
+    ///
+    /// イテレーターの要素は `(チャンクインデックス, サンプルインデックスの範囲)` となる。
+    /// チャンクインデックスは 1 始まりで、サンプルインデックスは 0 始まり。
+    ///
+    /// 最後のエントリーは、それ以降の全てのチャンク（`chunk_count` 番目まで）に適用される。
+    /// 最初のエントリーよりも前に位置するチャンクは、サンプルを含まないものとして扱われる。
+    pub fn chunk_sample_ranges(
+        &self,
+        chunk_count: u32,
+    ) -> impl '_ + Iterator<Item = (u32, std::ops::Range<u32>)> {
+        let mut entry_index = 0;
+        let mut sample_index = 0u32;
+        (1..=chunk_count).map(move |chunk_index| {
+            while self
+                .entries
+                .get(entry_index + 1)
+                .is_some_and(|x| x.first_chunk.get() <= chunk_index)
+            {
+                entry_index += 1;
+            }
+            let sample_count = self
+                .entries
+                .get(entry_index)
+                .filter(|x| x.first_chunk.get() <= chunk_index)
+                .map_or(0, |x| x.sample_per_chunk);
+
+            let start = sample_index;
+            sample_index = sample_index.saturating_add(sample_count);
+            (chunk_index, start..sample_index)
+        })
+    }
+
+    /// 指定されたチャンクに含まれるサンプルの数を返す
+    ///
+    /// `chunk_index` は 1 始まりのインデックスで、最初のエントリーよりも前のチャンクが指定された場合には [`None`] が返される。
+    /// なお、このボックスはチャンクの総数を保持していないので、範囲の上限はチェックされない。
+    ///
+    /// エントリー群を二分探索するので、計算量はエントリー数の対数となる。
+    pub fn samples_per_chunk_at(&self, chunk_index: u32) -> Option<u32> {
+        let i = match self
+            .entries
+            .binary_search_by_key(&chunk_index, |x| x.first_chunk.get())
+        {
+            Ok(i) => i,
+            Err(0) => return None,
+            Err(i) => i - 1,
+        };
+        Some(self.entries[i].sample_per_chunk)
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        self.profile.encode(&mut writer)?;
-        self.level.encode(&mut writer)?;
-        (self.bit_depth.to_bits()
-            | self.chroma_subsampling.to_bits()
-            | self.video_full_range_flag.to_bits())
-        .encode(&mut writer)?;
-        self.colour_primaries.encode(&mut writer)?;
-        self.transfer_characteristics.encode(&mut writer)?;
-        self.matrix_coefficients.encode(&mut writer)?;
-        (self.codec_initialization_data.len() as u16).encode(&mut writer)?;
-        writer.write_all(&self.codec_initialization_data)?;
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            entry.first_chunk.encode(&mut writer)?;
+            entry.sample_per_chunk.encode(&mut writer)?;
+            entry.sample_description_index.encode(&mut writer)?;
+        }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let header = FullBoxHeader::decode(&mut reader)?;
-        if header.version != 1 {
-            return Err(Error::invalid_data(&format!(
-                "Unexpected full box header version: box=vpcC, version={}",
-                header.version
-            )));
+        let _ = FullBoxHeader::decode(&mut reader)?;
+        let count = u32::decode(&mut reader)? as usize;
+        let mut entries = Vec::with_capacity(entries_capacity(count, reader.limit(), 12));
+        for _ in 0..count {
+            entries.push(StscEntry {
+                first_chunk: NonZeroU32::decode(&mut reader)?,
+                sample_per_chunk: u32::decode(&mut reader)?,
+                sample_description_index: NonZeroU32::decode(&mut reader)?,
+            });
         }
-
-        let profile = u8::decode(&mut reader)?;
-        let level = u8::decode(&mut reader)?;
-
-        let b = u8::decode(&mut reader)?;
-        let bit_depth = Uint::from_bits(b);
-        let chroma_subsampling = Uint::from_bits(b);
-        let video_full_range_flag = Uint::from_bits(b);
-        let colour_primaries = u8::decode(&mut reader)?;
-        let transfer_characteristics = u8::decode(&mut reader)?;
-        let matrix_coefficients = u8::decode(&mut reader)?;
-        let mut codec_initialization_data = vec![0; u16::decode(&mut reader)? as usize];
-        reader.read_exact(&mut codec_initialization_data)?;
-
-        Ok(Self {
-            profile,
-            level,
-            bit_depth,
-            chroma_subsampling,
-            video_full_range_flag,
-            colour_primaries,
-            transfer_characteristics,
-            matrix_coefficients,
-            codec_initialization_data,
-        })
+        Ok(Self { entries })
     }
 }
 
-impl Encode for VpccBox {
+impl Encode for StscBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3101,7 +6591,7 @@ impl Encode for VpccBox {
     }
 }
 
-impl Decode for VpccBox {
+impl Decode for StscBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3109,7 +6599,7 @@ impl Decode for VpccBox {
     }
 }
 
-impl BaseBox for VpccBox {
+impl BaseBox for StscBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3123,9 +6613,9 @@ impl BaseBox for VpccBox {
     }
 }
 
-impl FullBox for VpccBox {
+impl FullBox for StscBox {
     fn full_box_version(&self) -> u8 {
-        1
+        0
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
@@ -3133,53 +6623,97 @@ impl FullBox for VpccBox {
     }
 }
 
-/// [<https://aomediacodec.github.io/av1-isobmff/>] AV1SampleEntry class (親: [`StsdBox`])
+/// [ISO/IEC 14496-12] SampleSizeBox class (親: [`StblBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Av01Box {
-    pub visual: VisualSampleEntryFields,
-    pub av1c_box: Av1cBox,
-    pub unknown_boxes: Vec<UnknownBox>,
+pub enum StszBox {
+    Fixed {
+        sample_size: NonZeroU32,
+        sample_count: u32,
+    },
+    Variable {
+        entry_sizes: Vec<u32>,
+    },
 }
 
-impl Av01Box {
+impl StszBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"av01");
+    pub const TYPE: BoxType = BoxType::Normal(*b"stsz");
+
+    /// サンプルの数を返す
+    pub fn sample_count(&self) -> u32 {
+        match self {
+            Self::Fixed { sample_count, .. } => *sample_count,
+            Self::Variable { entry_sizes } => entry_sizes.len() as u32,
+        }
+    }
+
+    /// 指定されたサンプルのデータサイズ（バイト数）を返す
+    ///
+    /// `index` は 0 始まりのインデックスで、範囲外の場合には [`None`] が返される
+    pub fn sample_size_at(&self, index: u32) -> Option<u32> {
+        match self {
+            Self::Fixed {
+                sample_size,
+                sample_count,
+            } => (index < *sample_count).then_some(sample_size.get()),
+            Self::Variable { entry_sizes } => entry_sizes.get(index as usize).copied(),
+        }
+    }
+
+    /// 全サンプルのデータサイズの合計を返す
+    pub fn total_data_size(&self) -> u64 {
+        match self {
+            Self::Fixed {
+                sample_size,
+                sample_count,
+            } => sample_size.get() as u64 * *sample_count as u64,
+            Self::Variable { entry_sizes } => entry_sizes.iter().map(|&x| x as u64).sum(),
+        }
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        self.visual.encode(&mut writer)?;
-        self.av1c_box.encode(&mut writer)?;
-        for b in &self.unknown_boxes {
-            b.encode(&mut writer)?;
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        match self {
+            StszBox::Fixed {
+                sample_size,
+                sample_count,
+            } => {
+                sample_size.get().encode(&mut writer)?;
+                sample_count.encode(writer)?;
+            }
+            StszBox::Variable { entry_sizes } => {
+                0u32.encode(&mut writer)?;
+                (entry_sizes.len() as u32).encode(&mut writer)?;
+                for size in entry_sizes {
+                    size.encode(&mut writer)?;
+                }
+            }
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let visual = VisualSampleEntryFields::decode(&mut reader)?;
-        let mut av1c_box = None;
-        let mut unknown_boxes = Vec::new();
-        while reader.limit() > 0 {
-            let (header, mut reader) = BoxHeader::peek(&mut reader)?;
-            match header.box_type {
-                Av1cBox::TYPE if av1c_box.is_none() => {
-                    av1c_box = Some(Av1cBox::decode(&mut reader)?);
-                }
-                _ => {
-                    unknown_boxes.push(UnknownBox::decode(&mut reader)?);
-                }
+        let _ = FullBoxHeader::decode(&mut reader)?;
+        let sample_size = u32::decode(&mut reader)?;
+        let sample_count = u32::decode(&mut reader)?;
+        if let Some(sample_size) = NonZeroU32::new(sample_size) {
+            Ok(Self::Fixed {
+                sample_size,
+                sample_count,
+            })
+        } else {
+            let mut entry_sizes =
+                Vec::with_capacity(entries_capacity(sample_count as usize, reader.limit(), 4));
+            for _ in 0..sample_count {
+                entry_sizes.push(u32::decode(&mut reader)?);
             }
+            Ok(Self::Variable { entry_sizes })
         }
-        let av1c_box = av1c_box.ok_or_else(|| Error::missing_box("av1c", Self::TYPE))?;
-        Ok(Self {
-            visual,
-            av1c_box,
-            unknown_boxes,
-        })
     }
 }
 
-impl Encode for Av01Box {
+impl Encode for StszBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3187,7 +6721,7 @@ impl Encode for Av01Box {
     }
 }
 
-impl Decode for Av01Box {
+impl Decode for StszBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3195,7 +6729,7 @@ impl Decode for Av01Box {
     }
 }
 
-impl BaseBox for Av01Box {
+impl BaseBox for StszBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3205,120 +6739,108 @@ impl BaseBox for Av01Box {
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
-        Box::new(
-            std::iter::empty()
-                .chain(std::iter::once(&self.av1c_box).map(as_box_object))
-                .chain(self.unknown_boxes.iter().map(as_box_object)),
-        )
+        Box::new(std::iter::empty())
     }
 }
 
-/// [<https://aomediacodec.github.io/av1-isobmff/>] AV1CodecConfigurationBox class (親: [`StsdBox`])
+impl FullBox for StszBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
+}
+
+/// [ISO/IEC 14496-12] ChunkOffsetBox class (親: [`StblBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Av1cBox {
-    pub seq_profile: Uint<u8, 3, 5>,
-    pub seq_level_idx_0: Uint<u8, 5, 0>,
-    pub seq_tier_0: Uint<u8, 1, 7>,
-    pub high_bitdepth: Uint<u8, 1, 6>,
-    pub twelve_bit: Uint<u8, 1, 5>,
-    pub monochrome: Uint<u8, 1, 4>,
-    pub chroma_subsampling_x: Uint<u8, 1, 3>,
-    pub chroma_subsampling_y: Uint<u8, 1, 2>,
-    pub chroma_sample_position: Uint<u8, 2, 0>,
-    pub initial_presentation_delay_minus_one: Option<Uint<u8, 4, 0>>,
-    pub config_obus: Vec<u8>,
+pub struct StcoBox {
+    pub chunk_offsets: Vec<u32>,
 }
 
-impl Av1cBox {
+impl StcoBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"av1C");
-
-    const MARKER: Uint<u8, 1, 7> = Uint::new(1);
-    const VERSION: Uint<u8, 7, 0> = Uint::new(1);
+    pub const TYPE: BoxType = BoxType::Normal(*b"stco");
 
-    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        (Self::MARKER.to_bits() | Self::VERSION.to_bits()).encode(&mut writer)?;
-        (self.seq_profile.to_bits() | self.seq_level_idx_0.to_bits()).encode(&mut writer)?;
-        (self.seq_tier_0.to_bits()
-            | self.high_bitdepth.to_bits()
-            | self.twelve_bit.to_bits()
-            | self.monochrome.to_bits()
-            | self.chroma_subsampling_x.to_bits()
-            | self.chroma_subsampling_y.to_bits()
-            | self.chroma_sample_position.to_bits())
-        .encode(&mut writer)?;
-        if let Some(v) = self.initial_presentation_delay_minus_one {
-            (0b1_0000 | v.to_bits()).encode(&mut writer)?;
-        } else {
-            0u8.encode(&mut writer)?;
-        }
-        writer.write_all(&self.config_obus)?;
+    /// 全てのチャンクオフセットに `delta` を加算する
+    ///
+    /// `mdat` ボックスの位置が変わった場合（`moov` ボックスを `mdat` ボックスの前に移動した場合など）に、
+    /// オフセットを補正するために使える。
+    ///
+    /// 加算後のオフセットが [`u32`] の範囲に収まらない場合にはエラーが返され、`self` は変更されない
+    /// （その場合には [`Co64Box`] への変換が必要となる）
+    pub fn patch_offsets(&mut self, delta: i64) -> Result<()> {
+        let chunk_offsets = self
+            .chunk_offsets
+            .iter()
+            .map(|&offset| {
+                i64::from(offset)
+                    .checked_add(delta)
+                    .and_then(|x| u32::try_from(x).ok())
+                    .ok_or_else(|| {
+                        Error::invalid_input(&format!(
+                            "Chunk offset out of range after patching: offset={offset}, delta={delta}"
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.chunk_offsets = chunk_offsets;
         Ok(())
     }
 
-    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let b = u8::decode(&mut reader)?;
-        let marker = Uint::from_bits(b);
-        let version = Uint::from_bits(b);
-        if marker != Self::MARKER {
-            return Err(Error::invalid_data("Unexpected av1C marker"));
-        }
-        if version != Self::VERSION {
-            return Err(Error::invalid_data(&format!(
-                "Unsupported av1C version: {}",
-                version.get()
-            )));
-        }
-
-        let b = u8::decode(&mut reader)?;
-        let seq_profile = Uint::from_bits(b);
-        let seq_level_idx_0 = Uint::from_bits(b);
-
-        let b = u8::decode(&mut reader)?;
-        let seq_tier_0 = Uint::from_bits(b);
-        let high_bitdepth = Uint::from_bits(b);
-        let twelve_bit = Uint::from_bits(b);
-        let monochrome = Uint::from_bits(b);
-        let chroma_subsampling_x = Uint::from_bits(b);
-        let chroma_subsampling_y = Uint::from_bits(b);
-        let chroma_sample_position = Uint::from_bits(b);
-
-        let b = u8::decode(&mut reader)?;
-        let initial_presentation_delay_minus_one = if Uint::<u8, 1, 4>::from_bits(b).get() == 1 {
-            Some(Uint::from_bits(b))
-        } else {
-            None
-        };
-
-        let mut config_obus = Vec::new();
-        reader.read_to_end(&mut config_obus)?;
+    /// オフセットが `file_size` 以上になっているチャンクの（`chunk_offsets` 内での 0 始まりの）インデックス一覧を返す
+    ///
+    /// ファイルが途中で切り詰められた場合や、前方にバイト列が挿入・削除された場合などに、
+    /// チャンクオフセットがファイルの範囲外を指していないかを確認するために使える
+    pub fn out_of_range_chunks(&self, file_size: u64) -> Vec<usize> {
+        self.chunk_offsets
+            .iter()
+            .enumerate()
+            .filter(|(_, &offset)| offset as u64 >= file_size)
+            .map(|(i, _)| i)
+            .collect()
+    }
 
-        Ok(Self {
-            seq_profile,
-            seq_level_idx_0,
-            seq_tier_0,
-            high_bitdepth,
-            twelve_bit,
-            monochrome,
-            chroma_subsampling_x,
-            chroma_subsampling_y,
-            chroma_sample_position,
-            initial_presentation_delay_minus_one,
-            config_obus,
-        })
+    /// チャンクオフセットの最大値を返す（チャンクが存在しない場合は [`None`]）
+    pub fn max_offset(&self) -> Option<u32> {
+        self.chunk_offsets.iter().copied().max()
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        FullBoxHeader::from_box(self).encode(&mut writer)?;
+        (self.chunk_offsets.len() as u32).encode(&mut writer)?;
+        for offset in &self.chunk_offsets {
+            offset.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let _ = FullBoxHeader::decode(&mut reader)?;
+        let count = u32::decode(&mut reader)? as usize;
+        let mut chunk_offsets = Vec::with_capacity(entries_capacity(count, reader.limit(), 4));
+        for _ in 0..count {
+            chunk_offsets.push(u32::decode(&mut reader)?);
+        }
+        Ok(Self { chunk_offsets })
     }
 }
 
-impl Encode for Av1cBox {
+impl Encode for StcoBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
         Ok(())
     }
+
+    fn encoded_size(&self) -> u64 {
+        self.box_size().get()
+    }
 }
 
-impl Decode for Av1cBox {
+impl Decode for StcoBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3326,13 +6848,15 @@ impl Decode for Av1cBox {
     }
 }
 
-impl BaseBox for Av1cBox {
+impl BaseBox for StcoBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
 
     fn box_payload_size(&self) -> u64 {
-        ExternalBytes::calc(|writer| self.encode_payload(writer))
+        // エントリ数が多くなりがちなボックスなので、エンコードせずに計算でサイズを求める
+        // (フルボックスヘッダー + エントリ数 + エントリ群)
+        4 + 4 + self.chunk_offsets.len() as u64 * 4
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
@@ -3340,52 +6864,69 @@ impl BaseBox for Av1cBox {
     }
 }
 
-/// [`SttsBox`] が保持するエントリー
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct SttsEntry {
-    pub sample_count: u32,
-    pub sample_delta: u32,
+impl FullBox for StcoBox {
+    fn full_box_version(&self) -> u8 {
+        0
+    }
+
+    fn full_box_flags(&self) -> FullBoxFlags {
+        FullBoxFlags::new(0)
+    }
 }
 
-/// [ISO/IEC 14496-12] TimeToSampleBox class (親: [`StblBox`])
+/// [ISO/IEC 14496-12] ChunkLargeOffsetBox class (親: [`StblBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct SttsBox {
-    pub entries: Vec<SttsEntry>,
+pub struct Co64Box {
+    pub chunk_offsets: Vec<u64>,
 }
 
-impl SttsBox {
+impl Co64Box {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stts");
+    pub const TYPE: BoxType = BoxType::Normal(*b"co64");
 
-    /// サンプル群の尺を走査するイテレーターを受け取って、対応する [`SttsBox`] インスタンスを作成する
-    pub fn from_sample_deltas<I>(sample_deltas: I) -> Self
-    where
-        I: IntoIterator<Item = u32>,
-    {
-        let mut entries = Vec::<SttsEntry>::new();
-        for sample_delta in sample_deltas {
-            if let Some(last) = entries.last_mut() {
-                if last.sample_delta == sample_delta {
-                    last.sample_count += 1;
-                    continue;
-                }
-            }
-            entries.push(SttsEntry {
-                sample_count: 1,
-                sample_delta,
-            });
-        }
-        Self { entries }
+    /// 全てのチャンクオフセットに `delta` を加算する
+    ///
+    /// 加算後のオフセットが [`u64`] の範囲に収まらない場合にはエラーが返され、`self` は変更されない
+    pub fn patch_offsets(&mut self, delta: i64) -> Result<()> {
+        let chunk_offsets = self
+            .chunk_offsets
+            .iter()
+            .map(|&offset| {
+                offset.checked_add_signed(delta).ok_or_else(|| {
+                    Error::invalid_input(&format!(
+                        "Chunk offset out of range after patching: offset={offset}, delta={delta}"
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.chunk_offsets = chunk_offsets;
+        Ok(())
+    }
+
+    /// オフセットが `file_size` 以上になっているチャンクの（`chunk_offsets` 内での 0 始まりの）インデックス一覧を返す
+    ///
+    /// ファイルが途中で切り詰められた場合や、前方にバイト列が挿入・削除された場合などに、
+    /// チャンクオフセットがファイルの範囲外を指していないかを確認するために使える
+    pub fn out_of_range_chunks(&self, file_size: u64) -> Vec<usize> {
+        self.chunk_offsets
+            .iter()
+            .enumerate()
+            .filter(|(_, &offset)| offset >= file_size)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// チャンクオフセットの最大値を返す（チャンクが存在しない場合は [`None`]）
+    pub fn max_offset(&self) -> Option<u64> {
+        self.chunk_offsets.iter().copied().max()
     }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        (self.entries.len() as u32).encode(&mut writer)?;
-        for entry in &self.entries {
-            entry.sample_count.encode(&mut writer)?;
-            entry.sample_delta.encode(&mut writer)?;
+        (self.chunk_offsets.len() as u32).encode(&mut writer)?;
+        for offset in &self.chunk_offsets {
+            offset.encode(&mut writer)?;
         }
         Ok(())
     }
@@ -3393,26 +6934,27 @@ impl SttsBox {
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let _ = FullBoxHeader::decode(&mut reader)?;
         let count = u32::decode(&mut reader)? as usize;
-        let mut entries = Vec::with_capacity(count);
+        let mut chunk_offsets = Vec::with_capacity(entries_capacity(count, reader.limit(), 8));
         for _ in 0..count {
-            entries.push(SttsEntry {
-                sample_count: u32::decode(&mut reader)?,
-                sample_delta: u32::decode(&mut reader)?,
-            });
+            chunk_offsets.push(u64::decode(&mut reader)?);
         }
-        Ok(Self { entries })
+        Ok(Self { chunk_offsets })
     }
 }
 
-impl Encode for SttsBox {
+impl Encode for Co64Box {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
         Ok(())
     }
+
+    fn encoded_size(&self) -> u64 {
+        self.box_size().get()
+    }
 }
 
-impl Decode for SttsBox {
+impl Decode for Co64Box {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3420,13 +6962,15 @@ impl Decode for SttsBox {
     }
 }
 
-impl BaseBox for SttsBox {
+impl BaseBox for Co64Box {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
 
     fn box_payload_size(&self) -> u64 {
-        ExternalBytes::calc(|writer| self.encode_payload(writer))
+        // エントリ数が多くなりがちなボックスなので、エンコードせずに計算でサイズを求める
+        // (フルボックスヘッダー + エントリ数 + エントリ群)
+        4 + 4 + self.chunk_offsets.len() as u64 * 8
     }
 
     fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
@@ -3434,7 +6978,7 @@ impl BaseBox for SttsBox {
     }
 }
 
-impl FullBox for SttsBox {
+impl FullBox for Co64Box {
     fn full_box_version(&self) -> u8 {
         0
     }
@@ -3444,33 +6988,22 @@ impl FullBox for SttsBox {
     }
 }
 
-/// [`StscBox`] が保持するエントリー
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[allow(missing_docs)]
-pub struct StscEntry {
-    pub first_chunk: NonZeroU32,
-    pub sample_per_chunk: u32,
-    pub sample_description_index: NonZeroU32,
-}
-
-/// [ISO/IEC 14496-12] SampleToChunkBox class (親: [`StblBox`])
+/// [ISO/IEC 14496-12] SyncSampleBox class (親: [`StssBox`])
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct StscBox {
-    pub entries: Vec<StscEntry>,
+pub struct StssBox {
+    pub sample_numbers: Vec<NonZeroU32>,
 }
 
-impl StscBox {
+impl StssBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stsc");
+    pub const TYPE: BoxType = BoxType::Normal(*b"stss");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        (self.entries.len() as u32).encode(&mut writer)?;
-        for entry in &self.entries {
-            entry.first_chunk.encode(&mut writer)?;
-            entry.sample_per_chunk.encode(&mut writer)?;
-            entry.sample_description_index.encode(&mut writer)?;
+        (self.sample_numbers.len() as u32).encode(&mut writer)?;
+        for offset in &self.sample_numbers {
+            offset.encode(&mut writer)?;
         }
         Ok(())
     }
@@ -3478,19 +7011,15 @@ impl StscBox {
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
         let _ = FullBoxHeader::decode(&mut reader)?;
         let count = u32::decode(&mut reader)? as usize;
-        let mut entries = Vec::with_capacity(count);
+        let mut sample_numbers = Vec::with_capacity(entries_capacity(count, reader.limit(), 4));
         for _ in 0..count {
-            entries.push(StscEntry {
-                first_chunk: NonZeroU32::decode(&mut reader)?,
-                sample_per_chunk: u32::decode(&mut reader)?,
-                sample_description_index: NonZeroU32::decode(&mut reader)?,
-            });
+            sample_numbers.push(NonZeroU32::decode(&mut reader)?);
         }
-        Ok(Self { entries })
+        Ok(Self { sample_numbers })
     }
 }
 
-impl Encode for StscBox {
+impl Encode for StssBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3498,7 +7027,7 @@ impl Encode for StscBox {
     }
 }
 
-impl Decode for StscBox {
+impl Decode for StssBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3506,7 +7035,7 @@ impl Decode for StscBox {
     }
 }
 
-impl BaseBox for StscBox {
+impl BaseBox for StssBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3520,7 +7049,7 @@ impl BaseBox for StscBox {
     }
 }
 
-impl FullBox for StscBox {
+impl FullBox for StssBox {
     fn full_box_version(&self) -> u8 {
         0
     }
@@ -3530,64 +7059,63 @@ impl FullBox for StscBox {
     }
 }
 
-/// [ISO/IEC 14496-12] SampleSizeBox class (親: [`StblBox`])
+/// [`CttsBox`] が保持するエントリー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct CttsEntry {
+    pub sample_count: u32,
+    pub sample_offset: i32,
+}
+
+/// [ISO/IEC 14496-12] CompositionOffsetBox class (親: [`StblBox`])
+///
+/// バージョン 0 では `sample_offset` は符号なし整数として定義されているが、
+/// 実際には負の値を書き込む実装も存在するため、デコード時にはバージョンによらず符号付き整数として扱っている。
+/// エンコード時には、負のオフセットが含まれる場合にのみバージョン 1 が使われる。
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub enum StszBox {
-    Fixed {
-        sample_size: NonZeroU32,
-        sample_count: u32,
-    },
-    Variable {
-        entry_sizes: Vec<u32>,
-    },
+pub struct CttsBox {
+    pub entries: Vec<CttsEntry>,
 }
 
-impl StszBox {
+impl CttsBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stsz");
+    pub const TYPE: BoxType = BoxType::Normal(*b"ctts");
+
+    /// サンプル毎のコンポジションオフセットを順に返すイテレーターを返す
+    pub fn sample_offsets(&self) -> impl '_ + Iterator<Item = i32> {
+        self.entries
+            .iter()
+            .flat_map(|x| std::iter::repeat_n(x.sample_offset, x.sample_count as usize))
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        match self {
-            StszBox::Fixed {
-                sample_size,
-                sample_count,
-            } => {
-                sample_size.get().encode(&mut writer)?;
-                sample_count.encode(writer)?;
-            }
-            StszBox::Variable { entry_sizes } => {
-                0u32.encode(&mut writer)?;
-                (entry_sizes.len() as u32).encode(&mut writer)?;
-                for size in entry_sizes {
-                    size.encode(&mut writer)?;
-                }
-            }
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            entry.sample_count.encode(&mut writer)?;
+            entry.sample_offset.encode(&mut writer)?;
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let _ = FullBoxHeader::decode(&mut reader)?;
-        let sample_size = u32::decode(&mut reader)?;
-        let sample_count = u32::decode(&mut reader)?;
-        if let Some(sample_size) = NonZeroU32::new(sample_size) {
-            Ok(Self::Fixed {
-                sample_size,
-                sample_count,
-            })
-        } else {
-            let mut entry_sizes = Vec::with_capacity(sample_count as usize);
-            for _ in 0..sample_count {
-                entry_sizes.push(u32::decode(&mut reader)?);
-            }
-            Ok(Self::Variable { entry_sizes })
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+
+        let count = u32::decode(&mut reader)? as usize;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            entries.push(CttsEntry {
+                sample_count: u32::decode(&mut reader)?,
+                sample_offset: i32::decode(&mut reader)?,
+            });
         }
+        Ok(Self { entries })
     }
 }
 
-impl Encode for StszBox {
+impl Encode for CttsBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3595,7 +7123,7 @@ impl Encode for StszBox {
     }
 }
 
-impl Decode for StszBox {
+impl Decode for CttsBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3603,7 +7131,7 @@ impl Decode for StszBox {
     }
 }
 
-impl BaseBox for StszBox {
+impl BaseBox for CttsBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3617,9 +7145,13 @@ impl BaseBox for StszBox {
     }
 }
 
-impl FullBox for StszBox {
+impl FullBox for CttsBox {
     fn full_box_version(&self) -> u8 {
-        0
+        if self.entries.iter().any(|x| x.sample_offset < 0) {
+            1
+        } else {
+            0
+        }
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
@@ -3627,38 +7159,128 @@ impl FullBox for StszBox {
     }
 }
 
-/// [ISO/IEC 14496-12] ChunkOffsetBox class (親: [`StblBox`])
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// [`SubsEntry`] が保持するサブサンプルの情報
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct StcoBox {
-    pub chunk_offsets: Vec<u32>,
+pub struct SubsSubsample {
+    pub subsample_size: u32,
+    pub subsample_priority: u8,
+    pub discardable: u8,
+    pub codec_specific_parameters: u32,
 }
 
-impl StcoBox {
+/// [`SubsBox`] が保持するエントリー
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubsEntry {
+    /// 直前のエントリーのサンプル番号との差分（先頭エントリーの場合はサンプル番号そのもの）
+    pub sample_delta: u32,
+
+    /// サンプル内のサブサンプル群（サンプルの先頭から順に並ぶ）
+    pub subsamples: Vec<SubsSubsample>,
+}
+
+/// [ISO/IEC 14496-12] SubSampleInformationBox class (親: [`StblBox`])
+///
+/// サブサンプルのサイズが `u16` の範囲に収まらない場合にのみバージョン 1 でエンコードされる。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubsBox {
+    /// フルボックスのフラグ（意味はコーデック毎に定義される）
+    pub flags: FullBoxFlags,
+
+    /// サブサンプル情報を持つサンプルごとのエントリー
+    pub entries: Vec<SubsEntry>,
+}
+
+impl SubsBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stco");
+    pub const TYPE: BoxType = BoxType::Normal(*b"subs");
+
+    /// 指定されたサンプルに含まれるサブサンプル群の、サンプル先頭からのバイト範囲を順に返す
+    ///
+    /// サンプル番号は 1 始まりで、対応するエントリーが存在しない場合には空のイテレーターを返す
+    pub fn subsample_ranges(
+        &self,
+        sample_index: NonZeroU32,
+    ) -> impl '_ + Iterator<Item = std::ops::Range<usize>> {
+        let mut sample_number = 0u64;
+        let subsamples = self
+            .entries
+            .iter()
+            .find(|entry| {
+                sample_number += u64::from(entry.sample_delta);
+                sample_number >= u64::from(sample_index.get())
+            })
+            .filter(|_| sample_number == u64::from(sample_index.get()))
+            .map(|entry| entry.subsamples.as_slice())
+            .unwrap_or_default();
+
+        let mut offset = 0;
+        subsamples.iter().map(move |subsample| {
+            let start = offset;
+            offset += subsample.subsample_size as usize;
+            start..offset
+        })
+    }
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
-        FullBoxHeader::from_box(self).encode(&mut writer)?;
-        (self.chunk_offsets.len() as u32).encode(&mut writer)?;
-        for offset in &self.chunk_offsets {
-            offset.encode(&mut writer)?;
+        let full_header = FullBoxHeader::from_box(self);
+        full_header.encode(&mut writer)?;
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            entry.sample_delta.encode(&mut writer)?;
+            u16::try_from(entry.subsamples.len())
+                .map_err(|_| Error::invalid_input("Too many subsamples"))?
+                .encode(&mut writer)?;
+            for subsample in &entry.subsamples {
+                if full_header.version == 1 {
+                    subsample.subsample_size.encode(&mut writer)?;
+                } else {
+                    (subsample.subsample_size as u16).encode(&mut writer)?;
+                }
+                subsample.subsample_priority.encode(&mut writer)?;
+                subsample.discardable.encode(&mut writer)?;
+                subsample.codec_specific_parameters.encode(&mut writer)?;
+            }
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let _ = FullBoxHeader::decode(&mut reader)?;
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+
         let count = u32::decode(&mut reader)? as usize;
-        let mut chunk_offsets = Vec::with_capacity(count);
+        let mut entries = Vec::new();
         for _ in 0..count {
-            chunk_offsets.push(u32::decode(&mut reader)?);
+            let sample_delta = u32::decode(&mut reader)?;
+            let subsample_count = u16::decode(&mut reader)? as usize;
+            let mut subsamples = Vec::with_capacity(subsample_count);
+            for _ in 0..subsample_count {
+                let subsample_size = if full_header.version == 1 {
+                    u32::decode(&mut reader)?
+                } else {
+                    u32::from(u16::decode(&mut reader)?)
+                };
+                subsamples.push(SubsSubsample {
+                    subsample_size,
+                    subsample_priority: u8::decode(&mut reader)?,
+                    discardable: u8::decode(&mut reader)?,
+                    codec_specific_parameters: u32::decode(&mut reader)?,
+                });
+            }
+            entries.push(SubsEntry {
+                sample_delta,
+                subsamples,
+            });
         }
-        Ok(Self { chunk_offsets })
+        Ok(Self {
+            flags: full_header.flags,
+            entries,
+        })
     }
 }
 
-impl Encode for StcoBox {
+impl Encode for SubsBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3666,7 +7288,7 @@ impl Encode for StcoBox {
     }
 }
 
-impl Decode for StcoBox {
+impl Decode for SubsBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3674,7 +7296,7 @@ impl Decode for StcoBox {
     }
 }
 
-impl BaseBox for StcoBox {
+impl BaseBox for SubsBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3688,48 +7310,135 @@ impl BaseBox for StcoBox {
     }
 }
 
-impl FullBox for StcoBox {
+impl FullBox for SubsBox {
     fn full_box_version(&self) -> u8 {
-        0
+        let is_large = self.entries.iter().any(|entry| {
+            entry
+                .subsamples
+                .iter()
+                .any(|x| x.subsample_size > u32::from(u16::MAX))
+        });
+        if is_large {
+            1
+        } else {
+            0
+        }
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
-        FullBoxFlags::new(0)
+        self.flags
     }
 }
 
-/// [ISO/IEC 14496-12] ChunkLargeOffsetBox class (親: [`StblBox`])
+/// [`SgpdBox`] が保持するサンプルグループ記述エントリー
+///
+/// エントリーの中身はグルーピング種別に依存するため、ここではバイト列のまま保持している
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct Co64Box {
-    pub chunk_offsets: Vec<u64>,
+pub struct SgpdEntry {
+    pub payload: Vec<u8>,
 }
 
-impl Co64Box {
+/// [ISO/IEC 14496-12] SampleGroupDescriptionBox class (親: [`StblBox`])
+///
+/// `default_length` が 0 の場合には、エントリー毎に長さが付与される（可変長）。
+/// `default_group_description_index` が指定されている場合にはバージョン 2 で、それ以外はバージョン 1 でエンコードされる。
+///
+/// なお、バージョン 0 のボックスは各エントリーの長さがグルーピング種別に依存し、汎用的には分割できないため未対応。
+/// [`StblBox`] のデコード時には、バージョン 0 の `sgpd` ボックスは [`StblBox::unknown_boxes`] に格納される。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct SgpdBox {
+    pub grouping_type: [u8; 4],
+    pub default_length: u32,
+    pub default_group_description_index: Option<u32>,
+    pub entries: Vec<SgpdEntry>,
+}
+
+impl SgpdBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"co64");
+    pub const TYPE: BoxType = BoxType::Normal(*b"sgpd");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        (self.chunk_offsets.len() as u32).encode(&mut writer)?;
-        for offset in &self.chunk_offsets {
-            offset.encode(&mut writer)?;
+        self.grouping_type.encode(&mut writer)?;
+        self.default_length.encode(&mut writer)?;
+        if let Some(index) = self.default_group_description_index {
+            index.encode(&mut writer)?;
+        }
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            if self.default_length == 0 {
+                let length = u32::try_from(entry.payload.len())
+                    .map_err(|_| Error::invalid_input("Too large sgpd entry"))?;
+                length.encode(&mut writer)?;
+            } else if entry.payload.len() != self.default_length as usize {
+                return Err(Error::invalid_input(&format!(
+                    "Inconsistent sgpd entry length: expected={}, actual={}",
+                    self.default_length,
+                    entry.payload.len()
+                )));
+            }
+            writer.write_all(&entry.payload)?;
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let _ = FullBoxHeader::decode(&mut reader)?;
-        let count = u32::decode(&mut reader)? as usize;
-        let mut chunk_offsets = Vec::with_capacity(count);
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        if full_header.version > 2 {
+            push_decode_warning(|| {
+                format!(
+                    "Unknown full box version of '{}' box: {} (decoded as version 2)",
+                    Self::TYPE,
+                    full_header.version
+                )
+            });
+        }
+
+        if full_header.version == 0 {
+            return Err(Error::invalid_data(
+                "Unsupported sgpd box version: 0 (entry lengths are unknown)",
+            ));
+        }
+
+        let grouping_type = <[u8; 4]>::decode(&mut reader)?;
+        let default_length = u32::decode(&mut reader)?;
+        let default_group_description_index = if full_header.version >= 2 {
+            Some(u32::decode(&mut reader)?)
+        } else {
+            None
+        };
+
+        let count = u32::decode(&mut reader)?;
+        let mut entries = Vec::new();
         for _ in 0..count {
-            chunk_offsets.push(u64::decode(&mut reader)?);
+            let length = if default_length == 0 {
+                u32::decode(&mut reader)?
+            } else {
+                default_length
+            };
+            if u64::from(length) > reader.limit() {
+                return Err(Error::invalid_data(&format!(
+                    "Too large sgpd entry: length={length}, remaining={}",
+                    reader.limit()
+                )));
+            }
+            let mut payload = vec![0; length as usize];
+            reader.read_exact(&mut payload)?;
+            entries.push(SgpdEntry { payload });
         }
-        Ok(Self { chunk_offsets })
+
+        Ok(Self {
+            grouping_type,
+            default_length,
+            default_group_description_index,
+            entries,
+        })
     }
 }
 
-impl Encode for Co64Box {
+impl Encode for SgpdBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3737,7 +7446,7 @@ impl Encode for Co64Box {
     }
 }
 
-impl Decode for Co64Box {
+impl Decode for SgpdBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3745,7 +7454,7 @@ impl Decode for Co64Box {
     }
 }
 
-impl BaseBox for Co64Box {
+impl BaseBox for SgpdBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3759,9 +7468,13 @@ impl BaseBox for Co64Box {
     }
 }
 
-impl FullBox for Co64Box {
+impl FullBox for SgpdBox {
     fn full_box_version(&self) -> u8 {
-        0
+        if self.default_group_description_index.is_some() {
+            2
+        } else {
+            1
+        }
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
@@ -3769,38 +7482,73 @@ impl FullBox for Co64Box {
     }
 }
 
-/// [ISO/IEC 14496-12] SyncSampleBox class (親: [`StssBox`])
+/// [`SbgpBox`] が保持するエントリー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct SbgpEntry {
+    pub sample_count: u32,
+
+    /// 対応する [`SgpdBox`] のエントリーのインデックス (1 始まり、0 はグループに属さないことを意味する)
+    pub group_description_index: u32,
+}
+
+/// [ISO/IEC 14496-12] SampleToGroupBox class (親: [`StblBox`])
+///
+/// `grouping_type_parameter` が指定されている場合にはバージョン 1 でエンコードされる。
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
-pub struct StssBox {
-    pub sample_numbers: Vec<NonZeroU32>,
+pub struct SbgpBox {
+    pub grouping_type: [u8; 4],
+    pub grouping_type_parameter: Option<u32>,
+    pub entries: Vec<SbgpEntry>,
 }
 
-impl StssBox {
+impl SbgpBox {
     /// ボックス種別
-    pub const TYPE: BoxType = BoxType::Normal(*b"stss");
+    pub const TYPE: BoxType = BoxType::Normal(*b"sbgp");
 
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         FullBoxHeader::from_box(self).encode(&mut writer)?;
-        (self.sample_numbers.len() as u32).encode(&mut writer)?;
-        for offset in &self.sample_numbers {
-            offset.encode(&mut writer)?;
+        self.grouping_type.encode(&mut writer)?;
+        if let Some(parameter) = self.grouping_type_parameter {
+            parameter.encode(&mut writer)?;
+        }
+        (self.entries.len() as u32).encode(&mut writer)?;
+        for entry in &self.entries {
+            entry.sample_count.encode(&mut writer)?;
+            entry.group_description_index.encode(&mut writer)?;
         }
         Ok(())
     }
 
     fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
-        let _ = FullBoxHeader::decode(&mut reader)?;
+        let full_header = FullBoxHeader::decode(&mut reader)?;
+        warn_if_unknown_version(Self::TYPE, full_header.version);
+
+        let grouping_type = <[u8; 4]>::decode(&mut reader)?;
+        let grouping_type_parameter = if full_header.version == 1 {
+            Some(u32::decode(&mut reader)?)
+        } else {
+            None
+        };
+
         let count = u32::decode(&mut reader)? as usize;
-        let mut sample_numbers = Vec::with_capacity(count);
+        let mut entries = Vec::new();
         for _ in 0..count {
-            sample_numbers.push(NonZeroU32::decode(&mut reader)?);
+            entries.push(SbgpEntry {
+                sample_count: u32::decode(&mut reader)?,
+                group_description_index: u32::decode(&mut reader)?,
+            });
         }
-        Ok(Self { sample_numbers })
+        Ok(Self {
+            grouping_type,
+            grouping_type_parameter,
+            entries,
+        })
     }
 }
 
-impl Encode for StssBox {
+impl Encode for SbgpBox {
     fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
         BoxHeader::from_box(self).encode(&mut writer)?;
         self.encode_payload(writer)?;
@@ -3808,7 +7556,7 @@ impl Encode for StssBox {
     }
 }
 
-impl Decode for StssBox {
+impl Decode for SbgpBox {
     fn decode<R: Read>(mut reader: R) -> Result<Self> {
         let header = BoxHeader::decode(&mut reader)?;
         header.box_type.expect(Self::TYPE)?;
@@ -3816,7 +7564,7 @@ impl Decode for StssBox {
     }
 }
 
-impl BaseBox for StssBox {
+impl BaseBox for SbgpBox {
     fn box_type(&self) -> BoxType {
         Self::TYPE
     }
@@ -3830,9 +7578,13 @@ impl BaseBox for StssBox {
     }
 }
 
-impl FullBox for StssBox {
+impl FullBox for SbgpBox {
     fn full_box_version(&self) -> u8 {
-        0
+        if self.grouping_type_parameter.is_some() {
+            1
+        } else {
+            0
+        }
     }
 
     fn full_box_flags(&self) -> FullBoxFlags {
@@ -3873,6 +7625,7 @@ impl OpusBox {
                     dops_box = Some(DopsBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(header.box_type, &[DopsBox::TYPE]);
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
@@ -3933,6 +7686,48 @@ impl Mp4aBox {
     /// ボックス種別
     pub const TYPE: BoxType = BoxType::Normal(*b"mp4a");
 
+    /// [`AacConfig`] から AAC 用のサンプルエントリーを作成する
+    ///
+    /// `esds` ボックスには MPEG-4 Audio (0x40) のオブジェクトタイプと、
+    /// [`AacConfig::to_decoder_specific_info()`] で作成した AudioSpecificConfig が設定される。
+    /// ビットレートなどの情報は 0 (不明) となる。
+    ///
+    /// なお、サンプリングレートが `u16` の範囲に収まらない場合には、
+    /// [`AudioSampleEntryFields::samplerate`] には 0 が設定される
+    pub fn from_aac_config(config: &AacConfig) -> Self {
+        Self {
+            audio: AudioSampleEntryFields {
+                data_reference_index: 1,
+                channelcount: u16::from(config.channel_count),
+                samplesize: AudioSampleEntryFields::DEFAULT_SAMPLESIZE,
+                samplerate: FixedPointNumber::new(
+                    u16::try_from(config.sample_rate).unwrap_or(0),
+                    0,
+                ),
+            },
+            esds_box: EsdsBox {
+                es: EsDescriptor {
+                    es_id: 0,
+                    stream_priority: Uint::new(0),
+                    depends_on_es_id: None,
+                    url_string: None,
+                    ocr_es_id: None,
+                    dec_config_descr: DecoderConfigDescriptor {
+                        object_type_indication: DecoderConfigDescriptor::OBJECT_TYPE_MPEG4_AUDIO,
+                        stream_type: Uint::new(DecoderConfigDescriptor::STREAM_TYPE_AUDIO),
+                        up_stream: Uint::new(0),
+                        buffer_size_db: Uint::new(0),
+                        max_bitrate: 0,
+                        avg_bitrate: 0,
+                        dec_specific_info: config.to_decoder_specific_info(),
+                    },
+                    sl_config_descr: SlConfigDescriptor,
+                },
+            },
+            unknown_boxes: Vec::new(),
+        }
+    }
+
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         self.audio.encode(&mut writer)?;
         self.esds_box.encode(&mut writer)?;
@@ -3953,6 +7748,7 @@ impl Mp4aBox {
                     esds_box = Some(EsdsBox::decode(&mut reader)?);
                 }
                 _ => {
+                    warn_if_duplicate(header.box_type, &[EsdsBox::TYPE]);
                     unknown_boxes.push(UnknownBox::decode(&mut reader)?);
                 }
             }
@@ -4000,6 +7796,204 @@ impl BaseBox for Mp4aBox {
     }
 }
 
+/// [QuickTime File Format] Timecode Sample Description
+///
+/// Final Cut Pro や DaVinci Resolve などが出力する QuickTime 形式のファイルに含まれるタイムコードトラックのサンプルエントリー。
+/// トラックの各サンプルは、先頭フレームのフレーム番号を表す 32 ビット整数となる
+/// （[`TmcdBox::decode_timecode_sample()`] でタイムコードに変換できる）。
+///
+/// なお、ソース名などを保持する子ボックスは [`TmcdBox::unknown_boxes`] に格納される。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TmcdBox {
+    /// データ参照インデックス
+    pub data_reference_index: NonZeroU16,
+
+    /// タイムコードのフラグ（[`TmcdBox::FLAG_DROP_FRAME`] など）
+    pub flags: u32,
+
+    /// タイムスケール
+    pub time_scale: u32,
+
+    /// 一フレームの尺（`time_scale` 単位）
+    pub frame_duration: u32,
+
+    /// 一秒あたりのフレーム数（29.97 fps の場合は 30）
+    pub number_of_frames: u8,
+
+    /// 未知の子ボックス群
+    pub unknown_boxes: Vec<UnknownBox>,
+}
+
+impl TmcdBox {
+    /// ボックス種別
+    pub const TYPE: BoxType = BoxType::Normal(*b"tmcd");
+
+    /// ドロップフレーム形式のタイムコードであることを示すフラグ
+    pub const FLAG_DROP_FRAME: u32 = 0x0001;
+
+    /// タイムコードが 24 時間で折り返すことを示すフラグ
+    pub const FLAG_24_HOUR_MAX: u32 = 0x0002;
+
+    /// 負の時刻を許容することを示すフラグ
+    pub const FLAG_NEGATIVE_TIMES_OK: u32 = 0x0004;
+
+    /// サンプルがタイムコードではなくカウンターであることを示すフラグ
+    pub const FLAG_COUNTER: u32 = 0x0008;
+
+    /// ドロップフレーム形式のタイムコードかどうかを返す
+    pub const fn is_drop_frame(&self) -> bool {
+        self.flags & Self::FLAG_DROP_FRAME != 0
+    }
+
+    /// サンプルがカウンターかどうかを返す
+    pub const fn is_counter(&self) -> bool {
+        self.flags & Self::FLAG_COUNTER != 0
+    }
+
+    /// タイムコードトラックのサンプルデータをデコードして、タイムコードを返す
+    ///
+    /// サンプルデータは先頭フレームのフレーム番号を表す 32 ビットのビッグエンディアン整数で、
+    /// このボックスの `number_of_frames` とドロップフレームフラグに従ってタイムコードに変換される
+    pub fn decode_timecode_sample(&self, data: &[u8]) -> Result<TimecodeValue> {
+        let Some(frame_number) = data.first_chunk::<4>().copied().map(u32::from_be_bytes) else {
+            return Err(Error::invalid_data("Too short timecode sample"));
+        };
+        TimecodeValue::from_frame_number(frame_number, self.number_of_frames, self.is_drop_frame())
+    }
+
+    fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
+        [0u8; 6].encode(&mut writer)?;
+        self.data_reference_index.encode(&mut writer)?;
+        [0u8; 4].encode(&mut writer)?;
+        self.flags.encode(&mut writer)?;
+        self.time_scale.encode(&mut writer)?;
+        self.frame_duration.encode(&mut writer)?;
+        self.number_of_frames.encode(&mut writer)?;
+        0u8.encode(&mut writer)?;
+        for b in &self.unknown_boxes {
+            b.encode(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_payload<R: Read>(mut reader: &mut std::io::Take<R>) -> Result<Self> {
+        let _ = <[u8; 6]>::decode(&mut reader)?;
+        let data_reference_index = NonZeroU16::decode(&mut reader)?;
+        let _ = <[u8; 4]>::decode(&mut reader)?;
+        let flags = u32::decode(&mut reader)?;
+        let time_scale = u32::decode(&mut reader)?;
+        let frame_duration = u32::decode(&mut reader)?;
+        let number_of_frames = u8::decode(&mut reader)?;
+        let _ = u8::decode(&mut reader)?;
+        let mut unknown_boxes = Vec::new();
+        while reader.limit() > 0 {
+            unknown_boxes.push(UnknownBox::decode(&mut reader)?);
+        }
+        Ok(Self {
+            data_reference_index,
+            flags,
+            time_scale,
+            frame_duration,
+            number_of_frames,
+            unknown_boxes,
+        })
+    }
+}
+
+impl Encode for TmcdBox {
+    fn encode<W: Write>(&self, mut writer: W) -> Result<()> {
+        BoxHeader::from_box(self).encode(&mut writer)?;
+        self.encode_payload(writer)?;
+        Ok(())
+    }
+}
+
+impl Decode for TmcdBox {
+    fn decode<R: Read>(mut reader: R) -> Result<Self> {
+        let header = BoxHeader::decode(&mut reader)?;
+        header.box_type.expect(Self::TYPE)?;
+        header.with_box_payload_reader(reader, Self::decode_payload)
+    }
+}
+
+impl BaseBox for TmcdBox {
+    fn box_type(&self) -> BoxType {
+        Self::TYPE
+    }
+
+    fn box_payload_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode_payload(writer))
+    }
+
+    fn children<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &'a dyn BaseBox>> {
+        Box::new(self.unknown_boxes.iter().map(as_box_object))
+    }
+}
+
+/// 時・分・秒・フレームからなるタイムコード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct TimecodeValue {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+}
+
+impl TimecodeValue {
+    /// フレーム番号をタイムコードに変換する
+    ///
+    /// `frames_per_second` には 29.97 fps の場合は 30 というように、整数に切り上げたフレームレートを指定する。
+    /// `drop_frame` が `true` の場合には、SMPTE のドロップフレーム方式
+    /// （10 の倍数以外の分の先頭でフレーム番号を `frames_per_second / 15` 個飛ばす）に従って変換する。
+    ///
+    /// 時の値は 24 で折り返す
+    pub fn from_frame_number(
+        frame_number: u32,
+        frames_per_second: u8,
+        drop_frame: bool,
+    ) -> Result<Self> {
+        if frames_per_second == 0 {
+            return Err(Error::invalid_input("Zero frames per second"));
+        }
+        let fps = u64::from(frames_per_second);
+        let mut n = u64::from(frame_number);
+
+        if drop_frame {
+            if !fps.is_multiple_of(30) {
+                return Err(Error::invalid_input(&format!(
+                    "Drop frame timecode is not supported for {fps} frames per second"
+                )));
+            }
+            let drop_frames = fps / 15;
+            let frames_per_10_minutes = fps * 60 * 10 - drop_frames * 9;
+            let frames_per_minute = frames_per_10_minutes / 10;
+            let d = n / frames_per_10_minutes;
+            let m = n % frames_per_10_minutes;
+            n += drop_frames * 9 * d;
+            if m > drop_frames {
+                n += drop_frames * ((m - drop_frames) / frames_per_minute);
+            }
+        }
+
+        Ok(Self {
+            hours: (n / (fps * 60 * 60) % 24) as u8,
+            minutes: (n / (fps * 60) % 60) as u8,
+            seconds: (n / fps % 60) as u8,
+            frames: (n % fps) as u8,
+        })
+    }
+
+    /// `HH:MM:SS:FF` 形式（ドロップフレームの場合は `HH:MM:SS;FF` 形式）の SMPTE タイムコード文字列を返す
+    pub fn to_smpte_string(&self, drop_frame: bool) -> String {
+        let separator = if drop_frame { ';' } else { ':' };
+        format!(
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
 /// 音声系の [`SampleEntry`] に共通のフィールドをまとめた構造体
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
@@ -4048,7 +8042,22 @@ impl Decode for AudioSampleEntryFields {
     }
 }
 
+/// [`DopsBox`] のチャンネルマッピングテーブル
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DopsChannelMappingTable {
+    /// Opus ストリームの総数
+    pub stream_count: u8,
+
+    /// ステレオ（二チャンネル）の Opus ストリームの数
+    pub coupled_count: u8,
+
+    /// 出力チャンネル毎の、デコードされたチャンネルのインデックス（要素数は出力チャンネル数と等しい）
+    pub channel_mapping: Vec<u8>,
+}
+
 /// [<https://gitlab.xiph.org/xiph/opus/-/blob/main/doc/opus_in_isobmff.html>] OpusSpecificBox class (親: [`OpusBox`])
+///
+/// `channel_mapping_family` が 0 以外の場合には、`channel_mapping_table` が必須となる
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
 pub struct DopsBox {
@@ -4056,6 +8065,8 @@ pub struct DopsBox {
     pub pre_skip: u16,
     pub input_sample_rate: u32,
     pub output_gain: i16,
+    pub channel_mapping_family: u8,
+    pub channel_mapping_table: Option<DopsChannelMappingTable>,
 }
 
 impl DopsBox {
@@ -4064,13 +8075,95 @@ impl DopsBox {
 
     const VERSION: u8 = 0;
 
+    /// ボックスの内容が Opus の仕様に沿っているかどうかを検証する
+    ///
+    /// 以下のいずれかに該当する場合にはエラーが返される:
+    /// - `output_channel_count` が 0
+    /// - `channel_mapping_family` が 0 で、チャンネル数が 3 以上か、チャンネルマッピングテーブルが存在する
+    /// - `channel_mapping_family` が 0 以外で、チャンネルマッピングテーブルが存在しないか、その内容が不正
+    ///
+    /// なお `pre_skip` については、RFC 7845 では値の推奨があるのみで制約はない
+    /// （実際に WebRTC の録画ファイルなどでは 0 が使われることがある）ため、検証の対象外としている
+    pub fn validate(&self) -> Result<()> {
+        if self.output_channel_count == 0 {
+            return Err(Error::invalid_data(
+                "Zero output channel count in 'dOps' box",
+            ));
+        }
+
+        let Some(table) = &self.channel_mapping_table else {
+            if self.channel_mapping_family != 0 {
+                return Err(Error::invalid_data(&format!(
+                    "Missing channel mapping table in 'dOps' box (channel mapping family is {})",
+                    self.channel_mapping_family
+                )));
+            }
+            if self.output_channel_count > 2 {
+                return Err(Error::invalid_data(&format!(
+                    "Too many output channels for channel mapping family 0 in 'dOps' box: {}",
+                    self.output_channel_count
+                )));
+            }
+            return Ok(());
+        };
+
+        if self.channel_mapping_family == 0 {
+            return Err(Error::invalid_data(
+                "Unexpected channel mapping table in 'dOps' box (channel mapping family is 0)",
+            ));
+        }
+        if table.stream_count == 0 || table.coupled_count > table.stream_count {
+            return Err(Error::invalid_data(&format!(
+                "Invalid stream count in 'dOps' box: stream_count={}, coupled_count={}",
+                table.stream_count, table.coupled_count
+            )));
+        }
+        if table.channel_mapping.len() != self.output_channel_count as usize {
+            return Err(Error::invalid_data(&format!(
+                "Inconsistent channel mapping size in 'dOps' box: expected={}, actual={}",
+                self.output_channel_count,
+                table.channel_mapping.len()
+            )));
+        }
+        let decoded_channels = u16::from(table.stream_count) + u16::from(table.coupled_count);
+        if let Some(&index) = table
+            .channel_mapping
+            .iter()
+            .find(|&&i| i != 255 && u16::from(i) >= decoded_channels)
+        {
+            return Err(Error::invalid_data(&format!(
+                "Out of range channel mapping index in 'dOps' box: {index}"
+            )));
+        }
+        Ok(())
+    }
+
     fn encode_payload<W: Write>(&self, mut writer: W) -> Result<()> {
         Self::VERSION.encode(&mut writer)?;
         self.output_channel_count.encode(&mut writer)?;
         self.pre_skip.encode(&mut writer)?;
         self.input_sample_rate.encode(&mut writer)?;
         self.output_gain.encode(&mut writer)?;
-        0u8.encode(writer)?; // ChannelMappingFamily
+        self.channel_mapping_family.encode(&mut writer)?;
+        if self.channel_mapping_family != 0 {
+            let table = self.channel_mapping_table.as_ref().ok_or_else(|| {
+                Error::invalid_input("Missing channel mapping table in 'dOps' box")
+            })?;
+            if table.channel_mapping.len() != self.output_channel_count as usize {
+                return Err(Error::invalid_input(&format!(
+                    "Inconsistent channel mapping size in 'dOps' box: expected={}, actual={}",
+                    self.output_channel_count,
+                    table.channel_mapping.len()
+                )));
+            }
+            table.stream_count.encode(&mut writer)?;
+            table.coupled_count.encode(&mut writer)?;
+            writer.write_all(&table.channel_mapping)?;
+        } else if self.channel_mapping_table.is_some() {
+            return Err(Error::invalid_input(
+                "Channel mapping table in 'dOps' box requires non-zero channel mapping family",
+            ));
+        }
         Ok(())
     }
 
@@ -4086,17 +8179,27 @@ impl DopsBox {
         let pre_skip = u16::decode(&mut reader)?;
         let input_sample_rate = u32::decode(&mut reader)?;
         let output_gain = i16::decode(&mut reader)?;
-        let channel_mapping_family = u8::decode(reader)?;
-        if channel_mapping_family != 0 {
-            return Err(Error::unsupported(
-                "`ChannelMappingFamily != 0` in 'dOps' box is not supported",
-            ));
-        }
+        let channel_mapping_family = u8::decode(&mut reader)?;
+        let channel_mapping_table = if channel_mapping_family != 0 {
+            let stream_count = u8::decode(&mut reader)?;
+            let coupled_count = u8::decode(&mut reader)?;
+            let mut channel_mapping = vec![0; output_channel_count as usize];
+            reader.read_exact(&mut channel_mapping)?;
+            Some(DopsChannelMappingTable {
+                stream_count,
+                coupled_count,
+                channel_mapping,
+            })
+        } else {
+            None
+        };
         Ok(Self {
             output_channel_count,
             pre_skip,
             input_sample_rate,
             output_gain,
+            channel_mapping_family,
+            channel_mapping_table,
         })
     }
 }
@@ -1,7 +1,10 @@
 //! ISO_IEC_14496-1 で定義されているディスクリプター群
 use std::io::{Read, Write};
 
-use crate::{Decode, Encode, Error, Result, Uint};
+use crate::{
+    io::{BitReader, BitWriter},
+    Decode, Encode, Error, Result, Uint,
+};
 
 /// [ISO_IEC_14496-1] ES_Descriptor class
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -115,6 +118,12 @@ pub struct DecoderConfigDescriptor {
 
 impl DecoderConfigDescriptor {
     const TAG: u8 = 4; // DecoderConfigDescrTag
+
+    /// MPEG-4 Audio (AAC など) を表す `object_type_indication` の値
+    pub const OBJECT_TYPE_MPEG4_AUDIO: u8 = 0x40;
+
+    /// 音声ストリームを表す `stream_type` の値
+    pub const STREAM_TYPE_AUDIO: u8 = 0x05;
 }
 
 impl Decode for DecoderConfigDescriptor {
@@ -193,8 +202,15 @@ impl Decode for DecoderSpecificInfo {
             )));
         }
 
-        let mut payload = vec![0; size];
-        reader.read_exact(&mut payload)?;
+        // サイズは信頼できない入力なので、事前にその分のメモリを確保することはしない
+        let mut payload = Vec::new();
+        (&mut reader).take(size as u64).read_to_end(&mut payload)?;
+        if payload.len() != size {
+            return Err(Error::invalid_data(&format!(
+                "Unexpected EOF while reading descriptor payload: expected={size} bytes, actual={} bytes",
+                payload.len()
+            )));
+        }
 
         Ok(Self { payload })
     }
@@ -208,6 +224,220 @@ impl Encode for DecoderSpecificInfo {
     }
 }
 
+impl DecoderSpecificInfo {
+    /// ペイロードを AAC の AudioSpecificConfig として解釈して、[`AacConfig`] を返す
+    ///
+    /// ペイロードが不正な場合や、未知のサンプリング周波数インデックスなどが含まれる場合には [`None`] が返される
+    pub fn to_aac_config(&self) -> Option<AacConfig> {
+        AacConfig::parse(&self.payload)
+    }
+}
+
+/// [ISO/IEC 14496-3] AudioSpecificConfig の主要なフィールドをまとめた構造体
+///
+/// HE-AAC (SBR) や HE-AACv2 (SBR + PS) が明示的に指定されている場合には、
+/// `object_type` はそれぞれ [`AacConfig::OBJECT_TYPE_SBR`] と [`AacConfig::OBJECT_TYPE_PS`] となり、
+/// `sample_rate` と `channel_count` はデコード後の出力の値（SBR による倍のサンプリングレートや PS によるステレオ化を反映したもの）となる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AacConfig {
+    /// オーディオオブジェクトタイプ (AAC-LC なら 2)
+    pub object_type: u8,
+
+    /// コアのサンプリング周波数インデックス（0xF の場合は周波数が明示的に指定されている）
+    pub sample_rate_index: u8,
+
+    /// チャンネル構成 (0 の場合はプログラムコンフィグエレメントで指定される)
+    pub channel_config: u8,
+
+    /// 出力のサンプリングレート
+    pub sample_rate: u32,
+
+    /// 出力のチャンネル数（`channel_config` が 0 の場合は 0）
+    pub channel_count: u8,
+}
+
+impl AacConfig {
+    /// AAC-LC のオーディオオブジェクトタイプ
+    pub const OBJECT_TYPE_AAC_LC: u8 = 2;
+
+    /// SBR (HE-AAC) のオーディオオブジェクトタイプ
+    pub const OBJECT_TYPE_SBR: u8 = 5;
+
+    /// PS (HE-AACv2) のオーディオオブジェクトタイプ
+    pub const OBJECT_TYPE_PS: u8 = 29;
+
+    /// サンプリング周波数インデックスに対応するサンプリング周波数の表
+    pub const SAMPLE_RATES: [u32; 13] = [
+        96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+    ];
+
+    const EXPLICIT_SAMPLE_RATE_INDEX: u8 = 0xF;
+
+    /// AAC-LC 用の設定を作成する
+    pub fn for_aac_lc(sample_rate: u32, channels: u8) -> Self {
+        Self {
+            object_type: Self::OBJECT_TYPE_AAC_LC,
+            sample_rate_index: Self::sample_rate_index(sample_rate),
+            channel_config: Self::channel_config(channels),
+            sample_rate,
+            channel_count: channels,
+        }
+    }
+
+    /// HE-AAC (AAC-LC + SBR) 用の設定を作成する
+    ///
+    /// `sample_rate` には出力のサンプリングレートを指定する（コアのサンプリングレートはその半分となる）
+    pub fn for_he_aac_v1(sample_rate: u32, channels: u8) -> Self {
+        Self {
+            object_type: Self::OBJECT_TYPE_SBR,
+            sample_rate_index: Self::sample_rate_index(sample_rate / 2),
+            channel_config: Self::channel_config(channels),
+            sample_rate,
+            channel_count: channels,
+        }
+    }
+
+    /// HE-AACv2 (AAC-LC + SBR + PS) 用の設定を作成する
+    ///
+    /// コアはモノラルで、出力はステレオとなる
+    pub fn for_he_aac_v2(sample_rate: u32) -> Self {
+        Self {
+            object_type: Self::OBJECT_TYPE_PS,
+            sample_rate_index: Self::sample_rate_index(sample_rate / 2),
+            channel_config: 1,
+            sample_rate,
+            channel_count: 2,
+        }
+    }
+
+    /// AudioSpecificConfig をエンコードして [`DecoderSpecificInfo`] を作成する
+    ///
+    /// HE-AAC と HE-AACv2 の場合には、SBR / PS を明示的に指定する形式でエンコードされる
+    pub fn to_decoder_specific_info(&self) -> DecoderSpecificInfo {
+        let mut writer = BitWriter::default();
+        Self::write_object_type(&mut writer, self.object_type);
+
+        let is_sbr = matches!(
+            self.object_type,
+            Self::OBJECT_TYPE_SBR | Self::OBJECT_TYPE_PS
+        );
+        let core_sample_rate = if is_sbr {
+            self.sample_rate / 2
+        } else {
+            self.sample_rate
+        };
+        Self::write_sample_rate(&mut writer, self.sample_rate_index, core_sample_rate);
+        writer.write(u32::from(self.channel_config), 4);
+
+        if is_sbr {
+            Self::write_sample_rate(
+                &mut writer,
+                Self::sample_rate_index(self.sample_rate),
+                self.sample_rate,
+            );
+            Self::write_object_type(&mut writer, Self::OBJECT_TYPE_AAC_LC);
+        }
+
+        // GASpecificConfig (frameLengthFlag, dependsOnCoreCoder, extensionFlag)
+        writer.write(0, 3);
+
+        DecoderSpecificInfo {
+            payload: writer.finish(),
+        }
+    }
+
+    fn sample_rate_index(sample_rate: u32) -> u8 {
+        Self::SAMPLE_RATES
+            .iter()
+            .position(|&x| x == sample_rate)
+            .map(|i| i as u8)
+            .unwrap_or(Self::EXPLICIT_SAMPLE_RATE_INDEX)
+    }
+
+    fn channel_config(channels: u8) -> u8 {
+        match channels {
+            1..=6 => channels,
+            7 => 11,
+            8 => 7,
+            24 => 13,
+            _ => 0,
+        }
+    }
+
+    fn write_object_type(writer: &mut BitWriter, object_type: u8) {
+        if object_type >= 32 {
+            writer.write(31, 5);
+            writer.write(u32::from(object_type - 32), 6);
+        } else {
+            writer.write(u32::from(object_type), 5);
+        }
+    }
+
+    fn write_sample_rate(writer: &mut BitWriter, index: u8, sample_rate: u32) {
+        if Self::SAMPLE_RATES.get(index as usize) == Some(&sample_rate) {
+            writer.write(u32::from(index), 4);
+        } else {
+            writer.write(u32::from(Self::EXPLICIT_SAMPLE_RATE_INDEX), 4);
+            writer.write(sample_rate, 24);
+        }
+    }
+
+    fn parse(payload: &[u8]) -> Option<Self> {
+        let mut reader = BitReader::new(payload);
+
+        let object_type = Self::read_object_type(&mut reader)?;
+        let (sample_rate_index, mut sample_rate) = Self::read_sample_rate(&mut reader)?;
+        let channel_config = reader.read(4)? as u8;
+        let mut channel_count = match channel_config {
+            0..=6 => channel_config,
+            7 => 8,
+            11 => 7,
+            12 => 8,
+            13 => 24,
+            _ => return None,
+        };
+
+        if matches!(object_type, Self::OBJECT_TYPE_SBR | Self::OBJECT_TYPE_PS) {
+            // 拡張（SBR）のサンプリング周波数が出力のサンプリングレートとなる
+            let (_, extension_sample_rate) = Self::read_sample_rate(&mut reader)?;
+            sample_rate = extension_sample_rate;
+
+            // 続くのはコアのオーディオオブジェクトタイプ
+            let _ = Self::read_object_type(&mut reader)?;
+
+            if object_type == Self::OBJECT_TYPE_PS && channel_count == 1 {
+                channel_count = 2;
+            }
+        }
+
+        Some(Self {
+            object_type,
+            sample_rate_index,
+            channel_config,
+            sample_rate,
+            channel_count,
+        })
+    }
+
+    fn read_object_type(reader: &mut BitReader) -> Option<u8> {
+        let object_type = reader.read(5)? as u8;
+        if object_type == 31 {
+            Some(32 + reader.read(6)? as u8)
+        } else {
+            Some(object_type)
+        }
+    }
+
+    fn read_sample_rate(reader: &mut BitReader) -> Option<(u8, u32)> {
+        let index = reader.read(4)? as u8;
+        if index == Self::EXPLICIT_SAMPLE_RATE_INDEX {
+            Some((index, reader.read(24)?))
+        } else {
+            Some((index, *Self::SAMPLE_RATES.get(index as usize)?))
+        }
+    }
+}
+
 /// [ISO_IEC_14496-1] SLConfigDescriptor class
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SlConfigDescriptor;
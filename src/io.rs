@@ -1,11 +1,13 @@
 use std::{
     backtrace::Backtrace,
+    cell::RefCell,
     io::{Cursor, ErrorKind, Read, Write},
     num::{NonZeroU16, NonZeroU32},
     panic::Location,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
-use crate::BoxType;
+use crate::{BaseBox, BoxHeader, BoxType};
 
 /// このライブラリ用の [`std::result::Result`] 型
 pub type Result<T> = std::result::Result<T, Error>;
@@ -21,6 +23,15 @@ pub struct Error {
     /// エラーが発生したボックスの種別
     pub box_type: Option<BoxType>,
 
+    /// エラーが発生したボックスに至るまでのボックスのパス
+    ///
+    /// 先頭が最も外側のボックスで、末尾は [`Error::box_type`] と同じになる。
+    ///
+    /// このライブラリのデコード処理でインデックスが記録されるのは、
+    /// `moov` ボックス内の `trak` ボックスと `stsd` ボックス内のサンプルエントリーのみで、
+    /// それ以外のボックスのインデックスは常に `None` となる
+    pub box_path: Vec<(BoxType, Option<usize>)>,
+
     /// エラー発生箇所を示すバックトレース
     ///
     /// バックトレースは `RUST_BACKTRACE` 環境変数が設定されていない場合には取得されない
@@ -47,15 +58,47 @@ impl Error {
 
     #[track_caller]
     pub(crate) fn unsupported(message: &str) -> Self {
-        Self::from(std::io::Error::new(ErrorKind::Other, message))
+        Self::from(std::io::Error::other(message))
     }
 
-    pub(crate) fn with_box_type(mut self, box_type: BoxType) -> Self {
+    pub(crate) fn with_box_type(self, box_type: BoxType) -> Self {
+        self.add_context(box_type, None)
+    }
+
+    /// エラーが発生したボックスに至るまでのパス（[`Error::box_path`]）の先頭にボックスを追加する
+    ///
+    /// `index` には、追加するボックスが親ボックスの中で何番目のボックスかを指定する
+    /// （同じ種類のボックスが一つしか存在し得ない場合などには `None` を指定する）。
+    /// [`Error::box_type`] が未設定の場合には `box_type` が設定される。
+    ///
+    /// 独自のボックスのデコード処理で、子ボックスのデコードエラーにパスの情報を追加するために使われる
+    pub fn add_context(mut self, box_type: BoxType, index: Option<usize>) -> Self {
         if self.box_type.is_none() {
             self.box_type = Some(box_type);
         }
+        self.box_path.insert(0, (box_type, index));
         self
     }
+
+    // 直前に [`Error::with_box_type()`] で追加されたボックスが、親の中で何番目のボックスかを設定する
+    pub(crate) fn with_box_index(mut self, index: usize) -> Self {
+        if let Some(x) = self.box_path.first_mut() {
+            x.1 = Some(index);
+        }
+        self
+    }
+
+    /// エラーが発生したボックスに至るまでのパスを `moov > trak[1] > mdia` のような形式の文字列で返す
+    pub fn box_path(&self) -> String {
+        self.box_path
+            .iter()
+            .map(|(ty, index)| match index {
+                Some(i) => format!("{ty}[{i}]"),
+                None => ty.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -65,6 +108,7 @@ impl From<std::io::Error> for Error {
             io_error: value,
             location: Some(std::panic::Location::caller()),
             box_type: None,
+            box_path: Vec::new(),
             backtrace: Backtrace::capture(),
         }
     }
@@ -84,7 +128,9 @@ impl std::fmt::Debug for Error {
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(ty) = self.box_type {
+        if !self.box_path.is_empty() {
+            write!(f, "[{}] ", self.box_path())?;
+        } else if let Some(ty) = self.box_type {
             write!(f, "[{ty}] ")?;
         }
 
@@ -106,6 +152,26 @@ impl std::fmt::Display for Error {
 pub trait Encode {
     /// `self` をバイト列に変換して `writer` に書き込む
     fn encode<W: Write>(&self, writer: W) -> Result<()>;
+
+    /// `self` をエンコードした際のバイト数を返す
+    ///
+    /// デフォルト実装では、バイト数を数えるだけのライターに対して [`Encode::encode()`] を実行する
+    /// （メモリの確保は行われないが、エンコード処理自体は走る）。
+    /// サイズを計算で求められる型では、より効率的な実装で上書きすることが推奨される。
+    fn encoded_size(&self) -> u64 {
+        ExternalBytes::calc(|writer| self.encode(writer))
+    }
+
+    /// `self` をバイト列に変換して、その結果を返す
+    ///
+    /// デフォルト実装では、エンコード処理を二重に行わないように、
+    /// 事前のサイズ計算はせずに [`Vec`] へ直接書き込む。
+    /// エンコードせずにサイズを求められる型では、事前に必要な分のメモリを確保する実装で上書きしてもいい。
+    fn encode_to_vec(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf)?;
+        Ok(buf)
+    }
 }
 
 impl Encode for u8 {
@@ -199,6 +265,46 @@ impl<T: Encode, const N: usize> Encode for [T; N] {
 pub trait Decode: Sized {
     /// `reader` から読み込んだバイト列から `Self` を構築する
     fn decode<R: Read>(reader: R) -> Result<Self>;
+
+    /// [`Decode::decode()`] と同様にデコードを行い、結果と合わせて `reader` から読み込んだバイト数を返す
+    ///
+    /// ファイルやソケットのようなストリームからボックスを一つずつ読み込む場合に、
+    /// 読み込み位置を把握するために使える
+    fn decode_with_size<R: Read>(reader: R) -> Result<(Self, u64)> {
+        let mut reader = ByteCountReader {
+            inner: reader,
+            count: 0,
+        };
+        let value = Self::decode(&mut reader)?;
+        Ok((value, reader.count))
+    }
+
+    /// `buf` が空になるまで [`Decode::decode()`] を繰り返し呼び出して、その結果を返す
+    ///
+    /// 同じ種類のボックス（例えば [`RootBox`](crate::boxes::RootBox)）が連続して並んでいるバイト列のデコードに使える
+    fn decode_all(buf: &[u8]) -> Result<Vec<Self>> {
+        let mut reader = buf;
+        let mut values = Vec::new();
+        while !reader.is_empty() {
+            values.push(Self::decode(&mut reader)?);
+        }
+        Ok(values)
+    }
+
+    /// `buf` 全体から一つの `Self` をデコードする
+    ///
+    /// デコード後に未読み込みのバイトが残っている場合にはエラーとなる
+    fn decode_exactly_one(buf: &[u8]) -> Result<Self> {
+        let mut reader = buf;
+        let value = Self::decode(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(Error::invalid_data(&format!(
+                "Unexpected trailing bytes: {} bytes",
+                reader.len()
+            )));
+        }
+        Ok(value)
+    }
 }
 
 impl Decode for u8 {
@@ -301,6 +407,225 @@ impl<T: Decode + Default + Copy, const N: usize> Decode for [T; N] {
     }
 }
 
+/// [`DecodeLenient::decode_with_options()`] でのデコード時の挙動を指定するためのオプション
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeOptions {
+    /// 仕様に準拠していないもののデコード自体は可能な入力を、エラーではなく警告として扱うかどうか
+    ///
+    /// 実際に出回っている MP4 ファイルには、細かい点で仕様に違反しているものの再生は可能なものが多いので、
+    /// そういったファイルを扱いたい場合には `true` を指定する
+    pub lenient: bool,
+}
+
+/// 寛容モードでのデコード中に検出された、仕様に準拠していない箇所についての警告
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DecodeWarning {
+    /// 警告の対象となったボックスに至るまでのパス（`moov > trak > mdia` のような形式）
+    pub box_path: String,
+
+    /// 警告の内容
+    pub message: String,
+}
+
+impl std::fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.box_path, self.message)
+    }
+}
+
+/// [`DecodeOptions`] を指定してデコードを行うためのトレイト
+///
+/// [`Decode`] を実装している全ての型で利用可能
+pub trait DecodeLenient: Decode {
+    /// オプションを指定して `buf` から `Self` をデコードする
+    ///
+    /// [`DecodeOptions::lenient`] が `true` の場合には、以下のような入力はエラーにはならずに、
+    /// 返り値の警告一覧にその旨が追加される:
+    /// - 一つしか存在してはいけないボックスの重複（先頭のものが使われる）
+    /// - `vmhd` ボックスのフラグが 1 以外
+    /// - 未知のフルボックスのバージョン（既知のバージョンとみなしてデコードを試みる）
+    /// - エントリーを一つも含まない `stsd` ボックス
+    ///
+    /// [`DecodeOptions::lenient`] が `false` の場合には [`Decode::decode()`] と同じ挙動となり、警告一覧は常に空になる
+    fn decode_with_options(
+        buf: &[u8],
+        options: DecodeOptions,
+    ) -> Result<(Self, Vec<DecodeWarning>)> {
+        if !options.lenient {
+            // 寛容モードでのデコード中に呼び出された場合でも、その設定を引き継がないようにする
+            let _guard = LenientDecodeGuard::enter(None);
+            return Ok((Self::decode(buf)?, Vec::new()));
+        }
+
+        let guard = LenientDecodeGuard::enter(Some(LenientDecodeContext::default()));
+        let value = Self::decode(buf)?;
+        Ok((value, guard.leave()))
+    }
+}
+
+impl<T: Decode> DecodeLenient for T {}
+
+/// ストリームからボックスを一つずつ読み込んでデコードするためのトレイト
+///
+/// [`Decode`] と [`BaseBox`] を実装している全ての型で利用可能
+pub trait DecodeRead: Decode + BaseBox {
+    /// `reader` からボックス一つ分のバイト列を読み込んでデコードし、結果と合わせて読み込んだバイト数を返す
+    ///
+    /// 最初にボックスヘッダーを読み込み、そこに記録されているサイズ分のペイロードを読み込んでからデコードを行う。
+    /// そのため `reader` からは、常にちょうどボックス一つ分のバイト列のみが読み込まれる。
+    /// ボックスのサイズが 0 （可変長）の場合には `reader` の終端までがペイロードとして読み込まれる。
+    ///
+    /// ペイロードの読み込みはサイズ分のメモリを事前に確保せずに行われるので、
+    /// 不正なサイズを含む入力を与えても、実際のデータ量を超えるメモリ確保は発生しない
+    fn decode_from_read<R: Read>(reader: &mut R) -> Result<(Self, u64)> {
+        let header = BoxHeader::decode(&mut *reader)?;
+        let mut bytes = Vec::new();
+        header.encode(&mut bytes)?;
+
+        if header.box_size.is_eof() {
+            reader.read_to_end(&mut bytes)?;
+        } else {
+            let payload_size = header
+                .box_size
+                .get()
+                .checked_sub(bytes.len() as u64)
+                .ok_or_else(|| {
+                    Error::invalid_data(&format!(
+                        "Too small box size: actual={}, expected={} or more",
+                        header.box_size.get(),
+                        bytes.len()
+                    ))
+                    .with_box_type(header.box_type)
+                })?;
+            let read_size = (&mut *reader).take(payload_size).read_to_end(&mut bytes)?;
+            if read_size as u64 != payload_size {
+                return Err(Error::from(std::io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("Unexpected EOF: expected={payload_size}, actual={read_size}"),
+                ))
+                .with_box_type(header.box_type));
+            }
+        }
+
+        let value = Self::decode(&bytes[..])?;
+        Ok((value, bytes.len() as u64))
+    }
+}
+
+impl<T: Decode + BaseBox> DecodeRead for T {}
+
+#[derive(Debug, Default)]
+struct LenientDecodeContext {
+    box_path: Vec<BoxType>,
+    warnings: Vec<DecodeWarning>,
+}
+
+thread_local! {
+    static LENIENT_DECODE_CONTEXT: RefCell<Option<LenientDecodeContext>> = const { RefCell::new(None) };
+}
+
+// プロセス全体で、寛容モードでのデコードが実行中のスレッドの数
+//
+// これが 0 の場合には、通常のデコード時にスレッドローカル変数へのアクセスを省略できる
+static LENIENT_DECODE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn lenient_decode_context_may_exist() -> bool {
+    LENIENT_DECODE_COUNT.load(Ordering::Relaxed) > 0
+}
+
+// デコード途中でエラーやパニックが発生した場合にも、確実にコンテキストを元に戻すためのガード
+struct LenientDecodeGuard {
+    previous: Option<LenientDecodeContext>,
+    lenient: bool,
+}
+
+impl LenientDecodeGuard {
+    fn enter(context: Option<LenientDecodeContext>) -> Self {
+        let lenient = context.is_some();
+        if lenient {
+            LENIENT_DECODE_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+        let previous = LENIENT_DECODE_CONTEXT.with(|c| c.replace(context));
+        Self { previous, lenient }
+    }
+
+    fn leave(self) -> Vec<DecodeWarning> {
+        LENIENT_DECODE_CONTEXT.with(|c| {
+            c.borrow_mut()
+                .as_mut()
+                .map(|c| std::mem::take(&mut c.warnings))
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl Drop for LenientDecodeGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        LENIENT_DECODE_CONTEXT.with(|c| c.replace(previous));
+        if self.lenient {
+            LENIENT_DECODE_COUNT.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// 寛容モードでのデコード中であれば、現在デコード中のボックスのパスに `box_type` を追加した上で `f` を呼び出す
+pub(crate) fn with_lenient_box_path<T>(box_type: BoxType, f: impl FnOnce() -> T) -> T {
+    if !lenient_decode_context_may_exist() {
+        return f();
+    }
+    let entered = LENIENT_DECODE_CONTEXT.with(|c| {
+        c.borrow_mut()
+            .as_mut()
+            .map(|c| c.box_path.push(box_type))
+            .is_some()
+    });
+    let value = f();
+    if entered {
+        LENIENT_DECODE_CONTEXT.with(|c| {
+            if let Some(c) = c.borrow_mut().as_mut() {
+                c.box_path.pop();
+            }
+        });
+    }
+    value
+}
+
+// 寛容モードでのデコード中であれば警告を追加する（それ以外の場合には何もしない）
+pub(crate) fn push_decode_warning<F>(f: F)
+where
+    F: FnOnce() -> String,
+{
+    if !lenient_decode_context_may_exist() {
+        return;
+    }
+    LENIENT_DECODE_CONTEXT.with(|c| {
+        if let Some(c) = c.borrow_mut().as_mut() {
+            let box_path = c
+                .box_path
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect::<Vec<_>>()
+                .join(" > ");
+            c.warnings.push(DecodeWarning {
+                box_path,
+                message: f(),
+            });
+        }
+    });
+}
+
+// 寛容モードでのデコード中であれば `error` を警告として扱い、それ以外の場合にはそのままエラーとして返す
+pub(crate) fn warn_if_lenient(error: Error) -> Result<()> {
+    let lenient =
+        lenient_decode_context_may_exist() && LENIENT_DECODE_CONTEXT.with(|c| c.borrow().is_some());
+    if !lenient {
+        return Err(error);
+    }
+    push_decode_warning(|| error.io_error.to_string());
+    Ok(())
+}
+
 #[derive(Debug, Default)]
 pub struct ExternalBytes(pub u64);
 
@@ -330,6 +655,20 @@ impl Write for ExternalBytes {
     }
 }
 
+#[derive(Debug)]
+struct ByteCountReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for ByteCountReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read_size = self.inner.read(buf)?;
+        self.count += read_size as u64;
+        Ok(read_size)
+    }
+}
+
 #[derive(Debug)]
 pub struct PeekReader<R, const N: usize> {
     buf: [u8; N],
@@ -357,10 +696,9 @@ impl<R: Read, const N: usize> PeekReader<R, N> {
 impl<R: Read, const N: usize> Read for PeekReader<R, N> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         if N < self.buf_start + buf.len() {
-            return Err(std::io::Error::new(
-                ErrorKind::Other,
-                format!("[BUG] Peek buffer exhausted: buffer_size={N}"),
-            ));
+            return Err(std::io::Error::other(format!(
+                "[BUG] Peek buffer exhausted: buffer_size={N}"
+            )));
         }
 
         let read_size = self
@@ -372,3 +710,81 @@ impl<R: Read, const N: usize> Read for PeekReader<R, N> {
         Ok(read_size)
     }
 }
+
+// MSB 優先でビット列を書き込むための簡易的なビットライター
+#[derive(Debug, Default)]
+pub(crate) struct BitWriter {
+    data: Vec<u8>,
+    bit_offset: usize,
+}
+
+impl BitWriter {
+    // 値の下位 `bits` ビットを書き込む
+    pub(crate) fn write(&mut self, value: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if self.bit_offset.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            if let Some(last) = self.data.last_mut() {
+                *last |= bit << (7 - self.bit_offset % 8);
+            }
+            self.bit_offset += 1;
+        }
+    }
+
+    // 末尾の端数ビットは 0 で埋められる
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+// バイト列を先頭から MSB 優先で読み進めるための簡易的なビットリーダー
+#[derive(Debug)]
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    bit_offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            bit_offset: 0,
+        }
+    }
+
+    // 32 ビット以下の値を読み込む（データが足りない場合は None を返す）
+    pub(crate) fn read(&mut self, bits: usize) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.data.get(self.bit_offset / 8)?;
+            let bit = (byte >> (7 - self.bit_offset % 8)) & 1;
+            value = (value << 1) | u32::from(bit);
+            self.bit_offset += 1;
+        }
+        Some(value)
+    }
+
+    // 符号なし指数ゴロム符号 (ue(v)) の値を読み込む
+    pub(crate) fn read_ue(&mut self) -> Option<u32> {
+        let mut leading_zeros = 0;
+        while self.read(1)? == 0 {
+            leading_zeros += 1;
+            if leading_zeros > 31 {
+                return None;
+            }
+        }
+        let suffix = self.read(leading_zeros)?;
+        ((1u64 << leading_zeros) - 1 + u64::from(suffix))
+            .try_into()
+            .ok()
+    }
+
+    // 符号付き指数ゴロム符号 (se(v)) の値を読み込む
+    pub(crate) fn read_se(&mut self) -> Option<i32> {
+        let v = i64::from(self.read_ue()?);
+        let v = if v % 2 == 1 { (v + 1) / 2 } else { -(v / 2) };
+        v.try_into().ok()
+    }
+}
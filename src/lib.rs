@@ -1,5 +1,7 @@
 //! MP4 のボックスのエンコードおよびデコードを行うためのライブラリ
 #![warn(missing_docs)]
+#[cfg(feature = "proptest_arbitrary")]
+mod arbitrary;
 pub mod aux;
 mod basic_types;
 pub mod boxes;
@@ -10,4 +12,6 @@ pub use basic_types::{
     BaseBox, BoxHeader, BoxSize, BoxType, Either, FixedPointNumber, FullBox, FullBoxFlags,
     FullBoxHeader, Mp4File, Mp4FileTime, Uint, Utf8String,
 };
-pub use io::{Decode, Encode, Error, Result};
+pub use io::{
+    Decode, DecodeLenient, DecodeOptions, DecodeRead, DecodeWarning, Encode, Error, Result,
+};
@@ -0,0 +1,183 @@
+use shiguredo_mp4::{
+    boxes::{Mp4aBox, RootBox, SampleEntry},
+    descriptors::{AacConfig, DecoderSpecificInfo},
+    Decode, Encode, Mp4File, Result,
+};
+
+fn to_aac_config(payload: &[u8]) -> Option<AacConfig> {
+    DecoderSpecificInfo {
+        payload: payload.to_vec(),
+    }
+    .to_aac_config()
+}
+
+#[test]
+fn aac_lc() {
+    // AAC-LC, 44100 Hz, ステレオ
+    assert_eq!(
+        to_aac_config(&[0x12, 0x10]),
+        Some(AacConfig {
+            object_type: AacConfig::OBJECT_TYPE_AAC_LC,
+            sample_rate_index: 4,
+            channel_config: 2,
+            sample_rate: 44100,
+            channel_count: 2,
+        })
+    );
+
+    // サンプリング周波数が明示的に指定されている場合
+    assert_eq!(
+        to_aac_config(&[0x17, 0x80, 0x56, 0x22, 0x10]),
+        Some(AacConfig {
+            object_type: AacConfig::OBJECT_TYPE_AAC_LC,
+            sample_rate_index: 0xF,
+            channel_config: 2,
+            sample_rate: 44100,
+            channel_count: 2,
+        })
+    );
+}
+
+#[test]
+fn he_aac_v1() {
+    // コアは 24000 Hz で、SBR によって 48000 Hz で出力される
+    assert_eq!(
+        to_aac_config(&[0x2B, 0x11, 0x88, 0x00]),
+        Some(AacConfig {
+            object_type: AacConfig::OBJECT_TYPE_SBR,
+            sample_rate_index: 6,
+            channel_config: 2,
+            sample_rate: 48000,
+            channel_count: 2,
+        })
+    );
+}
+
+#[test]
+fn he_aac_v2() {
+    // コアはモノラルだが、PS によってステレオで出力される
+    assert_eq!(
+        to_aac_config(&[0xEB, 0x09, 0x88, 0x00]),
+        Some(AacConfig {
+            object_type: AacConfig::OBJECT_TYPE_PS,
+            sample_rate_index: 6,
+            channel_config: 1,
+            sample_rate: 48000,
+            channel_count: 2,
+        })
+    );
+}
+
+#[test]
+fn invalid_config() {
+    assert_eq!(to_aac_config(&[]), None);
+    assert_eq!(to_aac_config(&[0x12]), None);
+
+    // 予約済みのサンプリング周波数インデックス (13)
+    assert_eq!(to_aac_config(&[0x16, 0x90]), None);
+}
+
+#[test]
+fn aac_config_of_real_file() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/beep-aac-audio.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let sample_entry = file
+        .boxes
+        .iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => b.trak_boxes[0].sample_entry().cloned(),
+            _ => None,
+        })
+        .expect("no sample entry");
+    let SampleEntry::Mp4a(mp4a) = sample_entry else {
+        panic!("not mp4a");
+    };
+
+    let config = mp4a
+        .esds_box
+        .es
+        .dec_config_descr
+        .dec_specific_info
+        .to_aac_config()
+        .expect("invalid AudioSpecificConfig");
+    assert_eq!(config.object_type, AacConfig::OBJECT_TYPE_AAC_LC);
+    assert_eq!(config.sample_rate, 44100);
+    assert_eq!(config.channel_count, 1);
+    Ok(())
+}
+
+#[test]
+fn encode_aac_config() {
+    // 既知の AudioSpecificConfig と一致する
+    let cases = [
+        (AacConfig::for_aac_lc(44100, 2), &[0x12, 0x10][..]),
+        (AacConfig::for_aac_lc(48000, 1), &[0x11, 0x88]),
+        (
+            AacConfig::for_he_aac_v1(48000, 2),
+            &[0x2B, 0x11, 0x88, 0x00],
+        ),
+        (AacConfig::for_he_aac_v2(48000), &[0xEB, 0x09, 0x88, 0x00]),
+    ];
+    for (config, expected) in cases {
+        let info = config.to_decoder_specific_info();
+        assert_eq!(info.payload, expected);
+        assert_eq!(info.to_aac_config(), Some(config));
+    }
+
+    // 標準外のサンプリングレートは明示的に指定される
+    let config = AacConfig::for_aac_lc(44000, 2);
+    assert_eq!(config.sample_rate_index, 0xF);
+    let info = config.to_decoder_specific_info();
+    assert_eq!(info.payload[..2], [0x17, 0x80]);
+    assert_eq!(info.to_aac_config(), Some(config));
+
+    let config = AacConfig::for_he_aac_v1(44000, 2);
+    assert_eq!(
+        config.to_decoder_specific_info().to_aac_config(),
+        Some(config)
+    );
+}
+
+#[test]
+fn mp4a_box_from_aac_config() -> Result<()> {
+    let config = AacConfig::for_aac_lc(44100, 1);
+    let mp4a_box = Mp4aBox::from_aac_config(&config);
+    assert_eq!(mp4a_box.audio.channelcount, 1);
+    assert_eq!(mp4a_box.audio.samplerate.integer, 44100);
+
+    let entry = SampleEntry::Mp4a(mp4a_box);
+    let bytes = entry.encode_to_vec()?;
+    let decoded = SampleEntry::decode(&bytes[..])?;
+    assert_eq!(decoded, entry);
+    assert_eq!(decoded.sample_rate(), Some(44100));
+    assert_eq!(decoded.channel_count(), Some(1));
+
+    let SampleEntry::Mp4a(b) = decoded else {
+        panic!("not mp4a");
+    };
+    assert_eq!(
+        b.esds_box
+            .es
+            .dec_config_descr
+            .dec_specific_info
+            .to_aac_config(),
+        Some(config)
+    );
+
+    // u16 に収まらないサンプリングレート
+    let mp4a_box = Mp4aBox::from_aac_config(&AacConfig::for_aac_lc(96000, 2));
+    assert_eq!(mp4a_box.audio.samplerate.integer, 0);
+    Ok(())
+}
+
+#[test]
+fn decode_decoder_specific_info_with_too_large_size() -> Result<()> {
+    let info = DecoderSpecificInfo::decode(&[0x05, 0x02, 0x12, 0x10][..])?;
+    assert_eq!(info.payload, [0x12, 0x10]);
+
+    // 記録されているサイズ（約 256 MB）に対して実際のペイロードが足りない
+    // （サイズ分のメモリを事前に確保することなくエラーになる）
+    let bytes = [0x05, 0xFF, 0xFF, 0xFF, 0x7F, 0x12, 0x10];
+    assert!(DecoderSpecificInfo::decode(&bytes[..]).is_err());
+    Ok(())
+}
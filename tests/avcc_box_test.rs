@@ -0,0 +1,211 @@
+use shiguredo_mp4::{
+    boxes::{AvccBox, AvccBoxBuilder, RootBox, SampleEntry},
+    Decode, Encode, Mp4File, Result, Uint,
+};
+
+fn avcc_box_with_sps(sps: &[u8]) -> AvccBox {
+    AvccBox {
+        avc_profile_indication: sps[1],
+        profile_compatibility: sps[2],
+        avc_level_indication: sps[3],
+        length_size_minus_one: Uint::new(3),
+        sps_list: vec![sps.to_vec()],
+        pps_list: Vec::new(),
+        chroma_format: Some(Uint::new(1)),
+        bit_depth_luma_minus8: Some(Uint::new(0)),
+        bit_depth_chroma_minus8: Some(Uint::new(0)),
+        sps_ext_list: Vec::new(),
+    }
+}
+
+// x264 が出力する 1920x1080 (High プロファイル、レベル 4.0) の SPS
+//
+// マクロブロック単位では 1920x1088 となり、下端の 8 ピクセルがクロッピングされる
+const SPS_1080P: [u8; 27] = [
+    0x67, 0x64, 0x00, 0x28, 0xAC, 0xD9, 0x40, 0x78, 0x02, 0x27, 0xE5, 0xC0, 0x44, 0x00, 0x00, 0x03,
+    0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0xC8, 0x3C, 0x60, 0xC6, 0x58,
+];
+
+#[test]
+fn parse_sps_resolution_with_cropping() {
+    let avcc_box = avcc_box_with_sps(&SPS_1080P);
+    assert_eq!(avcc_box.parse_sps_resolution(), Some((1920, 1080)));
+}
+
+#[test]
+fn parse_sps_resolution_of_real_file() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let sample_entry = file
+        .boxes
+        .iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => b.trak_boxes[0].sample_entry().cloned(),
+            _ => None,
+        })
+        .expect("no sample entry");
+    let SampleEntry::Avc1(mut avc1) = sample_entry else {
+        panic!("not avc1");
+    };
+    assert_eq!(avc1.true_resolution(), Some((640, 480)));
+
+    // サンプルエントリー側の値が不正でも、SPS から正しい解像度が得られる
+    avc1.visual.width = 0;
+    avc1.visual.height = 0;
+    assert_eq!(avc1.true_resolution(), Some((640, 480)));
+    Ok(())
+}
+
+#[test]
+fn parse_sps_resolution_failure() {
+    // SPS がない
+    let mut avcc_box = avcc_box_with_sps(&SPS_1080P);
+    avcc_box.sps_list.clear();
+    assert_eq!(avcc_box.parse_sps_resolution(), None);
+
+    // SPS 以外の NAL ユニット (PPS)
+    let mut pps = SPS_1080P;
+    pps[0] = 0x68;
+    assert_eq!(avcc_box_with_sps(&pps).parse_sps_resolution(), None);
+
+    // 途中で途切れている
+    assert_eq!(
+        avcc_box_with_sps(&SPS_1080P[..8]).parse_sps_resolution(),
+        None
+    );
+
+    // スケーリングリスト内の delta_scale が範囲外 (i32::MAX)
+    //
+    // High プロファイルで seq_scaling_matrix_present_flag と先頭の seq_scaling_list_present_flag が 1 になっている
+    let malformed_sps = [
+        0x67, 0x64, 0x00, 0x28, 0xAD, 0x80, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFE, 0x80,
+    ];
+    assert_eq!(
+        avcc_box_with_sps(&malformed_sps).parse_sps_resolution(),
+        None
+    );
+}
+
+// 1280x720 (Baseline プロファイル、レベル 3.1) の SPS と PPS
+const SPS_720P_BASELINE: [u8; 9] = [0x67, 0x42, 0xC0, 0x1F, 0x56, 0x80, 0x50, 0x05, 0xB9];
+const PPS: [u8; 4] = [0x68, 0xCE, 0x3C, 0x80];
+
+fn assert_round_trip(avcc_box: &AvccBox) -> Result<()> {
+    let bytes = avcc_box.encode_to_vec()?;
+    assert_eq!(AvccBox::decode(&bytes[..])?, *avcc_box);
+    Ok(())
+}
+
+#[test]
+fn builder_baseline_profile() -> Result<()> {
+    let avcc_box = AvccBoxBuilder::new(66, 0xC0, 31)
+        .add_sps(SPS_720P_BASELINE.to_vec())
+        .add_pps(PPS.to_vec())
+        .build()?;
+    assert_eq!(avcc_box.avc_profile_indication, 66);
+    assert_eq!(avcc_box.profile_compatibility, 0xC0);
+    assert_eq!(avcc_box.avc_level_indication, 31);
+    assert_eq!(avcc_box.length_size_minus_one.get(), 3);
+    assert_eq!(avcc_box.sps_list, [SPS_720P_BASELINE.to_vec()]);
+    assert_eq!(avcc_box.pps_list, [PPS.to_vec()]);
+    assert_eq!(avcc_box.chroma_format, None);
+    assert_eq!(avcc_box.parse_sps_resolution(), Some((1280, 720)));
+    assert_round_trip(&avcc_box)?;
+
+    // High プロファイル用のフィールドは指定できない
+    assert!(AvccBoxBuilder::new(66, 0xC0, 31)
+        .chroma_format(1)
+        .build()
+        .is_err());
+    assert!(AvccBoxBuilder::new(66, 0xC0, 31)
+        .bit_depth(8, 8)
+        .build()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn builder_main_profile() -> Result<()> {
+    let avcc_box = AvccBoxBuilder::new(77, 0x40, 40)
+        .length_size(2)?
+        .add_sps(vec![0x67, 0x4D, 0x40, 0x28])
+        .add_pps(PPS.to_vec())
+        .build()?;
+    assert_eq!(avcc_box.length_size_minus_one.get(), 1);
+    assert_round_trip(&avcc_box)?;
+
+    // 長さフィールドのサイズは 1、2、4 のいずれか
+    assert!(AvccBoxBuilder::new(77, 0x40, 40).length_size(1).is_ok());
+    assert!(AvccBoxBuilder::new(77, 0x40, 40).length_size(3).is_err());
+    assert!(AvccBoxBuilder::new(77, 0x40, 40).length_size(0).is_err());
+
+    // SPS と PPS の数の上限
+    let builder = (0..=AvccBoxBuilder::MAX_SPS_COUNT)
+        .fold(AvccBoxBuilder::new(77, 0x40, 40), |b, _| {
+            b.add_sps(vec![0x67])
+        });
+    assert!(builder.build().is_err());
+    let builder = (0..AvccBoxBuilder::MAX_SPS_COUNT)
+        .fold(AvccBoxBuilder::new(77, 0x40, 40), |b, _| {
+            b.add_sps(vec![0x67])
+        });
+    assert!(builder.build().is_ok());
+    let builder = (0..=AvccBoxBuilder::MAX_PPS_COUNT)
+        .fold(AvccBoxBuilder::new(77, 0x40, 40), |b, _| {
+            b.add_pps(vec![0x68])
+        });
+    assert!(builder.build().is_err());
+    Ok(())
+}
+
+#[test]
+fn builder_high_profile() -> Result<()> {
+    // High プロファイル用のフィールドが必須
+    let builder = AvccBoxBuilder::new(100, 0x00, 40)
+        .add_sps(SPS_1080P.to_vec())
+        .add_pps(PPS.to_vec());
+    assert!(builder.clone().build().is_err());
+    assert!(builder.clone().chroma_format(1).build().is_err());
+    assert!(builder.clone().bit_depth(8, 8).build().is_err());
+
+    let avcc_box = builder.chroma_format(1).bit_depth(8, 8).build()?;
+    assert_eq!(avcc_box.chroma_format, Some(Uint::new(1)));
+    assert_eq!(avcc_box.bit_depth_luma_minus8, Some(Uint::new(0)));
+    assert_eq!(avcc_box.bit_depth_chroma_minus8, Some(Uint::new(0)));
+    assert_eq!(avcc_box.parse_sps_resolution(), Some((1920, 1080)));
+    assert_round_trip(&avcc_box)?;
+    Ok(())
+}
+
+#[test]
+fn builder_high444_profile() -> Result<()> {
+    let avcc_box = AvccBoxBuilder::new(244, 0x00, 51)
+        .add_sps(vec![0x67, 0xF4, 0x00, 0x33])
+        .add_pps(PPS.to_vec())
+        .add_sps_ext(vec![0x6D, 0x00])
+        .chroma_format(3)
+        .bit_depth(10, 10)
+        .build()?;
+    assert_eq!(avcc_box.chroma_format, Some(Uint::new(3)));
+    assert_eq!(avcc_box.bit_depth_luma_minus8, Some(Uint::new(2)));
+    assert_eq!(avcc_box.bit_depth_chroma_minus8, Some(Uint::new(2)));
+    assert_eq!(avcc_box.sps_ext_list, [vec![0x6D, 0x00]]);
+    assert_round_trip(&avcc_box)?;
+
+    // 範囲外の値
+    let builder = AvccBoxBuilder::new(244, 0x00, 51);
+    assert!(builder
+        .clone()
+        .chroma_format(4)
+        .bit_depth(10, 10)
+        .build()
+        .is_err());
+    assert!(builder
+        .clone()
+        .chroma_format(3)
+        .bit_depth(7, 8)
+        .build()
+        .is_err());
+    assert!(builder.chroma_format(3).bit_depth(8, 16).build().is_err());
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use shiguredo_mp4::{BoxHeader, BoxSize, BoxType, Encode};
+
+const FREE: BoxType = BoxType::Normal(*b"free");
+const UUID: BoxType = BoxType::Uuid([0; 16]);
+
+#[test]
+fn with_payload_size_32bit_boundary() {
+    // ヘッダー (8 バイト) を含めてちょうど u32::MAX バイトになる場合は 32 ビットで表現される
+    let payload_size = u64::from(u32::MAX) - 8;
+    assert_eq!(payload_size, 4294967287);
+    assert_eq!(
+        BoxSize::with_payload_size(FREE, payload_size),
+        BoxSize::U32(u32::MAX)
+    );
+
+    // 1 バイトでも超える場合には 64 ビットになり、ヘッダーに 8 バイトが追加される
+    let payload_size = u64::from(u32::MAX) - 7;
+    assert_eq!(
+        BoxSize::with_payload_size(FREE, payload_size),
+        BoxSize::U64(payload_size + 16)
+    );
+    assert_eq!(
+        BoxSize::with_payload_size(FREE, u64::from(u32::MAX) - 6),
+        BoxSize::U64(u64::from(u32::MAX) - 6 + 16)
+    );
+
+    // UUID 形式のボックス種別の場合には、ヘッダーが 16 バイト大きくなる
+    let payload_size = u64::from(u32::MAX) - 24;
+    assert_eq!(
+        BoxSize::with_payload_size(UUID, payload_size),
+        BoxSize::U32(u32::MAX)
+    );
+    assert_eq!(
+        BoxSize::with_payload_size(UUID, payload_size + 1),
+        BoxSize::U64(payload_size + 1 + 32)
+    );
+}
+
+#[test]
+fn with_payload_size_64bit_limit() {
+    // ヘッダー (16 バイト) を含めてちょうど u64::MAX バイトになる
+    assert_eq!(
+        BoxSize::with_payload_size(FREE, u64::MAX - 16),
+        BoxSize::U64(u64::MAX)
+    );
+    assert_eq!(
+        BoxSize::checked_with_payload_size(FREE, u64::MAX - 16),
+        Some(BoxSize::U64(u64::MAX))
+    );
+
+    // それを超える場合にはオーバーフローする
+    assert_eq!(
+        BoxSize::checked_with_payload_size(FREE, u64::MAX - 15),
+        None
+    );
+    assert_eq!(BoxSize::checked_with_payload_size(FREE, u64::MAX), None);
+    assert_eq!(
+        BoxSize::checked_with_payload_size(UUID, u64::MAX - 31),
+        None
+    );
+}
+
+#[test]
+#[should_panic]
+fn with_payload_size_overflow() {
+    BoxSize::with_payload_size(FREE, u64::MAX);
+}
+
+#[test]
+fn box_header_new_with_size() {
+    let header = BoxHeader::new_with_size(FREE, 8).expect("valid size");
+    assert_eq!(header.box_size, BoxSize::U32(8));
+    assert_eq!(
+        header.encode_to_vec().expect("encode"),
+        b"\x00\x00\x00\x08free"
+    );
+
+    // ヘッダー自体のサイズよりも小さい場合はエラーになる
+    assert!(BoxHeader::new_with_size(FREE, 7).is_err());
+    assert!(BoxHeader::new_with_size(UUID, 23).is_err());
+    assert!(BoxHeader::new_with_size(UUID, 24).is_ok());
+
+    // 64 ビット版では、値が小さくてもサイズは 64 ビットで表現される
+    let header = BoxHeader::new_with_size_u64(FREE, 16).expect("valid size");
+    assert_eq!(header.box_size, BoxSize::U64(16));
+    assert_eq!(header.external_size(), 16);
+    assert_eq!(
+        header.encode_to_vec().expect("encode"),
+        b"\x00\x00\x00\x01free\x00\x00\x00\x00\x00\x00\x00\x10"
+    );
+    assert!(BoxHeader::new_with_size_u64(FREE, 15).is_err());
+}
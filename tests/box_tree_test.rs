@@ -0,0 +1,51 @@
+use shiguredo_mp4::{aux::BoxTreeNode, boxes::RootBox, BaseBox, Decode, Mp4File, Result};
+
+#[test]
+fn box_tree_from_root() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File<RootBox> = Mp4File::decode(&input_bytes[..])?;
+    let moov_box = file.find_moov().expect("no moov box");
+
+    let node = BoxTreeNode::from_root(moov_box);
+    assert_eq!(node.box_type, "moov");
+    assert_eq!(node.size, moov_box.box_size().get());
+    assert!(!node.is_unknown);
+    assert_eq!(
+        node.children
+            .iter()
+            .map(|c| c.box_type.as_str())
+            .collect::<Vec<_>>(),
+        moov_box
+            .children()
+            .map(|c| c.box_type().to_string())
+            .collect::<Vec<_>>()
+    );
+
+    // 子ボックスのサイズの合計は、親ボックスのペイロードサイズと一致する
+    let children_size = node.children.iter().map(|c| c.size).sum::<u64>();
+    assert_eq!(children_size, moov_box.box_payload_size());
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn box_tree_json() -> Result<()> {
+    use shiguredo_mp4::aux::mp4_box_tree_json;
+
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let json = mp4_box_tree_json(input_bytes)?;
+    assert!(json.starts_with('['));
+    assert!(json.contains(r#""type": "ftyp""#));
+    assert!(json.contains(r#""type": "stsd""#));
+
+    // 既知のボックスには unknown フィールドが付与されない
+    let node = BoxTreeNode::from_root(&shiguredo_mp4::boxes::FreeBox {
+        payload: vec![0; 4],
+    });
+    let json = node.to_json().expect("failed to serialize");
+    assert_eq!(json, "{\n  \"type\": \"free\",\n  \"size\": 12\n}");
+
+    // 不正なデータの場合はエラーになる
+    assert!(mp4_box_tree_json(&input_bytes[..100]).is_err());
+    Ok(())
+}
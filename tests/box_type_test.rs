@@ -0,0 +1,37 @@
+use shiguredo_mp4::BoxType;
+
+#[test]
+fn display_printable_ascii() {
+    assert_eq!(BoxType::Normal(*b"moov").to_string(), "moov");
+    assert_eq!(BoxType::Normal(*b"url ").to_string(), "url ");
+    assert!(!BoxType::Normal(*b"moov").is_copyright_tag());
+}
+
+#[test]
+fn display_copyright_tag() {
+    let ty = BoxType::Normal([0xA9, b'n', b'a', b'm']);
+    assert!(ty.is_copyright_tag());
+    assert_eq!(ty.to_string(), "©nam");
+    assert_eq!(BoxType::Normal(*b"\xA9ART").to_string(), "©ART");
+
+    // 先頭以外に表示できないバイトが含まれる場合は 16 進数表記になる
+    let ty = BoxType::Normal([0xA9, b'n', 0x00, b'm']);
+    assert!(ty.is_copyright_tag());
+    assert_eq!(ty.to_string(), "[a9 6e 00 6d]");
+}
+
+#[test]
+fn display_hex() {
+    assert_eq!(
+        BoxType::Normal([0x00, 0x01, 0xFF, 0x7F]).to_string(),
+        "[00 01 ff 7f]"
+    );
+
+    // UTF-8 として妥当でも、ASCII 以外の文字を含む場合は 16 進数表記になる
+    assert_eq!(BoxType::Normal(*b"\xC3\xA9ab").to_string(), "[c3 a9 61 62]");
+
+    // UUID 形式の種別は copyright タグにはならない
+    let mut uuid = [0; 16];
+    uuid[0] = 0xA9;
+    assert!(!BoxType::Uuid(uuid).is_copyright_tag());
+}
@@ -0,0 +1,191 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::{
+    aux::{SampleIter, SampleTableAccessor, SampleTableAccessorError},
+    boxes::{CttsBox, CttsEntry, RootBox, StblBox, StcoBox, StscBox, StscEntry, StszBox, SttsBox},
+    BoxType, Decode, Either, Encode, Mp4File, Result,
+};
+
+fn ctts_box_bytes(version: u8, entries: &[(u32, i32)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(16 + 8 * entries.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(b"ctts");
+    bytes.extend_from_slice(&[version, 0, 0, 0]);
+    bytes.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (sample_count, sample_offset) in entries {
+        bytes.extend_from_slice(&sample_count.to_be_bytes());
+        bytes.extend_from_slice(&sample_offset.to_be_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn decode_encode_version0() -> Result<()> {
+    let bytes = ctts_box_bytes(0, &[(2, 1024), (1, 0)]);
+    let ctts_box = CttsBox::decode(&bytes[..])?;
+    assert_eq!(
+        ctts_box.entries,
+        [
+            CttsEntry {
+                sample_count: 2,
+                sample_offset: 1024
+            },
+            CttsEntry {
+                sample_count: 1,
+                sample_offset: 0
+            }
+        ]
+    );
+    assert_eq!(
+        ctts_box.sample_offsets().collect::<Vec<_>>(),
+        [1024, 1024, 0]
+    );
+    assert_eq!(ctts_box.encode_to_vec()?, bytes);
+    Ok(())
+}
+
+#[test]
+fn decode_encode_version1() -> Result<()> {
+    // 負のオフセットが含まれる場合はバージョン 1 でエンコードされる
+    let bytes = ctts_box_bytes(1, &[(1, 512), (1, -512)]);
+    let ctts_box = CttsBox::decode(&bytes[..])?;
+    assert_eq!(ctts_box.sample_offsets().collect::<Vec<_>>(), [512, -512]);
+    assert_eq!(ctts_box.encode_to_vec()?, bytes);
+
+    // バージョン 0 でも、負の値はそのまま符号付き整数として解釈される
+    let bytes = ctts_box_bytes(0, &[(1, -512)]);
+    let ctts_box = CttsBox::decode(&bytes[..])?;
+    assert_eq!(ctts_box.sample_offsets().collect::<Vec<_>>(), [-512]);
+    Ok(())
+}
+
+// I P B B の 4 フレームからなる映像トラックのサンプルテーブルを作る
+//
+// デコード順は I(0) P(3) B(1) B(2) で、表示順に並べるためにコンポジションオフセットが付与される
+fn stbl_box_with_ctts() -> Result<StblBox> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let mut stbl_box = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box")
+        .trak_boxes
+        .swap_remove(0)
+        .mdia_box
+        .minf_box
+        .stbl_box;
+
+    stbl_box.stts_box = SttsBox::from_sample_deltas([512; 4]);
+    stbl_box.stsc_box = StscBox {
+        entries: vec![StscEntry {
+            first_chunk: NonZeroU32::MIN,
+            sample_per_chunk: 4,
+            sample_description_index: NonZeroU32::MIN,
+        }],
+    };
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: vec![100, 50, 10, 10],
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: vec![48],
+    });
+    stbl_box.stss_box = None;
+
+    // 一度エンコードしたものをデコードして、ctts ボックスが専用のフィールドに格納されることを確認する
+    let bytes = ctts_box_bytes(0, &[(1, 512), (1, 1536), (2, 0)]);
+    stbl_box.unknown_boxes.clear();
+    stbl_box.ctts_box = None;
+    let mut stbl_bytes = stbl_box.encode_to_vec()?;
+    stbl_bytes.extend_from_slice(&bytes);
+    let size = stbl_bytes.len() as u32;
+    stbl_bytes[..4].copy_from_slice(&size.to_be_bytes());
+
+    let stbl_box = StblBox::decode(&stbl_bytes[..])?;
+    assert!(stbl_box.unknown_boxes.is_empty());
+    assert!(stbl_box.ctts_box.is_some());
+    Ok(stbl_box)
+}
+
+#[test]
+fn composition_offsets() -> Result<()> {
+    let stbl_box = stbl_box_with_ctts()?;
+    stbl_box.validate().expect("invalid stbl box");
+
+    let expected_offsets = [512, 1536, 0, 0];
+    let expected_pts = [512, 2048, 1024, 1536];
+
+    let sample_table = SampleTableAccessor::new(&stbl_box).expect("invalid sample table");
+    let offsets = sample_table
+        .samples()
+        .map(|s| s.composition_offset())
+        .collect::<Vec<_>>();
+    let pts = sample_table
+        .samples()
+        .map(|s| s.presentation_timestamp())
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, expected_offsets);
+    assert_eq!(pts, expected_pts);
+
+    let samples = SampleIter::new(&stbl_box)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .expect("invalid sample table");
+    let offsets = samples
+        .iter()
+        .map(|s| s.composition_offset)
+        .collect::<Vec<_>>();
+    let pts = samples
+        .iter()
+        .map(|s| s.presentation_timestamp())
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, expected_offsets);
+    assert_eq!(pts, expected_pts);
+
+    Ok(())
+}
+
+#[test]
+fn no_ctts_box() -> Result<()> {
+    let mut stbl_box = stbl_box_with_ctts()?;
+    stbl_box.ctts_box = None;
+
+    // ctts ボックスがない場合は、オフセットは 0 で表示時刻とデコード時刻が一致する
+    let sample_table = SampleTableAccessor::new(&stbl_box).expect("invalid sample table");
+    for sample in sample_table.samples() {
+        assert_eq!(sample.composition_offset(), 0);
+        assert_eq!(sample.presentation_timestamp(), sample.timestamp() as i64);
+    }
+    for sample in SampleIter::new(&stbl_box) {
+        let sample = sample.expect("invalid sample table");
+        assert_eq!(sample.composition_offset, 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn inconsistent_sample_count() -> Result<()> {
+    let mut stbl_box = stbl_box_with_ctts()?;
+    stbl_box.ctts_box = Some(CttsBox {
+        entries: vec![CttsEntry {
+            sample_count: 3,
+            sample_offset: 0,
+        }],
+    });
+
+    assert!(stbl_box.validate().is_err());
+    assert!(matches!(
+        SampleTableAccessor::new(&stbl_box),
+        Err(SampleTableAccessorError::InconsistentSampleCount {
+            other_box_type: BoxType::Normal(b),
+            other_sample_count: 3,
+            ..
+        }) if &b == b"ctts"
+    ));
+    let results = SampleIter::new(&stbl_box).collect::<Vec<_>>();
+    assert_eq!(results.len(), 4);
+    assert!(results[3].is_err());
+    Ok(())
+}
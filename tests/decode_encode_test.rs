@@ -1,4 +1,11 @@
-use shiguredo_mp4::{BoxType, Decode, Encode, Mp4File, Result};
+use shiguredo_mp4::{
+    boxes::{
+        Brand, Co64Box, FastRootBox, FileTypeBoxContent, FreeBox, FtypBox, IgnoredBox, MdatBox,
+        MdatBoxRef, MediaInfoHeader, MinfBox, NmhdBox, PrftBox, RootBox, RootBoxRef, StcoBox,
+        StscBox, StssBox, StszBox, SttsBox, StypBox, UnknownBox,
+    },
+    BaseBox, BoxHeader, BoxSize, BoxType, Decode, DecodeRead, Either, Encode, Mp4File, Result,
+};
 
 #[test]
 fn decode_encode_black_h264_video_mp4() -> Result<()> {
@@ -153,6 +160,528 @@ fn decode_encode_beep_opus_audio_aac() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn validate_moov_box() -> Result<()> {
+    let inputs: [&[u8]; 6] = [
+        include_bytes!("testdata/black-h264-video.mp4"),
+        include_bytes!("testdata/black-h265-video.mp4"),
+        include_bytes!("testdata/black-vp9-video.mp4"),
+        include_bytes!("testdata/black-av1-video.mp4"),
+        include_bytes!("testdata/beep-opus-audio.mp4"),
+        include_bytes!("testdata/beep-aac-audio.mp4"),
+    ];
+    for input_bytes in inputs {
+        let file: Mp4File = Mp4File::decode(input_bytes)?;
+        for b in &file.boxes {
+            if let RootBox::Moov(moov_box) = b {
+                assert_eq!(moov_box.validate(), Ok(()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn encoded_size() -> Result<()> {
+    let inputs: [&[u8]; 6] = [
+        include_bytes!("testdata/black-h264-video.mp4"),
+        include_bytes!("testdata/black-h265-video.mp4"),
+        include_bytes!("testdata/black-vp9-video.mp4"),
+        include_bytes!("testdata/black-av1-video.mp4"),
+        include_bytes!("testdata/beep-opus-audio.mp4"),
+        include_bytes!("testdata/beep-aac-audio.mp4"),
+    ];
+    for input_bytes in inputs {
+        let file: Mp4File = Mp4File::decode(input_bytes)?;
+
+        let mut output_bytes = Vec::new();
+        file.encode(&mut output_bytes)?;
+        assert_eq!(file.encoded_size(), output_bytes.len() as u64);
+
+        let vec = file.encode_to_vec()?;
+        assert_eq!(vec, output_bytes);
+        assert_eq!(vec.capacity(), vec.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn encode_to_vec_encodes_once() -> Result<()> {
+    struct Counted(std::cell::Cell<usize>);
+
+    impl Encode for Counted {
+        fn encode<W: std::io::Write>(&self, mut writer: W) -> Result<()> {
+            self.0.set(self.0.get() + 1);
+            writer.write_all(b"test")?;
+            Ok(())
+        }
+    }
+
+    let value = Counted(std::cell::Cell::new(0));
+    assert_eq!(value.encode_to_vec()?, b"test");
+    assert_eq!(value.0.get(), 1);
+    Ok(())
+}
+
+#[test]
+fn decode_with_size() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/beep-opus-audio.mp4");
+    let (file, size) = Mp4File::<RootBox>::decode_with_size(&input_bytes[..])?;
+    assert_eq!(size, input_bytes.len() as u64);
+
+    // ボックス単位で読み込んだ場合にも、各ボックスのサイズと読み込みバイト数が一致する
+    let mut reader = &input_bytes[..];
+    let (ftyp_box, size) = FtypBox::decode_with_size(&mut reader)?;
+    assert_eq!(ftyp_box, file.ftyp_box);
+    assert_eq!(size, ftyp_box.box_size().get());
+    for expected in &file.boxes {
+        let (b, size) = RootBox::decode_with_size(&mut reader)?;
+        assert_eq!(&b, expected);
+        assert_eq!(size, b.box_size().get());
+    }
+    assert!(reader.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn decode_from_file() -> Result<()> {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/testdata/black-h264-video.mp4"
+    );
+    let file = std::fs::File::open(path)?;
+    let decoded: Mp4File = Mp4File::decode(std::io::BufReader::new(file))?;
+
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let expected: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    assert_eq!(decoded, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_from_read() -> Result<()> {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/testdata/black-h264-video.mp4"
+    );
+    let mut file = std::fs::File::open(path)?;
+    let decoded: Mp4File = Mp4File::decode_from_read(&mut file)?;
+
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let expected: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    assert_eq!(decoded, expected);
+
+    // ボックス単位で読み込んだ場合には、ちょうどボックス一つ分だけが読み込まれる
+    let mut reader = &input_bytes[..];
+    let (ftyp_box, size) = FtypBox::decode_from_read(&mut reader)?;
+    assert_eq!(ftyp_box, expected.ftyp_box);
+    assert_eq!(size, ftyp_box.box_size().get());
+    for b in &expected.boxes {
+        let (decoded, size) = RootBox::decode_from_read(&mut reader)?;
+        assert_eq!(&decoded, b);
+        assert_eq!(size, b.box_size().get());
+    }
+    assert!(reader.is_empty());
+
+    // サイズが 0 のボックスは終端までがペイロードとなる
+    let mut bytes = expected.ftyp_box.encode_to_vec()?;
+    bytes.extend_from_slice(&[0, 0, 0, 0, b'm', b'd', b'a', b't', 1, 2, 3]);
+    let decoded: Mp4File = Mp4File::decode_from_read(&mut std::io::Cursor::new(&bytes))?;
+    assert_eq!(
+        decoded.boxes,
+        [RootBox::Mdat(MdatBox {
+            is_variable_size: true,
+            payload: vec![1, 2, 3],
+        })]
+    );
+
+    // ペイロードが途中で途切れている
+    let truncated = [0, 0, 0, 100, b'm', b'd', b'a', b't', 1, 2, 3];
+    assert!(RootBox::decode_from_read(&mut &truncated[..]).is_err());
+
+    // 64 ビットのサイズ表現で 0 が指定されている
+    let zero_size = [0, 0, 0, 1, b'm', b'd', b'a', b't', 0, 0, 0, 0, 0, 0, 0, 0];
+    assert!(RootBox::decode_from_read(&mut &zero_size[..]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn decode_mdat_box_ref() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+
+    let mut offset = file.ftyp_box.box_size().get() as usize;
+    for b in &file.boxes {
+        if let RootBox::Mdat(mdat_box) = b {
+            let (mdat_box_ref, size) = MdatBoxRef::from_slice(&input_bytes[offset..])?;
+            assert_eq!(size as u64, mdat_box.box_size().get());
+            assert_eq!(mdat_box_ref.to_mdat_box(), *mdat_box);
+
+            // ペイロードは入力バイト列をそのまま参照している
+            assert!(std::ptr::eq(
+                mdat_box_ref.payload.as_ptr(),
+                input_bytes[offset..]
+                    .as_ptr()
+                    .wrapping_add(size - mdat_box.payload.len())
+            ));
+
+            let mut encoded = Vec::new();
+            mdat_box_ref.encode(&mut encoded)?;
+            assert_eq!(encoded, &input_bytes[offset..][..size]);
+            return Ok(());
+        }
+        offset += b.box_size().get() as usize;
+    }
+    panic!("no mdat box");
+}
+
+#[test]
+fn decode_mdat_box_ref_with_broken_size() {
+    // ヘッダーのサイズがバッファの長さを超えている
+    let bytes = [0, 0, 0, 100, b'm', b'd', b'a', b't', 1, 2, 3];
+    assert!(MdatBoxRef::from_slice(&bytes).is_err());
+
+    // 可変長の場合には末尾までがペイロードになる
+    let bytes = [0, 0, 0, 0, b'm', b'd', b'a', b't', 1, 2, 3];
+    let (mdat_box_ref, size) = MdatBoxRef::from_slice(&bytes).expect("decode");
+    assert!(mdat_box_ref.is_variable_size);
+    assert_eq!(mdat_box_ref.payload, [1, 2, 3]);
+    assert_eq!(size, bytes.len());
+}
+
+#[test]
+fn decode_ref_mp4_file() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let expected: Mp4File = Mp4File::decode(&input_bytes[..])?;
+
+    let (file, size) = Mp4File::<RootBoxRef>::decode_ref(&input_bytes[..])?;
+    assert_eq!(size, input_bytes.len());
+    assert_eq!(file.encode_to_vec()?, input_bytes);
+
+    // mdat ボックスのペイロードは入力バイト列を参照している
+    let input_range = input_bytes.as_ptr_range();
+    let mut mdat_count = 0;
+    for b in &file.boxes {
+        if let RootBoxRef::Mdat(b) = b {
+            assert!(input_range.contains(&b.payload.as_ptr()));
+            mdat_count += 1;
+        }
+    }
+    assert_eq!(mdat_count, 1);
+
+    assert_eq!(file.to_owned_file(), expected);
+
+    // 途中で途切れている場合はエラーになる
+    assert!(Mp4File::<RootBoxRef>::decode_ref(&input_bytes[..input_bytes.len() - 1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn decode_eof_sized_boxes() -> Result<()> {
+    // サイズフィールドが 0 の場合には、入力の末尾までがペイロードとなる
+    let mut bytes = vec![0, 0, 0, 0, b'm', b'd', b'a', b't'];
+    bytes.extend((0..1000).map(|i| i as u8));
+
+    let mdat_box = MdatBox::decode(&bytes[..])?;
+    assert!(mdat_box.is_variable_size);
+    assert!(mdat_box.box_size().is_eof());
+    assert_eq!(mdat_box.payload, &bytes[8..]);
+    assert_eq!(mdat_box.encode_to_vec()?, bytes);
+
+    // 未知のボックスでも同様
+    bytes[4..8].copy_from_slice(b"abcd");
+    let unknown_box = UnknownBox::decode(&bytes[..])?;
+    assert!(unknown_box.box_size.is_eof());
+    assert_eq!(unknown_box.payload.len(), 1000);
+    assert_eq!(unknown_box.encode_to_vec()?, bytes);
+
+    // ファイル末尾に位置するボックスとして扱われる
+    bytes[4..8].copy_from_slice(b"mdat");
+    let mut file_bytes = FtypBox(FileTypeBoxContent {
+        major_brand: Brand::ISOM,
+        minor_version: 0,
+        compatible_brands: vec![Brand::ISOM],
+    })
+    .encode_to_vec()?;
+    file_bytes.extend_from_slice(&bytes);
+    let file: Mp4File = Mp4File::decode(&file_bytes[..])?;
+    assert_eq!(file.boxes, [RootBox::Mdat(mdat_box)]);
+
+    assert!(BoxSize::VARIABLE_SIZE.is_eof());
+    assert!(!BoxSize::U32(8).is_eof());
+
+    // 可変長ボックスでは未読み込みのバイトのチェックは行われないが、サイズ指定がある場合には行われる
+    let header = BoxHeader {
+        box_type: BoxType::Normal(*b"test"),
+        box_size: BoxSize::VARIABLE_SIZE,
+    };
+    let payload = [1, 2, 3, 4];
+    let value = header.with_box_payload_reader(&payload[..], |reader| u8::decode(reader))?;
+    assert_eq!(value, 1);
+
+    let header = BoxHeader {
+        box_type: BoxType::Normal(*b"test"),
+        box_size: BoxSize::U32(12),
+    };
+    assert!(header
+        .with_box_payload_reader(&payload[..], |reader| u8::decode(reader))
+        .is_err());
+    let value = header.with_box_payload_reader(&payload[..], |reader| u32::decode(reader))?;
+    assert_eq!(value, 0x01020304);
+    Ok(())
+}
+
+#[test]
+fn decode_sample_table_boxes_with_huge_entry_count() {
+    // エントリー数には u32::MAX が記録されているが、実際のエントリーは一つ分しかない
+    let box_bytes = |box_type: &[u8; 4]| {
+        let mut bytes = vec![0, 0, 0, 28];
+        bytes.extend_from_slice(box_type);
+        bytes.extend_from_slice(&[0, 0, 0, 0]); // フルボックスヘッダー
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+        bytes
+    };
+    assert!(SttsBox::decode(&box_bytes(b"stts")[..]).is_err());
+    assert!(StscBox::decode(&box_bytes(b"stsc")[..]).is_err());
+    assert!(StcoBox::decode(&box_bytes(b"stco")[..]).is_err());
+    assert!(Co64Box::decode(&box_bytes(b"co64")[..]).is_err());
+    assert!(StssBox::decode(&box_bytes(b"stss")[..]).is_err());
+
+    // stsz ボックスはサンプルサイズ（0 = 可変）の後ろにサンプル数が続く
+    let mut bytes = vec![0, 0, 0, 24];
+    bytes.extend_from_slice(b"stsz");
+    bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+    bytes.extend_from_slice(&[0, 0, 0, 1]);
+    assert!(StszBox::decode(&bytes[..]).is_err());
+}
+
+#[test]
+fn decode_box_size_only_and_skip_box() -> Result<()> {
+    // 32 ビットのサイズ
+    let bytes = [0, 0, 0, 11, b'f', b'r', b'e', b'e', 1, 2, 3, 4, 5];
+    assert_eq!(BoxHeader::decode_size_only(&bytes)?, 11);
+    assert_eq!(BoxHeader::skip_box(&bytes)?, 11);
+
+    // 64 ビットのサイズ
+    let mut bytes = vec![0, 0, 0, 1, b'f', b'r', b'e', b'e', 0, 0, 0, 0, 0, 0, 0, 19];
+    bytes.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(BoxHeader::decode_size_only(&bytes)?, 19);
+    assert_eq!(BoxHeader::skip_box(&bytes)?, 19);
+
+    // 可変長の場合には末尾までがボックスになる
+    let bytes = [0, 0, 0, 0, b'm', b'd', b'a', b't', 1, 2, 3];
+    assert_eq!(BoxHeader::decode_size_only(&bytes)?, 0);
+    assert_eq!(BoxHeader::skip_box(&bytes)?, bytes.len());
+
+    // サイズがバッファの長さを超えている
+    let bytes = [0, 0, 0, 100, b'm', b'd', b'a', b't', 1, 2, 3];
+    assert_eq!(BoxHeader::decode_size_only(&bytes)?, 100);
+    assert!(BoxHeader::skip_box(&bytes).is_err());
+
+    // サイズがヘッダーのサイズよりも小さい
+    let bytes = [0, 0, 0, 4, b'f', b'r', b'e', b'e'];
+    assert!(BoxHeader::skip_box(&bytes).is_err());
+
+    // 64 ビットのサイズ表現で 0 が指定されている（可変長扱いにはならない）
+    let bytes = [0, 0, 0, 1, b'm', b'd', b'a', b't', 0, 0, 0, 0, 0, 0, 0, 0];
+    assert!(BoxHeader::skip_box(&bytes).is_err());
+
+    // サイズ部分が途中で途切れている
+    assert!(BoxHeader::decode_size_only(&[0, 0, 0]).is_err());
+    assert!(BoxHeader::decode_size_only(&[0, 0, 0, 1, b'm', b'd', b'a', b't', 0]).is_err());
+    Ok(())
+}
+
+#[test]
+fn decode_skipping_mdat() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let skipped = Mp4File::<Either<RootBox, IgnoredBox>>::decode_skipping_mdat(input_bytes)?;
+
+    assert_eq!(skipped.ftyp_box, file.ftyp_box);
+    assert_eq!(skipped.boxes.len(), file.boxes.len());
+    for (b0, b1) in file.boxes.iter().zip(&skipped.boxes) {
+        assert_eq!(b0.box_type(), b1.box_type());
+        assert_eq!(b0.box_size(), b1.box_size());
+        match b1 {
+            Either::A(b1) => {
+                assert_ne!(b1.box_type(), MdatBox::TYPE);
+                assert_eq!(b0, b1);
+            }
+            Either::B(b1) => {
+                assert_eq!(b1.box_type, MdatBox::TYPE);
+                assert_eq!(b1.box_payload_size, b0.box_payload_size());
+            }
+        }
+    }
+
+    // mdat ボックスのサイズが 64 ビットの 0 になっている場合はエラーになる
+    let mut bytes = file.ftyp_box.encode_to_vec()?;
+    bytes.extend_from_slice(&[0, 0, 0, 1, b'm', b'd', b'a', b't', 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert!(Mp4File::<Either<RootBox, IgnoredBox>>::decode_skipping_mdat(&bytes).is_err());
+    Ok(())
+}
+
+#[test]
+fn decode_fast() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let (fast, size) = Mp4File::<FastRootBox>::decode_fast(input_bytes)?;
+
+    assert_eq!(size, input_bytes.len());
+    assert_eq!(fast.ftyp_box, file.ftyp_box);
+    assert_eq!(fast.boxes.len(), file.boxes.len());
+    for (b0, b1) in file.boxes.iter().zip(&fast.boxes) {
+        assert_eq!(b0.box_type(), b1.box_type());
+        assert_eq!(b0.box_size(), b1.box_size());
+        assert_eq!(b0.box_payload_size(), b1.box_payload_size());
+        match (b0, b1) {
+            (RootBox::Moov(b0), FastRootBox::Moov(b1)) => assert_eq!(b0, b1),
+            (RootBox::Mdat(b0), FastRootBox::Unknown { byte_range, .. }) => {
+                // 記録された位置から mdat ボックスのペイロードを参照できる
+                let range = byte_range.start as usize..byte_range.end as usize;
+                assert_eq!(&input_bytes[range][8..], b0.payload);
+            }
+            (_, FastRootBox::Unknown { .. }) => {}
+            _ => panic!("unexpected box: {b1:?}"),
+        }
+    }
+
+    // Decode トレイト経由の場合は、位置はボックス先頭からの相対値となる
+    let mut bytes = Vec::new();
+    RootBox::Free(FreeBox {
+        payload: vec![0; 4],
+    })
+    .encode(&mut bytes)?;
+    let decoded = FastRootBox::decode(&bytes[..])?;
+    assert!(matches!(
+        &decoded,
+        FastRootBox::Unknown { box_type, byte_range, .. }
+            if *box_type == FreeBox::TYPE && *byte_range == (0..12)
+    ));
+    assert!(decoded.is_unknown_box());
+    Ok(())
+}
+
+#[test]
+fn decode_fast_truncated() -> Result<()> {
+    let ftyp_box = FtypBox(FileTypeBoxContent {
+        major_brand: Brand::ISOM,
+        minor_version: 0,
+        compatible_brands: vec![Brand::ISOM],
+    });
+    let mdat_box = MdatBox {
+        is_variable_size: false,
+        payload: vec![1; 100],
+    };
+    let mut bytes = ftyp_box.encode_to_vec()?;
+    let mdat_start = bytes.len();
+    bytes.extend_from_slice(&mdat_box.encode_to_vec()?);
+    let mdat_end = bytes.len();
+
+    // 末尾の mdat ボックスが途中で途切れていても、そのボックスの終端位置が返される
+    let (file, size) = Mp4File::<FastRootBox>::decode_fast(&bytes[..mdat_start + 16])?;
+    assert_eq!(size, mdat_end);
+    assert_eq!(
+        file.boxes,
+        [FastRootBox::Unknown {
+            box_type: MdatBox::TYPE,
+            box_size: BoxSize::U32(108),
+            byte_range: mdat_start as u64..mdat_end as u64,
+        }]
+    );
+
+    // ヘッダー自体が途切れている場合はエラーになる
+    assert!(Mp4File::<FastRootBox>::decode_fast(&bytes[..mdat_start + 4]).is_err());
+    Ok(())
+}
+
+#[test]
+fn decode_encode_styp_box() -> Result<()> {
+    let styp_box = StypBox::cmaf_segment();
+    let mut bytes = Vec::new();
+    RootBox::Styp(styp_box.clone()).encode(&mut bytes)?;
+    assert_eq!(bytes, b"\x00\x00\x00\x18stypcmfs\x00\x00\x00\x00cmfscmf2");
+
+    let decoded = RootBox::decode(&bytes[..])?;
+    assert_eq!(decoded, RootBox::Styp(styp_box));
+
+    // CMAF の音声・映像用のプリセット
+    let styp_box = StypBox::cmaf_audio();
+    assert!(styp_box.is_cmaf());
+    assert_eq!(styp_box.validate(), Vec::new());
+    let bytes = styp_box.encode_to_vec()?;
+    assert_eq!(
+        bytes,
+        b"\x00\x00\x00\x1cstypcmfs\x00\x00\x00\x00cmfscmfccaac"
+    );
+    assert_eq!(StypBox::decode(&bytes[..])?, styp_box);
+
+    let styp_box = StypBox::cmaf_video();
+    assert!(styp_box.is_cmaf());
+    assert_eq!(styp_box.validate(), Vec::new());
+    let bytes = styp_box.encode_to_vec()?;
+    assert_eq!(
+        bytes,
+        b"\x00\x00\x00\x1cstypcmfs\x00\x00\x00\x00cmfscmfccfhd"
+    );
+    assert_eq!(StypBox::decode(&bytes[..])?, styp_box);
+
+    // ftyp とはボックス種別のみが異なる
+    let ftyp_box = FtypBox(styp_box.0.clone());
+    assert_eq!(&ftyp_box.encode_to_vec()?[8..], &bytes[8..]);
+    assert!(FtypBox::decode(&bytes[..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn decode_encode_prft_box() -> Result<()> {
+    // 1970/1/1 00:00:00.5 は NTP 形式では 2208988800 秒 + 0.5 秒（端数部は 2^31）となる
+    let wall_clock = std::time::UNIX_EPOCH + std::time::Duration::from_millis(500);
+    let prft_box = PrftBox::from_system_time(1, 90000, wall_clock);
+    assert_eq!(prft_box.reference_track_id, 1);
+    assert_eq!(prft_box.ntp_timestamp, (2208988800 << 32) | (1 << 31));
+    assert_eq!(prft_box.media_time, 90000);
+
+    // メディア時刻が 32 ビットに収まる場合はバージョン 0 でエンコードされる
+    let mut bytes = Vec::new();
+    RootBox::Prft(prft_box.clone()).encode(&mut bytes)?;
+    assert_eq!(bytes.len(), 8 + 4 + 4 + 8 + 4);
+    assert_eq!(&bytes[4..9], b"prft\x00");
+    assert_eq!(RootBox::decode(&bytes[..])?, RootBox::Prft(prft_box));
+
+    // 収まらない場合はバージョン 1 になる
+    let prft_box = PrftBox::from_system_time(1, u64::from(u32::MAX) + 1, wall_clock);
+    let bytes = prft_box.encode_to_vec()?;
+    assert_eq!(bytes.len(), 8 + 4 + 4 + 8 + 8);
+    assert_eq!(bytes[8], 1);
+    assert_eq!(PrftBox::decode(&bytes[..])?, prft_box);
+    Ok(())
+}
+
+#[test]
+fn decode_all_and_exactly_one() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-av1-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+
+    let ftyp_size = file.ftyp_box.box_size().get() as usize;
+    let ftyp_box = FtypBox::decode_exactly_one(&input_bytes[..ftyp_size])?;
+    assert_eq!(ftyp_box, file.ftyp_box);
+    assert!(FtypBox::decode_exactly_one(&input_bytes[..]).is_err());
+
+    let boxes = RootBox::decode_all(&input_bytes[ftyp_size..])?;
+    assert_eq!(boxes, file.boxes);
+    assert!(RootBox::decode_all(&input_bytes[ftyp_size..input_bytes.len() - 1]).is_err());
+    assert_eq!(RootBox::decode_all(&[])?, Vec::new());
+
+    Ok(())
+}
+
 fn collect_unknown_box_types(mp4: &Mp4File) -> Vec<BoxType> {
     let mut stack = mp4.iter().collect::<Vec<_>>();
     let mut unknowns = Vec::new();
@@ -171,3 +700,73 @@ fn collect_unknown_box_types(mp4: &Mp4File) -> Vec<BoxType> {
 
     unknowns
 }
+
+#[test]
+fn decode_encode_uuid_box() -> Result<()> {
+    // UUID 形式のボックス種別は、`uuid` の後ろに続く 16 バイトで表現される
+    let mut input_bytes = Vec::new();
+    input_bytes.extend_from_slice(&(8 + 16 + 4u32).to_be_bytes());
+    input_bytes.extend_from_slice(b"uuid");
+    input_bytes.extend_from_slice(BoxType::PIFF_SAMPLE_ENCRYPTION.as_bytes());
+    input_bytes.extend_from_slice(&[0, 0, 0, 0]);
+
+    let header = BoxHeader::decode(&input_bytes[..])?;
+    assert_eq!(header.box_type, BoxType::PIFF_SAMPLE_ENCRYPTION);
+    assert_eq!(header.box_size, BoxSize::U32(28));
+    assert_eq!(header.external_size(), 24);
+
+    let unknown_box = UnknownBox::decode(&input_bytes[..])?;
+    assert_eq!(unknown_box.box_type, BoxType::PIFF_SAMPLE_ENCRYPTION);
+    assert_eq!(unknown_box.payload, [0, 0, 0, 0]);
+    assert_eq!(unknown_box.encode_to_vec()?, input_bytes);
+
+    assert_ne!(
+        BoxType::PIFF_SAMPLE_ENCRYPTION,
+        BoxType::PIFF_TRACK_ENCRYPTION
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_encode_minf_box_media_header() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let mut minf_box = file.find_moov().expect("no moov box").trak_boxes[0]
+        .mdia_box
+        .minf_box
+        .clone();
+    assert!(matches!(
+        minf_box.media_header_box,
+        Some(MediaInfoHeader::Vmhd(_))
+    ));
+
+    // nmhd はペイロードを持たないフルボックス
+    assert_eq!(
+        NmhdBox.encode_to_vec()?,
+        [0, 0, 0, 12, b'n', b'm', b'h', b'd', 0, 0, 0, 0]
+    );
+    assert_eq!(NmhdBox::decode(&NmhdBox.encode_to_vec()?[..])?, NmhdBox);
+
+    minf_box.media_header_box = Some(MediaInfoHeader::Nmhd(NmhdBox));
+    let bytes = minf_box.encode_to_vec()?;
+    let decoded = MinfBox::decode(&bytes[..])?;
+    assert_eq!(decoded, minf_box);
+    assert_eq!(
+        decoded.children().next().map(|b| b.box_type()),
+        Some(NmhdBox::TYPE)
+    );
+
+    // gmhd などの未対応のヘッダーしかない場合は None になり、元のボックスは unknown_boxes に格納される
+    minf_box.media_header_box = None;
+    minf_box.unknown_boxes.push(UnknownBox {
+        box_type: BoxType::Normal(*b"gmhd"),
+        box_size: BoxSize::U32(8),
+        payload: Vec::new(),
+    });
+    let bytes = minf_box.encode_to_vec()?;
+    let decoded = MinfBox::decode(&bytes[..])?;
+    assert_eq!(decoded, minf_box);
+    assert_eq!(decoded.unknown_boxes[0].box_type, BoxType::Normal(*b"gmhd"));
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use shiguredo_mp4::{
+    boxes::{DopsBox, DopsChannelMappingTable, RootBox, SampleEntry},
+    Decode, Encode, Mp4File, Result,
+};
+
+fn dops_box(output_channel_count: u8) -> DopsBox {
+    DopsBox {
+        output_channel_count,
+        pre_skip: 312,
+        input_sample_rate: 48000,
+        output_gain: 0,
+        channel_mapping_family: 0,
+        channel_mapping_table: None,
+    }
+}
+
+fn assert_round_trip(b: &DopsBox) -> Result<()> {
+    let bytes = b.encode_to_vec()?;
+    assert_eq!(&DopsBox::decode(&bytes[..])?, b);
+    Ok(())
+}
+
+#[test]
+fn mono_and_stereo() -> Result<()> {
+    for output_channel_count in [1, 2] {
+        let b = dops_box(output_channel_count);
+        assert!(b.validate().is_ok());
+        assert_round_trip(&b)?;
+    }
+
+    // マッピングファミリー 0 では 3 チャンネル以上は扱えない
+    assert!(dops_box(3).validate().is_err());
+    Ok(())
+}
+
+#[test]
+fn surround_5_1() -> Result<()> {
+    // Vorbis のチャンネル順 (L, C, R, Ls, Rs, LFE) で、L/R と Ls/Rs がステレオストリームとなる
+    let mut b = dops_box(6);
+    b.channel_mapping_family = 1;
+    b.channel_mapping_table = Some(DopsChannelMappingTable {
+        stream_count: 4,
+        coupled_count: 2,
+        channel_mapping: vec![0, 4, 1, 2, 3, 5],
+    });
+    assert!(b.validate().is_ok());
+    assert_round_trip(&b)?;
+
+    // エンコード結果はヘッダー + 固定長部分 (11 バイト) + テーブル (2 + 6 バイト)
+    assert_eq!(b.encode_to_vec()?.len(), 8 + 11 + 2 + 6);
+
+    // マッピングのインデックスがデコードされるチャンネル数の範囲外
+    let mut invalid = b.clone();
+    if let Some(table) = &mut invalid.channel_mapping_table {
+        table.channel_mapping[5] = 6;
+    }
+    assert!(invalid.validate().is_err());
+
+    // 無音チャンネル (255) は許容される
+    let mut silent = b.clone();
+    if let Some(table) = &mut silent.channel_mapping_table {
+        table.channel_mapping[5] = 255;
+    }
+    assert!(silent.validate().is_ok());
+
+    // テーブルの要素数がチャンネル数と一致しない
+    let mut invalid = b.clone();
+    invalid.output_channel_count = 5;
+    assert!(invalid.validate().is_err());
+    assert!(invalid.encode_to_vec().is_err());
+
+    // テーブルがない
+    let mut invalid = b;
+    invalid.channel_mapping_table = None;
+    assert!(invalid.validate().is_err());
+    assert!(invalid.encode_to_vec().is_err());
+    Ok(())
+}
+
+#[test]
+fn invalid_header_fields() {
+    assert!(dops_box(0).validate().is_err());
+
+    // pre_skip は推奨値があるのみなので、0 でもエラーにはならない
+    let mut b = dops_box(2);
+    b.pre_skip = 0;
+    assert!(b.validate().is_ok());
+}
+
+#[test]
+fn real_file() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/beep-opus-audio.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let sample_entry = file
+        .boxes
+        .iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => b.trak_boxes[0].sample_entry().cloned(),
+            _ => None,
+        })
+        .expect("no sample entry");
+    let SampleEntry::Opus(b) = sample_entry else {
+        panic!("not opus");
+    };
+    assert!(b.dops_box.validate().is_ok());
+    assert_eq!(b.dops_box.channel_mapping_family, 0);
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use shiguredo_mp4::{
+    boxes::{Co64Box, StcoBox},
+    Either,
+};
+
+type Value = Either<u32, String>;
+
+#[test]
+fn map() {
+    let a: Value = Either::A(1);
+    let b: Value = Either::B("foo".to_owned());
+
+    assert_eq!(a.clone().map_a(|x| x * 2), Either::A(2));
+    assert_eq!(b.clone().map_a(|x| x * 2), Either::B("foo".to_owned()));
+    assert_eq!(a.clone().map_b(|x| x.len()), Either::A(1));
+    assert_eq!(b.clone().map_b(|x| x.len()), Either::B(3));
+}
+
+#[test]
+fn as_ref_and_as_mut() {
+    let mut a: Value = Either::A(1);
+    let mut b: Value = Either::B("foo".to_owned());
+
+    assert_eq!(a.as_ref(), Either::A(&1));
+    assert_eq!(b.as_ref(), Either::B(&"foo".to_owned()));
+
+    if let Either::A(x) = a.as_mut() {
+        *x += 1;
+    }
+    if let Either::B(x) = b.as_mut() {
+        x.push_str("bar");
+    }
+    assert_eq!(a, Either::A(2));
+    assert_eq!(b, Either::B("foobar".to_owned()));
+}
+
+#[test]
+fn swap() {
+    let a: Value = Either::A(1);
+    let b: Value = Either::B("foo".to_owned());
+    assert_eq!(a.clone().swap(), Either::B(1));
+    assert_eq!(b.clone().swap(), Either::A("foo".to_owned()));
+    assert_eq!(a.clone().swap().swap(), a);
+}
+
+#[test]
+fn extract() {
+    let a: Value = Either::A(1);
+    let b: Value = Either::B("foo".to_owned());
+
+    assert_eq!(a.as_ref().a(), Some(&1));
+    assert_eq!(a.as_ref().b(), None);
+    assert_eq!(b.as_ref().a(), None);
+    assert_eq!(b.as_ref().b().map(|x| x.as_str()), Some("foo"));
+
+    assert_eq!(a.a(), Some(1));
+    assert_eq!(b.b(), Some("foo".to_owned()));
+}
+
+#[test]
+fn chunk_offset_box() {
+    // StblBox::stco_or_co64_box のような箱の Either でも使える
+    let mut stco_or_co64_box: Either<StcoBox, Co64Box> = Either::A(StcoBox {
+        chunk_offsets: vec![10, 20],
+    });
+    let len = stco_or_co64_box
+        .as_ref()
+        .map_a(|b| b.chunk_offsets.len())
+        .map_b(|b| b.chunk_offsets.len());
+    assert_eq!(len, Either::A(2));
+
+    if let Some(b) = stco_or_co64_box.as_mut().a() {
+        b.chunk_offsets.push(30);
+    }
+
+    // stco を co64 に変換する
+    let co64_box = stco_or_co64_box.map_a(|b| Co64Box {
+        chunk_offsets: b.chunk_offsets.into_iter().map(u64::from).collect(),
+    });
+    assert_eq!(
+        co64_box.a().map(|b| b.chunk_offsets),
+        Some(vec![10, 20, 30])
+    );
+}
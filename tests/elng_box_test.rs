@@ -0,0 +1,68 @@
+use shiguredo_mp4::{
+    boxes::{ElngBox, MdhdBox, MdiaBox, RootBox},
+    BaseBox, Decode, Encode, Mp4File, Result, Utf8String,
+};
+
+fn mdia_box() -> Result<MdiaBox> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File<RootBox> = Mp4File::decode(&input_bytes[..])?;
+    Ok(file.find_moov().expect("no moov box").trak_boxes[0]
+        .mdia_box
+        .clone())
+}
+
+fn elng_box(tag: &str) -> ElngBox {
+    ElngBox {
+        extended_language: Utf8String::new(tag).expect("invalid string"),
+    }
+}
+
+#[test]
+fn decode_encode_elng_box() -> Result<()> {
+    let b = elng_box("zh-Hans");
+    let bytes = b.encode_to_vec()?;
+    assert_eq!(&bytes[4..8], b"elng");
+    assert_eq!(&bytes[8..], b"\0\0\0\0zh-Hans\0");
+    assert_eq!(ElngBox::decode(&bytes[..])?, b);
+
+    // mdia ボックス内では hdlr と minf の間に配置される
+    let mut mdia_box = mdia_box()?;
+    mdia_box.elng_box = Some(b);
+    let decoded = MdiaBox::decode(&mdia_box.encode_to_vec()?[..])?;
+    assert_eq!(decoded, mdia_box);
+    let children = decoded
+        .children()
+        .map(|b| b.box_type().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(children[..4], ["mdhd", "hdlr", "elng", "minf"]);
+    Ok(())
+}
+
+#[test]
+fn language_bcp47_from_elng_box() -> Result<()> {
+    // elng ボックスがある場合は、mdhd の言語コードよりも優先される
+    let mut mdia_box = mdia_box()?;
+    mdia_box.mdhd_box.language = *b"eng";
+    mdia_box.elng_box = Some(elng_box("en-US"));
+    assert_eq!(mdia_box.language_bcp47(), Some("en-US"));
+    Ok(())
+}
+
+#[test]
+fn language_bcp47_from_mdhd_box() -> Result<()> {
+    let mut mdia_box = mdia_box()?;
+    mdia_box.elng_box = None;
+
+    mdia_box.mdhd_box.language = *b"jpn";
+    assert_eq!(mdia_box.language_bcp47(), Some("ja"));
+
+    mdia_box.mdhd_box.language = *b"zho";
+    assert_eq!(mdia_box.language_bcp47(), Some("zh"));
+
+    // 未定義や、対応表にない言語の場合
+    mdia_box.mdhd_box.language = MdhdBox::LANGUAGE_UNDEFINED;
+    assert_eq!(mdia_box.language_bcp47(), None);
+    mdia_box.mdhd_box.language = *b"xyz";
+    assert_eq!(mdia_box.language_bcp47(), None);
+    Ok(())
+}
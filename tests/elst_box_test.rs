@@ -0,0 +1,79 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::{
+    boxes::{ElstBox, ElstEntry},
+    FixedPointNumber,
+};
+
+fn entry(edit_duration: u64, media_time: i64) -> ElstEntry {
+    ElstEntry {
+        edit_duration,
+        media_time,
+        media_rate: FixedPointNumber::new(1, 0),
+    }
+}
+
+fn timescale(v: u32) -> NonZeroU32 {
+    NonZeroU32::new(v).expect("zero timescale")
+}
+
+#[test]
+fn initial_empty_edit() {
+    // 先頭に 1000 の無音・無表示区間がある
+    let elst_box = ElstBox {
+        entries: vec![entry(1000, ElstBox::EMPTY_EDIT_MEDIA_TIME), entry(10000, 0)],
+    };
+    assert!(elst_box.is_initial_empty_edit());
+    assert_eq!(elst_box.first_media_time(), Some(0));
+    assert_eq!(elst_box.total_edit_duration(), 11000);
+    assert_eq!(
+        elst_box.audio_priming_frames(timescale(48000), timescale(48000)),
+        Some(0)
+    );
+}
+
+#[test]
+fn aac_priming() {
+    // AAC の典型的なプライミング (2112 サンプル) をスキップする編集
+    let elst_box = ElstBox {
+        entries: vec![entry(441000, 2112)],
+    };
+    assert!(!elst_box.is_initial_empty_edit());
+    assert_eq!(elst_box.first_media_time(), Some(2112));
+    assert_eq!(elst_box.total_edit_duration(), 441000);
+    assert_eq!(
+        elst_box.audio_priming_frames(timescale(44100), timescale(44100)),
+        Some(2112)
+    );
+
+    // メディアのタイムスケールがサンプリングレートと異なる場合は変換される
+    let elst_box = ElstBox {
+        entries: vec![entry(441000, 4224)],
+    };
+    assert_eq!(
+        elst_box.audio_priming_frames(timescale(88200), timescale(44100)),
+        Some(2112)
+    );
+}
+
+#[test]
+fn no_edit_list() {
+    let elst_box = ElstBox {
+        entries: Vec::new(),
+    };
+    assert!(!elst_box.is_initial_empty_edit());
+    assert_eq!(elst_box.first_media_time(), None);
+    assert_eq!(elst_box.total_edit_duration(), 0);
+    assert_eq!(
+        elst_box.audio_priming_frames(timescale(48000), timescale(48000)),
+        None
+    );
+
+    // 空の編集のみの場合
+    let elst_box = ElstBox {
+        entries: vec![entry(1000, ElstBox::EMPTY_EDIT_MEDIA_TIME)],
+    };
+    assert!(elst_box.is_initial_empty_edit());
+    assert_eq!(elst_box.first_media_time(), None);
+    assert_eq!(elst_box.total_edit_duration(), 1000);
+}
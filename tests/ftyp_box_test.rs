@@ -0,0 +1,190 @@
+use shiguredo_mp4::{
+    aux::BrandWarning,
+    boxes::{Brand, FileTypeBoxContent, FtypBox, MoovBox, SampleEntry},
+    Decode, Mp4File, Result,
+};
+
+fn decode_moov_box(input_bytes: &[u8]) -> Result<MoovBox> {
+    let file: Mp4File = Mp4File::decode(input_bytes)?;
+    Ok(file.find_moov().expect("no moov box").clone())
+}
+
+fn ftyp_box(major_brand: Brand, compatible_brands: &[Brand]) -> FtypBox {
+    FtypBox(FileTypeBoxContent {
+        major_brand,
+        minor_version: 0,
+        compatible_brands: compatible_brands.to_vec(),
+    })
+}
+
+#[test]
+fn validate_brands() {
+    // 問題のない指定
+    let b = ftyp_box(Brand::ISOM, &[Brand::ISOM, Brand::ISO2, Brand::MP41]);
+    assert_eq!(b.validate(), Vec::new());
+
+    let b = ftyp_box(Brand::ISOM, &[]);
+    assert_eq!(b.validate(), [BrandWarning::EmptyCompatibleBrands]);
+
+    let b = ftyp_box(Brand::ISOM, &[Brand::ISO2]);
+    assert_eq!(
+        b.validate(),
+        [BrandWarning::MajorBrandNotInCompatibleBrands]
+    );
+
+    let b = ftyp_box(
+        Brand::ISOM,
+        &[Brand::ISOM, Brand::AVC1, Brand::AVC1, Brand::AVC1],
+    );
+    assert_eq!(
+        b.validate(),
+        [BrandWarning::DuplicateCompatibleBrand(Brand::AVC1)]
+    );
+
+    let b = ftyp_box(Brand::MP41, &[Brand::MP41]);
+    assert_eq!(
+        b.validate(),
+        [BrandWarning::LegacyBrandWithoutModernEquivalent(
+            Brand::MP41
+        )]
+    );
+}
+
+#[test]
+fn fragmented_and_cmaf_brands() {
+    let b = ftyp_box(Brand::ISOM, &[Brand::ISOM, Brand::ISO2]);
+    assert!(!b.is_fragmented_mp4());
+    assert!(!b.is_cmaf());
+
+    let b = ftyp_box(Brand::new(*b"mf4v"), &[Brand::ISOM]);
+    assert!(b.is_fragmented_mp4());
+    assert!(!b.is_cmaf());
+
+    let b = ftyp_box(Brand::ISO6, &[Brand::ISO6, Brand::CMFC]);
+    assert!(b.is_fragmented_mp4());
+    assert!(b.is_cmaf());
+}
+
+#[test]
+fn known_brands() {
+    assert!(Brand::ISOM.is_known());
+    assert!(Brand::CMFS.is_known());
+    assert!(!Brand::new(*b"XXXX").is_known());
+
+    assert_eq!(
+        Brand::ISOM.description(),
+        Some("ISO Base Media file format")
+    );
+    assert_eq!(Brand::new(*b"XXXX").description(), None);
+
+    // 全ての既知のブランドに説明があり、重複はない
+    let brands = Brand::known_brands().collect::<Vec<_>>();
+    assert!(brands.contains(&Brand::MP41));
+    for (i, brand) in brands.iter().enumerate() {
+        assert!(brand.is_known());
+        assert!(brand.description().is_some());
+        assert!(!brands[..i].contains(brand));
+    }
+
+    // const 文脈でも使える
+    const { assert!(Brand::AV01.is_known()) };
+}
+
+#[test]
+fn ftyp_from_sample_entries() -> Result<()> {
+    let h264 = decode_moov_box(include_bytes!("testdata/black-h264-video.mp4"))?;
+    let h265 = decode_moov_box(include_bytes!("testdata/black-h265-video.mp4"))?;
+    let vp9 = decode_moov_box(include_bytes!("testdata/black-vp9-video.mp4"))?;
+    let av1 = decode_moov_box(include_bytes!("testdata/black-av1-video.mp4"))?;
+    let aac = decode_moov_box(include_bytes!("testdata/beep-aac-audio.mp4"))?;
+    let opus = decode_moov_box(include_bytes!("testdata/beep-opus-audio.mp4"))?;
+    let entry = |moov_box: &MoovBox| -> SampleEntry {
+        moov_box.trak_boxes[0]
+            .sample_entry()
+            .expect("no sample entry")
+            .clone()
+    };
+
+    let b = h264.compute_ftyp();
+    assert_eq!(
+        b,
+        ftyp_box(
+            Brand::AVC1,
+            &[Brand::AVC1, Brand::ISOM, Brand::ISO2, Brand::MP41]
+        )
+    );
+    assert_eq!(b.validate(), Vec::new());
+
+    assert_eq!(
+        h265.compute_ftyp(),
+        ftyp_box(
+            Brand::HEV1,
+            &[Brand::HEV1, Brand::ISOM, Brand::ISO2, Brand::MP41]
+        )
+    );
+    assert_eq!(
+        av1.compute_ftyp(),
+        ftyp_box(
+            Brand::AV01,
+            &[Brand::AV01, Brand::ISOM, Brand::ISO2, Brand::MP41]
+        )
+    );
+    assert_eq!(
+        opus.compute_ftyp(),
+        ftyp_box(
+            Brand::OPUS,
+            &[Brand::OPUS, Brand::ISOM, Brand::ISO2, Brand::MP41]
+        )
+    );
+
+    // 対応するブランドがないコーデックの場合は isom がメジャーブランドになる
+    for moov_box in [&vp9, &aac] {
+        let b = moov_box.compute_ftyp();
+        assert_eq!(
+            b,
+            ftyp_box(Brand::ISOM, &[Brand::ISOM, Brand::ISO2, Brand::MP41])
+        );
+        assert_eq!(b.validate(), Vec::new());
+    }
+
+    // 映像と音声が混在する場合は、映像コーデックのブランドが優先される
+    let b = FtypBox::from_sample_entries(&[entry(&opus), entry(&aac), entry(&h264)]);
+    assert_eq!(
+        b,
+        ftyp_box(
+            Brand::AVC1,
+            &[
+                Brand::AVC1,
+                Brand::OPUS,
+                Brand::ISOM,
+                Brand::ISO2,
+                Brand::MP41
+            ]
+        )
+    );
+    assert_eq!(b.validate(), Vec::new());
+
+    // 同じコーデックのトラックが複数あってもブランドは重複しない
+    let b = FtypBox::from_sample_entries(&[entry(&h265), entry(&av1), entry(&h265)]);
+    assert_eq!(
+        b,
+        ftyp_box(
+            Brand::HEV1,
+            &[
+                Brand::HEV1,
+                Brand::AV01,
+                Brand::ISOM,
+                Brand::ISO2,
+                Brand::MP41
+            ]
+        )
+    );
+    assert_eq!(b.validate(), Vec::new());
+
+    // サンプルエントリーがない場合
+    assert_eq!(
+        FtypBox::from_sample_entries(&[]),
+        ftyp_box(Brand::ISOM, &[Brand::ISOM, Brand::ISO2, Brand::MP41])
+    );
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use shiguredo_mp4::FullBoxFlags;
+
+#[test]
+fn bits() {
+    let flags = FullBoxFlags::new(0b1011);
+    assert_eq!(flags.bits().collect::<Vec<_>>(), [0, 1, 3]);
+    assert_eq!(FullBoxFlags::empty().bits().count(), 0);
+
+    // フルボックスのフラグは 24 ビットだが、u32 の範囲であれば扱える
+    let flags = FullBoxFlags::new(1 << 31 | 1 << 23);
+    assert_eq!(flags.bits().collect::<Vec<_>>(), [23, 31]);
+
+    // from_flags() とは逆の変換になる
+    let flags = FullBoxFlags::from_flags([(2, true), (5, true), (7, false)]);
+    assert_eq!(flags.bits().collect::<Vec<_>>(), [2, 5]);
+}
+
+#[test]
+fn set_clear_toggle() {
+    let flags = FullBoxFlags::empty().set_bit(0).set_bit(8);
+    assert_eq!(flags.get(), 0x101);
+    assert!(flags.is_set(8));
+
+    // 既にセット済みのビットをセットしても変わらない
+    assert_eq!(flags.set_bit(0), flags);
+
+    let flags = flags.clear_bit(0);
+    assert_eq!(flags.get(), 0x100);
+    assert_eq!(flags.clear_bit(0), flags);
+
+    let flags = flags.toggle_bit(8).toggle_bit(1);
+    assert_eq!(flags.get(), 0b10);
+}
+
+#[test]
+fn bit_operations() {
+    let a = FullBoxFlags::new(0b0011);
+    let b = FullBoxFlags::new(0b0110);
+    assert_eq!(a | b, FullBoxFlags::new(0b0111));
+    assert_eq!(a & b, FullBoxFlags::new(0b0010));
+    assert_eq!(a & FullBoxFlags::empty(), FullBoxFlags::empty());
+}
@@ -0,0 +1,135 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::{
+    aux::{gopro_telemetry_samples, is_gopro_telemetry_track},
+    boxes::{
+        HdlrBox, RootBox, SampleEntry, StcoBox, StscBox, StscEntry, StsdBox, StszBox, SttsBox,
+        TrakBox, UnknownBox,
+    },
+    BoxSize, BoxType, Decode, Either, Mp4File, Result,
+};
+
+// GPMF の KLV 形式（4 バイトのキー、1 バイトの型、1 バイトのサイズ、2 バイトの繰り返し数、4 バイト境界までの値）のサンプルを作る
+fn gpmf_sample(key: &[u8; 4], value: u32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(key);
+    bytes.extend_from_slice(&[b'L', 4, 0, 1]);
+    bytes.extend_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+// 3 個の GPMF サンプルを 2 つのチャンクに分けて格納したテレメトリートラックと、そのデータを含むバイト列を作る
+//
+// チャンクの間には無関係なデータを挟んでおく
+fn gopro_telemetry_track() -> Result<(TrakBox, Vec<u8>, Vec<Vec<u8>>)> {
+    let samples = vec![
+        gpmf_sample(b"TICK", 1),
+        gpmf_sample(b"TICK", 2),
+        gpmf_sample(b"TOCK", 3),
+    ];
+
+    let mut file_data = vec![0xFF; 16];
+    let first_chunk_offset = file_data.len() as u32;
+    file_data.extend_from_slice(&samples[0]);
+    file_data.extend_from_slice(&samples[1]);
+    file_data.extend_from_slice(&[0xFF; 5]);
+    let second_chunk_offset = file_data.len() as u32;
+    file_data.extend_from_slice(&samples[2]);
+
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let mut trak_box = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box")
+        .trak_boxes
+        .swap_remove(0);
+
+    trak_box.mdia_box.hdlr_box = HdlrBox {
+        handler_type: HdlrBox::HANDLER_TYPE_META,
+        name: b"GoPro MET\0".to_vec(),
+    };
+    let stbl_box = &mut trak_box.mdia_box.minf_box.stbl_box;
+    stbl_box.stsd_box = StsdBox {
+        entries: vec![SampleEntry::Unknown(UnknownBox {
+            box_type: BoxType::Normal(*b"gpmd"),
+            box_size: BoxSize::U32(8),
+            payload: Vec::new(),
+        })],
+    };
+    stbl_box.stts_box = SttsBox::from_sample_deltas([1000; 3]);
+    stbl_box.stsc_box = StscBox {
+        entries: vec![
+            StscEntry {
+                first_chunk: NonZeroU32::MIN,
+                sample_per_chunk: 2,
+                sample_description_index: NonZeroU32::MIN,
+            },
+            StscEntry {
+                first_chunk: NonZeroU32::new(2).expect("non zero"),
+                sample_per_chunk: 1,
+                sample_description_index: NonZeroU32::MIN,
+            },
+        ],
+    };
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: samples.iter().map(|s| s.len() as u32).collect(),
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: vec![first_chunk_offset, second_chunk_offset],
+    });
+    stbl_box.stss_box = None;
+    stbl_box.ctts_box = None;
+    stbl_box.sgpd_boxes.clear();
+    stbl_box.sbgp_boxes.clear();
+
+    Ok((trak_box, file_data, samples))
+}
+
+#[test]
+fn telemetry_track_detection() -> Result<()> {
+    let (mut trak_box, _, _) = gopro_telemetry_track()?;
+    assert!(is_gopro_telemetry_track(&trak_box));
+
+    // 先頭にサイズバイトが格納されている形式のハンドラ名
+    trak_box.mdia_box.hdlr_box.name = b"\x0aGoPro MET ".to_vec();
+    assert!(is_gopro_telemetry_track(&trak_box));
+
+    // ハンドラ名が異なる
+    trak_box.mdia_box.hdlr_box.name = b"GoPro TCD\0".to_vec();
+    assert!(!is_gopro_telemetry_track(&trak_box));
+
+    // ハンドラー種別が異なる
+    trak_box.mdia_box.hdlr_box.name = b"GoPro MET\0".to_vec();
+    trak_box.mdia_box.hdlr_box.handler_type = HdlrBox::HANDLER_TYPE_VIDE;
+    assert!(!is_gopro_telemetry_track(&trak_box));
+    Ok(())
+}
+
+#[test]
+fn telemetry_samples() -> Result<()> {
+    let (mut trak_box, file_data, samples) = gopro_telemetry_track()?;
+
+    let payloads = gopro_telemetry_samples(&trak_box, &file_data)?.collect::<Vec<_>>();
+    assert_eq!(payloads, samples);
+
+    // 返されるスライスは file_data 内の該当範囲を指している
+    let base = file_data.as_ptr() as usize;
+    let offsets = payloads
+        .iter()
+        .map(|p| p.as_ptr() as usize - base)
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, [16, 28, 45]);
+
+    // データが file_data に収まらない
+    assert!(gopro_telemetry_samples(&trak_box, &file_data[..file_data.len() - 1]).is_err());
+
+    // テレメトリートラックではない
+    trak_box.mdia_box.hdlr_box.handler_type = HdlrBox::HANDLER_TYPE_VIDE;
+    assert!(gopro_telemetry_samples(&trak_box, &file_data).is_err());
+    Ok(())
+}
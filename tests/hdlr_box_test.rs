@@ -0,0 +1,54 @@
+use shiguredo_mp4::{boxes::HdlrBox, Decode, Encode, Result};
+
+fn hdlr_box(name: &[u8]) -> HdlrBox {
+    HdlrBox {
+        handler_type: HdlrBox::HANDLER_TYPE_VIDE,
+        name: name.to_vec(),
+    }
+}
+
+#[test]
+fn handler_name() {
+    // null 終端文字列
+    let b = hdlr_box(b"VideoHandler\0");
+    assert_eq!(b.handler_name_utf8(), Some("VideoHandler"));
+    assert_eq!(b.handler_name_lossy(), "VideoHandler");
+
+    // null 終端がない場合もそのまま扱える
+    let b = hdlr_box(b"VideoHandler");
+    assert_eq!(b.handler_name_utf8(), Some("VideoHandler"));
+
+    // 空の名前
+    assert_eq!(hdlr_box(b"").handler_name_utf8(), Some(""));
+    assert_eq!(hdlr_box(b"\0").handler_name_utf8(), Some(""));
+
+    // UTF-8 として不正なバイト列
+    let b = hdlr_box(b"Video\xffHandler\0");
+    assert_eq!(b.handler_name_utf8(), None);
+    assert_eq!(b.handler_name_lossy(), "Video\u{fffd}Handler");
+}
+
+#[test]
+fn set_handler_name() -> Result<()> {
+    let mut b = hdlr_box(b"");
+    b.set_handler_name("SoundHandler");
+    assert_eq!(b.name, b"SoundHandler\0");
+    assert_eq!(b.handler_name_utf8(), Some("SoundHandler"));
+
+    // エンコード・デコードしても名前は保持される
+    let decoded = HdlrBox::decode(&b.encode_to_vec()?[..])?;
+    assert_eq!(decoded.handler_name_utf8(), Some("SoundHandler"));
+
+    // null 文字以降は無視される
+    b.set_handler_name("foo\0bar");
+    assert_eq!(b.name, b"foo\0");
+    Ok(())
+}
+
+#[test]
+fn handler_type_constants() {
+    assert_eq!(&HdlrBox::HANDLER_TYPE_SUBT, b"subt");
+    assert_eq!(&HdlrBox::HANDLER_TYPE_TEXT, b"text");
+    assert_eq!(&HdlrBox::HANDLER_TYPE_META, b"meta");
+    assert_eq!(&HdlrBox::HANDLER_TYPE_HINT, b"hint");
+}
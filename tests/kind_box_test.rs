@@ -0,0 +1,92 @@
+use shiguredo_mp4::{
+    boxes::{KindBox, RootBox, TrakBox, UdtaBox, UnknownBox},
+    BaseBox, BoxSize, BoxType, Decode, Encode, Mp4File, Result, Utf8String,
+};
+
+fn kind_box(scheme_uri: &str, value: &str) -> KindBox {
+    KindBox {
+        scheme_uri: Utf8String::new(scheme_uri).expect("invalid string"),
+        value: Utf8String::new(value).expect("invalid string"),
+    }
+}
+
+fn trak_box() -> Result<TrakBox> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File<RootBox> = Mp4File::decode(&input_bytes[..])?;
+    Ok(file.find_moov().expect("no moov box").trak_boxes[0].clone())
+}
+
+#[test]
+fn decode_encode_kind_box() -> Result<()> {
+    let b = kind_box(KindBox::DASH_ROLE_SCHEME, "subtitle");
+    let bytes = b.encode_to_vec()?;
+    assert_eq!(&bytes[4..8], b"kind");
+    assert_eq!(&bytes[8..12], [0, 0, 0, 0]);
+    assert_eq!(&bytes[12..], b"urn:mpeg:dash:role:2011\0subtitle\0");
+    assert_eq!(KindBox::decode(&bytes[..])?, b);
+
+    // 値が空の場合
+    let b = kind_box(KindBox::HTML_KIND_SCHEME, "");
+    assert_eq!(KindBox::decode(&b.encode_to_vec()?[..])?, b);
+    Ok(())
+}
+
+#[test]
+fn trak_box_with_kind_boxes() -> Result<()> {
+    let mut trak_box = trak_box()?;
+    assert!(trak_box.kinds().is_empty());
+
+    trak_box.udta_box = Some(UdtaBox {
+        kind_boxes: vec![
+            kind_box(KindBox::DASH_ROLE_SCHEME, "main"),
+            kind_box(KindBox::HTML_KIND_SCHEME, "captions"),
+        ],
+        unknown_boxes: vec![UnknownBox {
+            box_type: BoxType::Normal(*b"name"),
+            box_size: BoxSize::U32(12),
+            payload: b"foo\0".to_vec(),
+        }],
+        trailing_bytes: Vec::new(),
+    });
+
+    let decoded = TrakBox::decode(&trak_box.encode_to_vec()?[..])?;
+    assert_eq!(decoded, trak_box);
+    assert_eq!(decoded.kinds().len(), 2);
+    assert_eq!(decoded.kinds()[0].value.get(), "main");
+    assert_eq!(
+        decoded.kinds()[1].scheme_uri.get(),
+        KindBox::HTML_KIND_SCHEME
+    );
+
+    // udta ボックスは mdia ボックスの後ろに配置される
+    let children = decoded
+        .children()
+        .map(|b| b.box_type().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(children.last().map(|s| s.as_str()), Some("udta"));
+    Ok(())
+}
+
+#[test]
+fn udta_box_with_zero_terminator() -> Result<()> {
+    // QuickTime 形式の 32 ビットのゼロ終端のみを含む udta ボックス
+    let bytes = [0, 0, 0, 12, b'u', b'd', b't', b'a', 0, 0, 0, 0];
+    let udta_box = UdtaBox::decode(&bytes[..])?;
+    assert!(udta_box.kind_boxes.is_empty());
+    assert!(udta_box.unknown_boxes.is_empty());
+    assert_eq!(udta_box.trailing_bytes, [0, 0, 0, 0]);
+    assert_eq!(udta_box.encode_to_vec()?, bytes);
+
+    // 子ボックスの後ろにゼロ終端がある場合
+    let kind_box = kind_box(KindBox::DASH_ROLE_SCHEME, "main");
+    let mut payload = kind_box.encode_to_vec()?;
+    payload.extend_from_slice(&[0, 0, 0, 0]);
+    let mut bytes = ((8 + payload.len()) as u32).to_be_bytes().to_vec();
+    bytes.extend_from_slice(b"udta");
+    bytes.extend_from_slice(&payload);
+    let udta_box = UdtaBox::decode(&bytes[..])?;
+    assert_eq!(udta_box.kind_boxes, [kind_box]);
+    assert_eq!(udta_box.trailing_bytes, [0, 0, 0, 0]);
+    assert_eq!(udta_box.encode_to_vec()?, bytes);
+    Ok(())
+}
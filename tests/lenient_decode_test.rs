@@ -0,0 +1,162 @@
+use shiguredo_mp4::{
+    boxes::{MoovBox, RootBox, UnknownBox},
+    BaseBox, BoxSize, Decode, DecodeLenient, DecodeOptions, Encode, Mp4File, Result,
+};
+
+const LENIENT: DecodeOptions = DecodeOptions { lenient: true };
+
+fn input_bytes() -> Vec<u8> {
+    include_bytes!("testdata/black-h264-video.mp4").to_vec()
+}
+
+fn moov_box_bytes() -> Result<Vec<u8>> {
+    let file = Mp4File::decode(&input_bytes()[..])?;
+    let moov_box = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box");
+    moov_box.encode_to_vec()
+}
+
+fn find_box_position(bytes: &[u8], box_type: &[u8; 4]) -> usize {
+    // ボックス種別の 4 バイト前がボックスの先頭
+    bytes
+        .windows(4)
+        .position(|x| x == box_type)
+        .expect("box not found")
+        - 4
+}
+
+#[test]
+fn conformant_file() -> Result<()> {
+    let bytes = input_bytes();
+    let (file, warnings): (Mp4File, _) = DecodeLenient::decode_with_options(&bytes, LENIENT)?;
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(file, Mp4File::decode(&bytes[..])?);
+
+    // 寛容モードでなければ警告は収集されない
+    let (_, warnings) = <Mp4File>::decode_with_options(&bytes, DecodeOptions::default())?;
+    assert!(warnings.is_empty());
+    Ok(())
+}
+
+#[test]
+fn vmhd_flags() -> Result<()> {
+    let mut bytes = moov_box_bytes()?;
+    let position = find_box_position(&bytes, b"vmhd");
+    bytes[position + 9..][..3].copy_from_slice(&[0, 0, 0]);
+
+    assert!(MoovBox::decode(&bytes[..]).is_err());
+    assert!(MoovBox::decode_with_options(&bytes, DecodeOptions::default()).is_err());
+
+    let (moov_box, warnings) = MoovBox::decode_with_options(&bytes, LENIENT)?;
+    assert_eq!(moov_box.trak_boxes.len(), 1);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].box_path, "moov > trak > mdia > minf > vmhd");
+    assert!(warnings[0].message.contains("flags"));
+    Ok(())
+}
+
+#[test]
+fn duplicate_box() -> Result<()> {
+    let mut moov_box = MoovBox::decode(&moov_box_bytes()?[..])?;
+    let mvhd_bytes = moov_box.mvhd_box.encode_to_vec()?;
+    let header_size = mvhd_bytes.len() - moov_box.mvhd_box.box_payload_size() as usize;
+    let mut duplicate_mvhd_box = moov_box.mvhd_box.clone();
+    duplicate_mvhd_box.next_track_id = 100;
+    moov_box.unknown_boxes.push(UnknownBox {
+        box_type: moov_box.mvhd_box.box_type(),
+        box_size: BoxSize::with_payload_size(
+            moov_box.mvhd_box.box_type(),
+            duplicate_mvhd_box.box_payload_size(),
+        ),
+        payload: duplicate_mvhd_box.encode_to_vec()?[header_size..].to_vec(),
+    });
+    let bytes = moov_box.encode_to_vec()?;
+
+    let (decoded, warnings) = MoovBox::decode_with_options(&bytes, LENIENT)?;
+    assert_eq!(decoded.mvhd_box, moov_box.mvhd_box);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].box_path, "moov");
+    assert!(warnings[0].message.contains("Duplicate 'mvhd' box"));
+    Ok(())
+}
+
+#[test]
+fn unknown_full_box_version() -> Result<()> {
+    let mut bytes = moov_box_bytes()?;
+    let position = find_box_position(&bytes, b"mdhd");
+    assert_eq!(bytes[position + 8], 0);
+    bytes[position + 8] = 2;
+
+    let (moov_box, warnings) = MoovBox::decode_with_options(&bytes, LENIENT)?;
+    assert_eq!(moov_box, MoovBox::decode(&moov_box_bytes()?[..])?);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].box_path, "moov > trak > mdia > mdhd");
+    assert!(warnings[0].message.contains("version"));
+    Ok(())
+}
+
+#[test]
+fn empty_stsd_box() -> Result<()> {
+    let mut moov_box = MoovBox::decode(&moov_box_bytes()?[..])?;
+    moov_box.trak_boxes[0]
+        .mdia_box
+        .minf_box
+        .stbl_box
+        .stsd_box
+        .entries
+        .clear();
+    let bytes = moov_box.encode_to_vec()?;
+
+    let (decoded, warnings) = MoovBox::decode_with_options(&bytes, LENIENT)?;
+    assert_eq!(decoded, moov_box);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].box_path,
+        "moov > trak > mdia > minf > stbl > stsd"
+    );
+    Ok(())
+}
+
+// vmhd ボックスのフラグを 0 に書き換えた moov ボックスのバイト列を作る
+fn non_conformant_moov_box_bytes() -> Result<Vec<u8>> {
+    let mut bytes = moov_box_bytes()?;
+    let position = find_box_position(&bytes, b"vmhd");
+    bytes[position + 9..][..3].copy_from_slice(&[0, 0, 0]);
+    Ok(bytes)
+}
+
+// デコード処理の中で、寛容モードではない通常のデコードを行う型
+struct StrictDecodeInside {
+    is_err: bool,
+}
+
+impl Decode for StrictDecodeInside {
+    fn decode<R: std::io::Read>(_reader: R) -> Result<Self> {
+        let bytes = non_conformant_moov_box_bytes()?;
+        let result = MoovBox::decode_with_options(&bytes, DecodeOptions::default());
+        Ok(Self {
+            is_err: result.is_err(),
+        })
+    }
+}
+
+#[test]
+fn strict_decode_inside_lenient_decode() -> Result<()> {
+    // 寛容モードの設定は、内側の通常のデコードには引き継がれない
+    let (decoded, warnings) = StrictDecodeInside::decode_with_options(&[], LENIENT)?;
+    assert!(decoded.is_err);
+    assert!(warnings.is_empty());
+
+    // 内側のデコードが終わった後は、外側の寛容モードが元に戻っている
+    let bytes = non_conformant_moov_box_bytes()?;
+    let (_, warnings) = MoovBox::decode_with_options(&bytes, LENIENT)?;
+    assert_eq!(warnings.len(), 1);
+    assert!(MoovBox::decode(&bytes[..]).is_err());
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use shiguredo_mp4::{
+    boxes::{MdhdBox, RootBox},
+    Decode, Encode, Mp4File, Result,
+};
+
+fn mdhd_box() -> Result<MdhdBox> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File<RootBox> = Mp4File::decode(&input_bytes[..])?;
+    let moov_box = file.find_moov().expect("no moov box");
+    Ok(moov_box.trak_boxes[0].mdia_box.mdhd_box.clone())
+}
+
+#[test]
+fn language_str() -> Result<()> {
+    let mut mdhd_box = mdhd_box()?;
+
+    // エンコード・デコードを経ても言語コードが保持される
+    mdhd_box.language = *b"jpn";
+    let decoded = MdhdBox::decode(&mdhd_box.encode_to_vec()?[..])?;
+    assert_eq!(decoded.language, *b"jpn");
+    assert_eq!(decoded.language_str(), Some("jpn"));
+
+    // 未定義の場合
+    mdhd_box.language = MdhdBox::LANGUAGE_UNDEFINED;
+    assert_eq!(mdhd_box.language_str(), None);
+
+    // 不正な言語コードの場合
+    mdhd_box.language = *b"JPN";
+    assert_eq!(mdhd_box.language_str(), None);
+    mdhd_box.language = [0xff, b'p', b'n'];
+    assert_eq!(mdhd_box.language_str(), None);
+    Ok(())
+}
@@ -0,0 +1,1009 @@
+use std::{collections::HashMap, num::NonZeroU32};
+
+use shiguredo_mp4::{
+    aux::{
+        SampleTableAccessor, TrackKind, ValidationCode, ValidationError, ValidationIssue,
+        ValidationSeverity,
+    },
+    boxes::{
+        Co64Box, EdtsBox, ElstBox, ElstEntry, MdiaBox, MoovBox, MvhdBox, RootBox, StblBox, StcoBox,
+        StscBox, StscEntry, StssBox, StszBox, SttsBox, SttsEntry, TkhdBox, TrakBox, UnknownBox,
+    },
+    BaseBox, BoxHeader, BoxSize, BoxType, Decode, Either, Encode, FixedPointNumber, Mp4File,
+    Result,
+};
+
+fn decode_moov_box(input_bytes: &[u8]) -> Result<MoovBox> {
+    let file: Mp4File = Mp4File::decode(input_bytes)?;
+    let moov_box = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box");
+    Ok(moov_box)
+}
+
+// 映像と音声の二つのトラックを含む moov ボックスを作る
+fn video_and_audio_moov_box() -> Result<MoovBox> {
+    let mut moov_box = decode_moov_box(include_bytes!("testdata/black-h264-video.mp4"))?;
+    let audio_moov_box = decode_moov_box(include_bytes!("testdata/beep-opus-audio.mp4"))?;
+
+    let mut audio_trak_box = audio_moov_box.trak_boxes[0].clone();
+    audio_trak_box.tkhd_box.track_id = 2;
+    moov_box.trak_boxes[0].tkhd_box.track_id = 1;
+    moov_box.trak_boxes.push(audio_trak_box);
+    moov_box.mvhd_box.next_track_id = 3;
+    Ok(moov_box)
+}
+
+// エンコード結果のバイト列から、直下の子ボックスの種別を順番に取り出す
+fn child_box_types(bytes: &[u8]) -> Result<Vec<BoxType>> {
+    let mut reader = &bytes[8..];
+    let mut box_types = Vec::new();
+    while !reader.is_empty() {
+        let header = BoxHeader::decode(reader)?;
+        box_types.push(header.box_type);
+        reader = &reader[BoxHeader::skip_box(reader)?..];
+    }
+    Ok(box_types)
+}
+
+fn unknown_box(box_type: &[u8; 4]) -> UnknownBox {
+    UnknownBox {
+        box_type: BoxType::Normal(*box_type),
+        box_size: BoxSize::U32(8),
+        payload: Vec::new(),
+    }
+}
+
+#[test]
+fn encode_children_in_spec_order() -> Result<()> {
+    let mut moov_box = decode_moov_box(include_bytes!("testdata/black-h264-video.mp4"))?;
+    moov_box.unknown_boxes = vec![
+        unknown_box(b"udta"),
+        unknown_box(b"meta"),
+        unknown_box(b"mvex"),
+        unknown_box(b"iods"),
+    ];
+    let trak_box = &mut moov_box.trak_boxes[0];
+    assert!(trak_box.edts_box.is_some());
+    trak_box.unknown_boxes = vec![
+        unknown_box(b"meta"),
+        unknown_box(b"test"),
+        unknown_box(b"tref"),
+    ];
+
+    // moov: mvhd, iods / meta, trak, その他
+    let bytes = moov_box.encode_to_vec()?;
+    let expected = [
+        MvhdBox::TYPE,
+        BoxType::Normal(*b"meta"),
+        BoxType::Normal(*b"iods"),
+        TrakBox::TYPE,
+        BoxType::Normal(*b"udta"),
+        BoxType::Normal(*b"mvex"),
+    ];
+    assert_eq!(child_box_types(&bytes)?, expected);
+    assert_eq!(
+        moov_box
+            .children()
+            .map(|b| b.box_type())
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    // trak: tkhd, tref, edts, meta, mdia, その他
+    let trak_box = &moov_box.trak_boxes[0];
+    let bytes = trak_box.encode_to_vec()?;
+    let expected = [
+        TkhdBox::TYPE,
+        BoxType::Normal(*b"tref"),
+        EdtsBox::TYPE,
+        BoxType::Normal(*b"meta"),
+        MdiaBox::TYPE,
+        BoxType::Normal(*b"test"),
+    ];
+    assert_eq!(child_box_types(&bytes)?, expected);
+    assert_eq!(
+        trak_box
+            .children()
+            .map(|b| b.box_type())
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    // デコード結果の unknown_boxes はエンコード時の順番に並ぶ
+    let decoded = MoovBox::decode(&moov_box.encode_to_vec()?[..])?;
+    assert_eq!(
+        decoded
+            .unknown_boxes
+            .iter()
+            .map(|b| b.box_type)
+            .collect::<Vec<_>>(),
+        [b"meta", b"iods", b"udta", b"mvex"].map(|t| BoxType::Normal(*t))
+    );
+    assert_eq!(
+        decoded.trak_boxes[0].unknown_boxes,
+        [
+            unknown_box(b"tref"),
+            unknown_box(b"meta"),
+            unknown_box(b"test")
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn into_single_track() -> Result<()> {
+    // 映像・音声・映像の三つのトラックを含む moov ボックスを作る
+    let mut moov_box = video_and_audio_moov_box()?;
+    let mut vp9_trak_box =
+        decode_moov_box(include_bytes!("testdata/black-vp9-video.mp4"))?.trak_boxes[0].clone();
+    vp9_trak_box.tkhd_box.track_id = 3;
+    moov_box.trak_boxes.push(vp9_trak_box);
+    moov_box.mvhd_box.next_track_id = 4;
+
+    for i in 0..3 {
+        let expected_trak_box = &moov_box.trak_boxes[i];
+        let single = moov_box.clone().into_single_track(i)?;
+        assert_eq!(&single.trak_boxes, std::slice::from_ref(expected_trak_box));
+        assert_eq!(
+            single.mvhd_box.next_track_id,
+            expected_trak_box.tkhd_box.track_id + 1
+        );
+        assert_eq!(single.mvhd_box.timescale, moov_box.mvhd_box.timescale);
+        let mdhd_box = &expected_trak_box.mdia_box.mdhd_box;
+        assert_eq!(
+            single.mvhd_box.duration,
+            mdhd_box.duration * single.mvhd_box.timescale.get() as u64
+                / mdhd_box.timescale.get() as u64
+        );
+
+        // 単一トラックの moov ボックスとしても問題がない
+        assert!(single.validate_deep().first_error().is_none());
+        let decoded = MoovBox::decode(&single.encode_to_vec()?[..])?;
+        assert_eq!(decoded, single);
+    }
+
+    // 範囲外のインデックスはエラー
+    assert!(moov_box.into_single_track(3).is_err());
+    Ok(())
+}
+
+#[test]
+fn remap_track_ids() -> Result<()> {
+    let mut moov_box = video_and_audio_moov_box()?;
+
+    moov_box.remap_track_ids(&HashMap::from([(1, 10), (2, 20)]))?;
+    let ids = moov_box
+        .trak_boxes
+        .iter()
+        .map(|b| b.tkhd_box.track_id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [10, 20]);
+    assert_eq!(moov_box.mvhd_box.next_track_id, 21);
+
+    // 存在しないトラック ID の指定はエラー
+    let original = moov_box.clone();
+    assert!(moov_box.remap_track_ids(&HashMap::from([(1, 30)])).is_err());
+    assert_eq!(moov_box, original);
+
+    // 書き換え後に ID が重複する場合もエラー
+    assert!(moov_box
+        .remap_track_ids(&HashMap::from([(10, 20)]))
+        .is_err());
+    assert_eq!(moov_box, original);
+
+    // 入れ替えは可能
+    moov_box.remap_track_ids(&HashMap::from([(10, 20), (20, 10)]))?;
+    let ids = moov_box
+        .trak_boxes
+        .iter()
+        .map(|b| b.tkhd_box.track_id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [20, 10]);
+
+    Ok(())
+}
+
+#[test]
+fn strip_edit_lists() -> Result<()> {
+    let mut moov_box = video_and_audio_moov_box()?;
+    let movie_timescale = moov_box.mvhd_box.timescale;
+
+    // 映像トラックには 1000 の空編集とそれに続くメディア編集を設定する
+    // （タイムスケールの変換が入らないようにメディアのタイムスケールを揃えておく）
+    let video = &mut moov_box.trak_boxes[0];
+    video.mdia_box.mdhd_box.timescale = movie_timescale;
+    video.edts_box = Some(EdtsBox {
+        elst_box: Some(ElstBox {
+            entries: vec![
+                ElstEntry {
+                    edit_duration: 1000,
+                    media_time: -1,
+                    media_rate: FixedPointNumber::new(1, 0),
+                },
+                ElstEntry {
+                    edit_duration: 10000,
+                    media_time: 0,
+                    media_rate: FixedPointNumber::new(1, 0),
+                },
+            ],
+        }),
+        unknown_boxes: Vec::new(),
+    });
+
+    // 音声トラックには先頭 312 をスキップするメディア編集を設定する
+    let audio = &mut moov_box.trak_boxes[1];
+    audio.edts_box = Some(EdtsBox {
+        elst_box: Some(ElstBox {
+            entries: vec![ElstEntry {
+                edit_duration: 10000,
+                media_time: 312,
+                media_rate: FixedPointNumber::new(1, 0),
+            }],
+        }),
+        unknown_boxes: Vec::new(),
+    });
+
+    assert_eq!(moov_box.strip_all_edit_lists(), [1000, -312]);
+    assert!(moov_box.trak_boxes.iter().all(|b| b.edts_box.is_none()));
+
+    // 編集リストがない場合は 0 になる
+    assert_eq!(moov_box.strip_all_edit_lists(), [0, 0]);
+
+    Ok(())
+}
+
+#[test]
+fn normalize_timescale() -> Result<()> {
+    let mut moov_box = video_and_audio_moov_box()?;
+    let duration_ms = |b: &TrakBox| {
+        let mdhd_box = &b.mdia_box.mdhd_box;
+        let total: u64 = b
+            .mdia_box
+            .minf_box
+            .stbl_box
+            .stts_box
+            .entries
+            .iter()
+            .map(|x| x.sample_count as u64 * x.sample_delta as u64)
+            .sum();
+        (
+            mdhd_box.duration * 1000 / mdhd_box.timescale.get() as u64,
+            total * 1000 / mdhd_box.timescale.get() as u64,
+        )
+    };
+
+    // 映像トラックを 90000 Hz の 25 fps にする
+    let video = &mut moov_box.trak_boxes[0];
+    let stts_box = &mut video.mdia_box.minf_box.stbl_box.stts_box;
+    let sample_count: u32 = stts_box.entries.iter().map(|x| x.sample_count).sum();
+    *stts_box = SttsBox::from_sample_deltas(vec![3600; sample_count as usize]);
+    video.mdia_box.mdhd_box.timescale = NonZeroU32::new(90000).expect("non zero");
+    video.mdia_box.mdhd_box.duration = 3600 * sample_count as u64;
+
+    // テストデータの音声トラックの尺はミリ秒単位では割り切れないので、ここでは対象外にする
+    let audio = moov_box.trak_boxes.pop().expect("audio track");
+    let expected = duration_ms(&moov_box.trak_boxes[0]);
+    moov_box.normalize_timescale(NonZeroU32::new(1000).expect("non zero"))?;
+    assert_eq!(
+        moov_box.trak_boxes[0].mdia_box.mdhd_box.timescale.get(),
+        1000
+    );
+    assert_eq!(duration_ms(&moov_box.trak_boxes[0]), expected);
+    assert!(moov_box.trak_boxes[0]
+        .mdia_box
+        .minf_box
+        .stbl_box
+        .stts_box
+        .entries
+        .iter()
+        .all(|x| x.sample_delta == 40));
+
+    // 整数に変換できない場合はエラーになり、元の値は変更されない
+    moov_box.trak_boxes.push(audio);
+    let original = moov_box.clone();
+    assert!(moov_box
+        .normalize_timescale(NonZeroU32::new(1000).expect("non zero"))
+        .is_err());
+    assert_eq!(moov_box, original);
+    assert!(moov_box
+        .normalize_timescale(NonZeroU32::new(44100).expect("non zero"))
+        .is_err());
+    assert_eq!(moov_box, original);
+
+    Ok(())
+}
+
+#[test]
+fn find_tracks() -> Result<()> {
+    let mut moov_box = video_and_audio_moov_box()?;
+
+    let video = moov_box.video_track().expect("video track");
+    assert_eq!(video.tkhd_box.track_id, 1);
+    let audio = moov_box.audio_track().expect("audio track");
+    assert_eq!(audio.tkhd_box.track_id, 2);
+
+    assert_eq!(moov_box.track_by_id(1), Some(&moov_box.trak_boxes[0]));
+    assert_eq!(moov_box.track_by_id(2), Some(&moov_box.trak_boxes[1]));
+    assert_eq!(moov_box.track_by_id(3), None);
+
+    assert_eq!(moov_box.tracks_by_handler(*b"vide").count(), 1);
+    assert_eq!(moov_box.tracks_by_handler(*b"soun").count(), 1);
+    assert_eq!(moov_box.tracks_by_handler(*b"text").count(), 0);
+
+    moov_box
+        .video_track_mut()
+        .expect("video track")
+        .tkhd_box
+        .track_id = 10;
+    moov_box
+        .audio_track_mut()
+        .expect("audio track")
+        .tkhd_box
+        .track_id = 20;
+    assert_eq!(moov_box.trak_boxes[0].tkhd_box.track_id, 10);
+    assert_eq!(moov_box.trak_boxes[1].tkhd_box.track_id, 20);
+
+    // 該当するトラックがない場合
+    moov_box.trak_boxes.pop();
+    assert!(moov_box.audio_track().is_none());
+    assert!(moov_box.audio_track_mut().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn trak_box_helpers() -> Result<()> {
+    let mut moov_box = video_and_audio_moov_box()?;
+
+    let video = &moov_box.trak_boxes[0];
+    assert_eq!(video.track_kind(), Some(TrackKind::Video));
+    assert_eq!(
+        video.sample_entry().map(|x| x.box_type().to_string()),
+        Some("avc1".to_owned())
+    );
+    let audio = &moov_box.trak_boxes[1];
+    assert_eq!(audio.track_kind(), Some(TrackKind::Audio));
+    assert_eq!(
+        audio.sample_entry().map(|x| x.box_type().to_string()),
+        Some("Opus".to_owned())
+    );
+
+    // stsd が空の場合やハンドラー種別が不明な場合
+    let trak_box = &mut moov_box.trak_boxes[0];
+    trak_box.mdia_box.minf_box.stbl_box.stsd_box.entries.clear();
+    trak_box.mdia_box.hdlr_box.handler_type = *b"text";
+    assert!(trak_box.sample_entry().is_none());
+    assert!(trak_box.sample_entry_mut().is_none());
+    assert_eq!(trak_box.track_kind(), None);
+
+    Ok(())
+}
+
+#[test]
+fn trak_box_statistics() -> Result<()> {
+    let moov_box = video_and_audio_moov_box()?;
+
+    // 映像トラック: 尺 512 / 12800 秒のサンプルが一つだけ
+    let video = &moov_box.trak_boxes[0];
+    assert_eq!(video.mdia_box.mdhd_box.duration_ms(), 40.0);
+    assert_eq!(video.sample_count(), 1);
+    assert_eq!(video.frame_rate(), Some(25.0));
+    assert_eq!(video.average_bitrate(), Some(796 * 8 * 25));
+
+    // 音声トラックにはフレームレートはない
+    let audio = &moov_box.trak_boxes[1];
+    assert_eq!(audio.frame_rate(), None);
+    let stbl_box = &audio.mdia_box.minf_box.stbl_box;
+    let sample_table = SampleTableAccessor::new(stbl_box).expect("invalid sample table");
+    assert_eq!(audio.sample_count(), sample_table.sample_count());
+    assert_eq!(
+        audio.average_bitrate(),
+        sample_table.average_bitrate(audio.mdia_box.mdhd_box.timescale)
+    );
+
+    // 尺が 0 の場合
+    let mut video = video.clone();
+    video.mdia_box.mdhd_box.duration = 0;
+    assert_eq!(video.frame_rate(), None);
+    assert_eq!(video.average_bitrate(), None);
+
+    Ok(())
+}
+
+#[test]
+fn error_box_path() -> Result<()> {
+    let moov_box = video_and_audio_moov_box()?;
+    let mut bytes = moov_box.encode_to_vec()?;
+
+    // 二番目のトラックの stsd ボックスのエントリー数を壊す
+    let stsd_position = bytes
+        .windows(4)
+        .enumerate()
+        .filter(|(_, x)| *x == b"stsd")
+        .nth(1)
+        .map(|(i, _)| i)
+        .expect("stsd box");
+    bytes[stsd_position + 8..][..4].copy_from_slice(&100u32.to_be_bytes());
+
+    let error = MoovBox::decode(&bytes[..]).expect_err("should fail");
+    assert_eq!(error.box_type, Some(BoxType::Normal(*b"stsd")));
+    assert_eq!(
+        error.box_path(),
+        "moov > trak[1] > mdia > minf > stbl > stsd"
+    );
+    assert!(error
+        .to_string()
+        .starts_with("[moov > trak[1] > mdia > minf > stbl > stsd] "));
+
+    Ok(())
+}
+
+#[test]
+fn error_add_context() {
+    let error = shiguredo_mp4::Error::from(std::io::Error::other("test"))
+        .add_context(BoxType::Normal(*b"traf"), Some(2))
+        .add_context(BoxType::Normal(*b"moof"), None);
+    assert_eq!(error.box_type, Some(BoxType::Normal(*b"traf")));
+    assert_eq!(error.box_path(), "moof > traf[2]");
+    assert!(error.to_string().starts_with("[moof > traf[2]] test"));
+}
+
+// mdat ボックスの後ろにある moov ボックスを前に移動して、チャンクオフセットを補正する
+fn relayout_moov_first(input_bytes: &[u8]) -> Result<Vec<u8>> {
+    let file: Mp4File = Mp4File::decode(input_bytes)?;
+    let mut moov_box = None;
+    let mut other_boxes = Vec::new();
+    for b in file.boxes {
+        match b {
+            RootBox::Moov(b) => moov_box = Some(b),
+            b => other_boxes.push(b),
+        }
+    }
+    let mut moov_box = moov_box.expect("no moov box");
+
+    // オフセットを補正しても stco ボックスのサイズは変わらないので、moov ボックスのサイズ分ずらせばいい
+    let moov_box_size = moov_box.box_size().get() as i64;
+    moov_box.patch_chunk_offsets(moov_box_size)?;
+    assert_eq!(moov_box.box_size().get() as i64, moov_box_size);
+
+    let mut output_bytes = file.ftyp_box.encode_to_vec()?;
+    for b in other_boxes {
+        if matches!(b, RootBox::Mdat(_)) {
+            output_bytes.extend_from_slice(&moov_box.encode_to_vec()?);
+        }
+        output_bytes.extend_from_slice(&b.encode_to_vec()?);
+    }
+    Ok(output_bytes)
+}
+
+fn sample_data(input_bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let moov_box = decode_moov_box(input_bytes)?;
+    let mut data = Vec::new();
+    for trak_box in &moov_box.trak_boxes {
+        let accessor = SampleTableAccessor::new(&trak_box.mdia_box.minf_box.stbl_box)
+            .expect("invalid sample table");
+        for sample in accessor.samples() {
+            let range = sample.data_range();
+            data.push(input_bytes[range.start as usize..range.end as usize].to_vec());
+        }
+    }
+    Ok(data)
+}
+
+#[test]
+fn patch_chunk_offsets_for_moov_first_layout() -> Result<()> {
+    for input_bytes in [
+        &include_bytes!("testdata/black-h264-video.mp4")[..],
+        &include_bytes!("testdata/beep-aac-audio.mp4")[..],
+    ] {
+        let output_bytes = relayout_moov_first(input_bytes)?;
+
+        // ボックスの並び順が変わるだけなので、再エンコードした元ファイルとサイズは一致する
+        let file: Mp4File = Mp4File::decode(input_bytes)?;
+        assert_eq!(output_bytes.len(), file.encode_to_vec()?.len());
+
+        // moov ボックスが mdat ボックスより前にある
+        let file: Mp4File = Mp4File::decode(&output_bytes[..])?;
+        let box_types = file.boxes.iter().map(|b| b.box_type()).collect::<Vec<_>>();
+        assert_eq!(
+            box_types,
+            [
+                BoxType::Normal(*b"free"),
+                BoxType::Normal(*b"moov"),
+                BoxType::Normal(*b"mdat")
+            ]
+        );
+
+        // 補正後のオフセットで元と同じサンプルデータが参照できる
+        let expected = sample_data(input_bytes)?;
+        assert!(!expected.is_empty());
+        assert_eq!(sample_data(&output_bytes)?, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn patch_offsets_out_of_range() -> Result<()> {
+    let mut stco_box = StcoBox {
+        chunk_offsets: vec![10, 20, u32::MAX - 5],
+    };
+
+    // 範囲外になる場合はエラーになり、元の値は変更されない
+    assert!(stco_box.patch_offsets(-11).is_err());
+    assert!(stco_box.patch_offsets(6).is_err());
+    assert_eq!(stco_box.chunk_offsets, [10, 20, u32::MAX - 5]);
+
+    stco_box.patch_offsets(-10)?;
+    assert_eq!(stco_box.chunk_offsets, [0, 10, u32::MAX - 15]);
+    stco_box.patch_offsets(15)?;
+    assert_eq!(stco_box.chunk_offsets, [15, 25, u32::MAX]);
+
+    let mut co64_box = Co64Box {
+        chunk_offsets: vec![10, u64::MAX - 5],
+    };
+    assert!(co64_box.patch_offsets(-11).is_err());
+    assert!(co64_box.patch_offsets(6).is_err());
+    assert_eq!(co64_box.chunk_offsets, [10, u64::MAX - 5]);
+
+    co64_box.patch_offsets(-10)?;
+    assert_eq!(co64_box.chunk_offsets, [0, u64::MAX - 15]);
+    co64_box.patch_offsets(15)?;
+    assert_eq!(co64_box.chunk_offsets, [15, u64::MAX]);
+    Ok(())
+}
+
+#[test]
+fn chunk_offsets_out_of_range() -> Result<()> {
+    let stco_box = StcoBox {
+        chunk_offsets: vec![10, u32::MAX, 99, 100],
+    };
+    assert_eq!(stco_box.out_of_range_chunks(100), [1, 3]);
+    assert_eq!(stco_box.out_of_range_chunks(u64::MAX), [] as [usize; 0]);
+    assert_eq!(stco_box.max_offset(), Some(u32::MAX));
+    assert_eq!(
+        StcoBox {
+            chunk_offsets: Vec::new()
+        }
+        .max_offset(),
+        None
+    );
+
+    let co64_box = Co64Box {
+        chunk_offsets: vec![u64::MAX, 10],
+    };
+    assert_eq!(co64_box.out_of_range_chunks(100), [0]);
+    assert_eq!(co64_box.max_offset(), Some(u64::MAX));
+
+    // stbl ボックス単位でのチェック
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let mut moov_box = decode_moov_box(input_bytes)?;
+    let stbl_box = &mut moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box;
+    assert!(stbl_box
+        .validate_chunk_offsets(input_bytes.len() as u64)
+        .is_empty());
+
+    stbl_box.stco_or_co64_box = Either::A(stco_box);
+    let errors = stbl_box.validate_chunk_offsets(100);
+    assert_eq!(
+        errors,
+        [
+            ValidationError::ChunkOffsetOutOfRange {
+                from: StcoBox::TYPE,
+                chunk_index: 2,
+                offset: u32::MAX as u64,
+                file_size: 100,
+            },
+            ValidationError::ChunkOffsetOutOfRange {
+                from: StcoBox::TYPE,
+                chunk_index: 4,
+                offset: 100,
+                file_size: 100,
+            },
+        ]
+    );
+    assert_eq!(
+        ValidationCode::from(&errors[0]),
+        ValidationCode::ChunkOffsetOutOfRange
+    );
+
+    stbl_box.stco_or_co64_box = Either::B(co64_box);
+    assert_eq!(
+        stbl_box.validate_chunk_offsets(100),
+        [ValidationError::ChunkOffsetOutOfRange {
+            from: Co64Box::TYPE,
+            chunk_index: 1,
+            offset: u64::MAX,
+            file_size: 100,
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_deep_real_files() -> Result<()> {
+    for input_bytes in [
+        &include_bytes!("testdata/black-h264-video.mp4")[..],
+        &include_bytes!("testdata/black-h265-video.mp4")[..],
+        &include_bytes!("testdata/black-vp9-video.mp4")[..],
+        &include_bytes!("testdata/black-av1-video.mp4")[..],
+        &include_bytes!("testdata/beep-aac-audio.mp4")[..],
+        &include_bytes!("testdata/beep-opus-audio.mp4")[..],
+    ] {
+        let report = decode_moov_box(input_bytes)?.validate_deep();
+        assert!(report.is_empty(), "{:?}", report.issues);
+    }
+    Ok(())
+}
+
+// 検証用の問題のない moov ボックスを作る
+//
+// サンプルテーブルは、2 つのチャンクにそれぞれ 2 つずつサンプルが属する構成に置き換えている
+fn moov_box_for_validation() -> Result<MoovBox> {
+    let mut moov_box = decode_moov_box(include_bytes!("testdata/black-h264-video.mp4"))?;
+    let stbl_box = &mut moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box;
+    stbl_box.stts_box = SttsBox::from_sample_deltas([128; 4]);
+    stbl_box.stsc_box = stsc_box(&[(1, 2, 1)]);
+    stbl_box.stsz_box = StszBox::Fixed {
+        sample_size: NonZeroU32::MIN.saturating_add(9),
+        sample_count: 4,
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: vec![100, 200],
+    });
+    stbl_box.stss_box = Some(StssBox {
+        sample_numbers: vec![index(1), index(3)],
+    });
+    assert!(moov_box.validate_deep().is_empty());
+    Ok(moov_box)
+}
+
+fn index(i: u32) -> NonZeroU32 {
+    NonZeroU32::new(i).expect("invalid index")
+}
+
+fn stsc_box(entries: &[(u32, u32, u32)]) -> StscBox {
+    StscBox {
+        entries: entries
+            .iter()
+            .map(
+                |&(first_chunk, sample_per_chunk, sample_description_index)| StscEntry {
+                    first_chunk: index(first_chunk),
+                    sample_per_chunk,
+                    sample_description_index: index(sample_description_index),
+                },
+            )
+            .collect(),
+    }
+}
+
+fn stbl_box_mut(moov_box: &mut MoovBox) -> &mut StblBox {
+    &mut moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box
+}
+
+#[track_caller]
+fn assert_single_issue(moov_box: &MoovBox, code: ValidationCode) -> ValidationIssue {
+    let report = moov_box.validate_deep();
+    let codes = report.issues.iter().map(|x| x.code).collect::<Vec<_>>();
+    assert_eq!(codes, [code]);
+    report.issues[0].clone()
+}
+
+#[test]
+fn validate_deep_no_tracks() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes.clear();
+    let issue = assert_single_issue(&moov_box, ValidationCode::NoTracks);
+    assert_eq!(issue.severity, ValidationSeverity::Warning);
+    assert_eq!(issue.path, "moov");
+    assert_eq!(moov_box.validate_deep().first_error(), None);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_next_track_id_too_small() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.mvhd_box.next_track_id = moov_box.trak_boxes[0].tkhd_box.track_id;
+    let issue = assert_single_issue(&moov_box, ValidationCode::NextTrackIdTooSmall);
+    assert_eq!(issue.severity, ValidationSeverity::Error);
+    assert_eq!(issue.path, "moov > mvhd");
+    assert_eq!(moov_box.validate_deep().first_error(), Some(&issue));
+
+    // 全ビットが 1 の場合は問題なし
+    moov_box.mvhd_box.next_track_id = u32::MAX;
+    assert!(moov_box.validate_deep().is_empty());
+    Ok(())
+}
+
+#[test]
+fn validate_deep_track_id_zero() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes[0].tkhd_box.track_id = 0;
+    let issue = assert_single_issue(&moov_box, ValidationCode::TrackIdZero);
+    assert_eq!(issue.path, "moov > trak[0] > tkhd");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_track_id_duplicate() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes.push(moov_box.trak_boxes[0].clone());
+    let issue = assert_single_issue(&moov_box, ValidationCode::TrackIdDuplicate);
+    assert_eq!(issue.path, "moov > trak[1] > tkhd");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_mdhd_duration_mismatch_mvhd() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+
+    // 編集リストがある場合はチェック対象外
+    moov_box.mvhd_box.duration -= 1;
+    assert!(moov_box.validate_deep().is_empty());
+
+    moov_box.trak_boxes[0].edts_box = None;
+    let issue = assert_single_issue(&moov_box, ValidationCode::MdhdDurationMismatchMvhd);
+    assert_eq!(issue.severity, ValidationSeverity::Warning);
+    assert_eq!(issue.path, "moov > trak[0] > mdia > mdhd");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_mdhd_duration_mismatch_stts() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes[0].mdia_box.mdhd_box.duration -= 1;
+    assert_single_issue(&moov_box, ValidationCode::MdhdDurationMismatchStts);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stts_duration_overflow() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+
+    // 尺の合計が u64 の範囲を超える stts でも panic しない
+    stbl_box_mut(&mut moov_box).stts_box.entries = vec![
+        SttsEntry {
+            sample_count: u32::MAX,
+            sample_delta: u32::MAX,
+        };
+        2
+    ];
+    let codes = moov_box
+        .validate_deep()
+        .issues
+        .iter()
+        .map(|x| x.code)
+        .collect::<Vec<_>>();
+    assert!(codes.contains(&ValidationCode::MdhdDurationMismatchStts));
+    Ok(())
+}
+
+#[test]
+fn validate_deep_elst_media_time_out_of_range() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    let media_duration = moov_box.trak_boxes[0].mdia_box.mdhd_box.duration as i64;
+    let elst_box = moov_box.trak_boxes[0]
+        .edts_box
+        .as_mut()
+        .and_then(|b| b.elst_box.as_mut())
+        .expect("no elst box");
+    elst_box.entries[0].media_time = media_duration + 1;
+    let issue = assert_single_issue(&moov_box, ValidationCode::ElstMediaTimeOutOfRange);
+    assert_eq!(issue.path, "moov > trak[0] > edts > elst");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_handler_media_header_mismatch() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes[0].mdia_box.hdlr_box.handler_type = *b"soun";
+    assert_single_issue(&moov_box, ValidationCode::HandlerMediaHeaderMismatch);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_empty_sample_description() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stsd_box.entries.clear();
+    let issue = assert_single_issue(&moov_box, ValidationCode::EmptySampleDescription);
+    assert_eq!(issue.path, "moov > trak[0] > mdia > minf > stbl > stsd");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_empty_sample_table() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes[0].mdia_box.mdhd_box.duration = 0;
+    let stbl_box = stbl_box_mut(&mut moov_box);
+    stbl_box.stts_box.entries.clear();
+    stbl_box.stsc_box.entries.clear();
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: Vec::new(),
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: Vec::new(),
+    });
+    stbl_box.stss_box = None;
+    let issue = assert_single_issue(&moov_box, ValidationCode::EmptySampleTable);
+    assert_eq!(issue.severity, ValidationSeverity::Warning);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_sample_count_mismatch() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stsz_box = StszBox::Fixed {
+        sample_size: NonZeroU32::MIN,
+        sample_count: 5,
+    };
+    let issue = assert_single_issue(&moov_box, ValidationCode::SampleCountMismatch);
+    assert_eq!(issue.path, "moov > trak[0] > mdia > minf > stbl");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_chunk_count_mismatch() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stsc_box = stsc_box(&[(1, 2, 1), (3, 2, 1)]);
+    assert_single_issue(&moov_box, ValidationCode::ChunkCountMismatch);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stsc_first_chunk_out_of_order() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stsc_box = stsc_box(&[(1, 2, 1), (1, 2, 1)]);
+    assert_single_issue(&moov_box, ValidationCode::StscFirstChunkOutOfOrder);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stsc_first_chunk_not_one() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stsc_box = stsc_box(&[(2, 4, 1)]);
+    let issue = assert_single_issue(&moov_box, ValidationCode::StscFirstChunkNotOne);
+    assert_eq!(issue.path, "moov > trak[0] > mdia > minf > stbl > stsc");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stsc_sample_description_index_out_of_range() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stsc_box = stsc_box(&[(1, 2, 2)]);
+    assert_single_issue(
+        &moov_box,
+        ValidationCode::StscSampleDescriptionIndexOutOfRange,
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stsc_zero_samples_per_chunk() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    let stbl_box = stbl_box_mut(&mut moov_box);
+    stbl_box.stsc_box = stsc_box(&[(1, 2, 1), (2, 0, 1), (3, 2, 1)]);
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: vec![100, 150, 200],
+    });
+    assert_single_issue(&moov_box, ValidationCode::StscZeroSamplesPerChunk);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_chunk_offset_overflow() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stco_or_co64_box = Either::B(Co64Box {
+        chunk_offsets: vec![100, u64::MAX - 5],
+    });
+    assert_single_issue(&moov_box, ValidationCode::ChunkOffsetOverflow);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_chunk_data_overlap() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+
+    // 先頭のチャンクのデータ範囲は 100..120 なので、隣接していても重なってはいない
+    stbl_box_mut(&mut moov_box).stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: vec![120, 100],
+    });
+    assert!(moov_box.validate_deep().is_empty());
+
+    stbl_box_mut(&mut moov_box).stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: vec![100, 119],
+    });
+    let issue = assert_single_issue(&moov_box, ValidationCode::ChunkDataOverlap);
+    assert_eq!(issue.path, "moov > trak[0] > mdia > minf > stbl > stco");
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stss_sample_number_out_of_range() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stss_box = Some(StssBox {
+        sample_numbers: vec![index(1), index(5)],
+    });
+    assert_single_issue(&moov_box, ValidationCode::StssSampleNumberOutOfRange);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_stss_not_sorted() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    stbl_box_mut(&mut moov_box).stss_box = Some(StssBox {
+        sample_numbers: vec![index(3), index(1)],
+    });
+    assert_single_issue(&moov_box, ValidationCode::StssNotSorted);
+    Ok(())
+}
+
+#[test]
+fn validate_deep_multiple_issues() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    moov_box.trak_boxes[0].mdia_box.mdhd_box.duration -= 1;
+    moov_box.trak_boxes[0].tkhd_box.track_id = 0;
+
+    // 最初の問題で打ち切られずに全てが報告される
+    let report = moov_box.validate_deep();
+    let codes = report.issues.iter().map(|x| x.code).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        [
+            ValidationCode::TrackIdZero,
+            ValidationCode::MdhdDurationMismatchStts
+        ]
+    );
+    assert_eq!(
+        report.first_error().map(|x| x.code),
+        Some(ValidationCode::TrackIdZero)
+    );
+    Ok(())
+}
+
+#[test]
+fn validate_huge_sample_per_chunk() -> Result<()> {
+    let mut moov_box = moov_box_for_validation()?;
+    let stbl_box = stbl_box_mut(&mut moov_box);
+
+    // stsc の sample_per_chunk が巨大でも、stts のサンプル数を超える分は走査されずに即座にエラーとなる
+    stbl_box.stsc_box = stsc_box(&[(1, u32::MAX, 1)]);
+    for stsz_box in [
+        StszBox::Fixed {
+            sample_size: NonZeroU32::MIN.saturating_add(9),
+            sample_count: 4,
+        },
+        StszBox::Variable {
+            entry_sizes: vec![10; 4],
+        },
+    ] {
+        stbl_box.stsz_box = stsz_box;
+        assert_eq!(
+            stbl_box.validate(),
+            Err(ValidationError::SampleCountMismatch {
+                from: StscBox::TYPE,
+                expected: 4,
+                got: u32::MAX as u64 * 2,
+            })
+        );
+    }
+
+    // チャンクオフセットとサンプルサイズの合計のオーバーフローは引き続き検出される
+    stbl_box.stsc_box = stsc_box(&[(1, 2, 1)]);
+    stbl_box.stco_or_co64_box = Either::B(Co64Box {
+        chunk_offsets: vec![100, u64::MAX - 10],
+    });
+    assert_eq!(
+        stbl_box.validate(),
+        Err(ValidationError::ChunkOffsetOverflow)
+    );
+    stbl_box.stco_or_co64_box = Either::B(Co64Box {
+        chunk_offsets: vec![100, u64::MAX - 20],
+    });
+    assert_eq!(stbl_box.validate(), Ok(()));
+    Ok(())
+}
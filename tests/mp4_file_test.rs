@@ -0,0 +1,224 @@
+use shiguredo_mp4::{
+    aux::SampleTableAccessor,
+    boxes::{Brand, FileTypeBoxContent, FtypBox, MdatBox, RootBox, UnknownBox},
+    BaseBox, BoxType, Decode, Encode, Mp4File, Result,
+};
+
+// ftyp + moov (mvhd のみ) + mdat からなる最小限の MP4 ファイル
+fn minimal_mp4_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    // ftyp
+    bytes.extend_from_slice(&[0, 0, 0, 20]);
+    bytes.extend_from_slice(b"ftyp");
+    bytes.extend_from_slice(b"isom");
+    bytes.extend_from_slice(&[0, 0, 0, 0]);
+    bytes.extend_from_slice(b"isom");
+
+    // moov
+    bytes.extend_from_slice(&[0, 0, 0, 8 + 108]);
+    bytes.extend_from_slice(b"moov");
+
+    // mvhd (version 0)
+    bytes.extend_from_slice(&[0, 0, 0, 108]);
+    bytes.extend_from_slice(b"mvhd");
+    bytes.extend_from_slice(&[0, 0, 0, 0]); // version, flags
+    bytes.extend_from_slice(&[0, 0, 0, 0]); // creation_time
+    bytes.extend_from_slice(&[0, 0, 0, 0]); // modification_time
+    bytes.extend_from_slice(&1000u32.to_be_bytes()); // timescale
+    bytes.extend_from_slice(&2500u32.to_be_bytes()); // duration
+    bytes.extend_from_slice(&[0, 1, 0, 0]); // rate
+    bytes.extend_from_slice(&[1, 0]); // volume
+    bytes.extend_from_slice(&[0; 2 + 4 * 2]); // reserved
+    for v in [0x00010000, 0, 0, 0, 0x00010000, 0, 0, 0, 0x40000000u32] {
+        bytes.extend_from_slice(&v.to_be_bytes()); // matrix
+    }
+    bytes.extend_from_slice(&[0; 4 * 6]); // pre_defined
+    bytes.extend_from_slice(&[0, 0, 0, 1]); // next_track_id
+
+    // mdat
+    bytes.extend_from_slice(&[0, 0, 0, 12]);
+    bytes.extend_from_slice(b"mdat");
+    bytes.extend_from_slice(&[1, 2, 3, 4]);
+
+    bytes
+}
+
+#[test]
+fn find_boxes() -> Result<()> {
+    let bytes = minimal_mp4_bytes();
+    let file: Mp4File = Mp4File::decode(&bytes[..])?;
+
+    let ftyp_box = file.find_ftyp().expect("no ftyp box");
+    assert_eq!(ftyp_box.major_brand, Brand::ISOM);
+
+    let moov_box = file.find_moov().expect("no moov box");
+    assert_eq!(moov_box.mvhd_box.timescale.get(), 1000);
+    assert!(moov_box.trak_boxes.is_empty());
+
+    assert_eq!(
+        file.find_mdat_payloads().collect::<Vec<_>>(),
+        [&[1, 2, 3, 4]]
+    );
+
+    assert!(file.duration_ms() > Some(0.0));
+    assert_eq!(file.duration_ms(), Some(2500.0));
+
+    let box_types = file.iter().map(|b| b.box_type().to_string());
+    assert_eq!(box_types.collect::<Vec<_>>(), ["ftyp", "moov", "mdat"]);
+    Ok(())
+}
+
+#[test]
+fn encode_round_trip() -> Result<()> {
+    let mut inputs = vec![
+        minimal_mp4_bytes(),
+        include_bytes!("testdata/black-h264-video.mp4").to_vec(),
+        include_bytes!("testdata/beep-opus-audio.mp4").to_vec(),
+    ];
+
+    // 末尾の mdat ボックスが可変長の場合
+    let mut bytes = minimal_mp4_bytes();
+    let mdat_start = bytes.len() - 12;
+    bytes[mdat_start..mdat_start + 4].copy_from_slice(&[0, 0, 0, 0]);
+    inputs.push(bytes);
+
+    for bytes in inputs {
+        let file: Mp4File = Mp4File::decode(&bytes[..])?;
+        assert_eq!(file.total_encoded_size(), bytes.len() as u64);
+        assert_eq!(file.encoded_size(), bytes.len() as u64);
+
+        let re_encoded = file.encode_to_vec()?;
+        assert_eq!(re_encoded, bytes);
+        assert_eq!(re_encoded.capacity(), bytes.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn find_boxes_without_moov() -> Result<()> {
+    let bytes = minimal_mp4_bytes();
+    let mut file: Mp4File = Mp4File::decode(&bytes[..])?;
+    file.boxes.retain(|b| b.box_type().as_bytes() != b"moov");
+
+    assert!(file.find_ftyp().is_some());
+    assert!(file.find_moov().is_none());
+    assert_eq!(file.duration_ms(), None);
+    assert_eq!(file.find_mdat_payloads().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn build_from_scratch() -> Result<()> {
+    // 既存のファイルから moov ボックスとサンプルデータだけを取り出す
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let input: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let mut moov_box = input.find_moov().expect("no moov box").clone();
+    let stbl_box = &moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box;
+    let sample_table = SampleTableAccessor::new(stbl_box).expect("invalid sample table");
+    let sample_data = sample_table
+        .samples()
+        .map(|s| s.read_data(input_bytes).map(|d| d.to_vec()))
+        .collect::<Result<Vec<_>>>()?;
+
+    // ftyp + moov + mdat の順に並べたファイルを新規に作る
+    let mut file = Mp4File {
+        ftyp_box: FtypBox(FileTypeBoxContent {
+            major_brand: Brand::ISOM,
+            minor_version: 0,
+            compatible_brands: vec![Brand::ISOM, Brand::ISO2, Brand::AVC1, Brand::MP41],
+        }),
+        boxes: Vec::new(),
+    };
+    assert!(file.boxes.is_empty());
+
+    // mdat ボックスのペイロードの開始位置に合わせて、チャンクオフセットを補正する
+    let mdat_box = MdatBox {
+        is_variable_size: false,
+        payload: sample_data.concat(),
+    };
+    let original_offset = sample_table
+        .get_sample(std::num::NonZeroU32::MIN)
+        .expect("no sample")
+        .data_offset() as i64;
+    let mdat_payload_offset = (file.ftyp_box.box_size().get()
+        + moov_box.box_size().get()
+        + (mdat_box.box_size().get() - mdat_box.payload.len() as u64))
+        as i64;
+    moov_box.patch_chunk_offsets(mdat_payload_offset - original_offset)?;
+
+    file.boxes.push(RootBox::Moov(moov_box));
+    file.boxes.push(RootBox::Mdat(mdat_box));
+    assert_eq!(file.boxes.len(), 2);
+
+    let bytes = file.encode_to_vec()?;
+    let decoded: Mp4File = Mp4File::decode(&bytes[..])?;
+    assert_eq!(decoded, file);
+
+    let box_types = decoded.iter().map(|b| b.box_type().to_string());
+    assert_eq!(box_types.collect::<Vec<_>>(), ["ftyp", "moov", "mdat"]);
+
+    // 新しいファイルのオフセットから、元と同じサンプルデータが読み出せる
+    let moov_box = decoded.find_moov().expect("no moov box");
+    let stbl_box = &moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box;
+    let sample_table = SampleTableAccessor::new(stbl_box).expect("invalid sample table");
+    for (sample, expected) in sample_table.samples().zip(&sample_data) {
+        assert_eq!(sample.read_data(&bytes)?, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn inject_raw_boxes() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let mut file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+
+    // 独自の uuid ボックスのバイト列
+    let mut uuid_bytes = Vec::new();
+    uuid_bytes.extend_from_slice(&(8u32 + 16 + 4).to_be_bytes());
+    uuid_bytes.extend_from_slice(b"uuid");
+    uuid_bytes.extend_from_slice(&[0xAB; 16]);
+    uuid_bytes.extend_from_slice(b"test");
+
+    // バイト列を UnknownBox としてデコードすれば、ヘッダーの検証も同時に行われる
+    let uuid_box = UnknownBox::decode(&uuid_bytes[..])?;
+    assert_eq!(uuid_box.box_type, BoxType::Uuid([0xAB; 16]));
+    assert!(UnknownBox::decode(&[0, 0, 0, 4, b'u', b'u', b'i', b'd'][..]).is_err());
+    assert!(UnknownBox::decode(&uuid_bytes[..uuid_bytes.len() - 1]).is_err());
+
+    // moov ボックスの子として追加する
+    let moov_box = file
+        .boxes
+        .iter_mut()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box");
+    moov_box.unknown_boxes.push(uuid_box.clone());
+
+    // トップレベル（末尾）に追加する
+    file.boxes.push(RootBox::Unknown(uuid_box));
+
+    let bytes = file.encode_to_vec()?;
+    let decoded: Mp4File = Mp4File::decode(&bytes[..])?;
+    let moov_box = decoded.find_moov().expect("no moov box");
+    assert_eq!(
+        moov_box
+            .unknown_boxes
+            .last()
+            .map(|b| b.encode_to_vec())
+            .transpose()?,
+        Some(uuid_bytes.clone())
+    );
+    assert_eq!(
+        decoded
+            .boxes
+            .last()
+            .map(|b| b.encode_to_vec())
+            .transpose()?,
+        Some(uuid_bytes.clone())
+    );
+    assert!(bytes.ends_with(&uuid_bytes));
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use shiguredo_mp4::Mp4FileTime;
+
+#[test]
+fn year_1904() {
+    let time = Mp4FileTime::try_from_secs(0).expect("out of range");
+    assert_eq!(time, Mp4FileTime::default());
+    assert_eq!(time.to_unix_secs(), None);
+
+    // UNIX エポックの一秒前
+    let time = Mp4FileTime::from_secs(Mp4FileTime::UNIX_EPOCH.as_secs() - 1);
+    assert_eq!(time.to_unix_secs(), None);
+}
+
+#[test]
+fn year_1970() {
+    assert_eq!(Mp4FileTime::UNIX_EPOCH.as_secs(), 2082844800);
+    assert_eq!(Mp4FileTime::UNIX_EPOCH.to_unix_secs(), Some(0));
+    assert_eq!(
+        Mp4FileTime::from_unix_time(Duration::ZERO),
+        Mp4FileTime::UNIX_EPOCH
+    );
+}
+
+#[test]
+fn year_2038() {
+    // 2038/1/19 03:14:08 (UTC): 32 ビット符号付き整数の UNIX 時刻が溢れる時点
+    let unix_secs = 1 << 31;
+    let time = Mp4FileTime::from_unix_time(Duration::from_secs(unix_secs));
+    assert_eq!(Mp4FileTime::try_from_secs(time.as_secs()), Some(time));
+    assert_eq!(time.to_unix_secs(), Some(unix_secs as i64));
+}
+
+#[test]
+fn year_2106() {
+    // 2106/2/7 06:28:16 (UTC): 32 ビット符号なし整数の UNIX 時刻が溢れる時点
+    let unix_secs = 1 << 32;
+    let time = Mp4FileTime::from_unix_time(Duration::from_secs(unix_secs));
+    assert_eq!(Mp4FileTime::try_from_secs(time.as_secs()), Some(time));
+    assert_eq!(time.to_unix_secs(), Some(unix_secs as i64));
+
+    // 32 ビットの MP4 時刻（バージョン 0 のボックス）で表現可能な最大値は 2040 年なので、それよりも後になる
+    assert!(time.as_secs() > u64::from(u32::MAX));
+}
+
+#[test]
+fn year_9999() {
+    // 9999/12/31 23:59:59 (UTC)
+    let time = Mp4FileTime::try_from_secs(Mp4FileTime::MAX_SECS).expect("out of range");
+    assert_eq!(time.to_unix_secs(), Some(253402300799));
+
+    // 10000/1/1 00:00:00 (UTC) 以降は範囲外
+    assert_eq!(Mp4FileTime::try_from_secs(Mp4FileTime::MAX_SECS + 1), None);
+    assert_eq!(Mp4FileTime::try_from_secs(u64::MAX), None);
+
+    // from_secs() は範囲のチェックを行わない
+    assert_eq!(Mp4FileTime::from_secs(u64::MAX).as_secs(), u64::MAX);
+    assert_eq!(Mp4FileTime::from_secs(u64::MAX).to_unix_secs(), None);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn to_chrono_utc() {
+    use chrono::{TimeZone, Utc};
+
+    assert_eq!(Mp4FileTime::from_secs(0).to_chrono_utc(), None);
+    assert_eq!(
+        Mp4FileTime::UNIX_EPOCH.to_chrono_utc(),
+        Some(Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        Mp4FileTime::from_unix_time(Duration::from_secs(1 << 31)).to_chrono_utc(),
+        Some(Utc.with_ymd_and_hms(2038, 1, 19, 3, 14, 8).unwrap())
+    );
+    assert_eq!(
+        Mp4FileTime::from_unix_time(Duration::from_secs(1 << 32)).to_chrono_utc(),
+        Some(Utc.with_ymd_and_hms(2106, 2, 7, 6, 28, 16).unwrap())
+    );
+    assert_eq!(
+        Mp4FileTime::from_secs(Mp4FileTime::MAX_SECS).to_chrono_utc(),
+        Some(Utc.with_ymd_and_hms(9999, 12, 31, 23, 59, 59).unwrap())
+    );
+}
@@ -0,0 +1,227 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::{
+    aux::SampleTableAccessor,
+    boxes::{
+        Brand, FileTypeBoxContent, FtypBox, MdatBox, MoovBox, RootBox, SampleEntry, StcoBox,
+        StscBox, StscEntry, StssBox, StszBox, SttsBox, TrakBox,
+    },
+    BaseBox, Decode, Either, Encode, FixedPointNumber, Mp4File, Result, Uint,
+};
+
+const VIDEO_FRAME_COUNT: usize = 30;
+const VIDEO_TIMESCALE: u32 = 30;
+const AUDIO_PACKET_COUNT: usize = 50;
+const AUDIO_TIMESCALE: u32 = 48000;
+const AUDIO_SAMPLES_PER_PACKET: u32 = 960;
+
+// 1280x720 (Baseline プロファイル、レベル 3.1) の SPS と PPS
+const SPS_720P: [u8; 9] = [0x67, 0x42, 0xC0, 0x1F, 0x56, 0x80, 0x50, 0x05, 0xB9];
+const PPS: [u8; 4] = [0x68, 0xCE, 0x3C, 0x80];
+
+fn decode_moov_box(input_bytes: &[u8]) -> Result<MoovBox> {
+    let file: Mp4File = Mp4File::decode(input_bytes)?;
+    Ok(file.find_moov().expect("no moov box").clone())
+}
+
+// 各トラックのサンプルテーブルを、サンプル毎に一つのチャンクとなるように設定する
+fn set_sample_table(trak_box: &mut TrakBox, sample_delta: u32, sample_sizes: &[u32]) {
+    let stbl_box = &mut trak_box.mdia_box.minf_box.stbl_box;
+    stbl_box.stts_box = SttsBox::from_sample_deltas(sample_sizes.iter().map(|_| sample_delta));
+    stbl_box.stsc_box = StscBox {
+        entries: vec![StscEntry {
+            first_chunk: NonZeroU32::MIN,
+            sample_per_chunk: 1,
+            sample_description_index: NonZeroU32::MIN,
+        }],
+    };
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: sample_sizes.to_vec(),
+    };
+    stbl_box.stco_or_co64_box = Either::A(StcoBox {
+        chunk_offsets: Vec::new(),
+    });
+    stbl_box.ctts_box = None;
+    stbl_box.sgpd_boxes.clear();
+    stbl_box.sbgp_boxes.clear();
+}
+
+fn video_trak_box(sample_sizes: &[u32]) -> Result<TrakBox> {
+    let mut trak_box = decode_moov_box(include_bytes!("testdata/black-h264-video.mp4"))?
+        .trak_boxes
+        .swap_remove(0);
+    let duration = VIDEO_FRAME_COUNT as u64;
+    trak_box.tkhd_box.track_id = 1;
+    trak_box.tkhd_box.width = FixedPointNumber::new(1280, 0);
+    trak_box.tkhd_box.height = FixedPointNumber::new(720, 0);
+    trak_box.edts_box = None;
+    trak_box.mdia_box.mdhd_box.timescale = NonZeroU32::new(VIDEO_TIMESCALE).expect("non zero");
+    trak_box.mdia_box.mdhd_box.duration = duration;
+
+    let Some(SampleEntry::Avc1(avc1)) = trak_box.sample_entry_mut() else {
+        panic!("not avc1");
+    };
+    avc1.visual.width = 1280;
+    avc1.visual.height = 720;
+    avc1.avcc_box.avc_profile_indication = SPS_720P[1];
+    avc1.avcc_box.profile_compatibility = SPS_720P[2];
+    avc1.avcc_box.avc_level_indication = SPS_720P[3];
+    avc1.avcc_box.length_size_minus_one = Uint::new(3);
+    avc1.avcc_box.sps_list = vec![SPS_720P.to_vec()];
+    avc1.avcc_box.pps_list = vec![PPS.to_vec()];
+    avc1.avcc_box.chroma_format = None;
+    avc1.avcc_box.bit_depth_luma_minus8 = None;
+    avc1.avcc_box.bit_depth_chroma_minus8 = None;
+    avc1.avcc_box.sps_ext_list.clear();
+
+    set_sample_table(&mut trak_box, 1, sample_sizes);
+
+    // 先頭フレームのみがキーフレーム
+    trak_box.mdia_box.minf_box.stbl_box.stss_box = Some(StssBox {
+        sample_numbers: vec![NonZeroU32::MIN],
+    });
+    Ok(trak_box)
+}
+
+fn audio_trak_box(sample_sizes: &[u32]) -> Result<TrakBox> {
+    let mut trak_box = decode_moov_box(include_bytes!("testdata/beep-opus-audio.mp4"))?
+        .trak_boxes
+        .swap_remove(0);
+    let duration = (AUDIO_PACKET_COUNT as u32 * AUDIO_SAMPLES_PER_PACKET) as u64;
+    trak_box.tkhd_box.track_id = 2;
+    trak_box.edts_box = None;
+    trak_box.mdia_box.mdhd_box.timescale = NonZeroU32::new(AUDIO_TIMESCALE).expect("non zero");
+    trak_box.mdia_box.mdhd_box.duration = duration;
+    assert!(matches!(
+        trak_box.sample_entry(),
+        Some(SampleEntry::Opus(_))
+    ));
+
+    set_sample_table(&mut trak_box, AUDIO_SAMPLES_PER_PACKET, sample_sizes);
+    trak_box.mdia_box.minf_box.stbl_box.stss_box = None;
+    Ok(trak_box)
+}
+
+fn stco_offsets(trak_box: &TrakBox) -> &[u32] {
+    let Either::A(stco_box) = &trak_box.mdia_box.minf_box.stbl_box.stco_or_co64_box else {
+        panic!("not stco");
+    };
+    &stco_box.chunk_offsets
+}
+
+#[test]
+fn mux_video_and_audio_tracks() -> Result<()> {
+    // 各サンプルのデータは、トラックとサンプルの番号から決定的に作る
+    let video_samples = (0..VIDEO_FRAME_COUNT)
+        .map(|i| vec![0x10 | (i % 16) as u8; if i == 0 { 1000 } else { 100 + i }])
+        .collect::<Vec<_>>();
+    let audio_samples = (0..AUDIO_PACKET_COUNT)
+        .map(|i| vec![0x80 | (i % 16) as u8; 50 + i % 7])
+        .collect::<Vec<_>>();
+    let sizes = |samples: &[Vec<u8>]| samples.iter().map(|s| s.len() as u32).collect::<Vec<_>>();
+
+    let ftyp_box = FtypBox(FileTypeBoxContent {
+        major_brand: Brand::ISOM,
+        minor_version: 0,
+        compatible_brands: vec![Brand::ISOM, Brand::ISO2, Brand::AVC1, Brand::MP41],
+    });
+    let mut moov_box = MoovBox {
+        mvhd_box: decode_moov_box(include_bytes!("testdata/black-h264-video.mp4"))?.mvhd_box,
+        trak_boxes: vec![
+            video_trak_box(&sizes(&video_samples))?,
+            audio_trak_box(&sizes(&audio_samples))?,
+        ],
+        unknown_boxes: Vec::new(),
+    };
+    moov_box.mvhd_box.timescale = NonZeroU32::new(1000).expect("non zero");
+    moov_box.mvhd_box.duration = 1000;
+    moov_box.mvhd_box.next_track_id = 3;
+    for trak_box in &mut moov_box.trak_boxes {
+        trak_box.tkhd_box.duration = 1000;
+    }
+
+    // ftyp + mdat + moov の順に配置する
+    //
+    // mdat 内では、映像と音声のサンプルを交互に並べ、それぞれを一つのチャンクとする
+    let mdat_header_size = 8;
+    let mut offset = ftyp_box.box_size().get() as u32 + mdat_header_size;
+    let mut payload = Vec::new();
+    let mut video_offsets = Vec::new();
+    let mut audio_offsets = Vec::new();
+    for i in 0..VIDEO_FRAME_COUNT.max(AUDIO_PACKET_COUNT) {
+        for (samples, offsets) in [
+            (&video_samples, &mut video_offsets),
+            (&audio_samples, &mut audio_offsets),
+        ] {
+            if let Some(sample) = samples.get(i) {
+                offsets.push(offset);
+                offset += sample.len() as u32;
+                payload.extend_from_slice(sample);
+            }
+        }
+    }
+    for (trak_box, offsets) in moov_box
+        .trak_boxes
+        .iter_mut()
+        .zip([&video_offsets, &audio_offsets])
+    {
+        trak_box.mdia_box.minf_box.stbl_box.stco_or_co64_box = Either::A(StcoBox {
+            chunk_offsets: offsets.clone(),
+        });
+    }
+    let mdat_box = MdatBox {
+        is_variable_size: false,
+        payload,
+    };
+    assert_eq!(mdat_box.box_size().get() - mdat_box.payload.len() as u64, 8);
+
+    let file = Mp4File {
+        ftyp_box,
+        boxes: vec![RootBox::Mdat(mdat_box), RootBox::Moov(moov_box)],
+    };
+    let bytes = file.encode_to_vec()?;
+
+    // 出力結果をデコードして中身を確認する
+    let decoded: Mp4File = Mp4File::decode(&bytes[..])?;
+    assert_eq!(decoded, file);
+    let moov_box = decoded.find_moov().expect("no moov box");
+    assert_eq!(moov_box.trak_boxes.len(), 2);
+    assert!(moov_box.validate_deep().is_empty());
+
+    let video_track = moov_box.video_track().expect("no video track");
+    let audio_track = moov_box.audio_track().expect("no audio track");
+    assert!(video_track.sample_entry().is_some());
+    let Some(SampleEntry::Avc1(avc1)) = video_track.sample_entry() else {
+        panic!("not avc1");
+    };
+    assert_eq!(avc1.true_resolution(), Some((1280, 720)));
+
+    // stts: 全てのサンプルの尺が同じなので、エントリーは一つにまとまる
+    let video_stts = &video_track.mdia_box.minf_box.stbl_box.stts_box;
+    let audio_stts = &audio_track.mdia_box.minf_box.stbl_box.stts_box;
+    assert_eq!(video_stts.entries.len(), 1);
+    assert_eq!(video_stts.entries[0].sample_count, VIDEO_FRAME_COUNT as u32);
+    assert_eq!(audio_stts.entries.len(), 1);
+    assert_eq!(
+        audio_stts.entries[0].sample_count,
+        AUDIO_PACKET_COUNT as u32
+    );
+    assert_eq!(audio_stts.entries[0].sample_delta, AUDIO_SAMPLES_PER_PACKET);
+
+    // stco: 先頭の映像サンプルは ftyp と mdat のヘッダーの直後から始まる
+    assert_eq!(stco_offsets(video_track), video_offsets);
+    assert_eq!(stco_offsets(audio_track), audio_offsets);
+    assert_eq!(video_offsets[0], 32 + 8);
+    assert_eq!(audio_offsets[0], video_offsets[0] + 1000);
+
+    // サンプルテーブル経由で、元のサンプルデータが読み出せる
+    for (trak_box, samples) in [(video_track, &video_samples), (audio_track, &audio_samples)] {
+        let stbl_box = &trak_box.mdia_box.minf_box.stbl_box;
+        let sample_table = SampleTableAccessor::new(stbl_box).expect("invalid sample table");
+        assert_eq!(sample_table.sample_count() as usize, samples.len());
+        for (sample, expected) in sample_table.samples().zip(samples) {
+            assert_eq!(sample.read_data(&bytes)?, expected);
+        }
+    }
+    Ok(())
+}
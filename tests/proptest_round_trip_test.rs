@@ -0,0 +1,226 @@
+//! `proptest_arbitrary` feature が有効な場合にのみ実行されるプロパティベーステスト
+#![cfg(feature = "proptest_arbitrary")]
+use proptest::{collection::vec, prelude::*, sample::select};
+use shiguredo_mp4::{
+    boxes::{
+        Av1cBox, AvccBox, Co64Box, CttsBox, DopsBox, ElstBox, FreeBox, FtypBox, HvccBox,
+        HvccNalUintArray, MdatBox, MdhdBox, MoovBox, MvhdBox, PrftBox, RootBox, SbgpBox, SgpdBox,
+        StcoBox, StscBox, StssBox, StszBox, SttsBox, StypBox, SubsBox, TkhdBox, TrakBox, VpccBox,
+    },
+    Decode, Encode, Mp4File, Uint,
+};
+
+fn assert_round_trip<T>(value: T) -> Result<(), TestCaseError>
+where
+    T: Encode + Decode + PartialEq + std::fmt::Debug,
+{
+    let bytes = value
+        .encode_to_vec()
+        .map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(value.encoded_size(), bytes.len() as u64);
+
+    let decoded = T::decode(&bytes[..]).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(&decoded, &value);
+
+    let mut re_encoded = Vec::new();
+    decoded
+        .encode(&mut re_encoded)
+        .map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(re_encoded, bytes);
+    Ok(())
+}
+
+// 1 から 64 バイトの NAL ユニット
+fn nal_unit() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 1..=64)
+}
+
+// 実在するプロファイルと、それに整合するフィールドを持つ AvccBox を生成する
+//
+// High 系のプロファイル (100 以上) の場合にのみ、クロマフォーマットなどの拡張フィールドが設定される
+fn valid_avcc_box() -> impl Strategy<Value = AvccBox> {
+    (
+        any::<AvccBox>(),
+        select(vec![66u8, 77, 88, 100, 110, 122, 244]),
+        vec(nal_unit(), 0..=5),
+        vec(nal_unit(), 0..=10),
+        (0..=3u8, 0..=6u8, 0..=6u8, vec(nal_unit(), 0..=2)),
+    )
+        .prop_map(
+            |(
+                base,
+                avc_profile_indication,
+                sps_list,
+                pps_list,
+                (chroma_format, bit_depth_luma_minus8, bit_depth_chroma_minus8, sps_ext_list),
+            )| {
+                let has_ext = avc_profile_indication >= 100;
+                AvccBox {
+                    avc_profile_indication,
+                    sps_list,
+                    pps_list,
+                    chroma_format: has_ext.then_some(Uint::new(chroma_format)),
+                    bit_depth_luma_minus8: has_ext.then_some(Uint::new(bit_depth_luma_minus8)),
+                    bit_depth_chroma_minus8: has_ext.then_some(Uint::new(bit_depth_chroma_minus8)),
+                    sps_ext_list: if has_ext { sps_ext_list } else { Vec::new() },
+                    ..base
+                }
+            },
+        )
+}
+
+// 実在するプロファイルと、VPS / SPS / PPS の配列を持つ HvccBox を生成する
+fn valid_hvcc_box() -> impl Strategy<Value = HvccBox> {
+    (
+        any::<HvccBox>(),
+        select(vec![1u8, 2, 3, 4]),
+        (0..=3u8, 0..=4u8, 0..=4u8),
+        vec(nal_unit(), 1..=2),
+        vec(nal_unit(), 1..=5),
+        vec(nal_unit(), 0..=10),
+    )
+        .prop_map(
+            |(
+                base,
+                general_profile_idc,
+                (chroma_format_idc, bit_depth_luma_minus8, bit_depth_chroma_minus8),
+                vps_list,
+                sps_list,
+                pps_list,
+            )| {
+                let nalu_arrays = [(32, vps_list), (33, sps_list), (34, pps_list)]
+                    .into_iter()
+                    .filter(|(_, nalus)| !nalus.is_empty())
+                    .map(|(nal_unit_type, nalus)| HvccNalUintArray {
+                        array_completeness: Uint::new(1),
+                        nal_unit_type: Uint::new(nal_unit_type),
+                        nalus,
+                    })
+                    .collect();
+                HvccBox {
+                    general_profile_space: Uint::new(0),
+                    general_profile_idc: Uint::new(general_profile_idc),
+                    chroma_format_idc: Uint::new(chroma_format_idc),
+                    bit_depth_luma_minus8: Uint::new(bit_depth_luma_minus8),
+                    bit_depth_chroma_minus8: Uint::new(bit_depth_chroma_minus8),
+                    nalu_arrays,
+                    ..base
+                }
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn round_trip_moov(moov: MoovBox) {
+        assert_round_trip(moov)?;
+    }
+
+    #[test]
+    fn round_trip_leaf_boxes(
+        ftyp: FtypBox,
+        styp: StypBox,
+        free: FreeBox,
+        mdat: MdatBox,
+        mvhd: MvhdBox,
+        tkhd: TkhdBox,
+        mdhd: MdhdBox,
+        elst: ElstBox,
+        prft: PrftBox,
+    ) {
+        assert_round_trip(ftyp)?;
+        assert_round_trip(styp)?;
+        assert_round_trip(free)?;
+        assert_round_trip(mdat)?;
+        assert_round_trip(mvhd)?;
+        assert_round_trip(tkhd)?;
+        assert_round_trip(mdhd)?;
+        assert_round_trip(elst)?;
+        assert_round_trip(prft)?;
+    }
+
+    #[test]
+    fn round_trip_sample_table_boxes(
+        stts: SttsBox,
+        stsc: StscBox,
+        stsz: StszBox,
+        stco: StcoBox,
+        co64: Co64Box,
+        stss: StssBox,
+        ctts: CttsBox,
+        sgpd: SgpdBox,
+        sbgp: SbgpBox,
+        subs: SubsBox,
+    ) {
+        assert_round_trip(stts)?;
+        assert_round_trip(stsc)?;
+        assert_round_trip(stsz)?;
+        assert_round_trip(stco)?;
+        assert_round_trip(co64)?;
+        assert_round_trip(stss)?;
+        assert_round_trip(ctts)?;
+        assert_round_trip(sgpd)?;
+        assert_round_trip(sbgp)?;
+        assert_round_trip(subs)?;
+    }
+
+    #[test]
+    fn round_trip_codec_boxes(
+        avcc: AvccBox,
+        hvcc: HvccBox,
+        dops: DopsBox,
+        av1c: Av1cBox,
+        vpcc: VpccBox,
+    ) {
+        assert_round_trip(avcc)?;
+        assert_round_trip(hvcc)?;
+        assert_round_trip(dops)?;
+        assert_round_trip(av1c)?;
+        assert_round_trip(vpcc)?;
+    }
+
+    #[test]
+    fn round_trip_valid_codec_boxes(
+        avcc in valid_avcc_box(),
+        hvcc in valid_hvcc_box(),
+    ) {
+        prop_assert!(avcc.sps_list.len() <= 5);
+        prop_assert_eq!(avcc.chroma_format.is_some(), avcc.avc_profile_indication >= 100);
+        assert_round_trip(avcc)?;
+        assert_round_trip(hvcc)?;
+    }
+
+    #[test]
+    fn round_trip_trak(trak: TrakBox) {
+        assert_round_trip(trak)?;
+    }
+
+    #[test]
+    fn round_trip_mp4_file(
+        ftyp: FtypBox,
+        moov: MoovBox,
+        free: FreeBox,
+        mut mdat: MdatBox,
+        is_last_mdat_variable_size: bool,
+    ) {
+        // 可変長の mdat ボックスはファイル末尾にしか置けない
+        mdat.is_variable_size = false;
+        let last_mdat = MdatBox {
+            is_variable_size: is_last_mdat_variable_size,
+            payload: mdat.payload.clone(),
+        };
+        let file = Mp4File {
+            ftyp_box: ftyp,
+            boxes: vec![
+                RootBox::Free(free),
+                RootBox::Mdat(mdat),
+                RootBox::Moov(moov),
+                RootBox::Mdat(last_mdat),
+            ],
+        };
+        // encoded_size() は total_encoded_size() で計算され、実際のバイト数と一致する
+        assert_round_trip(file)?;
+    }
+}
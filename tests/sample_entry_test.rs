@@ -0,0 +1,408 @@
+use std::collections::HashSet;
+
+use shiguredo_mp4::{
+    aux::TrackKind,
+    boxes::{ColrBox, RootBox, SampleEntry, StsdBox, UnknownBox, Vp08Box, VpccBox},
+    BoxSize, BoxType, Decode, Encode, Mp4File, Result, Uint,
+};
+
+fn decode_sample_entry(input_bytes: &[u8]) -> Result<SampleEntry> {
+    let file: Mp4File = Mp4File::decode(input_bytes)?;
+    let sample_entry = file
+        .boxes
+        .iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => b.trak_boxes[0].sample_entry().cloned(),
+            _ => None,
+        })
+        .expect("no sample entry");
+    Ok(sample_entry)
+}
+
+#[test]
+fn visual_sample_entries() -> Result<()> {
+    let vp09 = decode_sample_entry(include_bytes!("testdata/black-vp9-video.mp4"))?;
+    let SampleEntry::Vp09(b) = &vp09 else {
+        panic!("not vp09");
+    };
+    let vp08 = SampleEntry::Vp08(Vp08Box {
+        visual: b.visual.clone(),
+        vpcc_box: b.vpcc_box.clone(),
+        unknown_boxes: Vec::new(),
+    });
+
+    let entries = [
+        decode_sample_entry(include_bytes!("testdata/black-h264-video.mp4"))?,
+        decode_sample_entry(include_bytes!("testdata/black-h265-video.mp4"))?,
+        vp08,
+        vp09,
+        decode_sample_entry(include_bytes!("testdata/black-av1-video.mp4"))?,
+    ];
+    assert!(matches!(entries[0], SampleEntry::Avc1(_)));
+    assert!(matches!(entries[1], SampleEntry::Hev1(_)));
+    assert!(matches!(entries[4], SampleEntry::Av01(_)));
+
+    for entry in entries {
+        assert!(entry.is_video());
+        assert!(!entry.is_audio());
+        assert!(entry.is_known());
+        assert_eq!(entry.track_kind(), Some(TrackKind::Video));
+
+        let visual = entry.visual_fields().expect("visual fields");
+        assert_eq!(entry.width(), Some(visual.width));
+        assert_eq!(entry.height(), Some(visual.height));
+        assert_ne!(visual.width, 0);
+        assert_ne!(visual.height, 0);
+
+        assert!(entry.audio_fields().is_none());
+        assert_eq!(entry.sample_rate(), None);
+        assert_eq!(entry.channel_count(), None);
+    }
+    Ok(())
+}
+
+#[test]
+fn audio_sample_entries() -> Result<()> {
+    let opus = decode_sample_entry(include_bytes!("testdata/beep-opus-audio.mp4"))?;
+    assert!(matches!(opus, SampleEntry::Opus(_)));
+    assert_eq!(opus.sample_rate(), Some(48000));
+    assert_eq!(opus.channel_count(), Some(2));
+
+    let mp4a = decode_sample_entry(include_bytes!("testdata/beep-aac-audio.mp4"))?;
+    assert!(matches!(mp4a, SampleEntry::Mp4a(_)));
+    assert_eq!(mp4a.sample_rate(), Some(44100));
+    assert_eq!(mp4a.channel_count(), Some(1));
+
+    for entry in [opus, mp4a] {
+        assert!(entry.is_audio());
+        assert!(!entry.is_video());
+        assert!(entry.is_known());
+        assert_eq!(entry.track_kind(), Some(TrackKind::Audio));
+
+        assert!(entry.audio_fields().is_some());
+        assert!(entry.visual_fields().is_none());
+        assert_eq!(entry.width(), None);
+        assert_eq!(entry.height(), None);
+    }
+    Ok(())
+}
+
+#[test]
+fn sample_entry_hash() -> Result<()> {
+    let entries = [
+        decode_sample_entry(include_bytes!("testdata/black-h264-video.mp4"))?,
+        decode_sample_entry(include_bytes!("testdata/black-h265-video.mp4"))?,
+        decode_sample_entry(include_bytes!("testdata/black-vp9-video.mp4"))?,
+        decode_sample_entry(include_bytes!("testdata/black-av1-video.mp4"))?,
+        decode_sample_entry(include_bytes!("testdata/beep-opus-audio.mp4"))?,
+        decode_sample_entry(include_bytes!("testdata/beep-aac-audio.mp4"))?,
+    ];
+
+    // 同じ内容のエントリーは重複が除去される
+    let set = entries
+        .iter()
+        .chain(entries.iter())
+        .cloned()
+        .collect::<HashSet<_>>();
+    assert_eq!(set.len(), entries.len());
+    for entry in &entries {
+        assert!(set.contains(entry));
+    }
+
+    // 一部のフィールドだけが異なる場合は別のエントリーとして扱われる
+    let SampleEntry::Avc1(mut avc1) = entries[0].clone() else {
+        panic!("not avc1");
+    };
+    avc1.visual.width += 1;
+    assert!(!set.contains(&SampleEntry::Avc1(avc1)));
+    Ok(())
+}
+
+#[test]
+fn unknown_sample_entry() {
+    let entry = SampleEntry::Unknown(UnknownBox {
+        box_type: BoxType::Normal(*b"test"),
+        box_size: BoxSize::U32(8),
+        payload: Vec::new(),
+    });
+    assert!(!entry.is_video());
+    assert!(!entry.is_audio());
+    assert!(!entry.is_known());
+    assert_eq!(entry.track_kind(), None);
+    assert!(entry.visual_fields().is_none());
+    assert!(entry.audio_fields().is_none());
+    assert_eq!(entry.width(), None);
+    assert_eq!(entry.height(), None);
+    assert_eq!(entry.sample_rate(), None);
+    assert_eq!(entry.channel_count(), None);
+    assert_eq!(entry.codec_string(), None);
+}
+
+#[test]
+fn unknown_sample_entry_round_trip() -> Result<()> {
+    // 64 ビットのサイズ表現を使った未知のサンプルエントリーを含む stsd ボックス
+    let mut entry_bytes = Vec::new();
+    entry_bytes.extend_from_slice(&1u32.to_be_bytes());
+    entry_bytes.extend_from_slice(b"test");
+    entry_bytes.extend_from_slice(&24u64.to_be_bytes());
+    entry_bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]); // reserved, data_reference_index
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(16 + entry_bytes.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(b"stsd");
+    bytes.extend_from_slice(&[0, 0, 0, 0]); // version, flags
+    bytes.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    bytes.extend_from_slice(&entry_bytes);
+
+    let stsd_box = StsdBox::decode(&bytes[..])?;
+    let SampleEntry::Unknown(b) = &stsd_box.entries[0] else {
+        panic!("not unknown");
+    };
+    assert_eq!(b.box_size, BoxSize::U64(24));
+    assert_eq!(b.payload, [0, 0, 0, 0, 0, 0, 0, 1]);
+
+    // ヘッダーの形式も含めて元のバイト列が再現される
+    assert_eq!(stsd_box.encode_to_vec()?, bytes);
+    assert_eq!(stsd_box.entries[0].encode_to_vec()?, entry_bytes);
+    let decoded = StsdBox::decode(&stsd_box.encode_to_vec()?[..])?;
+    assert_eq!(decoded, stsd_box);
+    Ok(())
+}
+
+#[test]
+fn hevc_codec_string() -> Result<()> {
+    // black-h265-video.mp4 は Main プロファイル、レベル 3 (90)
+    let hev1 = decode_sample_entry(include_bytes!("testdata/black-h265-video.mp4"))?;
+    assert_eq!(hev1.codec_string().as_deref(), Some("hev1.1.6.L90.90"));
+
+    let SampleEntry::Hev1(b) = &hev1 else {
+        panic!("not hev1");
+    };
+    assert_eq!(b.hvcc_box.codec_string(), "hvc1.1.6.L90.90");
+
+    // Main 10 プロファイル、レベル 4 (120)
+    let mut hvcc_box = b.hvcc_box.clone();
+    hvcc_box.general_profile_idc = Uint::new(2);
+    hvcc_box.general_profile_compatibility_flags = 0x20000000;
+    hvcc_box.general_level_idc = 120;
+    hvcc_box.general_constraint_indicator_flags = Uint::new(0xB0_00_00_00_00_00);
+    assert_eq!(hvcc_box.codec_string(), "hvc1.2.4.L120.B0");
+
+    // プロファイル空間と High ティアが指定され、途中に 0 のバイトを含む制約フラグ
+    hvcc_box.general_profile_space = Uint::new(1);
+    hvcc_box.general_tier_flag = Uint::new(1);
+    hvcc_box.general_profile_idc = Uint::new(4);
+    hvcc_box.general_profile_compatibility_flags = 0x82000000;
+    hvcc_box.general_level_idc = 186;
+    hvcc_box.general_constraint_indicator_flags = Uint::new(0x98_00_00_01_00_00);
+    assert_eq!(hvcc_box.codec_string(), "hvc1.A4.41.H186.98.0.0.1");
+
+    // 制約フラグが全て 0 の場合には省略される
+    hvcc_box.general_constraint_indicator_flags = Uint::new(0);
+    assert_eq!(hvcc_box.codec_string(), "hvc1.A4.41.H186");
+    Ok(())
+}
+
+#[test]
+fn hev1_and_hvc1() -> Result<()> {
+    let entry = decode_sample_entry(include_bytes!("testdata/black-h265-video.mp4"))?;
+    let SampleEntry::Hev1(mut hev1) = entry else {
+        panic!("not hev1");
+    };
+    assert!(!hev1.hvcc_box.nalu_arrays.is_empty());
+
+    // 全てのパラメーターセットが hvcC ボックスに含まれている場合は hvc1 に変換できる
+    for array in &mut hev1.hvcc_box.nalu_arrays {
+        array.array_completeness = Uint::new(1);
+    }
+    assert!(!hev1.is_parameter_set_in_bitstream());
+    let hvc1 = hev1.clone().into_hvc1()?;
+    assert!(hvc1.validate_all_parameter_sets_complete());
+    assert_eq!(hvc1.hvcc_box, hev1.hvcc_box);
+
+    let entry = SampleEntry::Hvc1(hvc1);
+    assert_eq!(entry.codec_string().as_deref(), Some("hvc1.1.6.L90.90"));
+    let bytes = entry.encode_to_vec()?;
+    assert_eq!(&bytes[4..8], b"hvc1");
+    assert_eq!(SampleEntry::decode(&bytes[..])?, entry);
+
+    // 一部のパラメーターセットがビットストリーム中に含まれる可能性がある場合は変換できない
+    hev1.hvcc_box.nalu_arrays[0].array_completeness = Uint::new(0);
+    assert!(hev1.is_parameter_set_in_bitstream());
+    assert!(hev1.clone().into_hvc1().is_err());
+
+    let SampleEntry::Hvc1(mut hvc1) = entry else {
+        unreachable!();
+    };
+    hvc1.hvcc_box = hev1.hvcc_box;
+    assert!(!hvc1.validate_all_parameter_sets_complete());
+    Ok(())
+}
+
+#[test]
+fn av1_codec_string() -> Result<()> {
+    // black-av1-video.mp4 は Main プロファイル、レベル 3.0 (4)、8 ビット、4:2:0 で colr ボックスを含まない
+    let av01 = decode_sample_entry(include_bytes!("testdata/black-av1-video.mp4"))?;
+    assert_eq!(av01.codec_string().as_deref(), Some("av01.0.04M.08"));
+
+    let SampleEntry::Av01(b) = &av01 else {
+        panic!("not av01");
+    };
+    assert_eq!(b.colr_box(), None);
+    assert_eq!(b.av1c_box.codec_string(None), "av01.0.04M.08");
+
+    // 色情報がデフォルト値の場合には短い形式になる
+    let bt709 = ColrBox::Nclx {
+        colour_primaries: 1,
+        transfer_characteristics: 1,
+        matrix_coefficients: 1,
+        full_range_flag: false,
+    };
+    assert_eq!(b.av1c_box.codec_string(Some(&bt709)), "av01.0.04M.08");
+
+    // nclx 以外の色情報は無視される
+    let icc = ColrBox::Other {
+        colour_type: *b"prof",
+        payload: vec![1, 2, 3],
+    };
+    assert_eq!(b.av1c_box.codec_string(Some(&icc)), "av01.0.04M.08");
+
+    // 10 ビットの HDR10 (BT.2020 / PQ) 映像
+    let mut av1c_box = b.av1c_box.clone();
+    av1c_box.high_bitdepth = Uint::new(1);
+    av1c_box.chroma_sample_position = Uint::new(2);
+    let bt2020_pq = ColrBox::Nclx {
+        colour_primaries: 9,
+        transfer_characteristics: 16,
+        matrix_coefficients: 9,
+        full_range_flag: false,
+    };
+    assert_eq!(
+        av1c_box.codec_string(Some(&bt2020_pq)),
+        "av01.0.04M.10.0.112.09.16.09.0"
+    );
+
+    // High プロファイル、High ティア、4:4:4、フルレンジ
+    av1c_box.seq_profile = Uint::new(1);
+    av1c_box.seq_level_idx_0 = Uint::new(13);
+    av1c_box.seq_tier_0 = Uint::new(1);
+    av1c_box.chroma_subsampling_x = Uint::new(0);
+    av1c_box.chroma_subsampling_y = Uint::new(0);
+    let full_range = ColrBox::Nclx {
+        colour_primaries: 1,
+        transfer_characteristics: 1,
+        matrix_coefficients: 1,
+        full_range_flag: true,
+    };
+    assert_eq!(
+        av1c_box.codec_string(Some(&full_range)),
+        "av01.1.13H.10.0.000.01.01.01.1"
+    );
+
+    // Professional プロファイルの 12 ビット・モノクロ
+    av1c_box.seq_profile = Uint::new(2);
+    av1c_box.twelve_bit = Uint::new(1);
+    av1c_box.monochrome = Uint::new(1);
+    av1c_box.chroma_subsampling_x = Uint::new(1);
+    av1c_box.chroma_subsampling_y = Uint::new(1);
+    av1c_box.chroma_sample_position = Uint::new(0);
+    assert_eq!(
+        av1c_box.codec_string(None),
+        "av01.2.13H.12.1.110.01.01.01.0"
+    );
+
+    // サンプルエントリー内の colr ボックスは自動的に参照される
+    let mut b = b.clone();
+    b.unknown_boxes
+        .push(UnknownBox::decode(&bt2020_pq.encode_to_vec()?[..])?);
+    assert_eq!(b.colr_box(), Some(bt2020_pq));
+    assert_eq!(
+        SampleEntry::Av01(b).codec_string().as_deref(),
+        Some("av01.0.04M.08.0.110.09.16.09.0")
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_encode_colr_box() -> Result<()> {
+    let nclx = ColrBox::Nclx {
+        colour_primaries: 9,
+        transfer_characteristics: 16,
+        matrix_coefficients: 9,
+        full_range_flag: true,
+    };
+    let bytes = nclx.encode_to_vec()?;
+    assert_eq!(
+        bytes,
+        b"\x00\x00\x00\x13colrnclx\x00\x09\x00\x10\x00\x09\x80"
+    );
+    assert_eq!(ColrBox::decode(&bytes[..])?, nclx);
+
+    let icc = ColrBox::Other {
+        colour_type: *b"rICC",
+        payload: vec![1, 2, 3, 4],
+    };
+    let bytes = icc.encode_to_vec()?;
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(ColrBox::decode(&bytes[..])?, icc);
+    Ok(())
+}
+
+#[test]
+fn vp9_codec_string() -> Result<()> {
+    // black-vp9-video.mp4 はプロファイル 0、レベル 3 (30)、8 ビットで色情報は未指定 (2)
+    let vp09 = decode_sample_entry(include_bytes!("testdata/black-vp9-video.mp4"))?;
+    assert_eq!(
+        vp09.codec_string().as_deref(),
+        Some("vp09.00.30.08.01.02.02.02.00")
+    );
+
+    let SampleEntry::Vp09(b) = &vp09 else {
+        panic!("not vp09");
+    };
+    let mut vpcc_box = b.vpcc_box.clone();
+    assert_eq!(vpcc_box.codec_string(false), "vp09.00.30.08.01.02.02.02.00");
+
+    // 色情報がデフォルト値の場合には短い形式になる
+    vpcc_box.level = 10;
+    vpcc_box.colour_primaries = VpccBox::DEFAULT_COLOUR_PRIMARIES;
+    vpcc_box.transfer_characteristics = VpccBox::DEFAULT_TRANSFER_CHARACTERISTICS;
+    vpcc_box.matrix_coefficients = VpccBox::DEFAULT_MATRIX_COEFFICIENTS;
+    assert_eq!(vpcc_box.codec_string(false), "vp09.00.10.08.01.01.01.01.00");
+    assert_eq!(vpcc_box.codec_string_short(false), "vp09.00.10.08");
+    assert_eq!(vpcc_box.codec_string(true), "vp08.00.10.08.01.01.01.01.00");
+    assert_eq!(vpcc_box.codec_string_short(true), "vp08.00.10.08");
+
+    let vp08 = SampleEntry::Vp08(Vp08Box {
+        visual: b.visual.clone(),
+        vpcc_box: vpcc_box.clone(),
+        unknown_boxes: Vec::new(),
+    });
+    assert_eq!(vp08.codec_string().as_deref(), Some("vp08.00.10.08"));
+
+    // 10 ビットの HDR10 (BT.2020 / PQ) 映像
+    vpcc_box.profile = 2;
+    vpcc_box.level = 41;
+    vpcc_box.bit_depth = Uint::new(10);
+    vpcc_box.colour_primaries = 9;
+    vpcc_box.transfer_characteristics = 16;
+    vpcc_box.matrix_coefficients = 9;
+    assert_eq!(
+        vpcc_box.codec_string_short(false),
+        "vp09.02.41.10.01.09.16.09.00"
+    );
+
+    // 4:4:4 のフルレンジ映像
+    vpcc_box.profile = 1;
+    vpcc_box.bit_depth = Uint::new(8);
+    vpcc_box.chroma_subsampling = Uint::new(3);
+    vpcc_box.colour_primaries = VpccBox::DEFAULT_COLOUR_PRIMARIES;
+    vpcc_box.transfer_characteristics = VpccBox::DEFAULT_TRANSFER_CHARACTERISTICS;
+    vpcc_box.matrix_coefficients = VpccBox::DEFAULT_MATRIX_COEFFICIENTS;
+    vpcc_box.video_full_range_flag = Uint::new(1);
+    assert_eq!(
+        vpcc_box.codec_string_short(false),
+        "vp09.01.41.08.03.01.01.01.01"
+    );
+    Ok(())
+}
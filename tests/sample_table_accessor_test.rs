@@ -0,0 +1,336 @@
+use std::{io::ErrorKind, num::NonZeroU32};
+
+use shiguredo_mp4::{
+    aux::{SampleIter, SampleTableAccessor, SampleTableAccessorError},
+    boxes::{
+        Brand, Co64Box, FileTypeBoxContent, FtypBox, MdatBox, MoovBox, RootBox, SampleEntry,
+        StblBox, StcoBox, StscBox, StscEntry, StsdBox, StszBox, SttsBox, UnknownBox,
+    },
+    BoxSize, BoxType, Decode, Either, Encode, Mp4File, Result,
+};
+
+fn index(i: u32) -> NonZeroU32 {
+    NonZeroU32::new(i).expect("invalid index")
+}
+
+// ftyp + mdat からなる MP4 ファイルのバイト列と、その中のサンプル群を参照する stbl ボックスを作る
+//
+// サンプルは 5 個で、先頭のチャンクに 3 個、次のチャンクに 2 個が属する
+fn minimal_mp4() -> Result<(Vec<u8>, StblBox, Vec<Vec<u8>>)> {
+    let samples = vec![
+        vec![1, 1, 1],
+        vec![2],
+        vec![3, 3, 3, 3],
+        vec![4],
+        vec![5, 5, 5, 5, 5],
+    ];
+
+    let ftyp_box = FtypBox(FileTypeBoxContent {
+        major_brand: Brand::ISOM,
+        minor_version: 0,
+        compatible_brands: vec![Brand::ISOM],
+    });
+    let mdat_box = MdatBox {
+        is_variable_size: false,
+        payload: samples.concat(),
+    };
+    let mut bytes = ftyp_box.encode_to_vec()?;
+    bytes.extend_from_slice(&mdat_box.encode_to_vec()?);
+    let mdat_payload_offset = (bytes.len() - mdat_box.payload.len()) as u32;
+
+    let stbl_box = StblBox {
+        stsd_box: StsdBox {
+            entries: vec![SampleEntry::Unknown(UnknownBox {
+                box_type: BoxType::Normal(*b"test"),
+                box_size: BoxSize::U32(8),
+                payload: Vec::new(),
+            })],
+        },
+        stts_box: SttsBox::from_sample_deltas([10; 5]),
+        stsc_box: StscBox {
+            entries: vec![
+                StscEntry {
+                    first_chunk: index(1),
+                    sample_per_chunk: 3,
+                    sample_description_index: index(1),
+                },
+                StscEntry {
+                    first_chunk: index(2),
+                    sample_per_chunk: 2,
+                    sample_description_index: index(1),
+                },
+            ],
+        },
+        stsz_box: StszBox::Variable {
+            entry_sizes: samples.iter().map(|s| s.len() as u32).collect(),
+        },
+        stco_or_co64_box: Either::A(StcoBox {
+            chunk_offsets: vec![mdat_payload_offset, mdat_payload_offset + 8],
+        }),
+        stss_box: None,
+        ctts_box: None,
+        subs_box: None,
+        sgpd_boxes: Vec::new(),
+        sbgp_boxes: Vec::new(),
+        unknown_boxes: Vec::new(),
+    };
+
+    Ok((bytes, stbl_box, samples))
+}
+
+#[test]
+fn read_sample_and_chunk() -> Result<()> {
+    let (bytes, stbl_box, samples) = minimal_mp4()?;
+    let sample_table = SampleTableAccessor::new(&stbl_box).expect("invalid sample table");
+
+    // サンプル単位
+    for (i, expected) in samples.iter().enumerate() {
+        let sample_index = index(i as u32 + 1);
+        assert_eq!(sample_table.read_sample(sample_index, &bytes)?, expected);
+
+        let sample = sample_table.get_sample(sample_index).expect("no sample");
+        assert_eq!(sample.read_data(&bytes)?, expected);
+    }
+
+    // チャンク単位
+    let chunk = sample_table.get_chunk(index(1)).expect("no chunk");
+    assert_eq!(chunk.data_size(), 8);
+    assert_eq!(chunk.read_data(&bytes)?, samples[..3].concat());
+    assert_eq!(
+        sample_table.read_chunk(index(1), &bytes)?,
+        chunk.read_data(&bytes)?
+    );
+    assert_eq!(
+        sample_table.read_chunk(index(2), &bytes)?,
+        samples[3..].concat()
+    );
+
+    // チャンクサイズの合計はサンプルデータサイズの合計と等しい
+    let total_chunk_size = sample_table.chunks().map(|c| c.data_size()).sum::<u64>();
+    assert_eq!(total_chunk_size, sample_table.total_data_size());
+    assert_eq!(total_chunk_size, samples.concat().len() as u64);
+
+    Ok(())
+}
+
+#[test]
+fn read_out_of_bounds() -> Result<()> {
+    let (bytes, stbl_box, _) = minimal_mp4()?;
+    let sample_table = SampleTableAccessor::new(&stbl_box).expect("invalid sample table");
+
+    // 存在しないサンプルやチャンクの指定
+    let e = sample_table
+        .read_sample(index(6), &bytes)
+        .expect_err("no error");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidInput);
+    let e = sample_table
+        .read_chunk(index(3), &bytes)
+        .expect_err("no error");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidInput);
+
+    // データの範囲が source に収まらない
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(sample_table.read_sample(index(4), truncated).is_ok());
+    let e = sample_table
+        .read_sample(index(5), truncated)
+        .expect_err("no error");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidData);
+    let e = sample_table
+        .read_chunk(index(2), truncated)
+        .expect_err("no error");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn read_chunks_of_real_file() -> Result<()> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    let moov_box: MoovBox = file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box");
+
+    let stbl_box = &moov_box.trak_boxes[0].mdia_box.minf_box.stbl_box;
+    let sample_table = SampleTableAccessor::new(stbl_box).expect("invalid sample table");
+    for chunk in sample_table.chunks() {
+        let expected = chunk
+            .samples()
+            .map(|s| s.read_data(input_bytes).map(|d| d.to_vec()))
+            .collect::<Result<Vec<_>>>()?
+            .concat();
+        assert_eq!(chunk.read_data(input_bytes)?, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn index_out_of_bounds() -> Result<()> {
+    let (_, stbl_box, _) = minimal_mp4()?;
+    let sample_table = SampleTableAccessor::new(&stbl_box).expect("invalid sample table");
+
+    assert!(sample_table.try_get_sample(index(5)).is_ok());
+    let e = sample_table.try_get_sample(index(6)).expect_err("no error");
+    assert!(matches!(
+        e,
+        SampleTableAccessorError::SampleIndexOutOfBounds { index: 6, count: 5 }
+    ));
+    assert!(e.is_bounds_error());
+    assert!(!e.is_corruption_error());
+
+    assert!(sample_table.try_get_chunk(index(2)).is_ok());
+    let e = sample_table.try_get_chunk(index(3)).expect_err("no error");
+    assert!(matches!(
+        e,
+        SampleTableAccessorError::ChunkIndexOutOfBounds { index: 3, count: 2 }
+    ));
+    assert!(e.is_bounds_error());
+    assert_eq!(
+        e.to_string(),
+        "Chunk index 3 is out of bounds (chunk count is 2)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn empty_stsc_box() -> Result<()> {
+    let (_, mut stbl_box, _) = minimal_mp4()?;
+    stbl_box.stsc_box.entries.clear();
+
+    let e = SampleTableAccessor::new(&stbl_box).expect_err("no error");
+    assert!(matches!(
+        e,
+        SampleTableAccessorError::StscInconsistent { .. }
+    ));
+    assert!(e.is_corruption_error());
+
+    let e = SampleIter::new(&stbl_box)
+        .next()
+        .expect("no item")
+        .expect_err("no error");
+    assert!(matches!(
+        e,
+        SampleTableAccessorError::StscInconsistent { .. }
+    ));
+    Ok(())
+}
+
+#[test]
+fn empty_stsz_box() -> Result<()> {
+    let (_, mut stbl_box, _) = minimal_mp4()?;
+    stbl_box.stsz_box = StszBox::Variable {
+        entry_sizes: Vec::new(),
+    };
+
+    let e = SampleTableAccessor::new(&stbl_box).expect_err("no error");
+    assert!(matches!(e, SampleTableAccessorError::StszEmpty));
+    assert!(e.is_corruption_error());
+
+    let e = SampleIter::new(&stbl_box)
+        .next()
+        .expect("no item")
+        .expect_err("no error");
+    assert!(matches!(e, SampleTableAccessorError::StszEmpty));
+    Ok(())
+}
+
+#[test]
+fn co64_offset_overflow() -> Result<()> {
+    let (_, mut stbl_box, _) = minimal_mp4()?;
+
+    // 二番目のチャンクの末尾のサンプルのデータ位置が u64 の範囲を超える
+    stbl_box.stco_or_co64_box = Either::B(Co64Box {
+        chunk_offsets: vec![0, u64::MAX - 1],
+    });
+    let e = SampleTableAccessor::new(&stbl_box).expect_err("no error");
+    assert!(matches!(
+        e,
+        SampleTableAccessorError::Co64OffsetOverflow {
+            chunk_index: 2,
+            offset
+        } if offset == u64::MAX - 1
+    ));
+    assert!(e.is_corruption_error());
+
+    // データの終端がちょうど u64::MAX に収まる場合は問題ない
+    stbl_box.stco_or_co64_box = Either::B(Co64Box {
+        chunk_offsets: vec![0, u64::MAX - 6],
+    });
+    assert!(SampleTableAccessor::new(&stbl_box).is_ok());
+    Ok(())
+}
+
+#[test]
+fn error_with_context() -> Result<()> {
+    let (_, stbl_box, _) = minimal_mp4()?;
+    let sample_table = SampleTableAccessor::new(&stbl_box).expect("invalid sample table");
+
+    let e = sample_table
+        .try_get_sample(index(6))
+        .expect_err("no error")
+        .with_context("track 1")
+        .with_context("input.mp4");
+
+    // 後から付与した文脈情報ほど前に来る
+    assert_eq!(e.context(), ["input.mp4", "track 1"]);
+    assert_eq!(
+        e.to_string(),
+        "input.mp4: track 1: Sample index 6 is out of bounds (sample count is 5)"
+    );
+    assert!(matches!(
+        e.inner_error(),
+        SampleTableAccessorError::SampleIndexOutOfBounds { index: 6, count: 5 }
+    ));
+    assert!(e.is_bounds_error());
+    assert!(!e.is_corruption_error());
+
+    // 文脈情報がないエラー
+    let e = sample_table.try_get_chunk(index(3)).expect_err("no error");
+    assert!(e.context().is_empty());
+    assert!(matches!(
+        e.inner_error(),
+        SampleTableAccessorError::ChunkIndexOutOfBounds { .. }
+    ));
+    Ok(())
+}
+
+#[test]
+fn sample_info_read_data() -> Result<()> {
+    let (bytes, stbl_box, samples) = minimal_mp4()?;
+    let infos = SampleIter::new(&stbl_box)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .expect("invalid sample table");
+    assert_eq!(infos.len(), samples.len());
+
+    for (info, expected) in infos.iter().zip(&samples) {
+        assert!(info.is_within_bounds(bytes.len()));
+        let data = info.read_data(&bytes)?;
+        assert_eq!(data, expected);
+        assert_eq!(data.as_ptr(), bytes[info.data_offset as usize..].as_ptr());
+        assert_eq!(info.read_to_vec(&bytes)?, *expected);
+    }
+
+    // データの範囲が source に収まらない
+    let truncated = &bytes[..bytes.len() - 1];
+    let last = infos.last().expect("no sample");
+    assert!(!last.is_within_bounds(truncated.len()));
+    assert!(last.read_to_vec(truncated).is_err());
+    let e = last.read_data(truncated).expect_err("no error");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        e.io_error.to_string(),
+        format!(
+            "Data range out of bounds: range={}..{}, source_len={}",
+            last.data_offset,
+            bytes.len(),
+            truncated.len()
+        )
+    );
+    Ok(())
+}
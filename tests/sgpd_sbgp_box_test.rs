@@ -0,0 +1,210 @@
+use shiguredo_mp4::{
+    boxes::{RootBox, SbgpBox, SbgpEntry, SgpdBox, SgpdEntry, StblBox},
+    Decode, Encode, Mp4File, Result,
+};
+
+fn box_bytes(box_type: &[u8; 4], version: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(12 + payload.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(box_type);
+    bytes.extend_from_slice(&[version, 0, 0, 0]);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+#[test]
+fn decode_encode_sgpd_box() -> Result<()> {
+    // バージョン 1 で固定長エントリー
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"tscl");
+    payload.extend_from_slice(&2u32.to_be_bytes()); // default_length
+    payload.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    payload.extend_from_slice(&[0, 1, 2, 3]);
+    let bytes = box_bytes(b"sgpd", 1, &payload);
+    let sgpd_box = SgpdBox::decode(&bytes[..])?;
+    assert_eq!(sgpd_box.grouping_type, *b"tscl");
+    assert_eq!(sgpd_box.default_length, 2);
+    assert_eq!(sgpd_box.default_group_description_index, None);
+    assert_eq!(
+        sgpd_box.entries,
+        [
+            SgpdEntry {
+                payload: vec![0, 1]
+            },
+            SgpdEntry {
+                payload: vec![2, 3]
+            }
+        ]
+    );
+    assert_eq!(sgpd_box.encode_to_vec()?, bytes);
+
+    // バージョン 2 で可変長エントリー
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"seig");
+    payload.extend_from_slice(&0u32.to_be_bytes()); // default_length
+    payload.extend_from_slice(&1u32.to_be_bytes()); // default_group_description_index
+    payload.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    payload.extend_from_slice(&1u32.to_be_bytes());
+    payload.extend_from_slice(&[9]);
+    payload.extend_from_slice(&3u32.to_be_bytes());
+    payload.extend_from_slice(&[7, 8, 9]);
+    let bytes = box_bytes(b"sgpd", 2, &payload);
+    let sgpd_box = SgpdBox::decode(&bytes[..])?;
+    assert_eq!(sgpd_box.default_length, 0);
+    assert_eq!(sgpd_box.default_group_description_index, Some(1));
+    assert_eq!(sgpd_box.entries[0].payload, [9]);
+    assert_eq!(sgpd_box.entries[1].payload, [7, 8, 9]);
+    assert_eq!(sgpd_box.encode_to_vec()?, bytes);
+
+    Ok(())
+}
+
+#[test]
+fn decode_sgpd_box_version0() -> Result<()> {
+    // バージョン 0 はエントリーの長さが分からないので、単体ではデコードできない
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"roll");
+    payload.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    payload.extend_from_slice(&[0xFF, 0xFF, 0x00, 0x01, 0x02]);
+    let sgpd_bytes = box_bytes(b"sgpd", 0, &payload);
+    assert!(SgpdBox::decode(&sgpd_bytes[..]).is_err());
+
+    // stbl ボックス内では未知のボックスとして扱われ、そのまま再エンコードされる
+    let mut stbl_box = stbl_box()?;
+    stbl_box.unknown_boxes.clear();
+    stbl_box.sgpd_boxes.clear();
+    let mut bytes = stbl_box.encode_to_vec()?;
+    bytes.extend_from_slice(&sgpd_bytes);
+    let size = bytes.len() as u32;
+    bytes[..4].copy_from_slice(&size.to_be_bytes());
+
+    let decoded = StblBox::decode(&bytes[..])?;
+    assert!(decoded.sgpd_boxes.is_empty());
+    assert_eq!(decoded.unknown_boxes.len(), 1);
+    assert_eq!(decoded.unknown_boxes[0].box_type, SgpdBox::TYPE);
+    assert_eq!(decoded.encode_to_vec()?, bytes);
+    Ok(())
+}
+
+#[test]
+fn decode_sgpd_box_too_large_entry() {
+    // エントリーの長さがペイロードの残りを超えている場合は、メモリを確保する前にエラーになる
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"seig");
+    payload.extend_from_slice(&0u32.to_be_bytes()); // default_length
+    payload.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    payload.extend_from_slice(&u32::MAX.to_be_bytes());
+    payload.extend_from_slice(&[0, 1, 2]);
+    let bytes = box_bytes(b"sgpd", 1, &payload);
+    assert!(SgpdBox::decode(&bytes[..]).is_err());
+
+    // 固定長の場合も同様
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"seig");
+    payload.extend_from_slice(&u32::MAX.to_be_bytes()); // default_length
+    payload.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    let bytes = box_bytes(b"sgpd", 1, &payload);
+    assert!(SgpdBox::decode(&bytes[..]).is_err());
+}
+
+#[test]
+fn encode_sgpd_box_inconsistent_length() {
+    let sgpd_box = SgpdBox {
+        grouping_type: *b"tscl",
+        default_length: 2,
+        default_group_description_index: None,
+        entries: vec![SgpdEntry {
+            payload: vec![0, 1, 2],
+        }],
+    };
+    assert!(sgpd_box.encode_to_vec().is_err());
+}
+
+#[test]
+fn decode_encode_sbgp_box() -> Result<()> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"tscl");
+    payload.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    payload.extend_from_slice(&3u32.to_be_bytes());
+    payload.extend_from_slice(&1u32.to_be_bytes());
+    payload.extend_from_slice(&5u32.to_be_bytes());
+    payload.extend_from_slice(&0u32.to_be_bytes());
+    let bytes = box_bytes(b"sbgp", 0, &payload);
+    let sbgp_box = SbgpBox::decode(&bytes[..])?;
+    assert_eq!(sbgp_box.grouping_type, *b"tscl");
+    assert_eq!(sbgp_box.grouping_type_parameter, None);
+    assert_eq!(
+        sbgp_box.entries,
+        [
+            SbgpEntry {
+                sample_count: 3,
+                group_description_index: 1
+            },
+            SbgpEntry {
+                sample_count: 5,
+                group_description_index: 0
+            }
+        ]
+    );
+    assert_eq!(sbgp_box.encode_to_vec()?, bytes);
+
+    // grouping_type_parameter はバージョン 1 でのみ存在する
+    let mut payload = Vec::new();
+    payload.extend_from_slice(b"tscl");
+    payload.extend_from_slice(&42u32.to_be_bytes()); // grouping_type_parameter
+    payload.extend_from_slice(&0u32.to_be_bytes()); // entry_count
+    let bytes = box_bytes(b"sbgp", 1, &payload);
+    let sbgp_box = SbgpBox::decode(&bytes[..])?;
+    assert_eq!(sbgp_box.grouping_type_parameter, Some(42));
+    assert_eq!(sbgp_box.encode_to_vec()?, bytes);
+
+    Ok(())
+}
+
+fn stbl_box() -> Result<StblBox> {
+    let input_bytes = include_bytes!("testdata/black-h264-video.mp4");
+    let file: Mp4File = Mp4File::decode(&input_bytes[..])?;
+    Ok(file
+        .boxes
+        .into_iter()
+        .find_map(|b| match b {
+            RootBox::Moov(b) => Some(b),
+            _ => None,
+        })
+        .expect("no moov box")
+        .trak_boxes
+        .swap_remove(0)
+        .mdia_box
+        .minf_box
+        .stbl_box)
+}
+
+#[test]
+fn stbl_box_with_sample_groups() -> Result<()> {
+    let mut stbl_box = stbl_box()?;
+
+    // 同じ stbl ボックス内に複数の sgpd / sbgp ボックスが存在してもよい
+    for grouping_type in [*b"tscl", *b"roll"] {
+        stbl_box.sgpd_boxes.push(SgpdBox {
+            grouping_type,
+            default_length: 1,
+            default_group_description_index: None,
+            entries: vec![SgpdEntry { payload: vec![0] }],
+        });
+        stbl_box.sbgp_boxes.push(SbgpBox {
+            grouping_type,
+            grouping_type_parameter: None,
+            entries: vec![SbgpEntry {
+                sample_count: 1,
+                group_description_index: 1,
+            }],
+        });
+    }
+
+    let bytes = stbl_box.encode_to_vec()?;
+    let decoded = StblBox::decode(&bytes[..])?;
+    assert_eq!(decoded.sgpd_boxes.len(), 2);
+    assert_eq!(decoded.sbgp_boxes.len(), 2);
+    assert_eq!(decoded, stbl_box);
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::boxes::{StscBox, StscEntry};
+
+fn entry(first_chunk: u32, sample_per_chunk: u32) -> StscEntry {
+    StscEntry {
+        first_chunk: NonZeroU32::new(first_chunk).expect("zero"),
+        sample_per_chunk,
+        sample_description_index: NonZeroU32::MIN,
+    }
+}
+
+fn three_entries_box() -> StscBox {
+    // チャンク 1..=4 は 3 サンプル、5..=9 は 1 サンプル、10 以降は 5 サンプル
+    StscBox {
+        entries: vec![entry(1, 3), entry(5, 1), entry(10, 5)],
+    }
+}
+
+#[test]
+fn chunk_sample_ranges_with_three_entries() {
+    let stsc_box = three_entries_box();
+    let chunk_count = 20;
+
+    let ranges = stsc_box
+        .chunk_sample_ranges(chunk_count)
+        .collect::<Vec<_>>();
+    assert_eq!(ranges.len(), chunk_count as usize);
+
+    let mut expected_start = 0;
+    for (i, (chunk_index, range)) in ranges.iter().enumerate() {
+        assert_eq!(*chunk_index, i as u32 + 1);
+
+        let expected_count = match chunk_index {
+            1..=4 => 3,
+            5..=9 => 1,
+            _ => 5,
+        };
+        assert_eq!(range.len(), expected_count, "chunk_index={chunk_index}");
+        assert_eq!(range.start, expected_start);
+        assert_eq!(
+            stsc_box.samples_per_chunk_at(*chunk_index),
+            Some(expected_count as u32)
+        );
+        expected_start = range.end;
+    }
+
+    // 4 * 3 + 5 * 1 + 11 * 5
+    assert_eq!(expected_start, 72);
+    assert_eq!(ranges[0], (1, 0..3));
+    assert_eq!(ranges[4], (5, 12..13));
+    assert_eq!(ranges[19], (20, 67..72));
+}
+
+#[test]
+fn chunk_sample_ranges_edge_cases() {
+    let stsc_box = three_entries_box();
+    assert_eq!(stsc_box.chunk_sample_ranges(0).count(), 0);
+
+    // チャンク数が最後のエントリーの開始位置よりも少ない
+    let ranges = stsc_box.chunk_sample_ranges(6).collect::<Vec<_>>();
+    assert_eq!(ranges.last(), Some(&(6, 13..14)));
+
+    // エントリーがない場合には全てのチャンクが空になる
+    let empty_box = StscBox {
+        entries: Vec::new(),
+    };
+    assert!(empty_box
+        .chunk_sample_ranges(3)
+        .all(|(_, range)| range.is_empty()));
+    assert_eq!(empty_box.samples_per_chunk_at(1), None);
+
+    // 最初のエントリーよりも前のチャンクはサンプルを含まない
+    let stsc_box = StscBox {
+        entries: vec![entry(3, 2)],
+    };
+    let ranges = stsc_box.chunk_sample_ranges(4).collect::<Vec<_>>();
+    assert_eq!(ranges, [(1, 0..0), (2, 0..0), (3, 0..2), (4, 2..4)]);
+    assert_eq!(stsc_box.samples_per_chunk_at(0), None);
+    assert_eq!(stsc_box.samples_per_chunk_at(2), None);
+    assert_eq!(stsc_box.samples_per_chunk_at(3), Some(2));
+    assert_eq!(stsc_box.samples_per_chunk_at(100), Some(2));
+}
@@ -0,0 +1,63 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::boxes::StszBox;
+
+#[test]
+fn fixed_sample_size() {
+    let stsz_box = StszBox::Fixed {
+        sample_size: NonZeroU32::new(100).expect("zero"),
+        sample_count: 3,
+    };
+    assert_eq!(stsz_box.sample_count(), 3);
+    assert_eq!(stsz_box.sample_size_at(0), Some(100));
+    assert_eq!(stsz_box.sample_size_at(2), Some(100));
+    assert_eq!(stsz_box.sample_size_at(3), None);
+    assert_eq!(stsz_box.total_data_size(), 300);
+
+    // 合計が u32 に収まらない場合でも正しく計算される
+    let stsz_box = StszBox::Fixed {
+        sample_size: NonZeroU32::MAX,
+        sample_count: u32::MAX,
+    };
+    assert_eq!(
+        stsz_box.total_data_size(),
+        u64::from(u32::MAX) * u64::from(u32::MAX)
+    );
+    assert_eq!(stsz_box.sample_size_at(u32::MAX - 1), Some(u32::MAX));
+    assert_eq!(stsz_box.sample_size_at(u32::MAX), None);
+}
+
+#[test]
+fn fixed_sample_size_without_samples() {
+    let stsz_box = StszBox::Fixed {
+        sample_size: NonZeroU32::MIN,
+        sample_count: 0,
+    };
+    assert_eq!(stsz_box.sample_count(), 0);
+    assert_eq!(stsz_box.sample_size_at(0), None);
+    assert_eq!(stsz_box.total_data_size(), 0);
+}
+
+#[test]
+fn variable_sample_size() {
+    let stsz_box = StszBox::Variable {
+        entry_sizes: vec![10, 20, u32::MAX],
+    };
+    assert_eq!(stsz_box.sample_count(), 3);
+    assert_eq!(stsz_box.sample_size_at(0), Some(10));
+    assert_eq!(stsz_box.sample_size_at(1), Some(20));
+    assert_eq!(stsz_box.sample_size_at(2), Some(u32::MAX));
+    assert_eq!(stsz_box.sample_size_at(3), None);
+    assert_eq!(stsz_box.sample_size_at(u32::MAX), None);
+    assert_eq!(stsz_box.total_data_size(), 30 + u64::from(u32::MAX));
+}
+
+#[test]
+fn empty_variable_sample_size() {
+    let stsz_box = StszBox::Variable {
+        entry_sizes: Vec::new(),
+    };
+    assert_eq!(stsz_box.sample_count(), 0);
+    assert_eq!(stsz_box.sample_size_at(0), None);
+    assert_eq!(stsz_box.total_data_size(), 0);
+}
@@ -0,0 +1,104 @@
+use shiguredo_mp4::boxes::{SttsBox, SttsEntry};
+
+#[test]
+fn all_same_delta() {
+    let stts_box = SttsBox::from_sample_deltas(vec![1000; 10]);
+    assert_eq!(
+        stts_box.entries,
+        [SttsEntry {
+            sample_count: 10,
+            sample_delta: 1000
+        }]
+    );
+    assert_eq!(stts_box.sample_deltas().collect::<Vec<_>>(), [1000; 10]);
+    assert_eq!(stts_box.sample_delta_at(0), Some(1000));
+    assert_eq!(stts_box.sample_delta_at(9), Some(1000));
+    assert_eq!(stts_box.sample_delta_at(10), None);
+}
+
+#[test]
+fn alternating_deltas() {
+    let n = 100;
+    let deltas = (0..2 * n).map(|i| 1000 + i % 2).collect::<Vec<_>>();
+    let stts_box = SttsBox::from_sample_deltas(deltas.iter().copied());
+    assert_eq!(stts_box.entries.len(), 2 * n as usize);
+    assert!(stts_box.entries.iter().all(|x| x.sample_count == 1));
+    assert_eq!(stts_box.sample_deltas().collect::<Vec<_>>(), deltas);
+    for (i, &delta) in deltas.iter().enumerate() {
+        assert_eq!(stts_box.sample_delta_at(i as u32), Some(delta));
+    }
+    assert_eq!(stts_box.sample_delta_at(2 * n), None);
+}
+
+#[test]
+fn mixed_runs() {
+    let deltas = [10, 10, 10, 20, 30, 30, 10];
+    let stts_box = SttsBox::from_sample_deltas(deltas);
+    assert_eq!(stts_box.entries.len(), 4);
+    assert_eq!(stts_box.sample_deltas().collect::<Vec<_>>(), deltas);
+    for (i, &delta) in deltas.iter().enumerate() {
+        assert_eq!(stts_box.sample_delta_at(i as u32), Some(delta));
+    }
+}
+
+#[test]
+fn single_sample() {
+    let stts_box = SttsBox::from_sample_deltas([3000]);
+    assert_eq!(
+        stts_box.entries,
+        [SttsEntry {
+            sample_count: 1,
+            sample_delta: 3000
+        }]
+    );
+    assert_eq!(stts_box.sample_deltas().collect::<Vec<_>>(), [3000]);
+    assert_eq!(stts_box.sample_delta_at(0), Some(3000));
+    assert_eq!(stts_box.sample_delta_at(1), None);
+}
+
+#[test]
+fn empty() {
+    let stts_box = SttsBox::from_sample_deltas([]);
+    assert!(stts_box.entries.is_empty());
+    assert_eq!(stts_box.sample_deltas().count(), 0);
+    assert_eq!(stts_box.sample_delta_at(0), None);
+}
+
+#[test]
+fn zero_count_entries() {
+    // サンプル数が 0 のエントリーは読み飛ばされる
+    let stts_box = SttsBox {
+        entries: vec![
+            SttsEntry {
+                sample_count: 0,
+                sample_delta: 10,
+            },
+            SttsEntry {
+                sample_count: 2,
+                sample_delta: 20,
+            },
+        ],
+    };
+    assert_eq!(stts_box.sample_deltas().collect::<Vec<_>>(), [20, 20]);
+    assert_eq!(stts_box.sample_delta_at(0), Some(20));
+    assert_eq!(stts_box.sample_delta_at(2), None);
+}
+
+#[test]
+fn ten_million_samples() {
+    let sample_count = 10_000_000;
+    let stts_box = SttsBox::from_sample_deltas(std::iter::repeat_n(1000, sample_count));
+    assert_eq!(
+        stts_box.entries,
+        [SttsEntry {
+            sample_count: sample_count as u32,
+            sample_delta: 1000
+        }]
+    );
+    assert_eq!(stts_box.sample_deltas().count(), sample_count);
+    assert_eq!(
+        stts_box.sample_delta_at(sample_count as u32 - 1),
+        Some(1000)
+    );
+    assert_eq!(stts_box.sample_delta_at(sample_count as u32), None);
+}
@@ -0,0 +1,97 @@
+use std::num::NonZeroU32;
+
+use shiguredo_mp4::{
+    boxes::{SubsBox, SubsEntry, SubsSubsample},
+    Decode, Encode, FullBoxFlags, Result,
+};
+
+fn index(i: u32) -> NonZeroU32 {
+    NonZeroU32::new(i).expect("invalid index")
+}
+
+fn subsample(subsample_size: u32) -> SubsSubsample {
+    SubsSubsample {
+        subsample_size,
+        subsample_priority: 0,
+        discardable: 0,
+        codec_specific_parameters: 0,
+    }
+}
+
+#[test]
+fn decode_encode_version0() -> Result<()> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&30u32.to_be_bytes());
+    bytes.extend_from_slice(b"subs");
+    bytes.extend_from_slice(&[0, 0, 0, 1]); // version=0, flags=1
+    bytes.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    bytes.extend_from_slice(&2u32.to_be_bytes()); // sample_delta
+    bytes.extend_from_slice(&1u16.to_be_bytes()); // subsample_count
+    bytes.extend_from_slice(&100u16.to_be_bytes()); // subsample_size
+    bytes.extend_from_slice(&[3, 1]); // subsample_priority, discardable
+    bytes.extend_from_slice(&7u32.to_be_bytes()); // codec_specific_parameters
+
+    let subs_box = SubsBox::decode(&bytes[..])?;
+    assert_eq!(subs_box.flags, FullBoxFlags::new(1));
+    assert_eq!(
+        subs_box.entries,
+        [SubsEntry {
+            sample_delta: 2,
+            subsamples: vec![SubsSubsample {
+                subsample_size: 100,
+                subsample_priority: 3,
+                discardable: 1,
+                codec_specific_parameters: 7,
+            }]
+        }]
+    );
+    assert_eq!(subs_box.encode_to_vec()?, bytes);
+    Ok(())
+}
+
+#[test]
+fn decode_encode_version1() -> Result<()> {
+    // u16 に収まらないサブサンプルサイズがある場合はバージョン 1 が使われる
+    let subs_box = SubsBox {
+        flags: FullBoxFlags::new(0),
+        entries: vec![SubsEntry {
+            sample_delta: 1,
+            subsamples: vec![subsample(10), subsample(0x10000)],
+        }],
+    };
+    let bytes = subs_box.encode_to_vec()?;
+    assert_eq!(bytes[8], 1);
+    assert_eq!(SubsBox::decode(&bytes[..])?, subs_box);
+    Ok(())
+}
+
+#[test]
+fn subsample_ranges() {
+    // サンプル 2 と 5 がサブサンプル情報を持つ
+    let subs_box = SubsBox {
+        flags: FullBoxFlags::new(0),
+        entries: vec![
+            SubsEntry {
+                sample_delta: 2,
+                subsamples: vec![subsample(5), subsample(10), subsample(3)],
+            },
+            SubsEntry {
+                sample_delta: 3,
+                subsamples: vec![subsample(8)],
+            },
+        ],
+    };
+
+    assert_eq!(
+        subs_box.subsample_ranges(index(2)).collect::<Vec<_>>(),
+        [0..5, 5..15, 15..18]
+    );
+    let mut ranges = subs_box.subsample_ranges(index(5));
+    assert_eq!(ranges.next(), Some(0..8));
+    assert_eq!(ranges.next(), None);
+
+    // エントリーが存在しないサンプル
+    for i in [1, 3, 4, 6] {
+        assert_eq!(subs_box.subsample_ranges(index(i)).count(), 0);
+    }
+}
@@ -0,0 +1,109 @@
+use std::num::NonZeroU16;
+
+use shiguredo_mp4::{
+    boxes::{SampleEntry, TimecodeValue, TmcdBox},
+    BoxType, Decode, Encode, Result,
+};
+
+fn tmcd_box(flags: u32, number_of_frames: u8) -> TmcdBox {
+    TmcdBox {
+        data_reference_index: NonZeroU16::MIN,
+        flags,
+        time_scale: 30000,
+        frame_duration: 1001,
+        number_of_frames,
+        unknown_boxes: Vec::new(),
+    }
+}
+
+#[test]
+fn decode_encode_tmcd_box() -> Result<()> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(34u32 + 12).to_be_bytes());
+    bytes.extend_from_slice(b"tmcd");
+    bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]); // reserved, data_reference_index
+    bytes.extend_from_slice(&[0, 0, 0, 0]); // reserved
+    bytes.extend_from_slice(&3u32.to_be_bytes()); // flags
+    bytes.extend_from_slice(&30000u32.to_be_bytes()); // time_scale
+    bytes.extend_from_slice(&1001u32.to_be_bytes()); // frame_duration
+    bytes.extend_from_slice(&[30, 0]); // number_of_frames, reserved
+
+    // ソース名を保持する子ボックス
+    bytes.extend_from_slice(&12u32.to_be_bytes());
+    bytes.extend_from_slice(b"name");
+    bytes.extend_from_slice(b"cam1");
+
+    let entry = SampleEntry::decode(&bytes[..])?;
+    let SampleEntry::Tmcd(b) = &entry else {
+        panic!("not tmcd");
+    };
+    assert!(entry.is_known());
+    assert!(!entry.is_video());
+    assert!(!entry.is_audio());
+    assert_eq!(entry.track_kind(), None);
+    assert_eq!(
+        b.flags,
+        TmcdBox::FLAG_DROP_FRAME | TmcdBox::FLAG_24_HOUR_MAX
+    );
+    assert!(b.is_drop_frame());
+    assert!(!b.is_counter());
+    assert_eq!(b.time_scale, 30000);
+    assert_eq!(b.frame_duration, 1001);
+    assert_eq!(b.number_of_frames, 30);
+    assert_eq!(b.unknown_boxes.len(), 1);
+    assert_eq!(b.unknown_boxes[0].box_type, BoxType::Normal(*b"name"));
+    assert_eq!(entry.codec_string(), None);
+    assert_eq!(entry.encode_to_vec()?, bytes);
+    Ok(())
+}
+
+#[test]
+fn decode_timecode_sample() -> Result<()> {
+    let b = tmcd_box(0, 25);
+    let timecode = b.decode_timecode_sample(&90061u32.to_be_bytes())?;
+    assert_eq!(
+        timecode,
+        TimecodeValue {
+            hours: 1,
+            minutes: 0,
+            seconds: 2,
+            frames: 11
+        }
+    );
+    assert_eq!(timecode.to_smpte_string(false), "01:00:02:11");
+
+    // 4 バイトに満たないサンプルはエラーになる
+    assert!(b.decode_timecode_sample(&[0, 0, 1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn smpte_29_97_drop_frame() -> Result<()> {
+    let b = tmcd_box(TmcdBox::FLAG_DROP_FRAME, 30);
+    let to_string = |frame_number: u32| -> Result<String> {
+        let timecode = b.decode_timecode_sample(&frame_number.to_be_bytes())?;
+        Ok(timecode.to_smpte_string(b.is_drop_frame()))
+    };
+
+    assert_eq!(to_string(0)?, "00:00:00;00");
+    assert_eq!(to_string(1799)?, "00:00:59;29");
+
+    // 10 の倍数以外の分の先頭では、フレーム番号 0 と 1 が飛ばされる
+    assert_eq!(to_string(1800)?, "00:01:00;02");
+    assert_eq!(to_string(3597)?, "00:01:59;29");
+    assert_eq!(to_string(3598)?, "00:02:00;02");
+
+    // 10 の倍数の分の先頭では飛ばされない
+    assert_eq!(to_string(17981)?, "00:09:59;29");
+    assert_eq!(to_string(17982)?, "00:10:00;00");
+    assert_eq!(to_string(17984)?, "00:10:00;02");
+    assert_eq!(to_string(19782)?, "00:11:00;02");
+
+    // 一時間 (29.97 fps では 107892 フレーム)
+    assert_eq!(to_string(107892)?, "01:00:00;00");
+
+    // ドロップフレームは 30 の倍数のフレームレートでのみ有効
+    assert!(TimecodeValue::from_frame_number(0, 25, true).is_err());
+    assert!(TimecodeValue::from_frame_number(0, 0, false).is_err());
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use shiguredo_mp4::Uint;
+
+type U5 = Uint<u8, 5>;
+
+#[test]
+fn max_value() {
+    assert_eq!(U5::MAX, 31);
+    assert_eq!(U5::max_value(), 31);
+    assert_eq!(Uint::<u8, 8>::MAX, u8::MAX);
+    assert_eq!(Uint::<u8, 1, 7>::MAX, 1);
+    assert_eq!(Uint::<u64, 48>::MAX, (1 << 48) - 1);
+    assert_eq!(Uint::<u64, 64>::MAX, u64::MAX);
+}
+
+#[test]
+fn checked_new() {
+    assert_eq!(U5::checked_new(31), Some(U5::new(31)));
+
+    // 5 ビットで表現できない値
+    assert_eq!(U5::checked_new(32), None);
+    assert_eq!(U5::checked_new(u8::MAX), None);
+}
+
+#[test]
+fn checked_and_saturating_add() {
+    let v = U5::new(30);
+    assert_eq!(v.checked_add(1), Some(U5::new(31)));
+    assert_eq!(v.checked_add(2), None);
+    assert_eq!(v.saturating_add(1), U5::new(31));
+    assert_eq!(v.saturating_add(2), U5::new(31));
+
+    // 内部の型でオーバーフローする場合も同様
+    assert_eq!(v.checked_add(u8::MAX), None);
+    assert_eq!(v.saturating_add(u8::MAX), U5::new(U5::MAX));
+    assert_eq!(Uint::<u8, 8>::new(200).saturating_add(100).get(), u8::MAX);
+}
+
+#[test]
+fn display_and_ordering() {
+    assert_eq!(U5::new(17).to_string(), "17");
+    assert_eq!(Uint::<u8, 2, 6>::from_bits(0b1100_0000).to_string(), "3");
+    assert!(U5::new(3) < U5::new(4));
+}
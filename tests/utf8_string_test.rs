@@ -0,0 +1,54 @@
+use std::io::ErrorKind;
+
+use shiguredo_mp4::{Decode, Encode, Result, Utf8String};
+
+#[test]
+fn new() {
+    let s = Utf8String::new("foo").expect("valid string");
+    assert_eq!(s.get(), "foo");
+    assert_eq!(s.len(), 3);
+    assert!(!s.is_empty());
+    assert!(Utf8String::EMPTY.is_empty());
+
+    // null 文字を含む
+    assert_eq!(Utf8String::new("foo\0bar"), None);
+}
+
+#[test]
+fn new_validated() -> Result<()> {
+    assert_eq!(
+        Utf8String::new_validated("あいう")?,
+        Utf8String::new("あいう").expect("valid string")
+    );
+    assert_eq!(Utf8String::new_validated("あいう")?.len(), 9);
+
+    let e = Utf8String::new_validated("foo\0bar").expect_err("null character");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidInput);
+    assert!(e.io_error.to_string().contains("byte offset 3"));
+
+    // 最大長ちょうどは問題ない
+    let s = "a".repeat(Utf8String::MAX_LEN);
+    assert_eq!(Utf8String::new_validated(&s)?.len(), Utf8String::MAX_LEN);
+
+    let s = "a".repeat(Utf8String::MAX_LEN + 1);
+    let e = Utf8String::new_validated(&s).expect_err("too long");
+    assert_eq!(e.io_error.kind(), ErrorKind::InvalidInput);
+
+    Ok(())
+}
+
+#[test]
+fn encode_decode() -> Result<()> {
+    let s = Utf8String::new_validated("http://example.com")?;
+    let bytes = s.encode_to_vec()?;
+    assert_eq!(bytes, b"http://example.com\0");
+    assert_eq!(Utf8String::decode(&bytes[..])?, s);
+
+    // 不正な UTF-8
+    assert!(Utf8String::decode(&[0xff, 0xfe, 0][..]).is_err());
+
+    // null 終端がない
+    assert!(Utf8String::decode(&b"foo"[..]).is_err());
+
+    Ok(())
+}